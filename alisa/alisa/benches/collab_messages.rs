@@ -0,0 +1,211 @@
+//! Measures how many bytes a collab client sends over a recorded editing session.
+//! Run using `cargo bench --bench collab_messages`.
+
+use std::time::Instant;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+
+}
+
+#[derive(Default)]
+pub struct Objects {
+    strokes: alisa::ObjList<Stroke>
+}
+
+/// The points of a stroke, stored as binary data like Cipollino's stroke geometry
+#[derive(Clone, Default)]
+pub struct Geometry(Vec<[f32; 3]>);
+
+impl alisa::Serializable for Geometry {
+
+    fn serialize(&self, _context: &alisa::SerializationContext) -> alisa::ABFValue {
+        alisa::ABFValue::Binary(self.0.iter().flatten().flat_map(|x| x.to_le_bytes()).collect())
+    }
+
+    fn deserialize(data: &alisa::ABFValue, _context: &mut alisa::DeserializationContext) -> Option<Self> {
+        let floats = data.as_binary()?.chunks_exact(4).map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]])).collect::<Vec<_>>();
+        Some(Self(floats.chunks_exact(3).map(|pt| [pt[0], pt[1], pt[2]]).collect()))
+    }
+
+    fn delete(&self, _queue: &mut Vec<alisa::AnyPtr>) {
+
+    }
+
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Stroke {
+    geometry: Geometry
+}
+
+alisa::object_set_property_operation!(Stroke, geometry, Geometry);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateStroke {
+    ptr: alisa::Ptr<Stroke>,
+    geometry: Geometry
+}
+
+impl alisa::Operation for CreateStroke {
+    type Project = Project;
+    const NAME: &'static str = "CreateStroke";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Stroke {
+            geometry: self.geometry.clone()
+        })
+    }
+}
+
+impl alisa::Object for Stroke {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.strokes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.strokes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self {}
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Stroke>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateStroke>(),
+        alisa::OperationKind::from::<SetStrokeGeometry>(),
+    ];
+
+}
+
+/// An operation in the recorded session
+enum Recorded {
+    Create(alisa::Ptr<Stroke>, Geometry),
+    SetGeometry(alisa::Ptr<Stroke>, Geometry)
+}
+
+impl Recorded {
+
+    fn name(&self) -> &'static str {
+        match self {
+            Recorded::Create(..) => <CreateStroke as alisa::Operation>::NAME,
+            Recorded::SetGeometry(..) => <SetStrokeGeometry as alisa::Operation>::NAME,
+        }
+    }
+
+    fn data(&self) -> alisa::ABFValue {
+        match self {
+            Recorded::Create(ptr, geometry) => alisa::serialize(&CreateStroke { ptr: *ptr, geometry: geometry.clone() }),
+            Recorded::SetGeometry(ptr, geometry) => alisa::serialize(&SetStrokeGeometry { ptr: *ptr, geometry_value: geometry.clone() }),
+        }
+    }
+
+    fn queue(&self, client: &alisa::Client<Project>) {
+        match self {
+            Recorded::Create(ptr, geometry) => client.queue_operation(CreateStroke { ptr: *ptr, geometry: geometry.clone() }),
+            Recorded::SetGeometry(ptr, geometry) => client.queue_operation(SetStrokeGeometry { ptr: *ptr, geometry_value: geometry.clone() }),
+        }
+    }
+
+}
+
+const STROKES: usize = 20;
+const POINTS_PER_STROKE: usize = 200;
+const DRAG_FRAMES: usize = 60;
+
+/// A deterministic editing session, split into frames: drawing some strokes, then dragging each of them around, one operation per stroke per frame
+fn record_session() -> Vec<Vec<Recorded>> {
+    let mut frames = Vec::new();
+    let mut strokes = Vec::new();
+    for i in 0..STROKES {
+        let ptr = alisa::Ptr::from_key((1 << 63) + i as u64);
+        let geometry = (0..POINTS_PER_STROKE).map(|j| {
+            let t = j as f32 / POINTS_PER_STROKE as f32;
+            [i as f32 * 10.0 + t * 100.0, (t * 12.0).sin() * 20.0, 0.5 + t * 0.5]
+        }).collect::<Vec<_>>();
+        frames.push(vec![Recorded::Create(ptr, Geometry(geometry.clone()))]);
+        strokes.push((ptr, geometry));
+    }
+    for frame in 0..DRAG_FRAMES {
+        let offset = frame as f32 * 0.75;
+        frames.push(strokes.iter().map(|(ptr, geometry)| {
+            let moved = geometry.iter().map(|[x, y, pressure]| [x + offset, y - offset, *pressure]).collect();
+            Recorded::SetGeometry(*ptr, Geometry(moved))
+        }).collect());
+    }
+    frames
+}
+
+/// Every operation sent in its own message, with the operation's name and uncompressed data
+fn naive_bytes(session: &[Vec<Recorded>]) -> usize {
+    session.iter().flatten().map(|recorded| {
+        let msg = alisa::Message::Operation {
+            operation: recorded.name().to_owned(),
+            data: recorded.data(),
+            seq: 0
+        };
+        alisa::encode_abf(&alisa::serialize(&msg)).len()
+    }).sum()
+}
+
+/// Replay the session through a collab client, sending its messages every `flush_interval` frames
+fn client_bytes(session: &[Vec<Recorded>], flush_interval: usize, compression_threshold: Option<usize>) -> usize {
+    let mut server = alisa::Server::<Project>::with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+    let (id, welcome) = server.add_client();
+    let mut client = alisa::Client::<Project>::collab(&welcome).unwrap();
+
+    let mut bytes = 0;
+    for (i, frame) in session.iter().enumerate() {
+        for recorded in frame {
+            recorded.queue(&client);
+        }
+        client.tick();
+        if (i + 1) % flush_interval != 0 && i + 1 != session.len() {
+            continue;
+        }
+        for msg in client.take_messages() {
+            let data = alisa::serialize(&msg);
+            bytes += match compression_threshold {
+                Some(threshold) => alisa::encode_abf_compressed(&data, threshold),
+                None => alisa::encode_abf(&data),
+            }.len();
+            server.receive_message(id, &msg);
+        }
+        for msg in server.take_all_msgs_to_send().remove(&id).unwrap_or_default() {
+            client.receive_message(&msg);
+        }
+    }
+    bytes
+}
+
+fn measure<F: FnOnce() -> usize>(name: &str, baseline: usize, f: F) {
+    let start = Instant::now();
+    let bytes = f();
+    let elapsed = start.elapsed();
+    println!("{:<48} {:>10} bytes {:>7.1}% {:>10.2?}", name, bytes, bytes as f64 / baseline as f64 * 100.0, elapsed);
+}
+
+fn main() {
+    let session = record_session();
+    let operations = session.iter().map(Vec::len).sum::<usize>();
+    println!("{} frames, {} operations", session.len(), operations);
+
+    let baseline = naive_bytes(&session);
+    measure("one message per operation", baseline, || baseline);
+    measure("batched, flushed every frame", baseline, || client_bytes(&session, 1, None));
+    measure("batched and compressed, flushed every frame", baseline, || client_bytes(&session, 1, Some(512)));
+    measure("batched and compressed, flushed every 4 frames", baseline, || client_bytes(&session, 4, Some(512)));
+}
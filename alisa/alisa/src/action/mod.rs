@@ -1,5 +1,5 @@
 
-use crate::Project;
+use crate::{ABFValue, DeserializationContext, Project, Serializable, SerializationContext};
 
 mod invertible_operation;
 pub use invertible_operation::*;
@@ -42,4 +42,37 @@ impl<P: Project> Action<P> {
         self.acts.is_empty()
     }
 
+    /// Serialize the action for storing it in a persistent undo/redo history
+    pub(crate) fn serialize(&self) -> ABFValue {
+        let acts = self.acts.iter().map(|act| ABFValue::Array(Box::new([
+            ABFValue::Str(act.operation.name().to_owned()),
+            act.operation.serialize()
+        ]))).collect();
+        ABFValue::Map(Box::new([
+            ("context".to_owned(), self.context.serialize(&SerializationContext::new())),
+            ("acts".to_owned(), ABFValue::Array(acts))
+        ]))
+    }
+
+    /// Deserialize an action stored in a persistent undo/redo history.
+    /// Fails if any of the action's operations is not registered using `OperationKind::from_invertible`.
+    pub(crate) fn deserialize(data: &ABFValue) -> Option<Self> {
+        let context = P::ActionContext::deserialize(data.get("context")?, &mut DeserializationContext::new())?;
+        let mut acts = Vec::new();
+        for act in data.get("acts")?.as_array()? {
+            let [name, operation] = act.as_array()? else {
+                return None;
+            };
+            let name = name.as_string()?;
+            let operation_kind = P::OPERATIONS.iter().find(|operation_kind| operation_kind.name == name)?;
+            acts.push(Act {
+                operation: (operation_kind.deserialize_invertible?)(operation)?
+            });
+        }
+        Some(Self {
+            acts,
+            context
+        })
+    }
+
 }
@@ -1,7 +1,7 @@
 
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::HashSet};
 
-use crate::{ABFValue, Delta, DeserializationContext, Message, OperationDyn, OperationSource, Project, ProjectContextMut, Recorder, UnconfirmedOperation, WelcomeMessage};
+use crate::{ABFValue, Access, AnyPtr, ClientId, Delta, DeserializationContext, InvalidatedObject, Message, OperationDyn, OperationOutcome, OperationSource, OperationTable, PermissionGuard, Project, ProjectContextMut, Recorder, Session, Touched, UnconfirmedOperation, WelcomeMessage};
 
 use super::{Client, ClientKind};
 
@@ -15,15 +15,33 @@ pub(crate) struct Collab<P: Project> {
     to_send: RefCell<Vec<Message>>,
     /// The next key available for use
     curr_key: RefCell<u64>,
+    /// The ID the server assigned to this client
+    id: ClientId,
+    /// The sequence number of the last operation received from the server
+    last_seq: u64,
+    /// Was the connection to the server lost? While offline, no messages are sent to the server.
+    offline: bool,
+    /// The objects we had loaded as of `last_seq`, before our unconfirmed operations, recorded when the connection was lost
+    loaded_when_disconnected: Vec<AnyPtr>,
+    /// Objects we asked the server to stop sending us changes to, which we hold on to until the server confirms
+    unload_requests: HashSet<AnyPtr>,
+    /// The IDs the server assigned to operations in the `WelcomeMessage`
+    operations: OperationTable
 }
 
 impl<P: Project> Collab<P> {
 
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(id: ClientId, seq: u64, operations: OperationTable) -> Self {
         Self {
             unconfirmed_operations: Vec::new(),
             to_send: RefCell::new(Vec::new()),
-            curr_key: RefCell::new(1 << 63)
+            curr_key: RefCell::new(1 << 63),
+            id,
+            last_seq: seq,
+            offline: false,
+            loaded_when_disconnected: Vec::new(),
+            unload_requests: HashSet::new(),
+            operations
         }
     }
 
@@ -35,33 +53,99 @@ impl<P: Project> Collab<P> {
     }
 
     pub(crate) fn load_objects(&mut self, objects: &mut P::Objects) {
+        // Objects requested while offline are loaded once the session is resumed
+        if self.offline {
+            return;
+        }
         for object_kind in P::OBJECTS {
             (object_kind.collab_load_objects)(objects, self);    
         }
     }
 
-    pub(crate) fn perform_operation(&mut self, operation: Box<dyn OperationDyn<Project = P>>, delta: Delta<P>) {
+    pub(crate) fn perform_operation(&mut self, operation: Box<dyn OperationDyn<Project = P>>, delta: Delta<P>, access: Access) {
+        let Some(unconfirmed_operation) = self.coalesce(UnconfirmedOperation { operation, delta, access }) else {
+            return;
+        };
+
         self.send_message(Message::Operation {
-            operation: operation.name().to_owned(),
-            data: operation.serialize()
+            operation: unconfirmed_operation.operation.name().to_owned(),
+            data: unconfirmed_operation.operation.serialize(),
+            seq: 0
         });
 
-        self.unconfirmed_operations.push(UnconfirmedOperation {
-            operation,
-            delta
-        });
+        self.unconfirmed_operations.push(unconfirmed_operation);
+    }
+
+    /// If the operation overwrites an earlier one that is still waiting to be sent, send the operation in the earlier one's place instead.
+    /// The operations in between must not use anything either of them used, so that performing the operation earlier doesn't change the outcome.
+    /// Gives the operation back if it couldn't be combined with an earlier one.
+    fn coalesce(&mut self, later: UnconfirmedOperation<P>) -> Option<UnconfirmedOperation<P>> {
+        // The operations waiting to be sent are the last ones in `unconfirmed_operations`, and their messages are in `to_send` in the same order
+        let mut to_send = self.to_send.borrow_mut();
+        let mut unsent = to_send.iter_mut().filter(|msg| matches!(msg, Message::Operation { .. })).collect::<Vec<_>>();
+        let first_unsent = self.unconfirmed_operations.len().saturating_sub(unsent.len());
+
+        let mut between = Access::default();
+        for idx in (first_unsent..self.unconfirmed_operations.len()).rev() {
+            let earlier = &mut self.unconfirmed_operations[idx];
+            if later.operation.supersedes(earlier.operation.as_ref()) && !between.overlaps(&earlier.access) && !between.overlaps(&later.access) {
+                *unsent[idx - first_unsent] = Message::Operation {
+                    operation: later.operation.name().to_owned(),
+                    data: later.operation.serialize(),
+                    seq: 0
+                };
+                earlier.operation = later.operation;
+                earlier.delta.append(later.delta);
+                earlier.access.extend(&later.access);
+                return None;
+            }
+            between.extend(&earlier.access);
+        }
+
+        Some(later)
     }
     
     pub(crate) fn send_message(&self, message: Message) {
+        if self.offline {
+            return;
+        }
         self.to_send.borrow_mut().push(message);
     }
 
+    /// Send the operations the server has not confirmed yet again, after the session was resumed
+    fn resume(&mut self) {
+        self.offline = false;
+        for unconfirmed_operation in &self.unconfirmed_operations {
+            self.send_message(Message::Operation {
+                operation: unconfirmed_operation.operation.name().to_owned(),
+                data: unconfirmed_operation.operation.serialize(),
+                seq: 0
+            });
+        }
+        // The server might have stopped sending us changes to these objects without us hearing about it, so we ask again to find out
+        for ptr in &self.unload_requests {
+            self.send_message(Message::Unload { ptr: *ptr });
+        }
+    }
+
+    /// Ask the server to stop sending us changes to an object, so that it can be unloaded
+    pub(crate) fn request_unload(&mut self, ptr: AnyPtr) {
+        self.unload_requests.insert(ptr);
+        self.send_message(Message::Unload { ptr });
+    }
+
+    /// Objects can only be unloaded once the server has confirmed all our operations, since undoing and reapplying them might need the objects they touched
+    pub(crate) fn can_unload(&self) -> bool {
+        !self.offline && self.unconfirmed_operations.is_empty()
+    }
+
     pub(crate) fn has_messages(&self) -> bool {
         !self.to_send.borrow().is_empty()
     } 
 
     pub(crate) fn take_messages(&self) -> Vec<Message> {
-        std::mem::replace(&mut *self.to_send.borrow_mut(), Vec::new())
+        let messages = std::mem::take(&mut *self.to_send.borrow_mut());
+        self.operations.batch(messages)
     }
 
 }
@@ -69,6 +153,16 @@ impl<P: Project> Collab<P> {
 impl<P: Project> Client<P> {
 
     pub fn collab(welcome_data: &WelcomeMessage) -> Option<Self> {
+        Self::collab_with_id(welcome_data, welcome_data.id)
+    }
+
+    /// Create a collab client from a snapshot of the project it received earlier, such as a `WelcomeMessage` saved from a previous session.
+    /// `id` is the ID returned by `Server::add_client_from_snapshot`. The server then sends the operations performed since the snapshot to catch the client up.
+    pub fn collab_from_snapshot(snapshot: &WelcomeMessage, id: ClientId) -> Option<Self> {
+        Self::collab_with_id(snapshot, id)
+    }
+
+    fn collab_with_id(welcome_data: &WelcomeMessage, id: ClientId) -> Option<Self> {
         #[cfg(debug_assertions)]
         verify_project_type::<P>();
 
@@ -83,24 +177,27 @@ impl<P: Project> Client<P> {
         }
 
         Some(Self {
-            kind: ClientKind::Collab(Collab::new()),
+            kind: ClientKind::Collab(Box::new(Collab::new(id, welcome_data.seq, OperationTable::new(welcome_data.operations.clone())))),
             project,
             objects,
             operations_to_perform: RefCell::new(Vec::new()),
             project_modified: false,
             undo_stack: RefCell::new(Vec::new()),
-            redo_stack: RefCell::new(Vec::new())
+            redo_stack: RefCell::new(Vec::new()),
+            unload_policies: Vec::new()
         })
     }
 
-    pub(crate) fn handle_operation_message(&mut self, operation_name: &str, data: &ABFValue) -> bool {
+    /// Perform an operation received in a message. Returns the outcome along with what the operation touched.
+    /// If a permission guard is given, the operation is undone if it tries to modify something the client that sent it isn't allowed to.
+    pub(crate) fn handle_operation_message(&mut self, operation_name: &str, data: &ABFValue, guard: Option<PermissionGuard<P>>) -> (OperationOutcome, Touched) {
         // Find the type of operation being performed
         let Some(operation_kind) = P::OPERATIONS.iter().find(|kind| kind.name == operation_name) else {
-            return false;
+            return (OperationOutcome::Failed, Touched::default());
         };
         // Deserialize the operation from the message
         let Some(operation) = (operation_kind.deserialize)(data) else {
-            return false;
+            return (OperationOutcome::Failed, Touched::default());
         };
 
         let mut project_context = ProjectContextMut {
@@ -116,35 +213,188 @@ impl<P: Project> Client<P> {
             }
         }
 
-        // Apply the newly-received operation
-        let mut recorder = Recorder::new(project_context, OperationSource::Server, None);
+        // Apply the newly-received operation, recording the changes if we might need to undo them
+        let mut delta = Delta::new();
+        let record_delta = guard.is_some();
+        let mut recorder = Recorder::new(project_context, OperationSource::Server, record_delta.then_some(&mut delta));
+        recorder.guard = guard;
         let success = (operation_kind.perform)(operation, &mut recorder) && *recorder.success.borrow();
+        recorder.check_created_permissions();
+        let denied = recorder.denied;
+        let used = recorder.access().objects;
+        let touched = Touched {
+            project: recorder.modified_project,
+            objects: recorder.modified,
+            created: recorder.created,
+            used
+        };
+        if denied {
+            let mut project_context = ProjectContextMut {
+                project: &mut self.project,
+                objects: &mut self.objects,
+                project_modified: &mut self.project_modified,
+            };
+            delta.undo(&mut project_context);
+        }
 
         // Reapply the operations we've done on top of the inserted operation
         if let Some(collab) = self.kind.as_collab() {
-            let unconfirmed_operations = std::mem::replace(&mut collab.unconfirmed_operations, Vec::new());
-            
-            for unconfirmed_operation in unconfirmed_operations {
-                let mut delta = Delta::new();
-                let project_context = ProjectContextMut {
+            let unconfirmed_operations = std::mem::take(&mut collab.unconfirmed_operations);
+            self.reapply_unconfirmed_operations(unconfirmed_operations);
+        }
+
+        let outcome = if denied {
+            OperationOutcome::Denied
+        } else if success {
+            OperationOutcome::Performed
+        } else {
+            OperationOutcome::Failed
+        };
+        (outcome, touched)
+    }
+
+    /// Perform the local operations the server has not confirmed yet again, after they were undone
+    fn reapply_unconfirmed_operations(&mut self, unconfirmed_operations: Vec<UnconfirmedOperation<P>>) {
+        for unconfirmed_operation in unconfirmed_operations {
+            let mut delta = Delta::new();
+            let project_context = ProjectContextMut {
+                project: &mut self.project,
+                objects: &mut self.objects,
+                project_modified: &mut self.project_modified,
+            };
+            let mut recorder = Recorder::new(project_context, OperationSource::Local, Some(&mut delta));
+            let failed = !unconfirmed_operation.operation.perform(&mut recorder) && *recorder.success.borrow();
+            let access = recorder.access();
+            if failed {
+                let mut project_context = ProjectContextMut {
                     project: &mut self.project,
                     objects: &mut self.objects,
                     project_modified: &mut self.project_modified,
                 };
-                let mut recorder = Recorder::new(project_context, OperationSource::Local, Some(&mut delta));
-                if !unconfirmed_operation.operation.perform(&mut recorder) && *recorder.success.borrow() {
-                    let mut project_context = ProjectContextMut {
-                        project: &mut self.project,
-                        objects: &mut self.objects,
-                        project_modified: &mut self.project_modified,
-                    };
-                    delta.undo(&mut project_context);
-                }
-                collab.unconfirmed_operations.push(UnconfirmedOperation { operation: unconfirmed_operation.operation, delta });
+                delta.undo(&mut project_context);
+            }
+            if let Some(collab) = self.kind.as_collab() {
+                collab.unconfirmed_operations.push(UnconfirmedOperation { operation: unconfirmed_operation.operation, delta, access });
+            }
+        }
+    }
+
+    /// Replace our copies of the project and objects with the server's, after an operation we couldn't perform ourselves
+    fn handle_invalidate_message(&mut self, project: Option<&ABFValue>, objects: &[InvalidatedObject]) {
+        let mut project_context = ProjectContextMut {
+            project: &mut self.project,
+            objects: &mut self.objects,
+            project_modified: &mut self.project_modified,
+        };
+
+        // Undo all the stuff we've done client side
+        if let Some(collab) = self.kind.as_collab() {
+            for unconfirmed_operation in collab.unconfirmed_operations.iter().rev() {
+                unconfirmed_operation.delta.undo(&mut project_context); 
             }
         }
 
-        success
+        if let Some(project) = project.and_then(|data| P::deserialize(data, &mut DeserializationContext::new())) {
+            *project_context.project_mut() = project;
+        }
+        for object in objects {
+            let object_kind = &P::OBJECTS[object.ptr.obj_type() as usize];
+            (object_kind.replace_object_from_message)(&mut self.objects, object.ptr.key(), object.obj.as_ref());
+        }
+
+        // Reapply the operations we've done on top of the server's state
+        if let Some(collab) = self.kind.as_collab() {
+            let unconfirmed_operations = std::mem::take(&mut collab.unconfirmed_operations);
+            self.reapply_unconfirmed_operations(unconfirmed_operations);
+        }
+    }
+
+    /// Undo the oldest local operation the server has not confirmed, after the server refused to perform it
+    fn reject_operation(&mut self) {
+        let mut project_context = ProjectContextMut {
+            project: &mut self.project,
+            objects: &mut self.objects,
+            project_modified: &mut self.project_modified,
+        };
+        let Some(collab) = self.kind.as_collab() else {
+            return;
+        };
+        if collab.unconfirmed_operations.is_empty() {
+            return;
+        }
+        for unconfirmed_operation in collab.unconfirmed_operations.iter().rev() {
+            unconfirmed_operation.delta.undo(&mut project_context); 
+        }
+        let mut unconfirmed_operations = std::mem::take(&mut collab.unconfirmed_operations);
+        unconfirmed_operations.remove(0);
+        self.reapply_unconfirmed_operations(unconfirmed_operations);
+    }
+
+    /// The session of a collab client, used to resume it after losing the connection to the server
+    pub fn session(&self) -> Option<Session> {
+        match &self.kind {
+            ClientKind::Local(..) => None,
+            ClientKind::Collab(collab) => Some(Session {
+                id: collab.id,
+                last_seq: collab.last_seq,
+                loaded: if collab.offline { collab.loaded_when_disconnected.clone() } else { self.loaded_objects() }
+            }),
+        }
+    }
+
+    fn loaded_objects(&self) -> Vec<AnyPtr> {
+        P::OBJECTS.iter()
+            .flat_map(|object_kind| (object_kind.loaded_keys)(&self.objects).into_iter().map(|key| AnyPtr::new(object_kind.object_type_id, key)))
+            .collect()
+    }
+
+    /// Has the connection to the server been lost?
+    pub fn is_offline(&self) -> bool {
+        match &self.kind {
+            ClientKind::Local(..) => false,
+            ClientKind::Collab(collab) => collab.offline,
+        }
+    }
+
+    /// Tell the client that the connection to the server was lost.
+    /// Until the session is resumed using `Server::resume_client`, operations are only performed locally.
+    /// Once the server replays the operations the client missed, the client's own operations are rebased on top of them and sent to the server.
+    pub fn disconnect(&mut self) {
+        let Some(collab) = self.kind.as_collab() else {
+            return;
+        };
+        collab.offline = true;
+        collab.to_send.borrow_mut().clear();
+
+        // The server replays the operations we missed on top of the objects we had as of `last_seq`, so we need to tell it which those were,
+        // leaving out the objects our own operations created and keeping the ones they deleted
+        let mut project_context = ProjectContextMut {
+            project: &mut self.project,
+            objects: &mut self.objects,
+            project_modified: &mut self.project_modified,
+        };
+        for unconfirmed_operation in collab.unconfirmed_operations.iter().rev() {
+            unconfirmed_operation.delta.undo(&mut project_context);
+        }
+        let loaded = self.loaded_objects();
+        if let Some(collab) = self.kind.as_collab() {
+            collab.loaded_when_disconnected = loaded;
+            let unconfirmed_operations = std::mem::take(&mut collab.unconfirmed_operations);
+            self.reapply_unconfirmed_operations(unconfirmed_operations);
+        }
+
+        // The server won't respond to load requests sent before the connection was lost, so they need to be sent again
+        for object_kind in P::OBJECTS {
+            (object_kind.reset_loading)(&mut self.objects);
+        }
+    }
+
+    /// Perform an operation sent by the server
+    fn receive_operation(&mut self, operation: &str, data: &ABFValue, seq: u64) {
+        self.handle_operation_message(operation, data, None);
+        if let Some(collab) = self.kind.as_collab() {
+            collab.last_seq = collab.last_seq.max(seq);
+        }
     }
 
     pub fn receive_message(&mut self, msg: &Message) {
@@ -153,8 +403,9 @@ impl<P: Project> Client<P> {
         }
 
         match msg {
-            Message::ConfirmOperation => {
+            Message::ConfirmOperation { seq } => {
                 if let Some(collab) = self.kind.as_collab() {
+                    collab.last_seq = collab.last_seq.max(*seq);
                     // The check is necessary because an unconfirmed operation might fail after it is reapplied,
                     // So it might never get re-added to the unconfirmed operation queue
                     if !collab.unconfirmed_operations.is_empty() {
@@ -162,20 +413,55 @@ impl<P: Project> Client<P> {
                     }
                 }
             },
-            Message::Operation { operation, data } => {
-                self.handle_operation_message(&operation, data);
+            Message::RejectOperation { seq } => {
+                if let Some(collab) = self.kind.as_collab() {
+                    collab.last_seq = collab.last_seq.max(*seq);
+                }
+                self.reject_operation();
+            },
+            Message::Operation { operation, data, seq } => {
+                self.receive_operation(operation, data, *seq);
             },
+            Message::Operations { operations } => {
+                for compact in operations {
+                    let Some(collab) = self.kind.as_collab() else {
+                        return;
+                    };
+                    let operation = collab.operations.name(compact.operation).unwrap_or_default().to_owned();
+                    self.receive_operation(&operation, &compact.data, compact.seq);
+                }
+            },
+            Message::Invalidate { seq, project, objects } => {
+                self.handle_invalidate_message(project.as_ref(), objects);
+                if let Some(collab) = self.kind.as_collab() {
+                    collab.last_seq = collab.last_seq.max(*seq);
+                }
+            },
+            Message::Unload { ptr } => {
+                // The server will no longer send us changes to the object, so it's time to let go of it
+                if let Some(collab) = self.kind.as_collab() {
+                    collab.unload_requests.remove(ptr);
+                }
+                let Some(object_kind) = P::OBJECTS.get(ptr.obj_type() as usize) else { return; };
+                (object_kind.unload)(&mut self.objects, ptr.key());
+            },
+            Message::Resumed => {
+                if let Some(collab) = self.kind.as_collab() {
+                    collab.resume();
+                }
+            },
+            // The server's copy of the object doesn't include the operations it hasn't confirmed yet, so it's put underneath them like an invalidation
             Message::Load { ptr, obj } => {
-                let obj_type = ptr.obj_type();
-                let key = ptr.key();
-                let object_kind = &P::OBJECTS[obj_type as usize];
-                (object_kind.load_object_from_message)(&mut self.objects, key, obj);
+                if P::OBJECTS.get(ptr.obj_type() as usize).is_none() {
+                    return;
+                }
+                self.handle_invalidate_message(None, &[InvalidatedObject { ptr: *ptr, obj: Some(obj.clone()) }]);
             },
             Message::LoadFailed { ptr } => {
-                let obj_type = ptr.obj_type();
-                let key = ptr.key();
-                let object_kind = &P::OBJECTS[obj_type as usize];
-                (object_kind.load_failed)(&mut self.objects, key); 
+                if P::OBJECTS.get(ptr.obj_type() as usize).is_none() {
+                    return;
+                }
+                self.handle_invalidate_message(None, &[InvalidatedObject { ptr: *ptr, obj: None }]);
             },
             _ => {}
         }
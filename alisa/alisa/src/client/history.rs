@@ -0,0 +1,209 @@
+
+use std::cell::RefCell;
+
+use crate::{ABFValue, Action, File, HistoryPtrs, Project};
+
+use super::{Client, Local};
+
+/// One of the two stacks of the undo/redo history
+#[derive(Clone, Copy)]
+pub(crate) enum HistoryStack {
+    Undo,
+    Redo
+}
+
+/// A modification to the undo/redo history, saved to the file together with the rest of the tick's changes
+enum HistoryChange {
+    Push(HistoryStack, ABFValue),
+    Pop(HistoryStack),
+    ClearRedo
+}
+
+/// The undo/redo history of a local client, stored in the project file
+pub(crate) struct PersistentHistory {
+    /// The maximum number of actions kept in each stack
+    depth: usize,
+    /// The pointers to the stored actions
+    ptrs: HistoryPtrs,
+    /// Changes to the history that still need to be saved
+    changes: Vec<HistoryChange>
+}
+
+impl PersistentHistory {
+
+    fn stack(&mut self, stack: HistoryStack) -> &mut Vec<u64> {
+        match stack {
+            HistoryStack::Undo => &mut self.ptrs.undo,
+            HistoryStack::Redo => &mut self.ptrs.redo,
+        }
+    }
+
+    /// Delete the oldest actions in the stack until it fits within the history depth
+    fn trim(&mut self, stack: HistoryStack, file: &mut File) {
+        let depth = self.depth;
+        let stack = self.stack(stack);
+        let excess = stack.len().saturating_sub(depth);
+        for ptr in stack.drain(..excess) {
+            file.delete_action(ptr);
+        }
+    }
+
+    pub(crate) fn save_changes(&mut self, file: &mut File) {
+        if self.changes.is_empty() {
+            return;
+        }
+
+        for change in std::mem::take(&mut self.changes) {
+            match change {
+                HistoryChange::Push(stack, data) => {
+                    let Some(ptr) = file.write_action(&data) else { continue; };
+                    self.stack(stack).push(ptr);
+                    self.trim(stack, file);
+                },
+                HistoryChange::Pop(stack) => {
+                    if let Some(ptr) = self.stack(stack).pop() {
+                        file.delete_action(ptr);
+                    }
+                },
+                HistoryChange::ClearRedo => {
+                    for ptr in std::mem::take(&mut self.ptrs.redo) {
+                        file.delete_action(ptr);
+                    }
+                },
+            }
+        }
+
+        file.write_history(&self.ptrs);
+    }
+
+    /// Reload the pointers to the stored actions, since compacting the file might have moved them
+    pub(crate) fn reload(&mut self, file: &mut File) {
+        if let Some(ptrs) = file.read_history() {
+            self.ptrs = ptrs;
+        }
+    }
+
+}
+
+impl<P: Project> Local<P> {
+
+    /// Load a stack of actions stored in the file, returning the actions and the pointers to the ones that were loaded.
+    fn load_history_stack(&mut self, mut ptrs: Vec<u64>) -> (Vec<Action<P>>, Vec<u64>) {
+        let mut actions = Vec::new();
+        let mut loaded = Vec::new();
+
+        // Load the stack from the top down
+        while let Some(ptr) = ptrs.pop() {
+            let Some(action) = self.file.read_action(ptr).and_then(|data| Action::deserialize(&data)) else {
+                // If an action can't be loaded, the actions below it can't be reached anymore, so they are deleted along with it
+                self.file.delete_action(ptr);
+                for ptr in ptrs.drain(..) {
+                    self.file.delete_action(ptr);
+                }
+                break;
+            };
+            actions.push(action);
+            loaded.push(ptr);
+        }
+
+        actions.reverse();
+        loaded.reverse();
+        (actions, loaded)
+    }
+
+    /// Start storing the undo/redo history in the file.
+    /// If the file already contains a history, it is loaded into the given stacks. Otherwise, the given stacks are saved to the file.
+    fn enable_history(&mut self, depth: usize, undo_stack: &mut Vec<Action<P>>, redo_stack: &mut Vec<Action<P>>) -> Option<()> {
+        self.file.begin_transaction();
+
+        let mut history = PersistentHistory {
+            depth,
+            ptrs: HistoryPtrs::default(),
+            changes: Vec::new()
+        };
+
+        if self.file.has_history() {
+            let ptrs = self.file.read_history().unwrap_or_default();
+            (*undo_stack, history.ptrs.undo) = self.load_history_stack(ptrs.undo);
+            (*redo_stack, history.ptrs.redo) = self.load_history_stack(ptrs.redo);
+        } else {
+            self.file.create_history(*self.curr_key.borrow())?;
+            for action in undo_stack.iter() {
+                history.changes.push(HistoryChange::Push(HistoryStack::Undo, action.serialize()));
+            }
+            for action in redo_stack.iter() {
+                history.changes.push(HistoryChange::Push(HistoryStack::Redo, action.serialize()));
+            }
+        }
+
+        // The depth might be smaller than when the history was saved
+        for stack in [undo_stack, redo_stack] {
+            let excess = stack.len().saturating_sub(depth);
+            stack.drain(..excess);
+        }
+        history.trim(HistoryStack::Undo, &mut self.file);
+        history.trim(HistoryStack::Redo, &mut self.file);
+
+        history.save_changes(&mut self.file);
+        self.file.write_history(&history.ptrs);
+        self.history = Some(history);
+
+        self.file.commit_transaction();
+        Some(())
+    }
+
+}
+
+impl<P: Project> Client<P> {
+
+    fn stack(&self, stack: HistoryStack) -> &RefCell<Vec<Action<P>>> {
+        match stack {
+            HistoryStack::Undo => &self.undo_stack,
+            HistoryStack::Redo => &self.redo_stack,
+        }
+    }
+
+    fn persistent_history(&mut self) -> Option<&mut PersistentHistory> {
+        self.kind.as_local()?.history.as_mut()
+    }
+
+    /// Push an action onto one of the stacks of the undo/redo history
+    pub(crate) fn push_history(&mut self, stack: HistoryStack, action: Action<P>) {
+        if let Some(history) = self.persistent_history() {
+            history.changes.push(HistoryChange::Push(stack, action.serialize()));
+            let depth = history.depth;
+            let mut actions = self.stack(stack).borrow_mut();
+            actions.push(action);
+            let excess = actions.len().saturating_sub(depth);
+            actions.drain(..excess);
+        } else {
+            self.stack(stack).borrow_mut().push(action);
+        }
+    }
+
+    /// Record that an action was popped from one of the stacks of the undo/redo history
+    pub(crate) fn popped_history(&mut self, stack: HistoryStack) {
+        if let Some(history) = self.persistent_history() {
+            history.changes.push(HistoryChange::Pop(stack));
+        }
+    }
+
+    pub(crate) fn clear_redo(&mut self) {
+        if let Some(history) = self.persistent_history() {
+            history.changes.push(HistoryChange::ClearRedo);
+        }
+        self.redo_stack.borrow_mut().clear();
+    }
+
+    /// Store the undo/redo history in the project file, keeping up to `depth` actions in each of the undo and redo stacks.
+    /// If the file already contains a history, it replaces the client's current one, so this should be called right after opening the project.
+    /// Only the operations registered using `OperationKind::from_invertible` can be stored.
+    /// Returns false if the client is not local.
+    pub fn enable_persistent_history(&mut self, depth: usize) -> bool {
+        let Some(local) = self.kind.as_local() else {
+            return false;
+        };
+        local.enable_history(depth, self.undo_stack.get_mut(), self.redo_stack.get_mut()).is_some()
+    }
+
+}
@@ -3,18 +3,20 @@ use std::{cell::RefCell, marker::PhantomData, path::Path};
 
 use crate::{File, ObjectKind, Project, SerializationContext};
 
-use super::{Client, ClientKind};
+use super::{Client, ClientKind, PersistentHistory};
 
 #[cfg(debug_assertions)]
 use super::verify_project_type;
 
 pub(crate) struct Local<P: Project> {
     /// The Verter file to which the project is saved
-    file: File,
+    pub(crate) file: File,
     /// The next key available for use
-    curr_key: RefCell<u64>,
+    pub(crate) curr_key: RefCell<u64>,
     /// Does the root data of the Verter file need to be updated?
     root_data_modified: RefCell<bool>,
+    /// The undo/redo history stored in the file, if enabled
+    pub(crate) history: Option<PersistentHistory>,
 
     /// Marker to make sure the type `P`` is used
     _marker: PhantomData<P>
@@ -27,6 +29,7 @@ impl<P: Project> Local<P> {
             file,
             curr_key: RefCell::new(curr_key),
             root_data_modified: RefCell::new(false),
+            history: None,
             _marker: PhantomData
         }
     }
@@ -45,6 +48,9 @@ impl<P: Project> Local<P> {
 
     pub(crate) fn save_changes(&mut self, project: &mut P, objects: &mut P::Objects, project_modified: &mut bool) {
 
+        // Save all of this tick's changes in a single transaction, so the file always reflects a consistent state of the project
+        self.file.begin_transaction();
+
         // Update file root data if necessary 
         if *self.root_data_modified.borrow() {
             self.update_root_data();
@@ -63,6 +69,13 @@ impl<P: Project> Local<P> {
             (object_kind.save_modifications)(&mut self.file, objects);
         }
 
+        // Undo/redo history modifications
+        if let Some(history) = &mut self.history {
+            history.save_changes(&mut self.file);
+        }
+
+        self.file.commit_transaction();
+
     }
 
     pub(crate) fn load_objects(&mut self, objects: &mut P::Objects) {
@@ -71,6 +84,14 @@ impl<P: Project> Local<P> {
         }
     }
 
+    pub(crate) fn compact(&mut self) -> Option<u64> {
+        let reclaimed_bytes = self.file.compact(*self.curr_key.borrow())?;
+        if let Some(history) = &mut self.history {
+            history.reload(&mut self.file);
+        }
+        Some(reclaimed_bytes)
+    }
+
     pub(crate) fn dyn_load(&mut self, obj_kind: &ObjectKind<P>, objects: &mut P::Objects, key: u64) {
         (obj_kind.load_object)(&mut self.file, objects, key);
     }
@@ -80,20 +101,30 @@ impl<P: Project> Local<P> {
 impl<P: Project> Client<P> {
 
     pub fn local<PathRef: AsRef<Path>>(path: PathRef) -> Option<Self> {
+        Self::local_with_file(File::open(path)?)
+    }
+
+    /// Create a local client for a project kept in a custom storage, such as `verter::MemoryStorage`.
+    pub fn local_with_storage<S: verter::Storage + 'static>(storage: S) -> Option<Self> {
+        Self::local_with_file(File::open_storage(storage)?)
+    }
+
+    fn local_with_file(opened_file: (File, P, P::Objects, u64, bool)) -> Option<Self> {
 
         #[cfg(debug_assertions)]
         verify_project_type::<P>();
-        
-        let (file, project, objects, curr_key, new_project) = File::open(path)?;
+
+        let (file, project, objects, curr_key, new_project) = opened_file;
 
         let mut client = Self {
-            kind: ClientKind::Local(Local::new(file, curr_key)),
+            kind: ClientKind::Local(Box::new(Local::new(file, curr_key))),
             project,
             objects,
             operations_to_perform: RefCell::new(Vec::new()),
             project_modified: false,
             undo_stack: RefCell::new(Vec::new()),
-            redo_stack: RefCell::new(Vec::new())
+            redo_stack: RefCell::new(Vec::new()),
+            unload_policies: Vec::new()
         };
 
         if new_project {
@@ -104,4 +135,10 @@ impl<P: Project> Client<P> {
         Some(client)
     }
 
+    /// Compact the project file, reclaiming the space left behind by deleted objects.
+    /// Returns the number of bytes reclaimed, or `None` if the client is not local or compaction failed.
+    pub fn compact(&mut self) -> Option<u64> {
+        self.kind.as_local()?.compact()
+    }
+
 }
@@ -10,16 +10,24 @@ use local::*;
 mod collab;
 pub(crate) use collab::*;
 
+mod history;
+pub(crate) use history::*;
+
+mod unload;
+pub use unload::*;
+
+mod snapshots;
+
 pub(crate) enum ClientKind<P: Project> {
-    Local(Local<P>),
-    Collab(Collab<P>)
+    Local(Box<Local<P>>),
+    Collab(Box<Collab<P>>)
 }
 
 impl<P: Project> ClientKind<P> {
 
     pub(crate) fn as_local(&mut self) -> Option<&mut Local<P>> {
         match self {
-            ClientKind::Local(local) => Some(local),
+            ClientKind::Local(local) => Some(local.as_mut()),
             ClientKind::Collab(..) => None,
         }
     }
@@ -54,7 +62,9 @@ pub struct Client<P: Project> {
     operations_to_perform: RefCell<Vec<OperationToPerform<P>>>,
     project_modified: bool,
     undo_stack: RefCell<Vec<Action<P>>>,
-    redo_stack: RefCell<Vec<Action<P>>>
+    redo_stack: RefCell<Vec<Action<P>>>,
+    /// Decide which objects to unload on every tick
+    unload_policies: Vec<Box<dyn UnloadPolicy<P>>>
 }
 
 impl<P: Project> Client<P> {
@@ -138,10 +148,11 @@ impl<P: Project> Client<P> {
             project_modified: &mut self.project_modified,
         }, OperationSource::Local, Some(&mut delta));
         let success = operation.perform(&mut recorder) && *recorder.success.borrow();
+        let access = recorder.access();
 
         if success {
             if let Some(collab) = self.kind.as_collab() {
-                collab.perform_operation(operation, delta); 
+                collab.perform_operation(operation, delta, access); 
             }
         } else {
             let mut context = ProjectContextMut {
@@ -187,20 +198,22 @@ impl<P: Project> Client<P> {
                 OperationToPerform::Action(action) => {
                     let inv_action = self.perform_action(action);
                     if !inv_action.is_empty() {
-                        self.undo_stack.borrow_mut().push(inv_action);
+                        self.push_history(HistoryStack::Undo, inv_action);
                     }
-                    self.redo_stack.borrow_mut().clear();
+                    self.clear_redo();
                 },
                 OperationToPerform::Undo(undo_action) => {
+                    self.popped_history(HistoryStack::Undo);
                     let redo_action = self.perform_action(undo_action);
                     if !redo_action.is_empty() {
-                        self.redo_stack.borrow_mut().push(redo_action);
+                        self.push_history(HistoryStack::Redo, redo_action);
                     }
                 },
                 OperationToPerform::Redo(redo_action) => {
+                    self.popped_history(HistoryStack::Redo);
                     let undo_action = self.perform_action(redo_action);
                     if !undo_action.is_empty() {
-                        self.undo_stack.borrow_mut().push(undo_action);
+                        self.push_history(HistoryStack::Undo, undo_action);
                     }
                 },
             }
@@ -220,6 +233,9 @@ impl<P: Project> Client<P> {
             (object_kind.clear_modifications)(&mut self.objects);
         }
 
+        // Now that all changes are saved, drop the objects that are no longer needed
+        self.unload_requested_objects();
+
     }
 
     pub fn has_messages(&self) -> bool {
@@ -248,15 +264,22 @@ impl<P: Project> Client<P> {
     }
 
     pub fn get<O: Object<Project = P>, T: Into<Ptr<O>>>(&self, ptr: T) -> Option<&O> {
-        O::list(&self.objects).get(ptr.into())
+        let ptr = ptr.into();
+        O::list(&self.objects).mark_used(ptr);
+        O::list(&self.objects).get(ptr)
     }
 
     pub fn get_ref<O: Object<Project = P>, T: Into<Ptr<O>>>(&'_ self, ptr: T) -> ObjRef<'_, O> {
-        O::list(&self.objects).get_ref(ptr.into())
+        let ptr = ptr.into();
+        O::list(&self.objects).mark_used(ptr);
+        O::list(&self.objects).get_ref(ptr)
     }
 
     pub fn request_load<O: Object<Project = P>, T: Into<Ptr<O>>>(&self, ptr: T) {
-        O::list(&self.objects).to_load.borrow_mut().insert(ptr.into());
+        let ptr = ptr.into();
+        O::list(&self.objects).mark_used(ptr);
+        O::list(&self.objects).to_unload.borrow_mut().remove(&ptr);
+        O::list(&self.objects).to_load.borrow_mut().insert(ptr);
     }
 
     pub fn undo_stack(&self) -> &RefCell<Vec<Action<P>>> {
@@ -0,0 +1,106 @@
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Operation, Project, RestoreSnapshot, Snapshot, SnapshotEntry, SnapshotInfo};
+
+use super::{Client, Local};
+
+impl<P: Project> Local<P> {
+
+    fn create_snapshot(&mut self, name: &str) -> Option<SnapshotInfo> {
+        let curr_key = *self.curr_key.borrow();
+
+        // Store the snapshot in a single transaction, so that a crash can't leave behind a snapshot missing from the index
+        self.file.begin_transaction();
+        let snapshot = self.file.capture_snapshot::<P>(curr_key)?;
+        let ptr = self.file.write_snapshot(&snapshot)?;
+        let mut index = self.file.read_snapshot_index();
+        let info = SnapshotInfo {
+            id: index.iter().map(|entry| entry.info.id + 1).max().unwrap_or(1),
+            name: name.to_owned(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0)
+        };
+        index.push(SnapshotEntry {
+            info: info.clone(),
+            ptr
+        });
+        self.file.write_snapshot_index(&index, curr_key)?;
+        self.file.commit_transaction();
+
+        Some(info)
+    }
+
+    fn snapshot(&mut self, id: u64) -> Option<Snapshot<P>> {
+        let entry = self.file.read_snapshot_index().into_iter().find(|entry| entry.info.id == id)?;
+        self.file.read_snapshot(entry.ptr)
+    }
+
+    fn delete_snapshot(&mut self, id: u64) -> Option<()> {
+        let mut index = self.file.read_snapshot_index();
+        let idx = index.iter().position(|entry| entry.info.id == id)?;
+        let entry = index.remove(idx);
+
+        self.file.begin_transaction();
+        self.file.write_snapshot_index(&index, *self.curr_key.borrow())?;
+        self.file.delete_snapshot(entry.ptr);
+        self.file.commit_transaction();
+        Some(())
+    }
+
+    /// Get the operation restoring a snapshot, loading everything it touches so that it can be performed
+    fn restore_snapshot_operation(&mut self, objects: &mut P::Objects, snapshot: &Snapshot<P>) -> Option<RestoreSnapshot<P>> {
+        let current = self.file.capture_snapshot::<P>(*self.curr_key.borrow())?;
+        let operation = snapshot.restore_operation(&current);
+        for ptr in operation.objects() {
+            self.dyn_load(&P::OBJECTS[ptr.obj_type() as usize], objects, ptr.key());
+        }
+        Some(operation)
+    }
+
+}
+
+impl<P: Project> Client<P> {
+
+    /// Save a named snapshot of the project in the project file.
+    /// The snapshot reflects the project as of the last tick, so operations that are still queued aren't included.
+    /// Returns `None` if the client is not local.
+    pub fn create_snapshot(&mut self, name: &str) -> Option<SnapshotInfo> {
+        self.kind.as_local()?.create_snapshot(name)
+    }
+
+    /// The snapshots stored in the project file, from oldest to newest
+    pub fn snapshots(&mut self) -> Vec<SnapshotInfo> {
+        let Some(local) = self.kind.as_local() else {
+            return Vec::new();
+        };
+        local.file.read_snapshot_index().into_iter().map(|entry| entry.info).collect()
+    }
+
+    /// Read a snapshot stored in the project file
+    pub fn snapshot(&mut self, id: u64) -> Option<Snapshot<P>> {
+        self.kind.as_local()?.snapshot(id)
+    }
+
+    /// Take a snapshot of the project without storing it, for comparing the current state of the project to a stored snapshot
+    pub fn capture_snapshot(&mut self) -> Option<Snapshot<P>> {
+        let local = self.kind.as_local()?;
+        let curr_key = *local.curr_key.borrow();
+        local.file.capture_snapshot(curr_key)
+    }
+
+    /// Delete a snapshot from the project file. Returns false if there is no such snapshot.
+    pub fn delete_snapshot(&mut self, id: u64) -> bool {
+        self.kind.as_local().and_then(|local| local.delete_snapshot(id)).is_some()
+    }
+
+    /// Get the operation that brings the project back to the state of a snapshot.
+    /// Queue it as part of an action to make restoring the snapshot undoable.
+    /// Returns `None` if the client is not local or the project doesn't register `RestoreSnapshot`.
+    pub fn restore_snapshot_operation(&mut self, snapshot: &Snapshot<P>) -> Option<RestoreSnapshot<P>> {
+        if !P::OPERATIONS.iter().any(|operation_kind| operation_kind.name == RestoreSnapshot::<P>::NAME) {
+            return None;
+        }
+        self.kind.as_local()?.restore_snapshot_operation(&mut self.objects, snapshot)
+    }
+
+}
@@ -0,0 +1,169 @@
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{AnyPtr, Object, Project, ProjectContext, Ptr, SerializationContext};
+
+use super::Client;
+
+/// Decides which objects a client should unload to keep its memory usage down.
+/// Added using `Client::add_unload_policy`.
+pub trait UnloadPolicy<P: Project>: Send {
+
+    /// Called on every tick. Returns the objects that should be unloaded.
+    /// The objects loaded along with them are unloaded too, unless something else still references them.
+    fn objects_to_unload(&mut self, context: &ProjectContext<P>) -> Vec<AnyPtr>;
+
+}
+
+/// Keeps at most `capacity` objects of type `O` loaded, unloading the ones that were least recently accessed through the client
+pub struct LruUnloadPolicy<O: Object> {
+    capacity: usize,
+    /// Incremented every time the policy is run
+    clock: u64,
+    last_used: HashMap<Ptr<O>, u64>
+}
+
+impl<O: Object> LruUnloadPolicy<O> {
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            last_used: HashMap::new()
+        }
+    }
+
+}
+
+impl<O: Object> UnloadPolicy<O::Project> for LruUnloadPolicy<O> {
+
+    fn objects_to_unload(&mut self, context: &ProjectContext<O::Project>) -> Vec<AnyPtr> {
+        let list = context.obj_list::<O>();
+        list.track_usage();
+        self.clock += 1;
+
+        for ptr in list.take_used() {
+            self.last_used.insert(ptr, self.clock);
+        }
+        // Objects loaded without being accessed, such as ones loaded along with other objects, count as just used
+        for (ptr, _) in list.iter() {
+            self.last_used.entry(ptr).or_insert(self.clock);
+        }
+        self.last_used.retain(|ptr, _| list.get(*ptr).is_some());
+
+        if self.last_used.len() <= self.capacity {
+            return Vec::new();
+        }
+        let mut by_age = self.last_used.iter().map(|(ptr, last_used)| (*last_used, *ptr)).collect::<Vec<_>>();
+        by_age.sort_by_key(|(last_used, ptr)| (*last_used, ptr.key));
+        let to_unload = by_age.into_iter().take(self.last_used.len() - self.capacity).map(|(_, ptr)| ptr).collect::<Vec<_>>();
+        for ptr in &to_unload {
+            self.last_used.remove(ptr);
+        }
+        to_unload.into_iter().map(|ptr| ptr.any()).collect()
+    }
+
+}
+
+impl<P: Project> Client<P> {
+
+    /// Request that an object be unloaded on the next tick, returning it to the `None` state.
+    /// The objects loaded along with it through `LoadingPtr`s and `OwningPtr`s are unloaded too.
+    /// Objects still referenced by another loaded object or by the project stay loaded.
+    /// Collab clients wait until the server has confirmed all their operations before unloading anything.
+    pub fn request_unload<O: Object<Project = P>, T: Into<Ptr<O>>>(&self, ptr: T) {
+        let ptr = ptr.into();
+        O::list(&self.objects).to_load.borrow_mut().remove(&ptr);
+        O::list(&self.objects).to_unload.borrow_mut().insert(ptr);
+    }
+
+    /// Add a policy deciding which objects to unload on every tick
+    pub fn add_unload_policy<Policy: UnloadPolicy<P> + 'static>(&mut self, policy: Policy) {
+        self.unload_policies.push(Box::new(policy));
+    }
+
+    pub(crate) fn unload_requested_objects(&mut self) {
+        if let Some(collab) = self.kind.as_collab() {
+            if !collab.can_unload() {
+                return;
+            }
+        }
+
+        let mut roots = Vec::new();
+        for object_kind in P::OBJECTS {
+            (object_kind.take_unload_requests)(&self.objects, &mut roots);
+        }
+        for policy in &mut self.unload_policies {
+            roots.extend(policy.objects_to_unload(&ProjectContext {
+                project: &self.project,
+                objects: &self.objects
+            }));
+        }
+        if roots.is_empty() {
+            return;
+        }
+
+        let to_unload = self.find_objects_to_unload(roots, true);
+        match self.kind.as_collab() {
+            // Collab clients hold on to the objects until the server confirms it won't send any more changes to them
+            Some(collab) => {
+                for ptr in to_unload {
+                    collab.request_unload(ptr);
+                }
+            },
+            None => self.unload(&to_unload)
+        }
+    }
+
+    /// Unload objects that are no longer referenced, returning the ones that were unloaded.
+    /// If `cascade` is set, the objects loaded along with the given objects are also unloaded.
+    pub(crate) fn unload_objects(&mut self, roots: Vec<AnyPtr>, cascade: bool) -> Vec<AnyPtr> {
+        let to_unload = self.find_objects_to_unload(roots, cascade);
+        self.unload(&to_unload);
+        to_unload
+    }
+
+    fn unload(&mut self, ptrs: &[AnyPtr]) {
+        for ptr in ptrs {
+            (P::OBJECTS[ptr.obj_type() as usize].unload)(&mut self.objects, ptr.key());
+        }
+    }
+
+    /// Find the objects that can be unloaded out of the given ones, which are the ones that aren't referenced by anything that stays loaded.
+    /// If `cascade` is set, the objects loaded along with the given objects are also included.
+    fn find_objects_to_unload(&self, roots: Vec<AnyPtr>, cascade: bool) -> Vec<AnyPtr> {
+        // Find everything that was loaded along with the objects
+        let mut to_unload = Vec::new();
+        let mut keys = HashSet::new();
+        let mut queue = roots;
+        while let Some(ptr) = queue.pop() {
+            let object_kind = &P::OBJECTS[ptr.obj_type() as usize];
+            if !(object_kind.is_loaded)(&self.objects, ptr.key()) || !keys.insert(ptr.key()) {
+                continue;
+            }
+            to_unload.push(ptr);
+            if cascade {
+                queue.extend((object_kind.references)(&self.objects, ptr.key()));
+            }
+        }
+
+        // Objects referenced by the project or by a loaded object that isn't being unloaded need to stay loaded, along with everything they reference
+        let context = SerializationContext::new();
+        self.project.serialize(&context);
+        let mut referenced = context.take_serialization_requests().into_iter().map(|(_, key)| key).collect::<HashSet<_>>();
+        for object_kind in P::OBJECTS {
+            (object_kind.collect_references)(&self.objects, &keys, &mut referenced);
+        }
+        let mut keep = to_unload.iter().filter(|ptr| referenced.contains(&ptr.key())).copied().collect::<Vec<_>>();
+        let mut kept = HashSet::new();
+        while let Some(ptr) = keep.pop() {
+            if !keys.contains(&ptr.key()) || !kept.insert(ptr.key()) {
+                continue;
+            }
+            keep.extend((P::OBJECTS[ptr.obj_type() as usize].references)(&self.objects, ptr.key()));
+        }
+        to_unload.retain(|ptr| !kept.contains(&ptr.key()));
+        to_unload
+    }
+
+}
@@ -0,0 +1,30 @@
+
+use std::path::Path;
+
+use crate::Project;
+
+use super::{File, HistoryPtrs, SnapshotEntry};
+
+/// Check the integrity of a project file without loading it.
+/// If `repair` is true, any problems found are also fixed.
+/// Fails with `verter::Error::CorruptedFile` if the root of the file cannot be read, since there is no way to tell which data is still in use.
+/// Fails with a `NotFound` IO error if there is no file at `path`, instead of creating an empty project like opening it normally would.
+pub fn check_file<P: Project>(path: impl AsRef<Path>, repair: bool) -> Result<verter::CheckReport, verter::Error> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Err(verter::Error::IO(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} does not exist", path.display()))));
+    }
+    let mut file = verter::File::open(path, P::verter_config())?;
+    let (keymap, _, project_ptr, history_ptr, snapshots_ptr, _) = File::try_open(&mut file).ok_or(verter::Error::CorruptedFile)?;
+
+    let mut chains = vec![project_ptr];
+    keymap.collect_chains(&mut file, &mut chains);
+    HistoryPtrs::collect_chains(&mut file, history_ptr, &mut chains);
+    SnapshotEntry::collect_chains(&mut file, snapshots_ptr, &mut chains);
+
+    if repair {
+        file.repair(&chains)
+    } else {
+        file.check(&chains)
+    }
+}
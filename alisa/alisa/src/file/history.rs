@@ -0,0 +1,104 @@
+
+use crate::{encode_abf, parse_abf, ABFValue};
+
+use super::File;
+
+/// The pointers to the page chains of the actions in a persistent undo/redo history, from the bottom of each stack to the top
+#[derive(Default)]
+pub(crate) struct HistoryPtrs {
+    pub(crate) undo: Vec<u64>,
+    pub(crate) redo: Vec<u64>
+}
+
+impl HistoryPtrs {
+
+    fn parse_stack(data: &ABFValue, key: &str) -> Option<Vec<u64>> {
+        data.get(key)?.as_array()?.iter().map(ABFValue::as_u64).collect()
+    }
+
+    fn stack_data(stack: &[u64]) -> ABFValue {
+        ABFValue::Array(stack.iter().map(|ptr| ABFValue::U64(*ptr)).collect())
+    }
+
+    fn read(file: &mut verter::File, history_ptr: u64) -> Option<Self> {
+        let data = parse_abf(&file.read(history_ptr).ok()?)?;
+        Some(Self {
+            undo: Self::parse_stack(&data, "undo")?,
+            redo: Self::parse_stack(&data, "redo")?
+        })
+    }
+
+    fn write(&self, file: &mut verter::File, history_ptr: u64) {
+        let data = encode_abf(&ABFValue::Map(Box::new([
+            ("undo".into(), Self::stack_data(&self.undo)),
+            ("redo".into(), Self::stack_data(&self.redo)),
+        ])));
+        let _ = file.write(history_ptr, &data);
+    }
+
+    /// Collect the pointers to all the page chains used by a history
+    pub(crate) fn collect_chains(file: &mut verter::File, history_ptr: u64, chains: &mut Vec<u64>) {
+        if history_ptr == 0 {
+            return;
+        }
+        chains.push(history_ptr);
+        if let Some(history) = Self::read(file, history_ptr) {
+            chains.extend(history.undo);
+            chains.extend(history.redo);
+        }
+    }
+
+}
+
+impl File {
+
+    /// Does the file contain a persistent undo/redo history?
+    pub fn has_history(&self) -> bool {
+        self.history_ptr != 0
+    }
+
+    /// Create an empty persistent undo/redo history in the file
+    pub fn create_history(&mut self, curr_key: u64) -> Option<()> {
+        self.history_ptr = self.file.alloc().ok()?;
+        HistoryPtrs::default().write(&mut self.file, self.history_ptr);
+        self.update_root(curr_key);
+        Some(())
+    }
+
+    pub fn read_history(&mut self) -> Option<HistoryPtrs> {
+        HistoryPtrs::read(&mut self.file, self.history_ptr)
+    }
+
+    pub fn write_history(&mut self, history: &HistoryPtrs) {
+        history.write(&mut self.file, self.history_ptr);
+    }
+
+    /// Store an action in a new page chain, returning the pointer to it
+    pub fn write_action(&mut self, data: &ABFValue) -> Option<u64> {
+        let ptr = self.file.alloc().ok()?;
+        self.write(ptr, data);
+        Some(ptr)
+    }
+
+    pub fn read_action(&mut self, ptr: u64) -> Option<ABFValue> {
+        self.read(ptr)
+    }
+
+    pub fn delete_action(&mut self, ptr: u64) {
+        let _ = self.file.delete(ptr);
+    }
+
+    /// Update the pointers stored in the history after compaction
+    pub(super) fn remap_history(&mut self, compaction: &verter::Compaction) {
+        if self.history_ptr == 0 {
+            return;
+        }
+        self.history_ptr = compaction.remap(self.history_ptr);
+        let Some(mut history) = self.read_history() else { return; };
+        for ptr in history.undo.iter_mut().chain(history.redo.iter_mut()) {
+            *ptr = compaction.remap(*ptr);
+        }
+        self.write_history(&history);
+    }
+
+}
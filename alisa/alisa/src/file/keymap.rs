@@ -183,4 +183,63 @@ impl Keymap {
         self.delete_at_node(self.root_node_ptr, path.as_slice(), file);
     }
 
+    fn remap_node(&mut self, node_ptr: u64, depth: usize, compaction: &verter::Compaction, file: &mut verter::File) -> Option<()> {
+        let node = self.get_node(node_ptr, file)?;
+
+        let mut modified = false;
+        for child in node.children.iter_mut() {
+            if *child != 0 && compaction.remap(*child) != *child {
+                *child = compaction.remap(*child);
+                modified = true;
+            }
+        }
+        if modified {
+            node.save(file, node_ptr);
+        }
+
+        // The children of nodes at the bottom of the tree are object pointers, so there's nothing more to remap
+        if depth + 1 == std::mem::size_of::<u64>() {
+            return Some(());
+        }
+
+        let children = node.children;
+        for child in children {
+            if child != 0 {
+                self.remap_node(child, depth + 1, compaction, file);
+            }
+        }
+
+        Some(())
+    }
+
+    /// Update the pointers stored in the keymap after the Verter file was compacted.
+    pub fn remap(&mut self, compaction: &verter::Compaction, file: &mut verter::File) {
+        self.map.clear();
+        self.nodes.clear();
+        self.root_node_ptr = compaction.remap(self.root_node_ptr);
+        self.remap_node(self.root_node_ptr, 0, compaction, file);
+    }
+
+    fn collect_chains_at_node(node_ptr: u64, depth: usize, file: &mut verter::File, chains: &mut Vec<u64>) {
+        chains.push(node_ptr);
+        let Ok(node_data) = file.read(node_ptr) else { return; };
+        let node = KeyTreeNode::deserialize(&node_data);
+        for child in node.children {
+            if child == 0 {
+                continue;
+            }
+            // The children of nodes at the bottom of the tree are object pointers
+            if depth + 1 == std::mem::size_of::<u64>() {
+                chains.push(child);
+            } else {
+                Self::collect_chains_at_node(child, depth + 1, file, chains);
+            }
+        }
+    }
+
+    /// Collect the pointers to all the page chains used by the keymap, including the chains storing object data.
+    pub fn collect_chains(&self, file: &mut verter::File, chains: &mut Vec<u64>) {
+        Self::collect_chains_at_node(self.root_node_ptr, 0, file, chains);
+    }
+
 }
@@ -2,21 +2,43 @@ use std::path::Path;
 
 use keymap::Keymap;
 
-use crate::{encode_abf, parse_abf, ABFValue, DeserializationContext, Project, SerializationContext};
+use crate::{encode_abf, migrate, parse_abf, ABFValue, DeserializationContext, Object, Project, SerializationContext};
 
 mod keymap;
 
+mod versions;
+use versions::*;
+
+mod history;
+pub(crate) use history::*;
+
+mod snapshots;
+pub(crate) use snapshots::*;
+
+mod check;
+pub use check::*;
+
 pub(crate) struct File {
     /// The Verter file. Verter is used to allow O(1) incremental file reads/updates. For more info, see [Verter on crates.io](https://crates.io/crates/verter).
     file: verter::File,
     /// The pointer to the project data in the Verter file.
     project_ptr: u64,
-    keymap: Keymap
+    keymap: Keymap,
+    /// The pointer to the persistent undo/redo history, or 0 if the file doesn't have one
+    history_ptr: u64,
+    /// The pointer to the index of the snapshots stored in the file, or 0 if the file has none
+    snapshots_ptr: u64,
+    /// The schema versions of the data stored in the file
+    versions: SchemaVersions
 }
 
+/// Objects saved at a different schema version than the one recorded in the root start with this marker, followed by their version as a little-endian U32.
+/// The marker is a small string holding a single 0xFF byte. That isn't valid UTF-8, so no ABF data starts with it and it can't be confused with the start of an object saved without a version.
+const VERSIONED_OBJECT_MARKER: [u8; 2] = [0b10000001, 0xFF];
+
 impl File {
 
-    fn try_open(file: &mut verter::File) -> Option<(Keymap, u64, u64)> {
+    fn try_open(file: &mut verter::File) -> Option<(Keymap, u64, u64, u64, u64, SchemaVersions)> {
 
         // Load file metadata
         let root_data = file.read_root().ok()?;
@@ -25,11 +47,20 @@ impl File {
         let curr_key = root_data.get("curr_key")?.as_u64()?;
         let project_ptr = root_data.get("project_ptr")?.as_u64()?;
         let keymap_ptr = root_data.get("keymap_ptr")?.as_u64()?;
+        let versions = SchemaVersions::from_root(&root_data)?;
+        let history_ptr = match root_data.get("history_ptr") {
+            Some(ptr) => ptr.as_u64()?,
+            None => 0
+        };
+        let snapshots_ptr = match root_data.get("snapshots_ptr") {
+            Some(ptr) => ptr.as_u64()?,
+            None => 0
+        };
 
         // Initialize the keymap
         let keymap = Keymap::new(keymap_ptr); 
 
-        Some((keymap, curr_key, project_ptr))
+        Some((keymap, curr_key, project_ptr, history_ptr, snapshots_ptr, versions))
     }
 
     pub fn load_requested_objects<P: Project>(&mut self, reqs: Vec<(u16, u64)>, objects: &mut P::Objects) {
@@ -38,53 +69,96 @@ impl File {
         }
     }
 
-    fn try_load_project<P: Project>(&mut self) -> Option<(P, P::Objects)> {
-        let project_data = self.read(self.project_ptr)?;
+    fn try_load_project<P: Project>(&mut self, project_data: &ABFValue) -> Option<(P, P::Objects)> {
         let mut objects = P::Objects::default();
         let mut context = DeserializationContext::new();
-        let project = P::deserialize(&project_data, &mut context)?; 
+        let project = P::deserialize(project_data, &mut context)?; 
         self.load_requested_objects::<P>(context.load_requests, &mut objects);
         Some((project, objects))
     }
 
-    fn write_root(file: &mut verter::File, curr_key: u64, project_ptr: u64, keymap_ptr: u64) {
+    fn write_root(file: &mut verter::File, curr_key: u64, project_ptr: u64, keymap_ptr: u64, history_ptr: u64, snapshots_ptr: u64, versions: &SchemaVersions) {
         let data = encode_abf(&ABFValue::Map(Box::new([
             ("curr_key".into(), ABFValue::U64(curr_key)),
             ("project_ptr".into(), ABFValue::U64(project_ptr)),
             ("keymap_ptr".into(), ABFValue::U64(keymap_ptr)),
+            ("history_ptr".into(), ABFValue::U64(history_ptr)),
+            ("snapshots_ptr".into(), ABFValue::U64(snapshots_ptr)),
+            ("project_version".into(), versions.project_data()),
+            ("object_versions".into(), versions.object_data()),
+            ("newest_object_versions".into(), versions.newest_object_data()),
         ])));
         let _ = file.write_root(&data);
     }
 
     pub fn open<P: Project, PathRef: AsRef<Path>>(path: PathRef) -> Option<(Self, P, P::Objects, u64, bool)> {
+        Self::open_storage(verter::FileStorage::open(path).ok()?)
+    }
+
+    pub fn open_storage<P: Project, S: verter::Storage + 'static>(storage: S) -> Option<(Self, P, P::Objects, u64, bool)> {
 
-        let mut file = verter::File::open(path, P::verter_config()).ok()?; // TODO: add configuration for magic bytes
+        let mut file = verter::File::open_storage(storage, P::verter_config()).ok()?;
+
+        // Set up the file in a single transaction, so that a crash can't leave behind a partially initialized project
+        file.begin_transaction().ok()?;
 
         // Load the project
 
-        let (keymap, curr_key, project_ptr) = if let Some((keymap, curr_key, project_ptr)) = Self::try_open(&mut file) {
-            (keymap, curr_key, project_ptr)
+        let (keymap, curr_key, project_ptr, history_ptr, snapshots_ptr, versions) = if let Some((keymap, curr_key, project_ptr, history_ptr, snapshots_ptr, versions)) = Self::try_open(&mut file) {
+            // Don't touch files saved by a newer version of the application, since we don't know how to read them
+            if versions.newer_than::<P>() {
+                file.rollback_transaction();
+                return None;
+            }
+            (keymap, curr_key, project_ptr, history_ptr, snapshots_ptr, versions)
         } else {
             let curr_key = 1;
             let (keymap, keymap_ptr) = Keymap::create_empty(&mut file)?;
             let project_ptr = file.alloc().ok()?; 
+            let versions = SchemaVersions::current::<P>();
 
-            Self::write_root(&mut file, curr_key, project_ptr, keymap_ptr);
+            Self::write_root(&mut file, curr_key, project_ptr, keymap_ptr, 0, 0, &versions);
 
-            (keymap, curr_key, project_ptr)
+            (keymap, curr_key, project_ptr, 0, 0, versions)
         };
 
         let mut file = Self {
             file,
             project_ptr,
             keymap,
+            history_ptr,
+            snapshots_ptr,
+            versions
         };
 
+        // Objects of the current versions might be written from now on, so older versions of the application must refuse to open the file
+        if file.versions.include_newest::<P>() {
+            file.update_root(curr_key);
+        }
+
         if file.read_bytes(file.project_ptr).is_none() {
             file.project_ptr = file.file.alloc().ok()?;
         }
 
-        let (project, objects, new_project) = if let Some((projects, objects)) = file.try_load_project() {
+        // Projects from older versions that can't be migrated are left untouched instead of being replaced by an empty project
+        let migrated = file.versions.project != P::MIGRATIONS.len() as u32;
+        let project_data = match file.read(file.project_ptr) {
+            Some(project_data) => match migrate(project_data, file.versions.project, P::MIGRATIONS) {
+                Some(project_data) => Some(project_data),
+                None => {
+                    file.file.rollback_transaction();
+                    return None;
+                }
+            },
+            None => None
+        };
+        let loaded = project_data.as_ref().and_then(|project_data| file.try_load_project(project_data));
+        if loaded.is_none() && project_data.is_some() && migrated {
+            file.file.rollback_transaction();
+            return None;
+        }
+
+        let (project, objects, new_project) = if let Some((projects, objects)) = loaded {
             (projects, objects, false)
         } else {
             let project = P::empty();
@@ -96,9 +170,30 @@ impl File {
             (project, objects, true)
         };
 
+        // Save the project in its current format, so that it doesn't need to be migrated again
+        let project_version = P::MIGRATIONS.len() as u32;
+        if file.versions.project != project_version {
+            let project_data = project.serialize(&SerializationContext::new());
+            file.write_project(&project_data);
+            file.versions.project = project_version;
+            file.update_root(curr_key);
+        }
+
+        file.commit_transaction();
+
         Some((file, project, objects, curr_key, new_project)) 
     }
 
+    /// Begin a transaction in the Verter file. Modifications are only written to disk once the transaction is committed.
+    pub fn begin_transaction(&mut self) {
+        let _ = self.file.begin_transaction();
+    }
+
+    /// Atomically write all modifications made since the transaction began to disk.
+    pub fn commit_transaction(&mut self) {
+        let _ = self.file.commit_transaction();
+    }
+
     pub fn read_bytes(&mut self, ptr: u64) -> Option<Vec<u8>> {
         self.file.read(ptr).ok()
     }
@@ -121,7 +216,33 @@ impl File {
     }
 
     pub fn update_root(&mut self, curr_key: u64) {
-        Self::write_root(&mut self.file, curr_key, self.project_ptr, self.keymap.ptr());
+        Self::write_root(&mut self.file, curr_key, self.project_ptr, self.keymap.ptr(), self.history_ptr, self.snapshots_ptr, &self.versions);
+    }
+
+    /// Read an object's data, migrating it to the current version of the object type.
+    pub fn read_object<O: Object>(&mut self, ptr: u64) -> Option<ABFValue> {
+        let bytes = self.read_bytes(ptr)?;
+        let (version, data) = match bytes.strip_prefix(&VERSIONED_OBJECT_MARKER) {
+            Some(versioned) => {
+                let version = u32::from_le_bytes(versioned.get(0..4)?.try_into().ok()?);
+                (version, parse_abf(&versioned[4..])?)
+            },
+            None => (self.versions.object(O::TYPE_ID), parse_abf(&bytes)?)
+        };
+        migrate(data, version, O::MIGRATIONS)
+    }
+
+    /// Write an object's data, marking it with the current version of the object type if it differs from the version recorded in the file.
+    pub fn write_object<O: Object>(&mut self, ptr: u64, data: &ABFValue) {
+        let version = O::MIGRATIONS.len() as u32;
+        if version == self.versions.object(O::TYPE_ID) {
+            self.write(ptr, data);
+            return;
+        }
+        let mut bytes = VERSIONED_OBJECT_MARKER.to_vec();
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes.extend_from_slice(&encode_abf(data));
+        self.write_bytes(ptr, &bytes);
     }
 
     pub fn get_ptr(&mut self, key: u64) -> Option<u64> {
@@ -132,4 +253,28 @@ impl File {
         self.keymap.delete(key, &mut self.file);
     }
 
+    /// Compact the Verter file, reclaiming the space left behind by deleted objects.
+    /// Returns the number of bytes reclaimed.
+    pub fn compact(&mut self, curr_key: u64) -> Option<u64> {
+        self.file.begin_transaction().ok()?;
+
+        let compaction = match self.file.compact() {
+            Ok(compaction) => compaction,
+            Err(_) => {
+                self.file.rollback_transaction();
+                return None;
+            }
+        };
+
+        // Compaction might have moved the data, so we need to update the pointers stored in the file
+        self.keymap.remap(&compaction, &mut self.file);
+        self.project_ptr = compaction.remap(self.project_ptr);
+        self.remap_history(&compaction);
+        self.remap_snapshots(&compaction, curr_key);
+        self.update_root(curr_key);
+
+        self.file.commit_transaction().ok()?;
+        Some(compaction.reclaimed_bytes)
+    }
+
 }
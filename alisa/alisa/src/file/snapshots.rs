@@ -0,0 +1,211 @@
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{migrate, parse_abf, ABFValue, AnyPtr, Project, Snapshot, SnapshotInfo};
+
+use super::{File, Keymap, SchemaVersions};
+
+/// A snapshot listed in the snapshot index of a file, along with the pointer to the page chain storing its data
+pub(crate) struct SnapshotEntry {
+    pub(crate) info: SnapshotInfo,
+    pub(crate) ptr: u64
+}
+
+impl SnapshotEntry {
+
+    fn parse(data: &ABFValue) -> Option<Self> {
+        Some(Self {
+            info: SnapshotInfo {
+                id: data.get("id")?.as_u64()?,
+                name: data.get("name")?.as_string()?.to_owned(),
+                created: data.get("created")?.as_u64()?
+            },
+            ptr: data.get("ptr")?.as_u64()?
+        })
+    }
+
+    fn data(&self) -> ABFValue {
+        ABFValue::Map(Box::new([
+            ("id".into(), ABFValue::U64(self.info.id)),
+            ("name".into(), ABFValue::Str(self.info.name.clone())),
+            ("created".into(), ABFValue::U64(self.info.created)),
+            ("ptr".into(), ABFValue::U64(self.ptr)),
+        ]))
+    }
+
+    fn read_index(file: &mut verter::File, snapshots_ptr: u64) -> Option<Vec<Self>> {
+        if snapshots_ptr == 0 {
+            return Some(Vec::new());
+        }
+        let data = parse_abf(&file.read(snapshots_ptr).ok()?)?;
+        data.as_array()?.iter().map(Self::parse).collect()
+    }
+
+    /// Collect the pointers to all the page chains used by the snapshots of a file
+    pub(crate) fn collect_chains(file: &mut verter::File, snapshots_ptr: u64, chains: &mut Vec<u64>) {
+        if snapshots_ptr == 0 {
+            return;
+        }
+        chains.push(snapshots_ptr);
+        if let Some(index) = Self::read_index(file, snapshots_ptr) {
+            chains.extend(index.iter().map(|entry| entry.ptr));
+        }
+    }
+
+}
+
+/// Collect every object pointer in some serialized data
+fn collect_obj_ptrs(data: &ABFValue, ptrs: &mut Vec<AnyPtr>) {
+    match data {
+        ABFValue::ObjPtr(obj_type, key) => ptrs.push(AnyPtr::new(*obj_type, *key)),
+        ABFValue::Array(values) => {
+            for value in values {
+                collect_obj_ptrs(value, ptrs);
+            }
+        },
+        ABFValue::Map(values) => {
+            for (_, value) in values {
+                collect_obj_ptrs(value, ptrs);
+            }
+        },
+        ABFValue::IndexedEnum(_, value) | ABFValue::NamedEnum(_, value) => collect_obj_ptrs(value, ptrs),
+        _ => {}
+    }
+}
+
+impl File {
+
+    pub fn read_snapshot_index(&mut self) -> Vec<SnapshotEntry> {
+        SnapshotEntry::read_index(&mut self.file, self.snapshots_ptr).unwrap_or_default()
+    }
+
+    pub fn write_snapshot_index(&mut self, index: &[SnapshotEntry], curr_key: u64) -> Option<()> {
+        if self.snapshots_ptr == 0 {
+            self.snapshots_ptr = self.file.alloc().ok()?;
+            self.update_root(curr_key);
+        }
+        let data = ABFValue::Array(index.iter().map(SnapshotEntry::data).collect());
+        self.write(self.snapshots_ptr, &data);
+        Some(())
+    }
+
+    /// Take a snapshot of the project as it is saved in the file.
+    /// Every object pointed to by the project or by another object in the snapshot is included, whatever the kind of pointer.
+    pub fn capture_snapshot<P: Project>(&mut self, curr_key: u64) -> Option<Snapshot<P>> {
+        let project = migrate(self.read(self.project_ptr)?, self.versions.project, P::MIGRATIONS)?;
+
+        let mut objects = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut to_visit = Vec::new();
+        collect_obj_ptrs(&project, &mut to_visit);
+        while let Some(ptr) = to_visit.pop() {
+            if ptr.key() == 0 || !visited.insert(ptr) {
+                continue;
+            }
+            let Some(object_kind) = P::OBJECTS.get(ptr.obj_type() as usize) else {
+                continue;
+            };
+            let Some(data) = (object_kind.read_from_file)(self, ptr.key()) else {
+                continue;
+            };
+            collect_obj_ptrs(&data, &mut to_visit);
+            objects.insert(ptr, data);
+        }
+
+        Some(Snapshot::new(project, objects, curr_key))
+    }
+
+    /// Store a snapshot in a new page chain, returning the pointer to it.
+    /// The schema versions are stored along with the data, so that snapshots taken by older versions of the application can be migrated.
+    pub fn write_snapshot<P: Project>(&mut self, snapshot: &Snapshot<P>) -> Option<u64> {
+        let versions = SchemaVersions::current::<P>();
+        let objects = snapshot.objects.iter().map(|(ptr, data)| ABFValue::Array(Box::new([
+            ABFValue::ObjPtr(ptr.obj_type(), ptr.key()),
+            data.clone()
+        ]))).collect();
+        let data = ABFValue::Map(Box::new([
+            ("curr_key".into(), ABFValue::U64(snapshot.curr_key)),
+            ("project_version".into(), versions.project_data()),
+            ("object_versions".into(), versions.object_data()),
+            ("project".into(), snapshot.project.clone()),
+            ("objects".into(), ABFValue::Array(objects)),
+        ]));
+        let ptr = self.file.alloc().ok()?;
+        self.write(ptr, &data);
+        Some(ptr)
+    }
+
+    pub fn read_snapshot<P: Project>(&mut self, ptr: u64) -> Option<Snapshot<P>> {
+        let data = self.read(ptr)?;
+        let versions = SchemaVersions::from_root(&data)?;
+        if versions.newer_than::<P>() {
+            return None;
+        }
+
+        let curr_key = data.get("curr_key")?.as_u64()?;
+        let project = migrate(data.get("project")?.clone(), versions.project, P::MIGRATIONS)?;
+        let mut objects = HashMap::new();
+        for object in data.get("objects")?.as_array()? {
+            let [ptr, object_data] = object.as_array()? else {
+                return None;
+            };
+            let (obj_type, key) = ptr.as_obj_ptr()?;
+            // Skip object types that no longer exist
+            let Some(object_kind) = P::OBJECTS.get(obj_type as usize) else {
+                continue;
+            };
+            let object_data = (object_kind.migrate)(object_data.clone(), versions.object(obj_type))?;
+            objects.insert(AnyPtr::new(obj_type, key), object_data);
+        }
+
+        Some(Snapshot::new(project, objects, curr_key))
+    }
+
+    pub fn delete_snapshot(&mut self, ptr: u64) {
+        let _ = self.file.delete(ptr);
+    }
+
+    /// Create a new project from a snapshot.
+    /// Fails if the storage already contains a project, so that nothing is overwritten.
+    pub fn create_from_snapshot<P: Project, S: verter::Storage + 'static>(storage: S, snapshot: &Snapshot<P>) -> Option<()> {
+        let mut file = verter::File::open_storage(storage, P::verter_config()).ok()?;
+        if Self::try_open(&mut file).is_some() {
+            return None;
+        }
+
+        file.begin_transaction().ok()?;
+
+        let (keymap, _) = Keymap::create_empty(&mut file)?;
+        let project_ptr = file.alloc().ok()?;
+        let mut file = Self {
+            file,
+            project_ptr,
+            keymap,
+            history_ptr: 0,
+            snapshots_ptr: 0,
+            versions: SchemaVersions::current::<P>()
+        };
+
+        file.write_project(&snapshot.project);
+        for (ptr, data) in &snapshot.objects {
+            (P::OBJECTS[ptr.obj_type() as usize].write_to_file)(&mut file, ptr.key(), data);
+        }
+        file.update_root(snapshot.curr_key);
+
+        file.file.commit_transaction().ok()
+    }
+
+    /// Update the pointers stored in the snapshot index after compaction
+    pub(super) fn remap_snapshots(&mut self, compaction: &verter::Compaction, curr_key: u64) {
+        if self.snapshots_ptr == 0 {
+            return;
+        }
+        self.snapshots_ptr = compaction.remap(self.snapshots_ptr);
+        let mut index = self.read_snapshot_index();
+        for entry in &mut index {
+            entry.ptr = compaction.remap(entry.ptr);
+        }
+        self.write_snapshot_index(&index, curr_key);
+    }
+
+}
@@ -0,0 +1,97 @@
+
+use crate::{ABFValue, Project};
+
+/// The schema versions of the data stored in a project file
+pub(crate) struct SchemaVersions {
+    /// The version of the project data
+    pub(crate) project: u32,
+    /// The version of the objects stored without an explicit version, indexed by object type id
+    pub(crate) objects: Vec<u32>,
+    /// The newest version of each object type the file might contain, indexed by object type id.
+    /// Objects marked with their own version can be newer than the ones in `objects`, so this records the newest version of the application that opened the file.
+    pub(crate) newest_objects: Vec<u32>
+}
+
+impl SchemaVersions {
+
+    /// The versions of the schema of the project type `P`
+    pub(crate) fn current<P: Project>() -> Self {
+        let mut objects = Vec::new();
+        for object_kind in P::OBJECTS {
+            let type_id = object_kind.object_type_id as usize;
+            if objects.len() <= type_id {
+                objects.resize(type_id + 1, 0);
+            }
+            objects[type_id] = object_kind.version;
+        }
+        Self {
+            project: P::MIGRATIONS.len() as u32,
+            newest_objects: objects.clone(),
+            objects
+        }
+    }
+
+    /// Read the versions from the root data of a file.
+    /// Files created before schema versions were introduced have no versions recorded, so everything in them is at version 0.
+    pub(crate) fn from_root(root_data: &ABFValue) -> Option<Self> {
+        let project = match root_data.get("project_version") {
+            Some(version) => version.as_u32()?,
+            None => 0
+        };
+        let objects = match root_data.get("object_versions") {
+            Some(versions) => versions.as_array()?.iter().map(ABFValue::as_u32).collect::<Option<Vec<_>>>()?,
+            None => Vec::new()
+        };
+        let newest_objects = match root_data.get("newest_object_versions") {
+            Some(versions) => versions.as_array()?.iter().map(ABFValue::as_u32).collect::<Option<Vec<_>>>()?,
+            None => objects.clone()
+        };
+        Some(Self {
+            project,
+            objects,
+            newest_objects
+        })
+    }
+
+    pub(crate) fn object(&self, type_id: u16) -> u32 {
+        self.objects.get(type_id as usize).copied().unwrap_or(0)
+    }
+
+    /// Is anything in the file from a newer version of the schema than the one of `P`?
+    pub(crate) fn newer_than<P: Project>(&self) -> bool {
+        let current = Self::current::<P>();
+        self.project > current.project ||
+            self.objects.iter().enumerate().any(|(type_id, version)| *version > current.object(type_id as u16)) ||
+            self.newest_objects.iter().enumerate().any(|(type_id, version)| *version > current.object(type_id as u16))
+    }
+
+    /// Record that objects from the schema of `P` might be written to the file.
+    /// Returns true if the newest object versions changed.
+    pub(crate) fn include_newest<P: Project>(&mut self) -> bool {
+        let current = Self::current::<P>();
+        let mut changed = false;
+        for (type_id, version) in current.objects.iter().enumerate() {
+            if self.newest_objects.len() <= type_id {
+                self.newest_objects.resize(type_id + 1, 0);
+            }
+            if self.newest_objects[type_id] < *version {
+                self.newest_objects[type_id] = *version;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub(crate) fn project_data(&self) -> ABFValue {
+        ABFValue::U32(self.project)
+    }
+
+    pub(crate) fn object_data(&self) -> ABFValue {
+        ABFValue::Array(self.objects.iter().map(|version| ABFValue::U32(*version)).collect())
+    }
+
+    pub(crate) fn newest_object_data(&self) -> ABFValue {
+        ABFValue::Array(self.newest_objects.iter().map(|version| ABFValue::U32(*version)).collect())
+    }
+
+}
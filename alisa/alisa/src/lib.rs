@@ -22,13 +22,23 @@ pub use action::*;
 
 mod file;
 pub(crate) use file::*;
+pub use file::check_file;
 
 mod serialization;
 pub use serialization::*;
 
+mod migration;
+pub use migration::*;
+
 mod tree;
 pub use tree::*;
 
+mod snapshot;
+pub use snapshot::*;
+
+mod simulation;
+pub use simulation::*;
+
 pub use verter;
 pub use alisa_proc_macros::*;
 pub use paste;
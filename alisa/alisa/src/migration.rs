@@ -0,0 +1,16 @@
+
+use crate::ABFValue;
+
+/// A function upgrading serialized data from one schema version to the next.
+/// Returns `None` if the data cannot be upgraded.
+pub type Migration = fn(ABFValue) -> Option<ABFValue>;
+
+/// Upgrade data stored at schema version `version` to the current version.
+/// Migration `i` upgrades data from version `i` to version `i + 1`, so the current version is the number of migrations.
+/// Returns `None` if a migration fails or if the data is from a newer version than the current one.
+pub fn migrate(mut data: ABFValue, version: u32, migrations: &[Migration]) -> Option<ABFValue> {
+    for migration in migrations.get(version as usize..)? {
+        data = migration(data)?;
+    }
+    Some(data)
+}
@@ -1,5 +1,5 @@
 
-use crate::{Project, Serializable};
+use crate::{Migration, Project, Serializable};
 
 mod ptr;
 pub use ptr::*;
@@ -15,6 +15,9 @@ pub trait Object: Sized + Clone + Serializable + 'static + Send + Sync{
     type Project: Project;
 
     const TYPE_ID: u16;
+    /// Migrations upgrading objects stored by older versions of the schema.
+    /// Migration `i` upgrades an object from version `i` to version `i + 1`, so the current version is the number of migrations.
+    const MIGRATIONS: &'static [Migration] = &[];
 
     fn list(objects: &<Self::Project as Project>::Objects) -> &ObjList<Self>;
     fn list_mut(objects: &mut <Self::Project as Project>::Objects) -> &mut ObjList<Self>;
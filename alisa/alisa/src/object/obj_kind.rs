@@ -1,12 +1,14 @@
 
 use std::{any::TypeId, collections::HashSet};
 
-use crate::{ABFValue, AnyPtr, Collab, Delta, DeserializationContext, File, Message, Project, SerializationContext};
+use crate::{migrate, ABFValue, AnyPtr, Collab, Delta, DeserializationContext, File, Message, Project, Recorder, SerializationContext};
 
 use super::{ObjRef, Object, Ptr};
 
 pub struct ObjectKind<P: Project> {
     pub(crate) object_type_id: u16,
+    /// The current schema version of the object type
+    pub(crate) version: u32,
     pub(crate) clear_modifications: fn(&mut P::Objects),
     pub(crate) clear_user_modified: fn(&mut P::Objects),
     pub(crate) save_modifications: fn(&mut File, objects: &mut P::Objects),
@@ -14,9 +16,29 @@ pub struct ObjectKind<P: Project> {
     pub(crate) collab_load_objects: fn(&mut P::Objects, &mut Collab<P>),
     pub(crate) load_object: fn(&mut File, &mut P::Objects, u64),
     pub(crate) load_object_from_message: fn(&mut P::Objects, u64, &ABFValue),
-    pub(crate) load_failed: fn(&mut P::Objects, u64),
+    pub(crate) replace_object_from_message: fn(&mut P::Objects, u64, Option<&ABFValue>),
+    pub(crate) reset_loading: fn(&mut P::Objects),
     pub(crate) serialize_object: fn(&mut P::Objects, u64, &SerializationContext) -> Option<ABFValue>,
     pub(crate) delete: fn(&mut P::Objects, u64, &mut Vec<AnyPtr>, delta: &mut Option<&mut Delta<P>>),
+    pub(crate) is_loaded: fn(&P::Objects, u64) -> bool,
+    pub(crate) unload: fn(&mut P::Objects, u64) -> bool,
+    pub(crate) take_unload_requests: fn(&P::Objects, &mut Vec<AnyPtr>),
+    /// The objects loaded along with an object, through `LoadingPtr`s and `OwningPtr`s
+    pub(crate) references: fn(&P::Objects, u64) -> Vec<AnyPtr>,
+    /// Collect the keys of objects loaded along with any loaded object of this type, skipping the objects in the exclusion set
+    pub(crate) collect_references: fn(&P::Objects, &HashSet<u64>, &mut HashSet<u64>),
+    /// The keys of the loaded objects of this type, in ascending order
+    pub(crate) loaded_keys: fn(&P::Objects) -> Vec<u64>,
+    pub(crate) request_load: fn(&P::Objects, u64),
+    /// Read an object's data straight from the file, migrated to the current version of the object type
+    pub(crate) read_from_file: fn(&mut File, u64) -> Option<ABFValue>,
+    pub(crate) write_to_file: fn(&mut File, u64, &ABFValue),
+    /// Upgrade an object's data saved at the given version to the current version
+    pub(crate) migrate: fn(ABFValue, u32) -> Option<ABFValue>,
+    /// The data of an object for a snapshot: `Some(None)` if it was deleted, `None` if its state is unknown
+    pub(crate) snapshot_data: fn(&P::Objects, u64) -> Option<Option<ABFValue>>,
+    /// Set an object to the given data, creating it if needed, or delete it if there is no data
+    pub(crate) restore: fn(&mut Recorder<P>, u64, Option<&ABFValue>) -> bool,
 
     #[cfg(debug_assertions)]
     pub(crate) type_id: fn() -> TypeId,
@@ -25,8 +47,10 @@ pub struct ObjectKind<P: Project> {
 fn load_object<O: Object>(file: &mut File, objects: &mut <O::Project as Project>::Objects, key: u64) {
     let ptr = Ptr::from_key(key);
 
-    // If the object is already loaded, skip loading it
-    if O::list(objects).get(ptr).is_some() {
+    // If the object is already loaded, skip loading it.
+    // Objects deleted since the last tick are skipped too, since the deletion hasn't reached the file yet.
+    let state = O::list(objects).get_ref(ptr);
+    if state.is_loaded() || state.is_deleted() {
         return;
     }
 
@@ -34,7 +58,7 @@ fn load_object<O: Object>(file: &mut File, objects: &mut <O::Project as Project>
         O::list_mut(objects).mark_deleted(ptr);
         return;
     };
-    let Some(object_data) = file.read(file_ptr) else {
+    let Some(object_data) = file.read_object::<O>(file_ptr) else {
         O::list_mut(objects).mark_deleted(ptr);
         return;
     };
@@ -44,9 +68,10 @@ fn load_object<O: Object>(file: &mut File, objects: &mut <O::Project as Project>
         return;
     };
 
-    file.load_requested_objects::<O::Project>(context.load_requests, objects);
-
+    // Insert the object before loading the objects it references, so that reference cycles don't recurse forever
     O::list_mut(objects).insert_loaded(ptr, object);
+
+    file.load_requested_objects::<O::Project>(context.load_requests, objects);
 }
 
 impl<P: Project> ObjectKind<P> {
@@ -54,6 +79,7 @@ impl<P: Project> ObjectKind<P> {
     pub const fn from<O: Object<Project = P>>() -> Self {
         Self {
             object_type_id: O::TYPE_ID,
+            version: O::MIGRATIONS.len() as u32,
             clear_modifications: |objects| {
                 O::list_mut(objects).modified.clear();
                 O::list_mut(objects).to_delete.clear();
@@ -66,7 +92,7 @@ impl<P: Project> ObjectKind<P> {
                     if let Some(object) = O::list(objects).get(*modified) {
                         let object_data = object.serialize(&SerializationContext::new());
                         if let Some(ptr) = file.get_ptr(modified.key) {
-                            file.write(ptr, &object_data);
+                            file.write_object::<O>(ptr, &object_data);
                         }
                     }
                 }
@@ -101,8 +127,15 @@ impl<P: Project> ObjectKind<P> {
                     O::list_mut(objects).insert_loaded(Ptr::from_key(key), obj);
                 }
             },
-            load_failed: |objects, key| {
-                O::list_mut(objects).mark_deleted(Ptr::from_key(key));
+            replace_object_from_message: |objects, key, data| {
+                let ptr = Ptr::from_key(key);
+                match data.and_then(|data| O::deserialize(data, &mut DeserializationContext::new())) {
+                    Some(obj) => O::list_mut(objects).replace(ptr, obj),
+                    None => O::list_mut(objects).mark_deleted(ptr),
+                }
+            },
+            reset_loading: |objects| {
+                O::list_mut(objects).reset_loading();
             },
             serialize_object: |objects, key, context| {
                 O::list(objects).get(Ptr::from_key(key)).map(|data| data.serialize(context))
@@ -117,6 +150,87 @@ impl<P: Project> ObjectKind<P> {
                     }
                 }
             },
+            is_loaded: |objects, key| {
+                O::list(objects).get(Ptr::from_key(key)).is_some()
+            },
+            unload: |objects, key| {
+                O::list_mut(objects).unload(Ptr::from_key(key))
+            },
+            take_unload_requests: |objects, requests| {
+                let to_unload = std::mem::take(&mut *O::list(objects).to_unload.borrow_mut());
+                requests.extend(to_unload.into_iter().map(|ptr| ptr.any()));
+            },
+            references: |objects, key| {
+                let Some(object) = O::list(objects).get(Ptr::from_key(key)) else {
+                    return Vec::new();
+                };
+                let context = SerializationContext::new();
+                object.serialize(&context);
+                context.take_serialization_requests().into_iter().map(|(obj_type, key)| AnyPtr::new(obj_type, key)).collect()
+            },
+            collect_references: |objects, exclude, references| {
+                for (ptr, object) in O::list(objects).iter() {
+                    if exclude.contains(&ptr.key) {
+                        continue;
+                    }
+                    let context = SerializationContext::new();
+                    object.serialize(&context);
+                    references.extend(context.take_serialization_requests().into_iter().map(|(_, key)| key));
+                }
+            },
+            loaded_keys: |objects| {
+                let mut keys = O::list(objects).iter().map(|(ptr, _)| ptr.key).collect::<Vec<_>>();
+                keys.sort();
+                keys
+            },
+            request_load: |objects, key| {
+                let ptr = Ptr::from_key(key);
+                O::list(objects).to_unload.borrow_mut().remove(&ptr);
+                O::list(objects).to_load.borrow_mut().insert(ptr);
+            },
+            read_from_file: |file, key| {
+                let ptr = file.get_ptr(key)?;
+                file.read_object::<O>(ptr)
+            },
+            write_to_file: |file, key, data| {
+                if let Some(ptr) = file.get_ptr(key) {
+                    file.write_object::<O>(ptr, data);
+                }
+            },
+            migrate: |data, version| {
+                migrate(data, version, O::MIGRATIONS)
+            },
+            snapshot_data: |objects, key| {
+                match O::list(objects).get_ref(Ptr::from_key(key)) {
+                    ObjRef::None | ObjRef::Loading => None,
+                    ObjRef::Loaded(object) => Some(Some(object.serialize(&SerializationContext::new()))),
+                    ObjRef::Deleted => Some(None),
+                }
+            },
+            restore: |recorder, key, data| {
+                let ptr = Ptr::<O>::from_key(key);
+                let state = recorder.context.obj_list().get_ref(ptr);
+                let (loaded, deleted) = (state.is_loaded(), state.is_deleted());
+                match data {
+                    Some(data) => {
+                        let Some(object) = O::deserialize(data, &mut DeserializationContext::new()) else {
+                            return false;
+                        };
+                        if !loaded {
+                            return recorder.add_obj(ptr, object);
+                        }
+                        let Some(current) = recorder.get_obj_mut(ptr) else {
+                            return false;
+                        };
+                        *current = object;
+                        true
+                    },
+                    // The objects owned by a deleted object are restored on their own, so they aren't deleted along with it
+                    None if loaded => recorder.delete_single_obj(ptr).is_some(),
+                    // An object the client doesn't know about might still exist, so only objects known to be gone can stay deleted
+                    None => deleted
+                }
+            },
             #[cfg(debug_assertions)]
             type_id: || TypeId::of::<O>(),
         }
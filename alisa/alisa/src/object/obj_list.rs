@@ -1,4 +1,4 @@
-use std::{any::{type_name, TypeId}, cell::RefCell, collections::{HashMap, HashSet}};
+use std::{any::{type_name, TypeId}, cell::{Cell, RefCell}, collections::{HashMap, HashSet}};
 
 use crate::Project;
 
@@ -95,6 +95,12 @@ pub struct ObjList<Obj: Object> {
     pub(crate) to_delete: HashSet<Ptr<Obj>>,
     /// Ptrs to objects that the user requested to load
     pub(crate) to_load: RefCell<HashSet<Ptr<Obj>>>,
+    /// Ptrs to objects that the user requested to unload
+    pub(crate) to_unload: RefCell<HashSet<Ptr<Obj>>>,
+    /// Should the objects the user accesses through the client be recorded in `used`?
+    track_usage: Cell<bool>,
+    /// Objects the user accessed through the client since usage was last taken. Used by unload policies.
+    used: RefCell<HashSet<Ptr<Obj>>>
 }
 
 impl<Obj: Object> ObjList<Obj> {
@@ -103,11 +109,10 @@ impl<Obj: Object> ObjList<Obj> {
         if ptr.is_null() {
             return false;
         }
-        match self.objs.get(&ptr) {
-            Some(ObjState::Loading) | Some(ObjState::Loaded(_)) => {
-                return false;
-            },
-            _ => {}
+        // An object that is still loading can be created by an operation from the server.
+        // The server handles messages in order, so its answer to the load request either came before the operation or will find the object already loaded.
+        if let Some(ObjState::Loaded(_)) = self.objs.get(&ptr) {
+            return false;
         }
         self.objs.insert(ptr, ObjState::Loaded(obj));
         self.modified.insert(ptr);
@@ -128,10 +133,56 @@ impl<Obj: Object> ObjList<Obj> {
         self.objs.insert(ptr, ObjState::Loaded(obj));
     }
 
+    /// Replace an object with a copy received from the server, whether or not it was loaded
+    pub(crate) fn replace(&mut self, ptr: Ptr<Obj>, obj: Obj) {
+        if ptr.is_null() {
+            return;
+        }
+        self.objs.insert(ptr, ObjState::Loaded(obj));
+        self.user_modified.insert(ptr);
+    }
+
     pub(crate) fn mark_loading(&mut self, ptr: Ptr<Obj>) {
         self.objs.insert(ptr, ObjState::Loading);
     }
 
+    /// Queue the objects that are still loading to be requested again
+    pub(crate) fn reset_loading(&mut self) {
+        let loading = self.objs.iter().filter(|(_, state)| matches!(state, ObjState::Loading)).map(|(ptr, _)| *ptr).collect::<Vec<_>>();
+        for ptr in loading {
+            self.objs.remove(&ptr);
+            self.to_load.borrow_mut().insert(ptr);
+        }
+    }
+
+    /// Drop a loaded object, returning it to the `None` state. Returns false if the object wasn't loaded.
+    pub(crate) fn unload(&mut self, ptr: Ptr<Obj>) -> bool {
+        if !matches!(self.objs.get(&ptr), Some(ObjState::Loaded(_))) {
+            return false;
+        }
+        self.objs.remove(&ptr);
+        self.user_modified.remove(&ptr);
+        self.used.borrow_mut().remove(&ptr);
+        true
+    }
+
+    pub(crate) fn mark_used(&self, ptr: Ptr<Obj>) {
+        if self.track_usage.get() {
+            self.used.borrow_mut().insert(ptr);
+        }
+    }
+
+    /// Start recording which objects the user accesses through the client
+    pub fn track_usage(&self) {
+        self.track_usage.set(true);
+    }
+
+    /// Take the set of objects the user accessed through the client since the last call.
+    /// Only recorded after `track_usage` is called.
+    pub fn take_used(&self) -> HashSet<Ptr<Obj>> {
+        self.used.take()
+    }
+
     pub(crate) fn mark_deleted(&mut self, ptr: Ptr<Obj>) {
         self.objs.insert(ptr, ObjState::Deleted);
     }
@@ -166,6 +217,11 @@ impl<Obj: Object> ObjList<Obj> {
         self.objs.get_mut(&ptr)?.as_mut()
     }
 
+    /// Iterate over the objects that are currently loaded, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Ptr<Obj>, &Obj)> {
+        self.objs.iter().filter_map(|(ptr, state)| Some((*ptr, state.as_ref()?)))
+    }
+
 }
 
 impl<O: Object> Default for ObjList<O> {
@@ -188,6 +244,9 @@ impl<O: Object> Default for ObjList<O> {
             user_modified: HashSet::new(),
             to_delete: HashSet::new(),
             to_load: RefCell::new(HashSet::new()),
+            to_unload: RefCell::new(HashSet::new()),
+            track_usage: Cell::new(false),
+            used: RefCell::new(HashSet::new())
         }
     }
 
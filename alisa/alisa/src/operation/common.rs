@@ -22,6 +22,10 @@ macro_rules! project_set_property_operation {
                     true
                 }
 
+                fn supersedes(&self, _earlier: &Self) -> bool {
+                    true
+                }
+
             }
 
             impl ::alisa::InvertibleOperation for [< Set $property:camel >] {
@@ -66,6 +70,10 @@ macro_rules! object_set_property_operation {
                     true
                 }
 
+                fn supersedes(&self, earlier: &Self) -> bool {
+                    self.ptr == earlier.ptr
+                }
+
             }
 
             impl ::alisa::InvertibleOperation for [< Set $object:camel $property:camel >] {
@@ -17,6 +17,11 @@ impl<P: Project> Delta<P> {
         self.deltas.push(Box::new(delta));
     }
 
+    /// Add the changes of a later delta, so that undoing this delta undoes both
+    pub fn append(&mut self, later: Delta<P>) {
+        self.deltas.extend(later.deltas);
+    }
+
     pub fn undo(&self, context: &mut ProjectContextMut<P>) {
         for delta in self.deltas.iter().rev() {
             delta(context);
@@ -1,7 +1,7 @@
 
-use std::any::{type_name, Any, TypeId};
+use std::{any::{type_name, Any, TypeId}, collections::HashSet};
 
-use crate::{ABFValue, DeserializationContext, Project, Serializable, SerializationContext};
+use crate::{ABFValue, AnyPtr, DeserializationContext, InvertibleOperation, InvertibleOperationDyn, Project, Serializable, SerializationContext};
 
 mod common;
 
@@ -11,6 +11,9 @@ pub(crate) use delta::*;
 mod recorder;
 pub use recorder::*;
 
+mod table;
+pub(crate) use table::*;
+
 /// Enum that indicates where an operation originated
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum OperationSource {
@@ -20,6 +23,67 @@ pub enum OperationSource {
     Server
 }
 
+/// The result of performing an operation received in a message
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OperationOutcome {
+    Performed,
+    Failed,
+    /// The operation tried to modify something the client that sent it isn't allowed to, so it was undone
+    Denied
+}
+
+/// What performing an operation touched
+#[derive(Default, Clone)]
+pub(crate) struct Touched {
+    /// Was the project modified?
+    pub(crate) project: bool,
+    /// The objects that were modified, created or deleted
+    pub(crate) objects: HashSet<AnyPtr>,
+    /// The objects that were created
+    pub(crate) created: HashSet<AnyPtr>,
+    /// The objects that were read or modified. A client missing one of them can't perform the operation itself.
+    pub(crate) used: HashSet<AnyPtr>
+}
+
+impl Touched {
+
+    /// Add everything another operation touched
+    pub(crate) fn extend(&mut self, other: &Touched) {
+        self.project |= other.project;
+        self.objects.extend(other.objects.iter().copied());
+        self.created.extend(other.created.iter().copied());
+        self.used.extend(other.used.iter().copied());
+    }
+
+}
+
+/// What an operation read or modified, used to tell whether operations can be reordered
+#[derive(Default)]
+pub(crate) struct Access {
+    /// Did the operation use the project?
+    pub(crate) project: bool,
+    pub(crate) objects: HashSet<AnyPtr>,
+    /// Did the operation get access to the whole project, through `Recorder::context`?
+    pub(crate) everything: bool
+}
+
+impl Access {
+
+    pub(crate) fn overlaps(&self, other: &Access) -> bool {
+        (self.everything && (other.everything || other.project || !other.objects.is_empty())) ||
+        (other.everything && (self.project || !self.objects.is_empty())) ||
+        (self.project && other.project) ||
+        !self.objects.is_disjoint(&other.objects)
+    }
+
+    pub(crate) fn extend(&mut self, other: &Access) {
+        self.project |= other.project;
+        self.objects.extend(other.objects.iter().copied());
+        self.everything |= other.everything;
+    }
+
+}
+
 /// An operation performed on the project. 
 /// Operations can be inverted for undo/redo. 
 /// Note that when collaborating, undoing an operation and redoing might not return to the original state of the project. 
@@ -34,6 +98,10 @@ pub trait Operation: Sized + Any + Serializable + Send + Sync {
     /// If the operation encoutered an error, it will not be broadcast to other clients.
     fn perform(&self, recorder: &mut Recorder<'_, Self::Project>) -> bool; 
 
+    /// Does performing this operation overwrite everything `earlier` changed, like setting the same property of the same object?
+    /// If so, a collab client only sends this operation when `earlier` hasn't been sent to the server yet.
+    fn supersedes(&self, _earlier: &Self) -> bool { false }
+
     /// Information about the operation used for debugging
     #[cfg(debug_assertions)]
     fn debug_info(&self) -> String { String::new() }
@@ -47,6 +115,8 @@ pub trait OperationDyn: Send + Sync {
     fn perform(&self, recorder: &mut Recorder<'_, Self::Project>) -> bool;
     fn name(&self) -> &'static str;
     fn serialize(&self) -> ABFValue;
+    fn supersedes(&self, earlier: &dyn OperationDyn<Project = Self::Project>) -> bool;
+    fn as_any(&self) -> &dyn Any;
 
     #[cfg(debug_assertions)]
     fn verify_operation_type(&self);
@@ -71,6 +141,14 @@ impl<O: Operation + Serializable> OperationDyn for O {
         self.serialize(&SerializationContext::new())
     }
 
+    fn supersedes(&self, earlier: &dyn OperationDyn<Project = Self::Project>) -> bool {
+        earlier.as_any().downcast_ref::<Self>().is_some_and(|earlier| Operation::supersedes(self, earlier))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     #[cfg(debug_assertions)]
     fn verify_operation_type(&self) {
         use crate::Client;
@@ -85,11 +163,15 @@ impl<O: Operation + Serializable> OperationDyn for O {
 
 }
 
+type DeserializeInvertible<P> = fn(&ABFValue) -> Option<Box<dyn InvertibleOperationDyn<Project = P>>>;
+
 /// A kind of operation, stored as a struct in `Project::OPERATIONS`.
 pub struct OperationKind<P: Project> {
     pub(crate) name: &'static str,
     pub(crate) deserialize: fn(&ABFValue) -> Option<Box<dyn Any>>,
     pub(crate) perform: fn(Box<dyn Any>, &mut Recorder<'_, P>) -> bool,
+    /// Deserialize the operation as part of an action. Only available for operations registered using `OperationKind::from_invertible`.
+    pub(crate) deserialize_invertible: Option<DeserializeInvertible<P>>,
 
     #[cfg(debug_assertions)]
     pub(crate) type_id: fn() -> TypeId,
@@ -109,6 +191,7 @@ impl<P: Project> OperationKind<P> {
                 let Ok(operation) = operation.downcast::<O>() else { return false; };
                 operation.perform(recorder)
             },
+            deserialize_invertible: None,
             #[cfg(debug_assertions)]
             type_id: || TypeId::of::<O>(),
             #[cfg(debug_assertions)]
@@ -116,10 +199,22 @@ impl<P: Project> OperationKind<P> {
         }
     }
 
+    /// Register an invertible operation.
+    /// Unlike operations registered using `OperationKind::from`, invertible operations can be stored as part of a persistent undo/redo history.
+    pub const fn from_invertible<O: InvertibleOperation<Project = P>>() -> Self {
+        Self {
+            deserialize_invertible: Some(|data| {
+                Some(Box::new(O::deserialize(data, &mut DeserializationContext::new())?))
+            }),
+            ..Self::from::<O>()
+        }
+    }
+
 }
 
 /// An operation that was not yet confirmed by the server. Used for moving backwards/forwards in time for conflict resolution.  
 pub(crate) struct UnconfirmedOperation<P: Project> {
     pub(crate) operation: Box<dyn OperationDyn<Project = P>>,
-    pub(crate) delta: Delta<P>
+    pub(crate) delta: Delta<P>,
+    pub(crate) access: Access
 }
@@ -1,8 +1,8 @@
 use std::{cell::RefCell, collections::HashSet};
 
-use crate::{AnyPtr, ObjRef, Object, Project, ProjectContext, ProjectContextMut, Ptr};
+use crate::{AnyPtr, ObjRef, Object, PermissionGuard, Project, ProjectContext, ProjectContextMut, Ptr};
 
-use super::{Delta, OperationSource};
+use super::{Access, Delta, OperationSource};
 
 pub struct Recorder<'a, P: Project> {
     pub(crate) context: ProjectContextMut<'a, P>,
@@ -13,13 +13,24 @@ pub struct Recorder<'a, P: Project> {
     pub(crate) delta: Option<&'a mut Delta<P>>,
 
     /// What objects were already modified by this recorder
-    modified: HashSet<AnyPtr>,
+    pub(crate) modified: HashSet<AnyPtr>,
     /// Was the project modified by the recorder
-    modified_project: bool,
+    pub(crate) modified_project: bool,
+    /// What objects were created by this recorder
+    pub(crate) created: HashSet<AnyPtr>,
+    /// The objects created by this recorder whose permissions still need to be checked
+    unchecked_created: HashSet<AnyPtr>,
+    /// What the operation read, on top of what it modified
+    read: RefCell<Access>,
 
     /// Was the operation successful?
     /// Note: the operation's `perform` method could also indicate that the operation was unsuccessful
-    pub(crate) success: RefCell<bool>
+    pub(crate) success: RefCell<bool>,
+
+    /// The permissions of the client that sent the operation, when performed by a server that restricts what clients can modify
+    pub(crate) guard: Option<PermissionGuard<'a, P>>,
+    /// Did the operation try to modify something the client isn't allowed to?
+    pub(crate) denied: bool
 }
 
 impl<'a, P: Project> Recorder<'a, P> {
@@ -31,11 +42,35 @@ impl<'a, P: Project> Recorder<'a, P> {
             source,
             modified: HashSet::new(),
             modified_project: false,
-            success: RefCell::new(true)
+            created: HashSet::new(),
+            unchecked_created: HashSet::new(),
+            read: RefCell::new(Access::default()),
+            success: RefCell::new(true),
+            guard: None,
+            denied: false
         }
     }
 
+    /// Check if the client that sent the operation may modify an object, or the project if `ptr` is `None`.
+    /// If not, the operation is marked as denied.
+    fn check_permission(&mut self, ptr: Option<AnyPtr>) -> bool {
+        let Some(guard) = &self.guard else {
+            return true;
+        };
+        let context = ProjectContext {
+            project: &*self.context.project,
+            objects: &*self.context.objects,
+        };
+        if guard.can_modify(&context, ptr) {
+            return true;
+        }
+        self.denied = true;
+        *self.success.borrow_mut() = false;
+        false
+    }
+
     pub fn context(&'a self) -> ProjectContext<'a, P> {
+        self.read.borrow_mut().everything = true;
         ProjectContext {
             project: &self.context.project,
             objects: &self.context.objects,
@@ -43,24 +78,30 @@ impl<'a, P: Project> Recorder<'a, P> {
     }
 
     pub fn project(&self) -> &P {
+        self.read.borrow_mut().project = true;
         self.context.project()
     }
 
     pub fn project_mut(&mut self) -> &mut P {
+        // The project can't be withheld from the operation, so if it isn't allowed to modify it, the operation is undone afterwards
+        if !self.modified_project {
+            self.check_permission(None);
+        }
         if let Some(delta) = self.delta.as_mut() {
             if !self.modified_project {
-                self.modified_project = true;
                 let old_project = self.context.project.clone();
                 delta.push(move |context| {
                     *context.project_mut() = old_project.clone();
                 });
             }
         }
+        self.modified_project = true;
         self.context.project_mut()
     }
 
     pub fn get_obj<O: Object<Project = P>, T: Into<Ptr<O>>>(&self, ptr: T) -> Option<&O> {
         let ptr = ptr.into();
+        self.read.borrow_mut().objects.insert(ptr.any());
         match self.context.obj_list().get_ref(ptr) {
             ObjRef::None | ObjRef::Loading => {
                 *self.success.borrow_mut() = false;
@@ -82,10 +123,14 @@ impl<'a, P: Project> Recorder<'a, P> {
             ObjRef::Loaded(_) => {},
             ObjRef::Deleted => { return None; }
         }
+        if !self.modified.contains(&ptr.any()) && !self.check_permission(Some(ptr.any())) {
+            return None;
+        }
 
+        let first_modification = self.modified.insert(ptr.any());
         let object = self.context.obj_list_mut().get_mut(ptr)?;
         if let Some(delta) = &mut self.delta {
-            if self.modified.insert(ptr.any()) {
+            if first_modification {
                 let old_object = object.clone();
                 delta.push(move |context| {
                     if let Some(obj) = context.obj_list_mut().get_mut(ptr) {
@@ -103,36 +148,63 @@ impl<'a, P: Project> Recorder<'a, P> {
         if self.context.obj_list().get(ptr).is_some() {
             return false;
         }
+        self.context.obj_list_mut().insert(ptr, object);
         if let Some(delta) = &mut self.delta {
             delta.push(move |context| {
                 context.obj_list_mut().delete(ptr);
             });
         }
-        self.context.obj_list_mut().insert(ptr, object);
         self.modified.insert(ptr.any());
+        self.created.insert(ptr.any());
+        // Objects are often created before the objects they are attached to, so they are checked once the operation is done
+        if self.guard.is_some() {
+            self.unchecked_created.insert(ptr.any());
+        }
         true
     }
 
+    /// Check if the client that sent the operation may modify the objects it created, now that the operation is done.
+    /// If not, the operation is marked as denied.
+    pub(crate) fn check_created_permissions(&mut self) {
+        for ptr in std::mem::take(&mut self.unchecked_created) {
+            if !self.check_permission(Some(ptr)) {
+                return;
+            }
+        }
+    }
+
     pub fn delete_obj<O: Object<Project = P>, T: Into<Ptr<O>>>(&mut self, ptr: T) -> Option<O> {
+        let object = self.delete_single_obj(ptr)?;
+
+        // Delete any "owned" objects
+        let mut deletion_queue = Vec::new();
+        object.delete(&mut deletion_queue);
+        while let Some(to_delete) = deletion_queue.pop() {
+            (P::OBJECTS[to_delete.obj_type() as usize].delete)(self.context.objects, to_delete.key(), &mut deletion_queue, &mut self.delta);
+            self.modified.insert(to_delete);
+        }
+
+        Some(object)
+    }
+
+    /// Delete an object without deleting the objects it owns
+    pub(crate) fn delete_single_obj<O: Object<Project = P>, T: Into<Ptr<O>>>(&mut self, ptr: T) -> Option<O> {
         let ptr = ptr.into();
 
-        // Delete the object itself
+        // Objects created by the same operation are checked once it's done, if they still exist by then
+        let created_by_operation = self.unchecked_created.remove(&ptr.any());
+        if !created_by_operation && self.context.obj_list().get(ptr).is_some() && !self.check_permission(Some(ptr.any())) {
+            return None;
+        }
+
         let object = self.context.obj_list_mut().delete(ptr)?;
         if let Some(delta) = &mut self.delta {
             let object_copy = object.clone();
             delta.push(move |context| {
                 context.obj_list_mut().insert(ptr, object_copy.clone());
             });
-            self.modified.insert(ptr.any());
-        }
-
-        // Delete any "owned" objects
-        let mut deletion_queue = Vec::new();
-        object.delete(&mut deletion_queue);
-        while let Some(to_delete) = deletion_queue.pop() {
-            (P::OBJECTS[to_delete.obj_type() as usize].delete)(self.context.objects, to_delete.key(), &mut deletion_queue, &mut self.delta);
-            self.modified.insert(to_delete);
         }
+        self.modified.insert(ptr.any());
 
         Some(object)
     }
@@ -141,4 +213,15 @@ impl<'a, P: Project> Recorder<'a, P> {
         self.source
     }
 
+    /// Everything the operation read or modified so far
+    pub(crate) fn access(&self) -> Access {
+        let mut access = Access {
+            project: self.modified_project,
+            objects: self.modified.clone(),
+            everything: false
+        };
+        access.extend(&self.read.borrow());
+        access
+    }
+
 }
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::{CompactOperation, Message, Project};
+
+/// Maps operation names to the numeric IDs used in `Message::Operations`.
+/// The IDs are the positions of the operations in the server's `Project::OPERATIONS`, and are sent to clients in the `WelcomeMessage`.
+pub(crate) struct OperationTable {
+    names: Vec<String>,
+    ids: HashMap<String, u64>
+}
+
+impl OperationTable {
+
+    pub(crate) fn new(names: Vec<String>) -> Self {
+        let ids = names.iter().enumerate().map(|(id, name)| (name.clone(), id as u64)).collect();
+        Self {
+            names,
+            ids
+        }
+    }
+
+    pub(crate) fn for_project<P: Project>() -> Self {
+        Self::new(P::OPERATIONS.iter().map(|kind| kind.name.to_owned()).collect())
+    }
+
+    pub(crate) fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub(crate) fn id(&self, name: &str) -> Option<u64> {
+        self.ids.get(name).copied()
+    }
+
+    pub(crate) fn name(&self, id: u64) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    /// Combine runs of consecutive `Message::Operation`s into `Message::Operations`.
+    /// Operations missing from the table, which happens when talking to a server that didn't send one, are left as they are.
+    pub(crate) fn batch(&self, messages: Vec<Message>) -> Vec<Message> {
+        let mut batched = Vec::new();
+        let mut batch = Vec::new();
+        for message in messages {
+            if let Message::Operation { operation, data, seq } = &message {
+                if let Some(id) = self.id(operation) {
+                    batch.push(CompactOperation {
+                        operation: id,
+                        data: data.clone(),
+                        seq: *seq
+                    });
+                    continue;
+                }
+            }
+            if !batch.is_empty() {
+                batched.push(Message::Operations { operations: std::mem::take(&mut batch) });
+            }
+            batched.push(message);
+        }
+        if !batch.is_empty() {
+            batched.push(Message::Operations { operations: batch });
+        }
+        batched
+    }
+
+}
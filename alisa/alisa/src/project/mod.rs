@@ -2,14 +2,15 @@
 mod project_context;
 pub use project_context::*;
 
-use crate::{Client, ObjectKind, OperationKind, Serializable};
+use crate::{Client, Migration, ObjectKind, OperationKind, Serializable};
 
 pub trait Project: Sized + Serializable + 'static + Clone + Sync + Send {
 
     /// A struct containing an `ObjList` for every kind of object in the project
     type Objects: Default;
     /// Some data associated with each action.
-    type ActionContext: Clone;
+    /// It is serializable so that actions can be stored in a persistent undo/redo history.
+    type ActionContext: Clone + Serializable;
 
     fn empty() -> Self;
     fn create_default(_client: &Client<Self>) {
@@ -18,11 +19,16 @@ pub trait Project: Sized + Serializable + 'static + Clone + Sync + Send {
 
     const OBJECTS: &'static [ObjectKind<Self>];
     const OPERATIONS: &'static [OperationKind<Self>];
+    /// Migrations upgrading project data stored by older versions of the schema.
+    /// Migration `i` upgrades the project from version `i` to version `i + 1`, so the current version is the number of migrations.
+    const MIGRATIONS: &'static [Migration] = &[];
 
     fn verter_config() -> verter::Config {
         verter::Config {
             magic_bytes: b"ALISA___",
             page_size: 64,
+            journal: true,
+            checksums: false,
         }
     }
 
@@ -21,8 +21,12 @@ impl Default for WelcomeObject {
 #[derive(Serializable)]
 pub struct WelcomeMessage {
     pub id: ClientId,
+    /// The sequence number of the last operation included in the snapshot of the project
+    pub seq: u64,
     pub project: ABFValue,
-    pub objects: Vec<WelcomeObject>
+    pub objects: Vec<WelcomeObject>,
+    /// The names of the operations the server knows. Operations in a `Message::Operations` are referred to by their index in this list.
+    pub operations: Vec<String>
 }
 
 impl Default for WelcomeMessage {
@@ -30,20 +34,78 @@ impl Default for WelcomeMessage {
     fn default() -> Self {
         Self {
             id: Default::default(),
+            seq: 0,
             project: ABFValue::PositiveInt(0),
-            objects: Vec::new()
+            objects: Vec::new(),
+            operations: Vec::new()
         }
     }
     
 }
 
+/// An object whose state is replaced by a `Message::Invalidate`
+#[derive(Clone, Default, Serializable)]
+pub struct InvalidatedObject {
+    pub ptr: AnyPtr,
+    /// The object's state on the server, or None if it was deleted
+    pub obj: Option<ABFValue>
+}
+
+/// An operation in a `Message::Operations`
+#[derive(Clone, Serializable)]
+pub struct CompactOperation {
+    /// The index of the operation's name in `WelcomeMessage::operations`
+    pub operation: u64,
+    pub data: ABFValue,
+    pub seq: u64
+}
+
+impl Default for CompactOperation {
+
+    fn default() -> Self {
+        Self {
+            operation: 0,
+            data: ABFValue::PositiveInt(0),
+            seq: 0
+        }
+    }
+
+}
+
+/// The information a collab client needs to resume its session after losing the connection to the server
+#[derive(Clone, Default, Serializable)]
+pub struct Session {
+    pub id: ClientId,
+    /// The sequence number of the last operation the client received from the server
+    pub last_seq: u64,
+    /// The objects the client had loaded as of `last_seq`, so the server knows which of the operations the client missed it can follow
+    pub loaded: Vec<AnyPtr>
+}
+
 #[derive(Clone, Serializable)]
 pub enum Message {
+    /// An operation. When sent by the server, `seq` is the sequence number the server assigned to it. Clients always send a `seq` of 0.
     Operation {
         operation: String,
-        data: ABFValue
+        data: ABFValue,
+        seq: u64
+    },
+    /// Sent by the server once it has performed an operation sent by the client, with the sequence number assigned to it
+    ConfirmOperation {
+        seq: u64
+    },
+    /// Sent by the server instead of `ConfirmOperation` if the client isn't allowed to perform the operation.
+    /// The client undoes the operation.
+    RejectOperation {
+        seq: u64
+    },
+    /// Sent by the server instead of an operation to a client that doesn't have all the objects the operation touched loaded.
+    /// Contains the state of the project, if it was touched, and of the touched objects after the operation was performed.
+    Invalidate {
+        seq: u64,
+        project: Option<ABFValue>,
+        objects: Vec<InvalidatedObject>
     },
-    ConfirmOperation,
     LoadRequest {
         ptr: AnyPtr
     },
@@ -53,5 +115,17 @@ pub enum Message {
     },
     LoadFailed {
         ptr: AnyPtr
+    },
+    /// Sent by the server after replaying the operations a client missed, either while disconnected or since the snapshot it joined from
+    Resumed,
+    /// Sent by a client that wants to unload an object.
+    /// The server stops sending the client changes to the object and sends the message back, at which point the client unloads it.
+    Unload {
+        ptr: AnyPtr
+    },
+    /// A batch of operations, in the order they were performed.
+    /// Equivalent to sending each operation in a `Message::Operation`, but the operations are referred to by ID instead of by name.
+    Operations {
+        operations: Vec<CompactOperation>
     }
 }
@@ -1,6 +1,10 @@
 
 use super::Decoder;
 
+/// The largest size compressed binary data may inflate to.
+/// The decompressed size is read from the data itself, so without a limit a small message could make the decoder allocate any amount of memory.
+pub const MAX_DECOMPRESSED_BINARY_SIZE: usize = 64 * 1024 * 1024;
+
 impl Decoder<'_> {
 
     pub fn is_binary(&self) -> bool {
@@ -25,4 +29,23 @@ impl Decoder<'_> {
         self.read_slice(length)
     }
 
+    pub fn is_compressed_binary(&self) -> bool {
+        self.peek() == Some(0b11111000)
+    }
+
+    pub fn read_compressed_binary(&mut self) -> Option<Vec<u8>> {
+        if !self.is_compressed_binary() {
+            return None;
+        }
+        self.read()?;
+        let length = u32::from_le_bytes(self.read_array()?) as usize;
+        let compressed_length = u32::from_le_bytes(self.read_array()?) as usize;
+        let compressed = self.read_slice(compressed_length)?;
+        if length > MAX_DECOMPRESSED_BINARY_SIZE {
+            return None;
+        }
+        let bytes = miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, length).ok()?;
+        (bytes.len() == length).then_some(bytes)
+    }
+
 }
@@ -5,6 +5,7 @@ mod int;
 mod float;
 mod string;
 mod binary;
+pub use binary::MAX_DECOMPRESSED_BINARY_SIZE;
 mod obj_ptr;
 mod enums;
 mod array;
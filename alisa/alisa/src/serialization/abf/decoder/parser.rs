@@ -32,6 +32,8 @@ impl Decoder<'_> {
             return Some(ABFValue::Str(str.into()));
         } else if let Some(binary) = self.read_binary() {
             return Some(ABFValue::Binary(binary.into()));
+        } else if let Some(binary) = self.read_compressed_binary() {
+            return Some(ABFValue::Binary(binary.into()));
         } else if let Some((obj_type, key)) = self.read_obj_ptr() {
             return Some(ABFValue::ObjPtr(obj_type, key));
         } else if let Some(arr_len) = self.read_array_length() {
@@ -155,6 +155,12 @@ impl Decoder<'_> {
             0b11111100 => {
                 self.read_array::<10>();
             },
+            // Compressed binary
+            0b11111000 => {
+                self.read_array::<4>();
+                let Some(length) = self.read_array() else { return; };
+                self.read_slice(u32::from_le_bytes(length) as usize);
+            },
             // Named unit enum
             0b11111110 => {
                 self.skip_symbol();
@@ -1,9 +1,31 @@
 
+use crate::MAX_DECOMPRESSED_BINARY_SIZE;
+
 use super::{Encoder, Result};
 
+/// The number of bytes before the data of an uncompressed binary: a tag followed by the length as a U8, U16 or U32
+fn plain_binary_header_len(n_bytes: usize) -> usize {
+    if n_bytes <= u8::MAX as usize {
+        2
+    } else if n_bytes <= u16::MAX as usize {
+        3
+    } else {
+        5
+    }
+}
+
 impl<'writer, W: std::io::Write> Encoder<'writer, W> {
 
     pub fn binary(&mut self, bytes: &[u8]) -> Result {
+        if self.compression_threshold.is_some_and(|threshold| bytes.len() >= threshold) && bytes.len() <= MAX_DECOMPRESSED_BINARY_SIZE {
+            let compressed = miniz_oxide::deflate::compress_to_vec(bytes, 6);
+            // The compressed form has a 9 byte header (tag, size and compressed size), while the plain form has a 2, 3 or 5 byte header.
+            // Only use the compressed form if it's actually smaller once the headers are included.
+            if compressed.len() + 9 < bytes.len() + plain_binary_header_len(bytes.len()) && compressed.len() <= u32::MAX as usize {
+                return self.compressed_binary(bytes.len(), &compressed);
+            }
+        }
+
         let n_bytes = bytes.len();
         if n_bytes <= u8::MAX as usize {
             self.write_byte(0b11001111)?;
@@ -22,4 +44,11 @@ impl<'writer, W: std::io::Write> Encoder<'writer, W> {
         self.write_bytes(&bytes[0..n_bytes])
     }
 
+    fn compressed_binary(&mut self, n_bytes: usize, compressed: &[u8]) -> Result {
+        self.write_byte(0b11111000)?;
+        self.write_bytes(&(n_bytes as u32).to_le_bytes())?;
+        self.write_bytes(&(compressed.len() as u32).to_le_bytes())?;
+        self.write_bytes(compressed)
+    }
+
 }
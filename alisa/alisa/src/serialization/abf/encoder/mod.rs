@@ -15,17 +15,26 @@ pub use value::*;
 type Result = std::io::Result<()>;
 
 pub struct Encoder<'writer, W: std::io::Write> {
-    writer: &'writer mut W
+    writer: &'writer mut W,
+    /// Binary data at least this many bytes long is compressed, if compressing it makes it smaller
+    compression_threshold: Option<usize>
 }
 
 impl<'writer, W: std::io::Write> Encoder<'writer, W> {
 
     pub fn new(writer: &'writer mut W) -> Self {
         Self {
-            writer
+            writer,
+            compression_threshold: None
         }
     }
 
+    /// Compress binary data that is at least `threshold` bytes long
+    pub fn with_binary_compression(mut self, threshold: usize) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
     fn write_bytes(&mut self, bytes: &[u8]) -> Result {
         self.writer.write(bytes).map(|_| ())
     }
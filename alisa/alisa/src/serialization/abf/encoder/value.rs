@@ -53,3 +53,10 @@ pub fn encode_abf(value: &ABFValue) -> Vec<u8> {
     let _ = Encoder::new(&mut data).value(value);
     data
 }
+
+/// Encode a value, compressing binary data that is at least `threshold` bytes long
+pub fn encode_abf_compressed(value: &ABFValue, threshold: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    let _ = Encoder::new(&mut data).with_binary_compression(threshold).value(value);
+    data
+}
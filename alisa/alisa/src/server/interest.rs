@@ -0,0 +1,243 @@
+
+use std::collections::HashSet;
+
+use crate::{ABFValue, AnyPtr, ClientId, InvalidatedObject, Message, Project, ProjectContext, SerializationContext, Touched};
+
+use super::{LoggedOperation, Server};
+
+/// How an operation concerns a client
+enum Interest {
+    /// The client has none of the objects the operation touched loaded
+    None,
+    /// The client has everything the operation used loaded, so it can perform the operation itself
+    Operation,
+    /// The client only has some of the objects the operation used loaded, so it needs the new state of the ones it has from the server
+    Invalidate
+}
+
+impl<P: Project> Server<P> {
+
+    fn interest(&self, client: ClientId, touched: &Touched) -> Interest {
+        let Some(client) = self.clients.get(&client) else {
+            return Interest::None;
+        };
+        // Objects created by the operation didn't exist for anyone before it, so they don't count
+        let existing = touched.objects.difference(&touched.created).collect::<Vec<_>>();
+        let loaded = existing.iter().filter(|ptr| client.loaded.contains(ptr)).count();
+        // An operation that read an object the client doesn't have can't be performed by the client, even if everything it modified is loaded
+        let has_used = touched.used.difference(&touched.created).all(|ptr| client.loaded.contains(ptr));
+        if loaded == existing.len() && has_used {
+            Interest::Operation
+        } else if loaded > 0 || touched.project {
+            Interest::Invalidate
+        } else {
+            Interest::None
+        }
+    }
+
+    /// Send an operation the server performed to the clients it concerns.
+    /// Clients that only have some of the objects it touched loaded get the new state of the objects instead.
+    pub(super) fn forward_operation(&mut self, sender: ClientId, operation: &str, data: &ABFValue, seq: u64, touched: &Touched) {
+        let clients = self.clients.keys().copied().filter(|id| *id != sender).collect::<Vec<_>>();
+        for client in clients {
+            match self.interest(client, touched) {
+                Interest::None => continue,
+                Interest::Operation => {
+                    self.send(client, Message::Operation {
+                        operation: operation.to_owned(),
+                        data: data.clone(),
+                        seq
+                    });
+                    self.update_loaded(client, touched);
+                },
+                Interest::Invalidate => {
+                    let msg = self.invalidate_message(client, seq, touched);
+                    self.send(client, msg);
+                },
+            }
+            self.send_new_references(client, touched);
+        }
+        self.update_loaded(sender, touched);
+        self.send_new_references(sender, touched);
+    }
+
+    /// Queue the operations a client missed, followed by a `Message::Resumed`.
+    /// Like `forward_operation`, the client only gets the operations it has everything loaded for.
+    /// Objects the client can't follow the changes to are sent in their current state once all the operations are queued.
+    /// Sending the current state any earlier would have the client perform the operations after it on top of a state that already includes them.
+    pub(super) fn replay_operations(&mut self, client: ClientId, missed: Vec<(LoggedOperation, Touched)>) {
+        // What the client can no longer follow the changes to, so it needs the current state
+        let mut stale = Touched::default();
+        // Everything the client saw change, which might point to objects it doesn't have yet
+        let mut replayed = Touched::default();
+
+        for (logged, touched) in missed {
+            if logged.client == client {
+                if logged.denied {
+                    self.send(client, Message::RejectOperation { seq: logged.seq });
+                } else {
+                    self.send(client, Message::ConfirmOperation { seq: logged.seq });
+                }
+                if logged.success {
+                    self.extend_loaded(client, &touched);
+                    replayed.extend(&touched);
+                }
+                continue;
+            }
+            if !logged.success {
+                continue;
+            }
+
+            let follows_stale = (touched.project && stale.project) ||
+                !touched.objects.is_disjoint(&stale.objects) ||
+                !touched.used.is_disjoint(&stale.objects);
+            match self.interest(client, &touched) {
+                Interest::None => continue,
+                Interest::Operation if !follows_stale => {
+                    self.send(client, Message::Operation {
+                        operation: logged.operation,
+                        data: logged.data,
+                        seq: logged.seq
+                    });
+                    self.extend_loaded(client, &touched);
+                },
+                _ => {
+                    stale.project |= touched.project;
+                    stale.objects.extend(touched.objects.iter().copied().filter(|ptr| self.client_has_loaded(client, *ptr)));
+                }
+            }
+            replayed.extend(&touched);
+        }
+
+        // Forget the objects the operations deleted
+        let followed = replayed.objects.iter()
+            .copied()
+            .filter(|ptr| !stale.objects.contains(ptr) && self.client_has_loaded(client, *ptr))
+            .collect::<Vec<_>>();
+        for ptr in followed {
+            if self.serialize_object(ptr).is_some() {
+                continue;
+            }
+            if let Some(server_client) = self.clients.get_mut(&client) {
+                server_client.loaded.remove(&ptr);
+            }
+        }
+
+        if stale.project || !stale.objects.is_empty() {
+            let msg = self.invalidate_message(client, self.log.seq(), &stale);
+            self.send(client, msg);
+        }
+        self.send_new_references(client, &replayed);
+        self.send(client, Message::Resumed);
+    }
+
+    /// Record that a client has the objects an operation touched, for operations replayed to the client.
+    /// Objects the operation deleted are forgotten once all the operations are replayed, since the server no longer knows which ones existed at the time.
+    fn extend_loaded(&mut self, client: ClientId, touched: &Touched) {
+        if let Some(client) = self.clients.get_mut(&client) {
+            client.loaded.extend(touched.objects.iter().copied());
+        }
+    }
+
+    /// Record that a client performed an operation, so it has the objects the operation created and no longer has the ones it deleted
+    fn update_loaded(&mut self, client: ClientId, touched: &Touched) {
+        let present = touched.objects.iter().map(|ptr| (*ptr, self.has_loaded(*ptr))).collect::<Vec<_>>();
+        let Some(client) = self.clients.get_mut(&client) else {
+            return;
+        };
+        for (ptr, present) in present {
+            if present {
+                client.loaded.insert(ptr);
+            } else {
+                client.loaded.remove(&ptr);
+            }
+        }
+    }
+
+    /// The new state of the touched objects the client has, for a client that couldn't perform the operation itself
+    fn invalidate_message(&mut self, client: ClientId, seq: u64, touched: &Touched) -> Message {
+        let project = touched.project.then(|| self.client.project.serialize(&SerializationContext::new()));
+
+        let mut objects = Vec::new();
+        for ptr in &touched.objects {
+            if !self.client_has_loaded(client, *ptr) {
+                continue;
+            }
+            let obj = self.serialize_object(*ptr);
+            if obj.is_none() {
+                if let Some(server_client) = self.clients.get_mut(&client) {
+                    server_client.loaded.remove(ptr);
+                }
+            }
+            objects.push(InvalidatedObject {
+                ptr: *ptr,
+                obj
+            });
+        }
+
+        Message::Invalidate {
+            seq,
+            project,
+            objects
+        }
+    }
+
+    /// After an operation, the objects it touched might point to objects the client doesn't have yet.
+    /// Send the client the ones that would have been loaded along with the objects it has.
+    fn send_new_references(&mut self, client: ClientId, touched: &Touched) {
+        let mut references = Vec::new();
+        if touched.project {
+            let context = SerializationContext::new();
+            self.client.project.serialize(&context);
+            references.extend(context.take_serialization_requests().into_iter().map(|(obj_type, key)| AnyPtr::new(obj_type, key)));
+        }
+        for ptr in &touched.objects {
+            if self.client_has_loaded(client, *ptr) {
+                references.extend((P::OBJECTS[ptr.obj_type() as usize].references)(&self.client.objects, ptr.key()));
+            }
+        }
+        self.push_objects(client, references);
+    }
+
+    /// Send objects to a client without it asking for them, along with everything they load.
+    /// Objects the client already has or isn't allowed to load are skipped.
+    fn push_objects(&mut self, client: ClientId, objects: Vec<AnyPtr>) {
+        let mut queue = objects;
+        let mut visited = HashSet::new();
+        while let Some(ptr) = queue.pop() {
+            if self.client_has_loaded(client, ptr) || !visited.insert(ptr) {
+                continue;
+            }
+            let object_kind = &P::OBJECTS[ptr.obj_type() as usize];
+            let local = self.client.kind.as_local().unwrap();
+            local.dyn_load(object_kind, &mut self.client.objects, ptr.key());
+            if !self.can_load(client, ptr) {
+                continue;
+            }
+
+            let context = SerializationContext::new();
+            let Some(obj) = (object_kind.serialize_object)(&mut self.client.objects, ptr.key(), &context) else {
+                continue;
+            };
+            queue.extend(context.take_serialization_requests().into_iter().map(|(obj_type, key)| AnyPtr::new(obj_type, key)));
+
+            if let Some(server_client) = self.clients.get_mut(&client) {
+                server_client.loaded.insert(ptr);
+            }
+            self.send(client, Message::Load { ptr, obj });
+        }
+    }
+
+    /// Is the client allowed to load the object?
+    pub(super) fn can_load(&self, client: ClientId, ptr: AnyPtr) -> bool {
+        let Some(permissions) = &self.permissions else {
+            return true;
+        };
+        let context = ProjectContext {
+            project: &self.client.project,
+            objects: &self.client.objects
+        };
+        permissions.can_load(client, &context, ptr)
+    }
+
+}
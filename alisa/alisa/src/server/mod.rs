@@ -0,0 +1,560 @@
+
+use std::{collections::{HashMap, HashSet}, fmt::Debug, io, path::Path, time::{Duration, Instant}};
+
+mod op_log;
+pub use op_log::*;
+
+mod permissions;
+pub use permissions::*;
+
+mod interest;
+
+mod snapshots;
+
+use crate::{deserialize, serialize, ABFValue, AnyPtr, Client, DeserializationContext, Message, ObjectKind, OperationOutcome, OperationTable, Project, Serializable, SerializationContext, Session, Touched, WelcomeMessage, WelcomeObject};
+
+struct ServerClient {
+    to_send: Vec<Message>,
+    to_server_key: HashMap<u64, u64>,
+    to_client_key: HashMap<u64, u64>,
+    /// Is the client currently connected? Disconnected clients are kept around so that they can resume their session.
+    connected: bool,
+    /// When the client disconnected, and the sequence number of the last operation before it did.
+    /// Used to expire sessions that can no longer be resumed.
+    disconnected: Option<(Instant, u64)>,
+    /// The objects the client might have loaded: the ones it was sent, and the ones touched by operations it was sent or performed
+    loaded: HashSet<AnyPtr>
+}
+
+pub struct Server<P: Project> {
+    /// The pseudo-client holding the server's project. Handles receiving operation messages and serialization.
+    client: Client<P>,
+    curr_client_id: u64,
+    clients: HashMap<ClientId, ServerClient>,
+    /// The log of the operations received, used to assign sequence numbers and replay operations to clients that missed them
+    log: OpLog,
+    /// The last error writing to the operation log, if it wasn't taken using `Server::take_log_error` yet
+    log_error: Option<io::Error>,
+    /// Decides what each client may modify and load. If None, clients can do anything.
+    permissions: Option<Box<dyn Permissions<P>>>,
+    /// The IDs of the operations in `Message::Operations`, sent to clients in the `WelcomeMessage`
+    operations: OperationTable,
+    /// How long the session of a disconnected client is kept around before it expires
+    session_timeout: Duration
+}
+
+/// How long the session of a disconnected client is kept by default, see `Server::set_session_timeout`
+pub const SESSION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// The reason a session couldn't be resumed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResumeError {
+    /// The session expired, or the client missed too many operations to catch up. The client needs to join again from scratch.
+    Expired,
+    /// The client's old connection is still open. The client can try again once the server notices the old connection was closed.
+    StillConnected
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub struct ClientId(pub u64);
+
+impl ClientId {
+
+    /// The ID operations performed by the server itself are logged with. Clients are never given this ID.
+    pub const SERVER: ClientId = ClientId(0);
+
+}
+
+impl Debug for ClientId {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+
+}
+
+impl Serializable for ClientId {
+
+    fn deserialize(data: &ABFValue, _context: &mut DeserializationContext) -> Option<Self> {
+        data.as_u64().map(|id| Self(id))
+    }
+    
+    fn serialize(&self, _context: &SerializationContext) -> ABFValue {
+        self.0.into()
+    }
+
+    fn delete(&self, _: &mut Vec<AnyPtr>) {
+        
+    }
+
+}
+
+impl<P: Project> Server<P> {
+
+    /// Open a server for the project at `path`. The operation log is stored next to the project, at `op_log_path(path)`.
+    pub fn new<PathRef: AsRef<Path>>(path: PathRef) -> Option<Self> {
+        let log = OpLog::open(path.as_ref())?;
+        Some(Self::with_client(Client::local(path)?, log))
+    }
+
+    /// Create a server for a project kept in a custom storage, such as `verter::MemoryStorage`.
+    /// The operation log is only kept in memory.
+    pub fn with_storage<S: verter::Storage + 'static>(storage: S) -> Option<Self> {
+        Some(Self::with_client(Client::local_with_storage(storage)?, OpLog::in_memory()))
+    }
+
+    fn with_client(client: Client<P>, log: OpLog) -> Self {
+        Self {
+            client,
+            curr_client_id: log.max_client_id() + 1,
+            clients: HashMap::new(),
+            log,
+            log_error: None,
+            permissions: None,
+            operations: OperationTable::for_project::<P>(),
+            session_timeout: SESSION_TIMEOUT
+        }
+    }
+
+    /// Restrict what clients may modify and load
+    pub fn set_permissions<Perms: Permissions<P> + 'static>(&mut self, permissions: Perms) {
+        self.permissions = Some(Box::new(permissions));
+    }
+
+    /// Set how long the session of a disconnected client is kept around before it expires
+    pub fn set_session_timeout(&mut self, timeout: Duration) {
+        self.session_timeout = timeout;
+    }
+
+    fn new_client(&mut self) -> ClientId {
+        let id = ClientId(self.curr_client_id);
+        self.curr_client_id += 1;
+
+        self.clients.insert(id, ServerClient {
+            to_send: Vec::new(),
+            to_server_key: HashMap::new(),
+            to_client_key: HashMap::new(),
+            connected: true,
+            disconnected: None,
+            loaded: HashSet::new()
+        });
+
+        id
+    }
+
+    /// The sequence number of the last operation received
+    pub fn seq(&self) -> u64 {
+        self.log.seq()
+    }
+
+    pub fn add_client(&mut self) -> (ClientId, WelcomeMessage) {
+        self.expire_sessions();
+        let id = self.new_client();
+        let welcome = self.welcome_message(id);
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.loaded = welcome.objects.iter().map(|object| object.ptr).collect();
+        }
+        (id, welcome)
+    }
+
+    fn welcome_message(&mut self, id: ClientId) -> WelcomeMessage {
+        let storing_context = SerializationContext::new();
+        let project_data = self.client.project.serialize(&storing_context); 
+
+        let mut welcome_objects = Vec::new(); 
+        let mut encoded = HashSet::new();
+        let mut to_encode = storing_context.take_serialization_requests();
+        while let Some((obj_type, key)) = to_encode.pop() {
+            encoded.insert(key);
+            let context = SerializationContext::new();
+            let Some(object_data) = (P::OBJECTS[obj_type as usize].serialize_object)(&mut self.client.objects, key, &context) else { continue; };
+            for (next_obj_type, next_key) in context.take_serialization_requests() {
+                if !encoded.contains(&next_key) {
+                    to_encode.push((next_obj_type, next_key));
+                }
+            }
+            welcome_objects.push(WelcomeObject {
+                ptr: AnyPtr::new(obj_type, key),
+                obj: object_data,
+            });
+        }
+
+        WelcomeMessage {
+            id,
+            seq: self.log.seq(),
+            project: project_data,
+            objects: welcome_objects,
+            operations: self.operations.names().to_vec()
+        }
+    }
+
+    /// Mark a client as disconnected. Messages are no longer sent to the client, but it can later resume its session using `Server::resume_client`.
+    /// The session expires after the session timeout, or once the client missed more operations than the server keeps around.
+    pub fn disconnect_client(&mut self, id: ClientId) {
+        let seq = self.log.seq();
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.connected = false;
+            client.disconnected = Some((Instant::now(), seq));
+            client.to_send.clear();
+        }
+    }
+
+    /// Does the server still have the client's session, either connected or waiting to be resumed?
+    pub fn has_session(&self, id: ClientId) -> bool {
+        self.clients.contains_key(&id)
+    }
+
+    /// Forget the sessions of disconnected clients that timed out or can no longer catch up.
+    /// Objects that were only kept loaded for those clients are unloaded.
+    pub fn expire_sessions(&mut self) {
+        let now = Instant::now();
+        let expired = self.clients.iter()
+            .filter(|(_, client)| client.disconnected.is_some_and(|(time, seq)| now.duration_since(time) >= self.session_timeout || self.log.since(seq).is_none()))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        let mut pinned = HashSet::new();
+        for id in expired {
+            if let Some(client) = self.clients.remove(&id) {
+                pinned.extend(client.loaded);
+            }
+        }
+        self.release_objects(pinned);
+    }
+
+    /// Unload the objects that no client has loaded anymore
+    fn release_objects(&mut self, ptrs: HashSet<AnyPtr>) {
+        let unused = ptrs.into_iter()
+            .filter(|ptr| !self.clients.values().any(|client| client.loaded.contains(ptr)))
+            .collect::<Vec<_>>();
+        if !unused.is_empty() {
+            self.client.unload_objects(unused, false);
+        }
+    }
+
+    /// The operations performed after the sequence number `seq`, along with what they touched.
+    /// Returns None if some of them are no longer kept in memory, or if they were performed before the server restarted, since the server doesn't know what those touched.
+    fn missed_operations(&self, seq: u64) -> Option<Vec<(LoggedOperation, Touched)>> {
+        self.log.since(seq)?.map(|(logged, touched)| Some((logged.clone(), touched.clone()?))).collect()
+    }
+
+    /// Resume the session of a client that lost its connection.
+    /// The operations the client missed are queued to be sent to it, followed by a `Message::Resumed`.
+    pub fn resume_client(&mut self, session: &Session) -> Result<(), ResumeError> {
+        self.expire_sessions();
+        let Some(client) = self.clients.get(&session.id) else {
+            return Err(ResumeError::Expired);
+        };
+        // The server might not have noticed the old connection was lost yet
+        if client.connected {
+            return Err(ResumeError::StillConnected);
+        }
+        // Make sure we still have all the operations the client missed
+        let Some(missed) = self.missed_operations(session.last_seq) else {
+            return Err(ResumeError::Expired);
+        };
+
+        // Messages sent before the connection was lost might not have arrived, so the client tells us what it actually has
+        let mut loaded = HashSet::new();
+        for ptr in &session.loaded {
+            let Some(object_kind) = P::OBJECTS.get(ptr.obj_type() as usize) else { continue; };
+            if self.permissions.is_some() {
+                let local = self.client.kind.as_local().unwrap();
+                local.dyn_load(object_kind, &mut self.client.objects, ptr.key());
+            }
+            if self.can_load(session.id, *ptr) {
+                loaded.insert(*ptr);
+            }
+        }
+        let Some(client) = self.clients.get_mut(&session.id) else {
+            return Err(ResumeError::Expired);
+        };
+        client.connected = true;
+        client.disconnected = None;
+        client.to_send.clear();
+        let released = std::mem::replace(&mut client.loaded, loaded);
+        self.release_objects(released);
+        self.replay_operations(session.id, missed);
+
+        Ok(())
+    }
+
+    /// Add a client that already has a snapshot of the project, such as the one in a `WelcomeMessage` it received earlier.
+    /// Instead of sending the whole project again, the operations performed after the snapshot's sequence number are queued to be sent to the client, followed by a `Message::Resumed`.
+    /// The client should be created using `Client::collab_from_snapshot`.
+    /// Returns None if the server no longer has all the operations the client missed, in which case it needs to join using `Server::add_client`.
+    pub fn add_client_from_snapshot(&mut self, snapshot_seq: u64) -> Option<ClientId> {
+        let missed = self.missed_operations(snapshot_seq)?;
+
+        let id = self.new_client();
+        // We don't know exactly what the snapshot contained, but it was made the same way as the current one
+        let loaded = self.welcome_message(id).objects.into_iter().map(|object| object.ptr).collect();
+        self.clients.get_mut(&id)?.loaded = loaded;
+        self.replay_operations(id, missed);
+        Some(id)
+    }
+
+    pub fn send(&mut self, to: ClientId, msg: Message) -> Option<()> {
+        let client = self.clients.get_mut(&to)?;
+        if client.connected {
+            client.to_send.push(msg);
+        }
+        Some(())
+    }
+
+    pub fn broadcast(&mut self, msg: &Message, except: Option<ClientId>) {
+        for (client_id, client) in self.clients.iter_mut() {
+            if Some(*client_id) != except && client.connected {
+                client.to_send.push(msg.clone());
+            }
+        }
+    }
+
+    pub fn receive_message(&mut self, client_id: ClientId, msg: &Message) {
+        match msg {
+            Message::Operation { operation, data, .. } => {
+                self.receive_operation(client_id, operation, data);
+            },
+            Message::Operations { operations } => {
+                for compact in operations {
+                    // Unknown operations still need to be confirmed, so they go through the same path as operations that fail
+                    let operation = self.operations.name(compact.operation).unwrap_or_default().to_owned();
+                    self.receive_operation(client_id, &operation, &compact.data);
+                }
+            },
+            Message::LoadRequest { ptr } => {
+                let obj_type = ptr.obj_type();
+                let key = ptr.key();
+                let object_kind = &P::OBJECTS[obj_type as usize];
+                self.handle_load_message(object_kind, key, client_id); 
+            },
+            Message::Unload { ptr } => {
+                self.handle_unload_message(*ptr, client_id);
+            },
+            _ => {
+                return;
+            }
+        }
+        self.expire_sessions();
+        self.client.tick();
+    }
+
+    /// Perform an operation sent by a client and let the clients it concerns know
+    fn receive_operation(&mut self, client_id: ClientId, operation: &str, data: &ABFValue) {
+        let seq = self.log.next_seq();
+        let guard = self.permissions.as_deref().map(|permissions| PermissionGuard {
+            permissions,
+            client: client_id
+        });
+        let (outcome, touched) = self.client.handle_operation_message(operation, data, guard);
+        let success = outcome == OperationOutcome::Performed;
+        let denied = outcome == OperationOutcome::Denied;
+        if denied {
+            self.send(client_id, Message::RejectOperation { seq });
+        } else {
+            self.send(client_id, Message::ConfirmOperation { seq });
+        }
+        if success {
+            self.forward_operation(client_id, operation, data, seq, &touched);
+        }
+        self.log_operation(LoggedOperation {
+            seq,
+            client: client_id,
+            operation: operation.to_owned(),
+            data: data.clone(),
+            success,
+            denied
+        }, touched);
+    }
+
+    fn log_operation(&mut self, operation: LoggedOperation, touched: Touched) {
+        if let Err(err) = self.log.append(operation, touched) {
+            self.log_error = Some(err);
+        }
+    }
+
+    /// Make sure the operations received so far are stored on disk.
+    /// This happens automatically before messages are taken using `Server::take_all_msgs_to_send`, so confirmations are only sent for operations that made it to disk.
+    pub fn sync_log(&mut self) {
+        if let Err(err) = self.log.sync() {
+            self.log_error = Some(err);
+        }
+    }
+
+    /// Take the last error that occurred while writing to the operation log.
+    /// Operations are still performed and sent to clients when the log can't be written, so the server should report these errors.
+    pub fn take_log_error(&mut self) -> Option<io::Error> {
+        self.log_error.take()
+    }
+
+    fn handle_load_message(&mut self, object_kind: &ObjectKind<P>, key: u64, client_id: ClientId) {
+        if self.permissions.is_some() {
+            let local = self.client.kind.as_local().unwrap();
+            local.dyn_load(object_kind, &mut self.client.objects, key);
+            let ptr = AnyPtr::new(object_kind.object_type_id, key);
+            if !self.can_load(client_id, ptr) {
+                self.send(client_id, Message::LoadFailed { ptr });
+                return;
+            }
+        }
+
+        let mut to_encode = vec![(object_kind, key)];
+        let mut encoded = HashSet::new();
+        while let Some((object_kind, key)) = to_encode.pop() {
+            encoded.insert(key);
+
+            let local = self.client.kind.as_local().unwrap();
+            local.dyn_load(&object_kind, &mut self.client.objects, key);
+            let context = SerializationContext::new();
+            let data = (object_kind.serialize_object)(&mut self.client.objects, key, &context);
+
+            let ptr = AnyPtr::new(object_kind.object_type_id, key);
+            if let Some(data) = data {
+                if let Some(client) = self.clients.get_mut(&client_id) {
+                    client.loaded.insert(ptr);
+                }
+                self.send(client_id, Message::Load {
+                    ptr,
+                    obj: data
+                });
+            } else {
+                self.send(client_id, Message::LoadFailed { ptr });
+            }
+
+            for (obj_type, key) in context.take_serialization_requests() {
+                if !encoded.contains(&key) {
+                    to_encode.push((&P::OBJECTS[obj_type as usize], key));
+                }
+            }
+        }
+        
+    }
+
+    /// Forget that a client has an object loaded, and let the client know it can unload it.
+    /// Once no client has the object loaded, the server unloads it too, and loads it from disk again when someone needs it.
+    fn handle_unload_message(&mut self, ptr: AnyPtr, client_id: ClientId) {
+        let Some(client) = self.clients.get_mut(&client_id) else {
+            return;
+        };
+        client.loaded.remove(&ptr);
+        self.send(client_id, Message::Unload { ptr });
+        if self.clients.values().any(|client| client.loaded.contains(&ptr)) {
+            return;
+        }
+        self.client.unload_objects(vec![ptr], false);
+    }
+
+    /// Might the client have the object loaded?
+    pub fn client_has_loaded(&self, client: ClientId, ptr: AnyPtr) -> bool {
+        self.clients.get(&client).is_some_and(|client| client.loaded.contains(&ptr))
+    }
+
+    /// Does the server currently have the object loaded in memory?
+    pub fn has_loaded(&self, ptr: AnyPtr) -> bool {
+        (P::OBJECTS[ptr.obj_type() as usize].is_loaded)(&self.client.objects, ptr.key())
+    }
+
+    pub fn project(&self) -> &P {
+        &self.client.project
+    }
+
+    /// The serialized state of an object, loading it from disk if needed. Returns None if the object doesn't exist.
+    pub(crate) fn serialize_object(&mut self, ptr: AnyPtr) -> Option<ABFValue> {
+        let object_kind = &P::OBJECTS[ptr.obj_type() as usize];
+        let local = self.client.kind.as_local().unwrap();
+        local.dyn_load(object_kind, &mut self.client.objects, ptr.key());
+        (object_kind.serialize_object)(&mut self.client.objects, ptr.key(), &SerializationContext::new())
+    }
+
+    pub fn get_msgs_to_send(&self, client: ClientId) -> Option<&Vec<Message>> {
+        Some(&self.clients.get(&client)?.to_send)
+    }
+
+    pub fn get_msgs_to_send_mut(&mut self, client: ClientId) -> Option<&mut Vec<Message>> {
+        Some(&mut self.clients.get_mut(&client)?.to_send)
+    }
+
+    /// Take the messages queued for every client. Consecutive operations are combined into a `Message::Operations`.
+    /// The operation log is synced to disk first.
+    pub fn take_all_msgs_to_send(&mut self) -> HashMap<ClientId, Vec<Message>> {
+        self.sync_log();
+        self.clients.iter_mut().map(|(id, client)| (*id, self.operations.batch(std::mem::take(&mut client.to_send)))).collect()
+    }
+
+    fn map_to_client_keys(data: &mut ABFValue, to_client_key: &HashMap<u64, u64>) {
+        match data {
+            ABFValue::ObjPtr(_, key) => {
+                if let Some(mapped_key) = to_client_key.get(&key) {
+                    *key = *mapped_key;
+                }
+            },
+            ABFValue::Array(items) => {
+                for item in items {
+                    Self::map_to_client_keys(item, to_client_key);
+                }
+            },
+            ABFValue::Map(items) => {
+                for (_, item) in items {
+                    Self::map_to_client_keys(item, to_client_key);
+                }
+            },
+            ABFValue::IndexedEnum(_, data) => {
+                Self::map_to_client_keys(data, to_client_key);
+            },
+            ABFValue::NamedEnum(_, data) => {
+                Self::map_to_client_keys(data, to_client_key);
+            },
+            _ => {}
+        }
+    }
+
+    /// Serialize an object, taking key mapping into account
+    pub fn serialize<T: Serializable>(&self, client: ClientId, obj: &T) -> ABFValue {
+        let mut data = serialize(obj);
+        if let Some(client) = self.clients.get(&client) {
+            Self::map_to_client_keys(&mut data, &client.to_client_key);
+        }
+        data
+    }
+
+    fn map_to_server_keys(data: &mut ABFValue, to_client_key: &mut HashMap<u64, u64>, to_server_key: &mut HashMap<u64, u64>, client: &Client<P>) {
+        match data {
+            ABFValue::ObjPtr(_, key) => {
+                if let Some(mapped_key) = to_server_key.get(&key) {
+                    *key = *mapped_key;
+                } else if *key & (1 << 63) > 0 {
+                    let server_key = client.next_key();
+                    to_server_key.insert(*key, server_key);
+                    to_client_key.insert(server_key, *key);
+                    *key = server_key;
+                }
+            },
+            ABFValue::Array(items) => {
+                for item in items {
+                    Self::map_to_server_keys(item, to_client_key, to_server_key, client);
+                }
+            },
+            ABFValue::Map(items) => {
+                for (_, item) in items {
+                    Self::map_to_server_keys(item, to_client_key, to_server_key, client);
+                }
+            },
+            ABFValue::IndexedEnum(_, data) => {
+                Self::map_to_server_keys(data, to_client_key, to_server_key, client);
+            },
+            ABFValue::NamedEnum(_, data) => {
+                Self::map_to_server_keys(data, to_client_key, to_server_key, client);
+            },
+            _ => {}
+        }
+    }
+
+    /// Deserialize an object, taking key mapping into account
+    pub fn deserialize<T: Serializable>(&mut self, client: ClientId, mut data: ABFValue) -> Option<T> {
+        if let Some(client) = self.clients.get_mut(&client) {
+            Self::map_to_server_keys(&mut data, &mut client.to_client_key, &mut client.to_server_key, &self.client);
+        }
+        deserialize::<T>(&data)
+    }
+
+}
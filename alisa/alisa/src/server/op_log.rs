@@ -0,0 +1,295 @@
+
+use std::{collections::VecDeque, fs, io::{self, Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}};
+
+use crate::{encode_abf, parse_abf, ABFValue, Touched};
+
+use super::ClientId;
+
+/// An operation received by the server, as recorded in the operation log
+#[derive(Clone)]
+pub struct LoggedOperation {
+    /// The sequence number the server assigned to the operation
+    pub seq: u64,
+    /// The client that sent the operation
+    pub client: ClientId,
+    pub operation: String,
+    /// The operation's data, exactly as the server received it.
+    /// Keys the client created itself are only replaced with the server's keys if the message was read using `Server::deserialize`.
+    pub data: ABFValue,
+    /// Was the operation performed successfully? Failed operations are only confirmed to the client that sent them.
+    pub success: bool,
+    /// Was the operation undone because the client wasn't allowed to perform it?
+    pub denied: bool
+}
+
+impl LoggedOperation {
+
+    fn encode(&self) -> Vec<u8> {
+        encode_abf(&ABFValue::Map(Box::new([
+            ("seq".into(), ABFValue::U64(self.seq)),
+            ("client".into(), ABFValue::U64(self.client.0)),
+            ("operation".into(), ABFValue::Str(self.operation.clone())),
+            ("data".into(), self.data.clone()),
+            ("success".into(), ABFValue::Bool(self.success)),
+            ("denied".into(), ABFValue::Bool(self.denied)),
+        ])))
+    }
+
+    fn from_abf(data: &ABFValue) -> Option<Self> {
+        Some(Self {
+            seq: data.get("seq")?.as_u64()?,
+            client: ClientId(data.get("client")?.as_u64()?),
+            operation: data.get("operation")?.as_string()?.to_owned(),
+            data: data.get("data")?.clone(),
+            success: data.get("success")?.as_bool()?,
+            denied: data.get("denied")?.as_bool()?
+        })
+    }
+
+}
+
+/// The record a compacted log starts with, standing in for the operations that were dropped
+struct CompactedRecord {
+    /// The largest client ID found in the dropped operations
+    max_client_id: u64
+}
+
+impl CompactedRecord {
+
+    fn encode(&self) -> Vec<u8> {
+        encode_abf(&ABFValue::Map(Box::new([
+            ("compacted_max_client_id".into(), ABFValue::U64(self.max_client_id)),
+        ])))
+    }
+
+    fn from_abf(data: &ABFValue) -> Option<Self> {
+        Some(Self {
+            max_client_id: data.get("compacted_max_client_id")?.as_u64()?
+        })
+    }
+
+}
+
+/// The number of recent operations kept in memory, for resuming sessions and catching up late joiners.
+/// Clients that missed more operations than this can't catch up, and need to join again from scratch.
+const RECENT_OPERATIONS: usize = 4096;
+
+/// Once the log file holds this many operations, it is compacted down to the ones kept in memory.
+/// Older operations can't be replayed to anyone anymore, so they only take up space.
+const COMPACT_THRESHOLD: usize = 2 * RECENT_OPERATIONS;
+
+/// The log of the operations the server received.
+/// For projects stored on disk, the log is kept in a file next to the project.
+/// Each record is the length of the encoded operation as a little-endian u32, followed by the operation encoded as ABF.
+/// Records are appended as operations come in, and the log is compacted once it gets too long.
+/// A compacted log starts with a record holding the largest client ID of the operations that were dropped.
+pub(crate) struct OpLog {
+    file: Option<fs::File>,
+    /// The path of the log file, if the log is stored on disk
+    path: Option<PathBuf>,
+    /// The number of operations in the log file
+    records: usize,
+    /// Were operations written to the file since it was last synced?
+    unsynced: bool,
+    /// The most recently logged operations, in order, along with what they touched.
+    /// What operations touched isn't stored in the log file, so it's missing for operations from before the server restarted.
+    recent: VecDeque<(LoggedOperation, Option<Touched>)>,
+    /// The sequence number of the last logged operation
+    seq: u64,
+    /// The largest client ID found in the log
+    max_client_id: u64
+}
+
+/// The path of the operation log belonging to the project at `path`
+pub fn op_log_path(path: &Path) -> PathBuf {
+    let mut log_path = path.as_os_str().to_owned();
+    log_path.push(".oplog");
+    log_path.into()
+}
+
+fn read_record(data: &[u8], offset: usize) -> Option<(ABFValue, usize)> {
+    let len_bytes = data.get(offset..(offset + 4))?;
+    let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+    let record = data.get((offset + 4)..(offset + 4 + len))?;
+    Some((parse_abf(record)?, offset + 4 + len))
+}
+
+/// Read the records of an operation log, along with the largest client ID of the operations dropped when the log was compacted and the length of the valid part of the log.
+/// Reading stops at the first incomplete or corrupted record, which can be left behind if the server crashed while writing it.
+fn read_records(data: &[u8]) -> (Vec<LoggedOperation>, u64, usize) {
+    let mut operations = Vec::new();
+    let mut compacted_max_client_id = 0;
+    let mut offset = 0;
+    if let Some((record, next_offset)) = read_record(data, offset) {
+        if let Some(compacted) = CompactedRecord::from_abf(&record) {
+            compacted_max_client_id = compacted.max_client_id;
+            offset = next_offset;
+        }
+    }
+    while let Some((record, next_offset)) = read_record(data, offset) {
+        let Some(operation) = LoggedOperation::from_abf(&record) else { break; };
+        operations.push(operation);
+        offset = next_offset;
+    }
+    (operations, compacted_max_client_id, offset)
+}
+
+fn write_record(file: &mut fs::File, record: &[u8]) -> io::Result<()> {
+    let mut data = Vec::with_capacity(4 + record.len());
+    data.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    data.extend_from_slice(record);
+    file.write_all(&data)
+}
+
+/// Read all the operations in the operation log of the project at `path`, in order.
+/// Useful for auditing the recent changes made to a project, or replaying them.
+/// Once the log grows long, it is compacted, dropping the operations older than the last few thousand.
+pub fn read_op_log<PathRef: AsRef<Path>>(path: PathRef) -> Option<Vec<LoggedOperation>> {
+    let data = fs::read(op_log_path(path.as_ref())).ok()?;
+    Some(read_records(&data).0)
+}
+
+impl OpLog {
+
+    pub(crate) fn in_memory() -> Self {
+        Self {
+            file: None,
+            path: None,
+            records: 0,
+            unsynced: false,
+            recent: VecDeque::new(),
+            seq: 0,
+            max_client_id: 0
+        }
+    }
+
+    /// Open the operation log of the project at `path`, creating it if it doesn't exist yet
+    pub(crate) fn open(path: &Path) -> Option<Self> {
+        let path = op_log_path(path);
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path).ok()?;
+
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).ok()?;
+        let (operations, compacted_max_client_id, valid_len) = read_records(&data);
+        // Get rid of any partially written record at the end, so new records are appended after the valid ones
+        if valid_len < data.len() {
+            file.set_len(valid_len as u64).ok()?;
+        }
+        file.seek(SeekFrom::Start(valid_len as u64)).ok()?;
+
+        let seq = operations.last().map(|operation| operation.seq).unwrap_or(0);
+        let max_client_id = operations.iter().map(|operation| operation.client.0).max().unwrap_or(0).max(compacted_max_client_id);
+        let records = operations.len();
+        let skip = operations.len().saturating_sub(RECENT_OPERATIONS);
+        Some(Self {
+            file: Some(file),
+            path: Some(path),
+            records,
+            unsynced: false,
+            recent: operations.into_iter().skip(skip).map(|operation| (operation, None)).collect(),
+            seq,
+            max_client_id
+        })
+    }
+
+    /// The sequence number of the last logged operation
+    pub(crate) fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// The largest client ID that appears in the log.
+    /// Used to avoid handing out the IDs of clients from before the server restarted.
+    pub(crate) fn max_client_id(&self) -> u64 {
+        self.max_client_id
+    }
+
+    /// Get the sequence number for the next operation
+    pub(crate) fn next_seq(&mut self) -> u64 {
+        self.seq += 1;
+        self.seq
+    }
+
+    /// Log an operation, along with what it touched. The operation is written to the log file right away, but only synced to disk by `OpLog::sync`.
+    pub(crate) fn append(&mut self, operation: LoggedOperation, touched: Touched) -> io::Result<()> {
+        self.max_client_id = self.max_client_id.max(operation.client.0);
+        self.recent.push_back((operation, Some(touched)));
+        if self.recent.len() > RECENT_OPERATIONS {
+            self.recent.pop_front();
+        }
+
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        write_record(file, &self.recent.back().unwrap().0.encode())?;
+        self.records += 1;
+        self.unsynced = true;
+
+        if self.records >= COMPACT_THRESHOLD {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Make sure the operations written to the log file since the last sync are stored on disk
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        if self.unsynced {
+            file.sync_data()?;
+            self.unsynced = false;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the log file with only the operations kept in memory.
+    /// The new log is written next to the old one and then moved over it, so a crash never leaves the log half-written.
+    fn compact(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut compacted_path = path.as_os_str().to_owned();
+        compacted_path.push(".compact");
+        let compacted_path = PathBuf::from(compacted_path);
+
+        let mut file = fs::File::create(&compacted_path)?;
+        write_record(&mut file, &CompactedRecord { max_client_id: self.max_client_id }.encode())?;
+        for (operation, _) in &self.recent {
+            write_record(&mut file, &operation.encode())?;
+        }
+        file.sync_data()?;
+        // Close the old log first, since some platforms can't replace open files
+        self.file = None;
+        let renamed = fs::rename(&compacted_path, path);
+        self.file = Some(fs::OpenOptions::new().append(true).open(path)?);
+        renamed?;
+
+        self.records = self.recent.len();
+        self.unsynced = false;
+        Ok(())
+    }
+
+    /// Get the operations performed after the sequence number `seq`, along with what they touched.
+    /// Returns None if some of them are no longer kept in memory.
+    pub(crate) fn since(&self, seq: u64) -> Option<impl Iterator<Item = &(LoggedOperation, Option<Touched>)>> {
+        let oldest_seq = self.seq - self.recent.len() as u64;
+        if seq < oldest_seq || seq > self.seq {
+            return None;
+        }
+        Some(self.recent.iter().filter(move |(operation, _)| operation.seq > seq))
+    }
+
+}
+
+impl Drop for OpLog {
+
+    fn drop(&mut self) {
+        let _ = self.sync();
+    }
+
+}
@@ -0,0 +1,31 @@
+
+use crate::{AnyPtr, Project, ProjectContext};
+
+use super::ClientId;
+
+/// Decides which parts of a project each client is allowed to modify and load.
+/// Set using `Server::set_permissions`. Without it, every client can do anything.
+pub trait Permissions<P: Project>: Send {
+
+    /// Can the client modify the object at `ptr`? `None` refers to the project itself.
+    /// Objects being created are checked once the operation is done, so that the objects they are attached to exist too, and objects being deleted are checked before they are removed.
+    fn can_modify(&self, client: ClientId, context: &ProjectContext<P>, ptr: Option<AnyPtr>) -> bool;
+
+    /// Can the client load the object at `ptr`? The objects loaded along with it are not checked separately.
+    fn can_load(&self, client: ClientId, context: &ProjectContext<P>, ptr: AnyPtr) -> bool;
+
+}
+
+/// The permissions of the client whose operation is being performed
+pub(crate) struct PermissionGuard<'a, P: Project> {
+    pub(crate) permissions: &'a dyn Permissions<P>,
+    pub(crate) client: ClientId
+}
+
+impl<P: Project> PermissionGuard<'_, P> {
+
+    pub(crate) fn can_modify(&self, context: &ProjectContext<P>, ptr: Option<AnyPtr>) -> bool {
+        self.permissions.can_modify(self.client, context, ptr)
+    }
+
+}
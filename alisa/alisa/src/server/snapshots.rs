@@ -0,0 +1,67 @@
+
+use crate::{ClientId, LoggedOperation, Operation, OperationOutcome, Project, RestoreSnapshot, Serializable, SerializationContext, Snapshot, SnapshotInfo};
+
+use super::{PermissionGuard, Server};
+
+impl<P: Project> Server<P> {
+
+    /// Save a named snapshot of the project in the project file
+    pub fn create_snapshot(&mut self, name: &str) -> Option<SnapshotInfo> {
+        self.client.create_snapshot(name)
+    }
+
+    /// The snapshots stored in the project file, from oldest to newest
+    pub fn snapshots(&mut self) -> Vec<SnapshotInfo> {
+        self.client.snapshots()
+    }
+
+    /// Read a snapshot stored in the project file
+    pub fn snapshot(&mut self, id: u64) -> Option<Snapshot<P>> {
+        self.client.snapshot(id)
+    }
+
+    /// Take a snapshot of the project without storing it, for comparing the current state of the project to a stored snapshot
+    pub fn capture_snapshot(&mut self) -> Option<Snapshot<P>> {
+        self.client.capture_snapshot()
+    }
+
+    /// Delete a snapshot from the project file. Returns false if there is no such snapshot.
+    pub fn delete_snapshot(&mut self, id: u64) -> bool {
+        self.client.delete_snapshot(id)
+    }
+
+    /// Bring the project back to the state of a snapshot, on behalf of `client`.
+    /// The changes are made by a `RestoreSnapshot` operation, which is sent to the clients like any other operation and logged as sent by `ClientId::SERVER`.
+    /// Like the client's own operations, the restore is denied if it would modify anything the client isn't allowed to. Restores requested by the server itself, using `ClientId::SERVER`, aren't restricted.
+    /// Returns false if the project doesn't register `RestoreSnapshot`, or the operation failed or was denied.
+    pub fn restore_snapshot(&mut self, client: ClientId, snapshot: &Snapshot<P>) -> bool {
+        let Some(operation) = self.client.restore_snapshot_operation(snapshot) else {
+            return false;
+        };
+        let data = operation.serialize(&SerializationContext::new());
+        let name = RestoreSnapshot::<P>::NAME;
+        let guard = self.permissions.as_deref().filter(|_| client != ClientId::SERVER).map(|permissions| PermissionGuard {
+            permissions,
+            client
+        });
+
+        let seq = self.log.next_seq();
+        let (outcome, touched) = self.client.handle_operation_message(name, &data, guard);
+        let success = outcome == OperationOutcome::Performed;
+        if success {
+            self.forward_operation(ClientId::SERVER, name, &data, seq, &touched);
+        }
+        self.log_operation(LoggedOperation {
+            seq,
+            client: ClientId::SERVER,
+            operation: name.to_owned(),
+            data,
+            success,
+            denied: outcome == OperationOutcome::Denied
+        }, touched);
+        self.client.tick();
+
+        success
+    }
+
+}
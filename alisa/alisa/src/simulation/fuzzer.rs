@@ -0,0 +1,99 @@
+use crate::{ABFValue, AnyPtr, Client, Object, Project, Ptr};
+
+use super::SimulationRng;
+
+/// Helps generate random operations for a client in a `Simulation`
+pub struct Fuzzer<'a, P: Project> {
+    pub(super) rng: &'a mut SimulationRng,
+    pub(super) client: &'a Client<P>,
+    /// The next key to use for new objects.
+    /// Every simulated client gets its own range of keys, since collab clients don't coordinate the keys of the objects they create.
+    pub(super) next_key: &'a mut u64,
+    /// The objects created so far by any client, which might have been deleted or not be loaded by this client
+    pub(super) known: &'a mut Vec<AnyPtr>
+}
+
+impl<P: Project> Fuzzer<'_, P> {
+
+    pub fn rng(&mut self) -> &mut SimulationRng {
+        self.rng
+    }
+
+    /// The client that will perform the operation
+    pub fn client(&self) -> &Client<P> {
+        self.client
+    }
+
+    /// A pointer for a new object
+    pub fn new_ptr<O: Object<Project = P>>(&mut self) -> Ptr<O> {
+        Ptr::from_key(self.new_key(O::TYPE_ID))
+    }
+
+    fn new_key(&mut self, obj_type: u16) -> u64 {
+        let key = *self.next_key;
+        *self.next_key += 1;
+        self.known.push(AnyPtr::new(obj_type, key));
+        key
+    }
+
+    /// A random object of the given type loaded by the client, if there is one
+    pub fn loaded_ptr<O: Object<Project = P>>(&mut self) -> Option<Ptr<O>> {
+        self.loaded_key(O::TYPE_ID).map(Ptr::from_key)
+    }
+
+    fn loaded_key(&mut self, obj_type: u16) -> Option<u64> {
+        let object_kind = P::OBJECTS.get(obj_type as usize)?;
+        let keys = (object_kind.loaded_keys)(&self.client.objects);
+        self.rng.pick(&keys).copied()
+    }
+
+    /// A random object of the given type. Usually one the client has loaded, but sometimes a new one or one the client doesn't have.
+    fn obj_key(&mut self, obj_type: u16, key: u64) -> u64 {
+        let roll = self.rng.unit();
+        if roll < 0.7 {
+            if let Some(key) = self.loaded_key(obj_type) {
+                return key;
+            }
+        }
+        if roll < 0.9 {
+            return self.new_key(obj_type);
+        }
+        let known = self.known.iter().filter(|ptr| ptr.obj_type() == obj_type).map(|ptr| ptr.key()).collect::<Vec<_>>();
+        self.rng.pick(&known).copied().unwrap_or(key)
+    }
+
+    fn string(&mut self) -> String {
+        let length = self.rng.below(8);
+        (0..length).map(|_| (b'a' + self.rng.below(26) as u8) as char).collect()
+    }
+
+    /// Randomize the values in the serialized form of an operation, keeping its shape.
+    /// Numbers, booleans and strings are replaced with random ones, and object pointers point to random objects of the same type.
+    /// Arrays keep their length and enums keep their variant, since the template doesn't say what else they could hold.
+    pub fn value(&mut self, template: &ABFValue) -> ABFValue {
+        match template {
+            ABFValue::Bool(_) => ABFValue::Bool(self.rng.chance(0.5)),
+            ABFValue::PositiveInt(_) => ABFValue::PositiveInt(self.rng.below(128) as u8),
+            ABFValue::U8(_) => ABFValue::U8(self.rng.below(256) as u8),
+            ABFValue::U16(_) => ABFValue::U16(self.rng.below(1024) as u16),
+            ABFValue::U32(_) => ABFValue::U32(self.rng.below(1024) as u32),
+            ABFValue::U64(_) => ABFValue::U64(self.rng.below(1024)),
+            ABFValue::I8(_) => ABFValue::I8(self.rng.below(256) as u8 as i8),
+            ABFValue::I16(_) => ABFValue::I16(self.rng.below(2048) as i16 - 1024),
+            ABFValue::I32(_) => ABFValue::I32(self.rng.below(2048) as i32 - 1024),
+            ABFValue::I64(_) => ABFValue::I64(self.rng.below(2048) as i64 - 1024),
+            ABFValue::F32(_) => ABFValue::F32((self.rng.unit() * 200.0 - 100.0) as f32),
+            ABFValue::F64(_) => ABFValue::F64(self.rng.unit() * 200.0 - 100.0),
+            ABFValue::Str(_) => ABFValue::Str(self.string()),
+            ABFValue::Binary(data) => ABFValue::Binary(data.clone()),
+            ABFValue::ObjPtr(obj_type, key) => ABFValue::ObjPtr(*obj_type, self.obj_key(*obj_type, *key)),
+            ABFValue::Array(items) => ABFValue::Array(items.iter().map(|item| self.value(item)).collect()),
+            ABFValue::Map(fields) => ABFValue::Map(fields.iter().map(|(name, value)| (name.clone(), self.value(value))).collect()),
+            ABFValue::IndexedUnitEnum(idx) => ABFValue::IndexedUnitEnum(*idx),
+            ABFValue::IndexedEnum(idx, data) => ABFValue::IndexedEnum(*idx, Box::new(self.value(data))),
+            ABFValue::NamedUnitEnum(name) => ABFValue::NamedUnitEnum(name.clone()),
+            ABFValue::NamedEnum(name, data) => ABFValue::NamedEnum(name.clone(), Box::new(self.value(data))),
+        }
+    }
+
+}
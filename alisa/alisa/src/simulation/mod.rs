@@ -0,0 +1,309 @@
+use std::collections::VecDeque;
+
+use crate::{deserialize, encode_abf_compressed, parse_abf, serialize, AnyPtr, Client, ClientId, Message, Operation, Project, SerializationContext, Server, WelcomeMessage};
+
+mod rng;
+pub use rng::*;
+
+mod fuzzer;
+pub use fuzzer::*;
+
+/// Generates a random operation and queues it on the fuzzer's client. Returns false if no operation was generated.
+type Generator<P> = Box<dyn FnMut(&mut Fuzzer<P>) -> bool>;
+
+/// A client in the simulation, along with its connection to the server
+struct SimulatedClient<P: Project> {
+    id: ClientId,
+    client: Client<P>,
+    /// Encoded messages on their way to the server, in the order they were sent
+    to_server: VecDeque<Vec<u8>>,
+    /// Encoded messages on their way to the client, in the order they were sent
+    to_client: VecDeque<Vec<u8>>,
+    /// The next key this client uses for new objects
+    next_key: u64,
+    connected: bool
+}
+
+/// Runs a server and several collab clients in-process, has the clients perform random operations and delivers their messages with random delays.
+/// Each connection delivers messages in order, like a websocket would, but connections are serviced in a random order, so messages from different clients interleave arbitrarily.
+/// Every choice is made using a random number generator seeded by the caller, so a failing seed can be replayed.
+/// Once the simulation settles, `check_convergence` makes sure every client ended up with the same state as the server.
+pub struct Simulation<P: Project> {
+    server: Server<P>,
+    clients: Vec<SimulatedClient<P>>,
+    rng: SimulationRng,
+    generators: Vec<(&'static str, Generator<P>)>,
+    /// Every object created during the simulation
+    known: Vec<AnyPtr>,
+    /// The chance of a client losing or regaining its connection on each step
+    disconnect_chance: f64
+}
+
+/// Binary data at least this long is compressed in simulated messages, so that compression is covered too
+const COMPRESSION_THRESHOLD: usize = 64;
+
+fn encode_message(msg: &Message) -> Vec<u8> {
+    encode_abf_compressed(&serialize(msg), COMPRESSION_THRESHOLD)
+}
+
+fn decode_message(data: &[u8]) -> Option<Message> {
+    deserialize(&parse_abf(data)?)
+}
+
+impl<P: Project> Simulation<P> {
+
+    /// Create a simulation with a server for an empty project kept in memory and `clients` collab clients
+    pub fn new(clients: usize, seed: u64) -> Self {
+        let mut server = Server::with_storage(verter::MemoryStorage::new()).expect("could not create server");
+        let clients = (0..clients).map(|idx| {
+            let (id, welcome) = server.add_client();
+            let welcome = parse_abf(&encode_abf_compressed(&serialize(&welcome), COMPRESSION_THRESHOLD))
+                .and_then(|data| deserialize::<WelcomeMessage>(&data))
+                .expect("could not decode welcome message");
+            SimulatedClient {
+                id,
+                client: Client::collab(&welcome).expect("could not create client"),
+                to_server: VecDeque::new(),
+                to_client: VecDeque::new(),
+                next_key: (idx as u64 + 1) << 40,
+                connected: true
+            }
+        }).collect();
+
+        Self {
+            server,
+            clients,
+            rng: SimulationRng::new(seed),
+            generators: Vec::new(),
+            known: Vec::new(),
+            disconnect_chance: 0.0
+        }
+    }
+
+    /// Let clients perform operations of type `O` generated by randomizing the values in `O::default()`. See `Fuzzer::value`.
+    pub fn fuzz<O: Operation<Project = P> + Default>(&mut self) {
+        let template = serialize(&O::default());
+        self.generate::<O, _>(move |fuzzer| deserialize(&fuzzer.value(&template)));
+    }
+
+    /// Let clients perform operations of type `O` made by a custom generator. The generator can return None to skip its turn.
+    pub fn generate<O: Operation<Project = P>, F: FnMut(&mut Fuzzer<P>) -> Option<O> + 'static>(&mut self, mut generator: F) {
+        self.generators.push((O::NAME, Box::new(move |fuzzer| {
+            let Some(operation) = generator(fuzzer) else {
+                return false;
+            };
+            fuzzer.client.queue_operation(operation);
+            true
+        })));
+    }
+
+    /// The operations in `Project::OPERATIONS` the simulation has no way of generating
+    pub fn unregistered_operations(&self) -> Vec<&'static str> {
+        P::OPERATIONS.iter()
+            .map(|kind| kind.name)
+            .filter(|name| !self.generators.iter().any(|(generated, _)| generated == name))
+            .collect()
+    }
+
+    /// Have clients randomly lose their connection to the server and resume their session later
+    pub fn set_disconnect_chance(&mut self, chance: f64) {
+        self.disconnect_chance = chance;
+    }
+
+    pub fn client(&self, idx: usize) -> &Client<P> {
+        &self.clients[idx].client
+    }
+
+    pub fn server(&self) -> &Server<P> {
+        &self.server
+    }
+
+    /// Take a single random step: performing operations, requesting objects or delivering messages
+    pub fn step(&mut self) {
+        if self.clients.is_empty() {
+            return;
+        }
+        let idx = self.rng.below(self.clients.len() as u64) as usize;
+
+        if self.rng.chance(self.disconnect_chance) {
+            if self.clients[idx].connected {
+                self.disconnect(idx);
+            } else {
+                self.reconnect(idx);
+            }
+            return;
+        }
+
+        match self.rng.below(10) {
+            0..=3 => self.perform_operations(idx),
+            4 => self.request_load(idx),
+            5..=7 => self.deliver_to_server(idx),
+            _ => self.deliver_to_client(idx)
+        }
+    }
+
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    fn perform_operations(&mut self, idx: usize) {
+        if self.generators.is_empty() {
+            return;
+        }
+        let n_operations = 1 + self.rng.below(3);
+        for _ in 0..n_operations {
+            let generator = self.rng.below(self.generators.len() as u64) as usize;
+            let client = &mut self.clients[idx];
+            let mut fuzzer = Fuzzer {
+                rng: &mut self.rng,
+                client: &client.client,
+                next_key: &mut client.next_key,
+                known: &mut self.known
+            };
+            (self.generators[generator].1)(&mut fuzzer);
+        }
+        self.clients[idx].client.tick();
+
+        // Sometimes hold on to the messages for a bit, like a client with a busy connection would
+        if self.rng.chance(0.5) {
+            self.flush(idx);
+        }
+    }
+
+    /// Ask a client to load a random object created during the simulation
+    fn request_load(&mut self, idx: usize) {
+        let Some(ptr) = self.rng.pick(&self.known).copied() else {
+            return;
+        };
+        let object_kind = &P::OBJECTS[ptr.obj_type() as usize];
+        (object_kind.request_load)(&self.clients[idx].client.objects, ptr.key());
+        self.flush(idx);
+    }
+
+    /// Tick a client and send the messages it has queued
+    fn flush(&mut self, idx: usize) {
+        let client = &mut self.clients[idx];
+        client.client.tick();
+        let messages = client.client.take_messages();
+        if client.connected {
+            client.to_server.extend(messages.iter().map(encode_message));
+        }
+    }
+
+    /// Queue the messages the server wants to send on the clients' connections
+    fn collect_server_messages(&mut self) {
+        for (id, messages) in self.server.take_all_msgs_to_send() {
+            let Some(client) = self.clients.iter_mut().find(|client| client.id == id) else {
+                continue;
+            };
+            if client.connected {
+                client.to_client.extend(messages.iter().map(encode_message));
+            }
+        }
+    }
+
+    fn deliver_to_server(&mut self, idx: usize) {
+        let n_messages = 1 + self.rng.below(4);
+        for _ in 0..n_messages {
+            let Some(data) = self.clients[idx].to_server.pop_front() else {
+                break;
+            };
+            let msg = decode_message(&data).expect("could not decode message sent to server");
+            self.server.receive_message(self.clients[idx].id, &msg);
+        }
+        self.collect_server_messages();
+    }
+
+    fn deliver_to_client(&mut self, idx: usize) {
+        let n_messages = 1 + self.rng.below(4);
+        for _ in 0..n_messages {
+            let Some(data) = self.clients[idx].to_client.pop_front() else {
+                break;
+            };
+            let msg = decode_message(&data).expect("could not decode message sent to client");
+            self.clients[idx].client.receive_message(&msg);
+        }
+        self.flush(idx);
+    }
+
+    /// Cut a client's connection. Messages that were on their way are lost.
+    fn disconnect(&mut self, idx: usize) {
+        let client = &mut self.clients[idx];
+        self.server.disconnect_client(client.id);
+        client.client.disconnect();
+        client.to_server.clear();
+        client.to_client.clear();
+        client.connected = false;
+    }
+
+    fn reconnect(&mut self, idx: usize) -> bool {
+        let client = &mut self.clients[idx];
+        let Some(session) = client.client.session() else {
+            return false;
+        };
+        if self.server.resume_client(&session).is_err() {
+            return false;
+        }
+        client.connected = true;
+        self.collect_server_messages();
+        true
+    }
+
+    /// Reconnect every client and deliver messages until there are none left.
+    /// Panics if the messages never stop, or if a client can't resume its session.
+    pub fn settle(&mut self) {
+        for idx in 0..self.clients.len() {
+            if !self.clients[idx].connected {
+                assert!(self.reconnect(idx), "client {} could not resume its session", idx);
+            }
+        }
+
+        for _ in 0..10000 {
+            for idx in 0..self.clients.len() {
+                self.flush(idx);
+            }
+            let settled = self.clients.iter().all(|client| client.to_server.is_empty() && client.to_client.is_empty());
+            if settled {
+                return;
+            }
+            for idx in 0..self.clients.len() {
+                while !self.clients[idx].to_server.is_empty() {
+                    self.deliver_to_server(idx);
+                }
+            }
+            for idx in 0..self.clients.len() {
+                while !self.clients[idx].to_client.is_empty() {
+                    self.deliver_to_client(idx);
+                }
+            }
+        }
+        panic!("simulation did not settle");
+    }
+
+    /// Check that every client has the same project as the server, and that every object a client has loaded matches the server's.
+    /// Should be called after `settle`.
+    pub fn check_convergence(&mut self) -> Result<(), String> {
+        let project = self.server.project().serialize(&SerializationContext::new());
+        for (idx, client) in self.clients.iter_mut().enumerate() {
+            let client_project = client.client.project.serialize(&SerializationContext::new());
+            if client_project != project {
+                return Err(format!("client {}'s project differs from the server's.\nclient: {:?}\nserver: {:?}", idx, client_project, project));
+            }
+
+            for object_kind in P::OBJECTS {
+                for key in (object_kind.loaded_keys)(&client.client.objects) {
+                    let ptr = AnyPtr::new(object_kind.object_type_id, key);
+                    let client_object = (object_kind.serialize_object)(&mut client.client.objects, key, &SerializationContext::new());
+                    let server_object = self.server.serialize_object(ptr);
+                    if client_object != server_object {
+                        return Err(format!("client {}'s copy of object {:?} differs from the server's.\nclient: {:?}\nserver: {:?}", idx, ptr, client_object, server_object));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+}
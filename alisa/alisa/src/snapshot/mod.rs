@@ -0,0 +1,138 @@
+
+use std::{collections::HashMap, marker::PhantomData, path::Path};
+
+use crate::{ABFValue, AnyPtr, DeserializationContext, File, Object, Project, Ptr};
+
+mod restore;
+pub use restore::*;
+
+/// A snapshot stored in a project file
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SnapshotInfo {
+    /// The ID of the snapshot, unique within the file
+    pub id: u64,
+    pub name: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub created: u64
+}
+
+/// The state of a project at some point in time: the project data and every object reachable from it.
+/// The data is kept serialized in the current format of the project's schema.
+pub struct Snapshot<P: Project> {
+    pub(crate) project: ABFValue,
+    pub(crate) objects: HashMap<AnyPtr, ABFValue>,
+    /// The next key available for use when the snapshot was taken
+    pub(crate) curr_key: u64,
+
+    _marker: PhantomData<P>
+}
+
+/// The changes between two snapshots
+#[derive(Default, Debug)]
+pub struct SnapshotDiff {
+    /// Did the project data change?
+    pub project: bool,
+    /// The objects that only exist in the newer snapshot
+    pub added: Vec<AnyPtr>,
+    /// The objects that only exist in the older snapshot
+    pub removed: Vec<AnyPtr>,
+    /// The objects that exist in both snapshots, but with different data
+    pub modified: Vec<AnyPtr>
+}
+
+impl SnapshotDiff {
+
+    pub fn is_empty(&self) -> bool {
+        !self.project && self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+}
+
+fn sort_ptrs(ptrs: &mut [AnyPtr]) {
+    ptrs.sort_by_key(|ptr| (ptr.obj_type(), ptr.key()));
+}
+
+impl<P: Project> Snapshot<P> {
+
+    pub(crate) fn new(project: ABFValue, objects: HashMap<AnyPtr, ABFValue>, curr_key: u64) -> Self {
+        Self {
+            project,
+            objects,
+            curr_key,
+            _marker: PhantomData
+        }
+    }
+
+    /// The project as it was when the snapshot was taken
+    pub fn project(&self) -> Option<P> {
+        P::deserialize(&self.project, &mut DeserializationContext::new())
+    }
+
+    /// An object as it was when the snapshot was taken
+    pub fn get<O: Object<Project = P>, T: Into<Ptr<O>>>(&self, ptr: T) -> Option<O> {
+        let data = self.objects.get(&ptr.into().any())?;
+        O::deserialize(data, &mut DeserializationContext::new())
+    }
+
+    /// The pointers to all the objects in the snapshot
+    pub fn objects(&self) -> impl Iterator<Item = AnyPtr> + '_ {
+        self.objects.keys().copied()
+    }
+
+    /// The serialized data of the project, for inspecting the snapshot without knowing the types involved
+    pub fn project_data(&self) -> &ABFValue {
+        &self.project
+    }
+
+    /// The serialized data of an object in the snapshot
+    pub fn data(&self, ptr: AnyPtr) -> Option<&ABFValue> {
+        self.objects.get(&ptr)
+    }
+
+    /// The changes made to the project going from this snapshot to a newer one
+    pub fn diff(&self, newer: &Snapshot<P>) -> SnapshotDiff {
+        let mut diff = SnapshotDiff {
+            project: self.project != newer.project,
+            ..SnapshotDiff::default()
+        };
+        for (ptr, data) in &newer.objects {
+            match self.objects.get(ptr) {
+                Some(old_data) if old_data != data => diff.modified.push(*ptr),
+                Some(_) => {},
+                None => diff.added.push(*ptr)
+            }
+        }
+        diff.removed = self.objects.keys().filter(|ptr| !newer.objects.contains_key(ptr)).copied().collect();
+        sort_ptrs(&mut diff.added);
+        sort_ptrs(&mut diff.removed);
+        sort_ptrs(&mut diff.modified);
+        diff
+    }
+
+    /// Save the snapshot as a new project file.
+    /// Fails if there already is a project at `path`.
+    pub fn save_as<PathRef: AsRef<Path>>(&self, path: PathRef) -> Option<()> {
+        self.save_to_storage(verter::FileStorage::open(path).ok()?)
+    }
+
+    /// Save the snapshot as a new project in a custom storage, such as `verter::MemoryStorage`.
+    /// Fails if the storage already contains a project.
+    pub fn save_to_storage<S: verter::Storage + 'static>(&self, storage: S) -> Option<()> {
+        File::create_from_snapshot(storage, self)
+    }
+
+    /// The operation that turns the project from the state in `current` into the state in this snapshot
+    pub(crate) fn restore_operation(&self, current: &Snapshot<P>) -> RestoreSnapshot<P> {
+        let diff = current.diff(self);
+        let project = diff.project.then(|| self.project.clone());
+        let mut objects = Vec::new();
+        for ptr in diff.removed {
+            objects.push((ptr, None));
+        }
+        for ptr in diff.added.into_iter().chain(diff.modified) {
+            objects.push((ptr, self.objects.get(&ptr).cloned()));
+        }
+        RestoreSnapshot::new(project, objects)
+    }
+
+}
@@ -0,0 +1,106 @@
+
+use std::marker::PhantomData;
+
+use crate::{ABFValue, AnyPtr, DeserializationContext, InvertibleOperation, Operation, Project, ProjectContext, Recorder, Serializable, SerializationContext};
+
+/// Set the project and a set of objects to the state they had in a snapshot.
+/// Projects that want to restore snapshots register it with `OperationKind::from_invertible::<RestoreSnapshot<Self>>()`, which makes restoring a snapshot undoable.
+pub struct RestoreSnapshot<P: Project> {
+    /// The project data to restore, if the project changed
+    project: Option<ABFValue>,
+    /// The data to restore each object to, or `None` if the object should be deleted
+    objects: Vec<(AnyPtr, Option<ABFValue>)>,
+
+    _marker: PhantomData<fn() -> P>
+}
+
+impl<P: Project> RestoreSnapshot<P> {
+
+    pub(crate) fn new(project: Option<ABFValue>, objects: Vec<(AnyPtr, Option<ABFValue>)>) -> Self {
+        Self {
+            project,
+            objects,
+            _marker: PhantomData
+        }
+    }
+
+    /// The objects restored by the operation
+    pub(crate) fn objects(&self) -> impl Iterator<Item = AnyPtr> + '_ {
+        self.objects.iter().map(|(ptr, _)| *ptr)
+    }
+
+    /// Does the operation change anything?
+    pub fn is_empty(&self) -> bool {
+        self.project.is_none() && self.objects.is_empty()
+    }
+
+}
+
+impl<P: Project> Default for RestoreSnapshot<P> {
+
+    fn default() -> Self {
+        Self::new(None, Vec::new())
+    }
+
+}
+
+impl<P: Project> Serializable for RestoreSnapshot<P> {
+
+    fn serialize(&self, context: &SerializationContext) -> ABFValue {
+        ABFValue::Map(Box::new([
+            ("project".into(), self.project.serialize(context)),
+            ("objects".into(), self.objects.serialize(context)),
+        ]))
+    }
+
+    fn deserialize(data: &ABFValue, context: &mut DeserializationContext) -> Option<Self> {
+        Some(Self::new(
+            Serializable::deserialize(data.get("project")?, context)?,
+            Serializable::deserialize(data.get("objects")?, context)?
+        ))
+    }
+
+    fn delete(&self, _: &mut Vec<AnyPtr>) {
+
+    }
+
+}
+
+impl<P: Project> Operation for RestoreSnapshot<P> {
+    type Project = P;
+
+    const NAME: &'static str = "RestoreSnapshot";
+
+    fn perform(&self, recorder: &mut Recorder<'_, P>) -> bool {
+        if let Some(project) = &self.project {
+            let Some(project) = P::deserialize(project, &mut DeserializationContext::new()) else {
+                return false;
+            };
+            *recorder.project_mut() = project;
+        }
+        for (ptr, data) in &self.objects {
+            let Some(object_kind) = P::OBJECTS.get(ptr.obj_type() as usize) else {
+                return false;
+            };
+            if !(object_kind.restore)(recorder, ptr.key(), data.as_ref()) {
+                return false;
+            }
+        }
+        true
+    }
+
+}
+
+impl<P: Project> InvertibleOperation for RestoreSnapshot<P> {
+    type Inverse = Self;
+
+    fn inverse(&self, context: &ProjectContext<P>) -> Option<Self> {
+        let project = self.project.as_ref().map(|_| context.project.serialize(&SerializationContext::new()));
+        let objects = self.objects.iter().map(|(ptr, _)| {
+            let object_kind = P::OBJECTS.get(ptr.obj_type() as usize)?;
+            Some((*ptr, (object_kind.snapshot_data)(context.objects, ptr.key())?))
+        }).collect::<Option<Vec<_>>>()?;
+        Some(Self::new(project, objects))
+    }
+
+}
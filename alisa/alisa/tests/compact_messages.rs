@@ -0,0 +1,264 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+
+}
+
+#[derive(Default)]
+pub struct Objects {
+    nodes: alisa::ObjList<Node>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Node {
+    x: i32
+}
+
+alisa::object_set_property_operation!(Node, x, i32);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateNode {
+    ptr: alisa::Ptr<Node>,
+    x: i32
+}
+
+impl alisa::Operation for CreateNode {
+    type Project = Project;
+    const NAME: &'static str = "CreateNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Node {
+            x: self.x
+        })
+    }
+}
+
+/// Set one node's value to another's, reading one node and modifying the other
+#[derive(alisa::Serializable, Default)]
+struct CopyX {
+    from: alisa::Ptr<Node>,
+    to: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for CopyX {
+    type Project = Project;
+    const NAME: &'static str = "CopyX";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        let Some(x) = recorder.get_obj(self.from).map(|node| node.x) else { return false; };
+        let Some(to) = recorder.get_obj_mut(self.to) else { return false; };
+        to.x = x;
+        true
+    }
+}
+
+impl alisa::Object for Node {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.nodes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.nodes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self {}
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Node>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateNode>(),
+        alisa::OperationKind::from::<SetNodeX>(),
+        alisa::OperationKind::from::<CopyX>(),
+    ];
+
+}
+
+/// Nobody may modify anything
+struct ReadOnly;
+
+impl alisa::Permissions<Project> for ReadOnly {
+
+    fn can_modify(&self, _client: alisa::ClientId, _context: &alisa::ProjectContext<Project>, _ptr: Option<alisa::AnyPtr>) -> bool {
+        false
+    }
+
+    fn can_load(&self, _client: alisa::ClientId, _context: &alisa::ProjectContext<Project>, _ptr: alisa::AnyPtr) -> bool {
+        true
+    }
+
+}
+
+fn create_nodes(server: &mut TestingServer<Project>) -> (alisa::Ptr<Node>, alisa::Ptr<Node>) {
+    // Make sure Alice gets some keys in her keychain
+    server.tick_alice();
+    server.stabilize();
+
+    let a = server.alice().next_ptr();
+    let b = server.alice().next_ptr();
+    server.alice().queue_operation(CreateNode { ptr: a, x: 1 });
+    server.alice().queue_operation(CreateNode { ptr: b, x: 2 });
+    server.tick_alice();
+    server.stabilize();
+
+    (a, b)
+}
+
+fn batched_operations(messages: &[alisa::Message]) -> Vec<&alisa::CompactOperation> {
+    messages.iter().flat_map(|msg| match msg {
+        alisa::Message::Operations { operations } => operations.iter().collect(),
+        _ => Vec::new()
+    }).collect()
+}
+
+#[test]
+fn welcome_lists_operations() {
+
+    let mut server = TestingServer::<Project>::new();
+    let snapshot = server.snapshot();
+    assert_eq!(snapshot.operations, vec!["CreateNode".to_owned(), "SetNodeX".to_owned(), "CopyX".to_owned()]);
+
+}
+
+#[test]
+fn operations_are_batched() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 10 });
+    server.alice().queue_operation(SetNodeX { ptr: b, x_value: 20 });
+    let messages = server.take_client_messages(0);
+    assert_eq!(messages.len(), 1);
+    let operations = batched_operations(&messages);
+    assert_eq!(operations.len(), 2);
+    assert!(operations.iter().all(|operation| operation.operation == 1));
+
+    server.send_client_messages(0, messages);
+    server.stabilize();
+    assert_eq!(server.bob().get(a).unwrap().x, 10);
+    assert_eq!(server.bob().get(b).unwrap().x, 20);
+
+}
+
+#[test]
+fn server_batches_operations() {
+
+    let mut server = alisa::Server::<Project>::with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+    let (alice_id, alice_welcome) = server.add_client();
+    let (bob_id, _) = server.add_client();
+    let mut alice = alisa::Client::<Project>::collab(&alice_welcome).unwrap();
+
+    let a = alisa::Ptr::from_key(1 << 63);
+    let b = alisa::Ptr::from_key((1 << 63) + 1);
+    alice.queue_operation(CreateNode { ptr: a, x: 1 });
+    alice.queue_operation(CreateNode { ptr: b, x: 2 });
+    alice.tick();
+    for message in alice.take_messages() {
+        server.receive_message(alice_id, &message);
+    }
+
+    let mut messages = server.take_all_msgs_to_send();
+    let bob_messages = messages.remove(&bob_id).unwrap();
+    assert_eq!(bob_messages.len(), 1);
+    assert_eq!(batched_operations(&bob_messages).len(), 2);
+
+}
+
+#[test]
+fn superseded_operations_are_coalesced() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+
+    // Simulate a drag that changes the node on every frame while the connection is busy
+    let seq = server.server().seq();
+    for x in 3..10 {
+        server.alice().queue_operation(SetNodeX { ptr: a, x_value: x });
+        server.tick_alice();
+    }
+    server.alice().queue_operation(SetNodeX { ptr: b, x_value: 5 });
+    let messages = server.take_client_messages(0);
+    assert_eq!(batched_operations(&messages).len(), 2);
+
+    server.send_client_messages(0, messages);
+    server.stabilize();
+    assert_eq!(server.server().seq(), seq + 2);
+    assert_eq!(server.alice().get(a).unwrap().x, 9);
+    assert_eq!(server.bob().get(a).unwrap().x, 9);
+    assert_eq!(server.bob().get(b).unwrap().x, 5);
+
+}
+
+#[test]
+fn coalesced_operations_are_rejected_together() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, _) = create_nodes(&mut server);
+    server.set_permissions(ReadOnly);
+
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 5 });
+    server.tick_alice();
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 6 });
+    server.tick_alice();
+    assert_eq!(server.alice().get(a).unwrap().x, 6);
+
+    server.stabilize();
+    assert_eq!(server.alice().get(a).unwrap().x, 1);
+    assert_eq!(server.bob().get(a).unwrap().x, 1);
+
+}
+
+#[test]
+fn unrelated_operations_are_not_coalesced() {
+
+    let mut server = TestingServer::<Project>::new();
+    server.tick_alice();
+    server.stabilize();
+
+    let a = server.alice().next_ptr();
+    let b = server.alice().next_ptr();
+    server.alice().queue_operation(CreateNode { ptr: a, x: 1 });
+    server.tick_alice();
+    server.alice().queue_operation(CreateNode { ptr: b, x: 2 });
+    let messages = server.take_client_messages(0);
+    assert_eq!(batched_operations(&messages).len(), 2);
+
+}
+
+#[test]
+fn operations_using_the_same_objects_are_not_reordered() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+
+    // Copying A into B in between means A's first value matters, so the first change to A has to be sent
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 5 });
+    server.tick_alice();
+    server.alice().queue_operation(CopyX { from: a, to: b });
+    server.tick_alice();
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 6 });
+    let messages = server.take_client_messages(0);
+    assert_eq!(batched_operations(&messages).len(), 3);
+
+    server.send_client_messages(0, messages);
+    server.stabilize();
+    assert_eq!(server.bob().get(a).unwrap().x, 6);
+    assert_eq!(server.bob().get(b).unwrap().x, 5);
+
+}
@@ -0,0 +1,141 @@
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+
+}
+
+#[derive(Default)]
+pub struct Objects {
+    things: alisa::ObjList<Thing>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Thing {
+    data: Vec<i32>
+}
+
+impl alisa::Object for Thing {
+
+    type Project = Project;
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.things
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.things
+    }
+}
+
+#[derive(alisa::Serializable, Default)]
+pub struct CreateThing {
+    ptr: alisa::Ptr<Thing>,
+    data: Vec<i32>
+}
+
+impl alisa::Operation for CreateThing {
+
+    type Project = Project;
+    const NAME: &'static str = "CreateThing";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Self::Project>) -> bool {
+        recorder.add_obj(self.ptr, Thing {
+            data: self.data.clone(),
+        })
+    }
+}
+
+#[derive(alisa::Serializable, Default)]
+pub struct DeleteThing {
+    ptr: alisa::Ptr<Thing>
+}
+
+impl alisa::Operation for DeleteThing {
+
+    type Project = Project;
+    const NAME: &'static str = "DeleteThing";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Self::Project>) -> bool {
+        recorder.delete_obj(self.ptr).is_some()
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self {}
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Thing>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateThing>(),
+        alisa::OperationKind::from::<DeleteThing>()
+    ];
+}
+
+fn thing_data(i: i32) -> Vec<i32> {
+    (0..(i * 10)).collect()
+}
+
+#[test]
+fn compaction() {
+    let path = "compaction.test";
+
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    let ptrs = (0..100).map(|i| {
+        let ptr = client.next_ptr();
+        client.queue_operation(CreateThing {
+            ptr,
+            data: thing_data(i),
+        });
+        (i, ptr)
+    }).collect::<Vec<_>>();
+    client.tick();
+
+    for (i, ptr) in &ptrs {
+        if i % 4 != 0 {
+            client.queue_operation(DeleteThing {
+                ptr: *ptr,
+            });
+        }
+    }
+    client.tick();
+
+    let file_size = std::fs::metadata(path).unwrap().len();
+    let reclaimed_bytes = client.compact().unwrap();
+    assert!(reclaimed_bytes > 0);
+    assert_eq!(std::fs::metadata(path).unwrap().len(), file_size - reclaimed_bytes);
+    drop(client);
+
+    // Compaction should leave behind a healthy file
+    assert!(alisa::check_file::<Project>(path, false).unwrap().is_ok());
+
+    // Make sure all the remaining objects can still be loaded
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    for (i, ptr) in &ptrs {
+        client.request_load(*ptr);
+        client.tick();
+        if i % 4 == 0 {
+            assert_eq!(client.get(*ptr).unwrap().data, thing_data(*i));
+        } else {
+            assert!(client.get(*ptr).is_none());
+        }
+    }
+    drop(client);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn check_missing_file() {
+    let path = "check_missing_file.test.cip";
+    assert!(alisa::check_file::<Project>(path, false).is_err());
+    assert!(alisa::check_file::<Project>(path, true).is_err());
+    assert!(!std::path::Path::new(path).exists());
+}
@@ -63,7 +63,7 @@ impl alisa::Project for Project {
 
 #[test]
 fn basic() {
-    let mut server = TestingServer::<Project>::new("basic.test");
+    let mut server = TestingServer::<Project>::new();
 
     server.alice().queue_operation(Set { n: 50 });
     server.send_alice_messages();
@@ -87,7 +87,7 @@ fn basic() {
 
 #[test]
 fn set_then_add() {
-    let mut server = TestingServer::<Project>::new("set_then_add.test");
+    let mut server = TestingServer::<Project>::new();
 
     server.alice().queue_operation(Set { n: 50 });
     server.bob().queue_operation(Add { n: 10 });
@@ -106,7 +106,7 @@ fn set_then_add() {
 
 #[test]
 fn add_then_set() {
-    let mut server = TestingServer::<Project>::new("add_then_set.test");
+    let mut server = TestingServer::<Project>::new();
 
     server.alice().queue_operation(Set { n: 50 });
     server.bob().queue_operation(Add { n: 10 });
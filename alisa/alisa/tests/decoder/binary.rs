@@ -39,3 +39,44 @@ fn binary_u32() {
     assert_eq!(alisa::parse_abf(u32_binary_bytes), Some(alisa::ABFValue::Binary(Box::new([65, 66, 67]))));
 
 }
+
+#[test]
+fn compressed_binary() {
+
+    let data = [65; 1000];
+    let bytes = alisa::encode_abf_compressed(&alisa::ABFValue::Binary(Box::new(data)), 256);
+    assert_eq!(bytes[0], 0b11111000);
+    assert!(bytes.len() < data.len());
+
+    let mut decoder = alisa::Decoder::new(&bytes);
+    assert!(decoder.is_compressed_binary());
+    assert_eq!(decoder.read_compressed_binary(), Some(data.to_vec()));
+    assert!(value_skipped(&bytes));
+
+    assert_eq!(alisa::parse_abf(&bytes), Some(alisa::ABFValue::Binary(Box::new(data))));
+
+}
+
+#[test]
+fn compressed_binary_size_checked() {
+
+    let data = [65; 1000];
+    let bytes = alisa::encode_abf_compressed(&alisa::ABFValue::Binary(Box::new(data)), 256);
+
+    // The declared size has to match what the data inflates to
+    let mut wrong_size = bytes.clone();
+    wrong_size[1..5].copy_from_slice(&999u32.to_le_bytes());
+    assert_eq!(alisa::parse_abf(&wrong_size), None);
+    wrong_size[1..5].copy_from_slice(&1001u32.to_le_bytes());
+    assert_eq!(alisa::parse_abf(&wrong_size), None);
+
+    // Data claiming to inflate to more than the limit is refused before it's decompressed
+    let zeros = vec![0; alisa::MAX_DECOMPRESSED_BINARY_SIZE + 1];
+    let compressed = miniz_oxide::deflate::compress_to_vec(&zeros, 6);
+    let mut too_large = vec![0b11111000];
+    too_large.extend_from_slice(&(zeros.len() as u32).to_le_bytes());
+    too_large.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    too_large.extend_from_slice(&compressed);
+    assert_eq!(alisa::Decoder::new(&too_large).read_compressed_binary(), None);
+
+}
@@ -45,3 +45,33 @@ fn binary() {
     }), binary_bytes.as_slice());
 
 }
+
+#[test]
+fn compressed_binary() {
+
+    let encode_compressed = |bytes: &[u8]| {
+        let mut data = Vec::new();
+        alisa::Encoder::new(&mut data).with_binary_compression(16).binary(bytes).expect("encoding failed");
+        data
+    };
+
+    // Data below the threshold is left alone
+    assert_eq!(encode_compressed(&[65; 8]), encode(|enc| enc.binary(&[65; 8])));
+
+    // So is data that doesn't get smaller
+    let noise = (0..64u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect::<Vec<_>>();
+    assert_eq!(encode_compressed(&noise), encode(|enc| enc.binary(&noise)));
+
+    // Or only gets smaller by less than the extra header size of the compressed form
+    for n_repeated in 0..64 {
+        let mut bytes = noise.clone();
+        bytes.extend(std::iter::repeat_n(65, n_repeated));
+        assert!(encode_compressed(&bytes).len() <= encode(|enc| enc.binary(&bytes)).len());
+    }
+
+    let compressed = encode_compressed(&[65; 1000]);
+    assert_eq!(compressed[0], 0b11111000);
+    assert_eq!(&compressed[1..5], &1000u32.to_le_bytes());
+    assert_eq!(compressed.len(), 9 + u32::from_le_bytes([compressed[5], compressed[6], compressed[7], compressed[8]]) as usize);
+
+}
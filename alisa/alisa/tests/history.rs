@@ -0,0 +1,165 @@
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+    x: i32,
+    y: i32
+}
+
+alisa::project_set_property_operation!(Project, x, i32);
+alisa::project_set_property_operation!(Project, y, i32);
+
+#[derive(Default)]
+pub struct Objects {
+
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = String;
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from_invertible::<SetX>(),
+        // Not registered as invertible, so actions using it can't be stored
+        alisa::OperationKind::from::<SetY>()
+    ];
+
+}
+
+fn set_x(client: &mut alisa::Client<Project>, x: i32) {
+    client.queue_action(alisa::Action::single(format!("Set X to {}", x), SetX { x }));
+    client.tick();
+}
+
+fn open(storage: &alisa::verter::MemoryStorage, depth: usize) -> alisa::Client<Project> {
+    let mut client = alisa::Client::<Project>::local_with_storage(storage.clone()).unwrap();
+    assert!(client.enable_persistent_history(depth));
+    client
+}
+
+#[test]
+fn persistent_history() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let mut client = open(&storage, 3);
+    for x in 1..=5 {
+        set_x(&mut client, x);
+    }
+    assert_eq!(client.undo().as_deref(), Some("Set X to 5"));
+    client.tick();
+    assert_eq!(client.x, 4);
+    drop(client);
+
+    let mut client = open(&storage, 3);
+    assert_eq!(client.x, 4);
+    assert_eq!(client.undo_stack().borrow().len(), 2);
+    assert_eq!(client.redo_stack().borrow().len(), 1);
+
+    assert_eq!(client.redo().as_deref(), Some("Set X to 5"));
+    client.tick();
+    assert_eq!(client.x, 5);
+
+    // Only 3 actions fit in the history
+    for expected_x in [4, 3, 2] {
+        client.undo();
+        client.tick();
+        assert_eq!(client.x, expected_x);
+    }
+    assert!(client.undo().is_none());
+    drop(client);
+
+    // Reopening with a smaller depth drops the oldest actions
+    let mut client = open(&storage, 2);
+    assert_eq!(client.undo_stack().borrow().len(), 0);
+    assert_eq!(client.redo_stack().borrow().len(), 2);
+    client.redo();
+    client.tick();
+    assert_eq!(client.x, 3);
+    drop(client);
+
+    // Performing a new action clears the stored redo stack
+    let mut client = open(&storage, 2);
+    set_x(&mut client, 10);
+    drop(client);
+    let client = open(&storage, 2);
+    assert_eq!(client.undo_stack().borrow().len(), 2);
+    assert_eq!(client.redo_stack().borrow().len(), 0);
+
+}
+
+#[test]
+fn persistent_history_unregistered_operation() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let mut client = open(&storage, 10);
+    set_x(&mut client, 1);
+    client.queue_action(alisa::Action::single("Set Y".to_owned(), SetY { y: 1 }));
+    client.tick();
+    set_x(&mut client, 2);
+    drop(client);
+
+    // The action setting Y can't be loaded, so it can't be undone past
+    let client = open(&storage, 10);
+    assert_eq!(client.undo_stack().borrow().len(), 1);
+    assert_eq!(client.undo_stack().borrow()[0].context, "Set X to 2");
+
+}
+
+#[test]
+fn persistent_history_operations_outside_actions() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let mut client = open(&storage, 10);
+    set_x(&mut client, 1);
+    // Operations queued on their own are never stored in the history, so they don't need to be registered as invertible
+    client.queue_operation(SetY { y: 1 });
+    client.tick();
+    set_x(&mut client, 2);
+    drop(client);
+
+    let mut client = open(&storage, 10);
+    assert_eq!(client.y, 1);
+    assert_eq!(client.undo_stack().borrow().len(), 2);
+    for expected_x in [1, 0] {
+        client.undo();
+        client.tick();
+        assert_eq!(client.x, expected_x);
+    }
+    assert_eq!(client.y, 1);
+
+}
+
+#[test]
+fn persistent_history_compaction() {
+
+    let path = "persistent_history_compaction.test";
+    let _ = std::fs::remove_file(path);
+
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    assert!(client.enable_persistent_history(2));
+    for x in 1..=10 {
+        set_x(&mut client, x);
+    }
+    client.compact().unwrap();
+    assert!(alisa::check_file::<Project>(path, false).unwrap().is_ok());
+    client.undo();
+    client.tick();
+    drop(client);
+
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    assert!(client.enable_persistent_history(2));
+    assert_eq!(client.x, 9);
+    client.undo();
+    client.tick();
+    assert_eq!(client.x, 8);
+    drop(client);
+
+    assert!(alisa::check_file::<Project>(path, false).unwrap().is_ok());
+    let _ = std::fs::remove_file(path);
+
+}
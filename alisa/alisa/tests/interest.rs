@@ -0,0 +1,246 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+
+}
+
+#[derive(Default)]
+pub struct Objects {
+    nodes: alisa::ObjList<Node>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Node {
+    x: i32,
+    next: alisa::LoadingPtr<Node>
+}
+
+alisa::object_set_property_operation!(Node, x, i32);
+alisa::object_set_property_operation!(Node, next, alisa::LoadingPtr<Node>);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateNode {
+    ptr: alisa::Ptr<Node>,
+    x: i32
+}
+
+impl alisa::Operation for CreateNode {
+    type Project = Project;
+    const NAME: &'static str = "CreateNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Node {
+            x: self.x,
+            next: alisa::LoadingPtr::default(),
+        })
+    }
+}
+
+/// Swap the values of two nodes, touching both of them
+#[derive(alisa::Serializable, Default)]
+struct SwapX {
+    a: alisa::Ptr<Node>,
+    b: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for SwapX {
+    type Project = Project;
+    const NAME: &'static str = "SwapX";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        let Some(a) = recorder.get_obj(self.a).map(|node| node.x) else { return false; };
+        let Some(b) = recorder.get_obj(self.b).map(|node| node.x) else { return false; };
+        let Some(node_a) = recorder.get_obj_mut(self.a) else { return false; };
+        node_a.x = b;
+        let Some(node_b) = recorder.get_obj_mut(self.b) else { return false; };
+        node_b.x = a;
+        true
+    }
+}
+
+impl alisa::Object for Node {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.nodes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.nodes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self {}
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Node>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateNode>(),
+        alisa::OperationKind::from::<SetNodeX>(),
+        alisa::OperationKind::from::<SetNodeNext>(),
+        alisa::OperationKind::from::<SwapX>(),
+    ];
+
+}
+
+/// Have Alice create two nodes that aren't reachable from the project, so clients joining later don't have them
+fn create_nodes(server: &mut TestingServer<Project>) -> (alisa::Ptr<Node>, alisa::Ptr<Node>) {
+    // Make sure Alice gets some keys in her keychain
+    server.tick_alice();
+    server.stabilize();
+
+    let a = server.alice().next_ptr();
+    let b = server.alice().next_ptr();
+    server.alice().queue_operation(CreateNode { ptr: a, x: 1 });
+    server.alice().queue_operation(CreateNode { ptr: b, x: 2 });
+    server.tick_alice();
+    server.stabilize();
+
+    (a, b)
+}
+
+fn operations_sent_to(server: &TestingServer<Project>, client: usize) -> usize {
+    let msgs = server.server().get_msgs_to_send(server.client_id(client)).unwrap();
+    msgs.iter().filter(|msg| matches!(msg, alisa::Message::Operation { .. } | alisa::Message::Invalidate { .. })).count()
+}
+
+#[test]
+fn unrelated_operations_are_filtered() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, _) = create_nodes(&mut server);
+    let carol = server.add_client();
+
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 10 });
+    server.send_alice_messages();
+
+    // Bob saw the node being created, so he gets the change. Carol never loaded it.
+    assert_eq!(operations_sent_to(&server, 1), 1);
+    assert_eq!(operations_sent_to(&server, carol), 0);
+    server.stabilize();
+    assert_eq!(server.bob().get(a).unwrap().x, 10);
+
+    // Carol still gets the latest version when she loads it
+    server.client(carol).request_load(a);
+    server.tick_client(carol);
+    server.stabilize();
+    assert_eq!(server.client(carol).get(a).unwrap().x, 10);
+
+}
+
+#[test]
+fn partially_loaded_operations_invalidate() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+    let carol = server.add_client();
+
+    server.client(carol).request_load(a);
+    server.tick_client(carol);
+    server.stabilize();
+
+    // Carol can't swap the nodes herself since she doesn't have B, so the server sends her the new state of A instead
+    server.alice().queue_operation(SwapX { a, b });
+    server.send_alice_messages();
+    let msgs = server.server().get_msgs_to_send(server.client_id(carol)).unwrap();
+    assert!(matches!(msgs.as_slice(), [alisa::Message::Invalidate { .. }]));
+
+    server.stabilize();
+    assert_eq!(server.client(carol).get(a).unwrap().x, 2);
+    assert!(server.client(carol).get_ref(b).is_none());
+    assert_eq!(server.bob().get(a).unwrap().x, 2);
+    assert_eq!(server.bob().get(b).unwrap().x, 1);
+
+}
+
+#[test]
+fn invalidate_keeps_unconfirmed_operations() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+    let carol = server.add_client();
+
+    server.client(carol).request_load(a);
+    server.tick_client(carol);
+    server.stabilize();
+
+    // Carol links A to B before she hears about the swap
+    server.alice().queue_operation(SwapX { a, b });
+    server.send_alice_messages();
+    server.client(carol).queue_operation(SetNodeNext { ptr: a, next_value: alisa::LoadingPtr::new(b) });
+    server.tick_client(carol);
+    server.stabilize();
+
+    let carol_a = server.client(carol).get(a).unwrap();
+    assert_eq!(carol_a.x, 2);
+    assert_eq!(carol_a.next.ptr(), b);
+    assert_eq!(server.alice().get(a).unwrap().next.ptr(), b);
+
+}
+
+#[test]
+fn linked_objects_are_sent() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+    let carol = server.add_client();
+
+    server.client(carol).request_load(a);
+    server.tick_client(carol);
+    server.stabilize();
+    assert!(server.client(carol).get_ref(b).is_none());
+
+    // Once A points to B, loading A means loading B, so Carol gets B without asking for it
+    server.alice().queue_operation(SetNodeNext { ptr: a, next_value: alisa::LoadingPtr::new(b) });
+    server.tick_alice();
+    server.stabilize();
+    assert_eq!(server.client(carol).get(b).unwrap().x, 2);
+    assert!(server.server().client_has_loaded(server.client_id(carol), b.any()));
+
+}
+
+#[test]
+fn resume_partially_loaded() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (a, b) = create_nodes(&mut server);
+    let carol = server.add_client();
+
+    server.client(carol).request_load(a);
+    server.tick_client(carol);
+    server.stabilize();
+    server.disconnect(carol);
+
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 3 });
+    server.alice().queue_operation(SetNodeX { ptr: b, x_value: 5 });
+    server.alice().queue_operation(SwapX { a, b });
+    server.alice().queue_operation(SetNodeX { ptr: a, x_value: 7 });
+    server.tick_alice();
+    server.stabilize();
+
+    // Carol can follow the first change to A herself, but not the swap, so she gets the current state of A after the other operations.
+    // The changes to B alone are filtered out.
+    assert!(server.reconnect(carol));
+    let msgs = server.server().get_msgs_to_send(server.client_id(carol)).unwrap();
+    assert!(matches!(msgs.as_slice(), [alisa::Message::Operation { .. }, alisa::Message::Invalidate { .. }, alisa::Message::Resumed]));
+
+    server.stabilize();
+    assert_eq!(server.client(carol).get(a).unwrap().x, 7);
+    assert!(server.client(carol).get_ref(b).is_none());
+    assert!(!server.server().client_has_loaded(server.client_id(carol), b.any()));
+    assert_eq!(server.alice().get(b).unwrap().x, 3);
+
+}
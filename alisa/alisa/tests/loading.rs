@@ -73,7 +73,7 @@ impl alisa::Project for Project {
 #[test]
 fn basic_loading() {
 
-    let mut server = TestingServer::<Project>::new("basic_loading.test");
+    let mut server = TestingServer::<Project>::new();
 
     // Make sure Alice gets some keys in her keychain
     server.tick_alice();
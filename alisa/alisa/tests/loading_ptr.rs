@@ -103,6 +103,31 @@ fn project_load() {
 
 }
 
+#[test]
+fn project_load_memory() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let mut client = alisa::Client::<Project>::local_with_storage(storage.clone()).unwrap();
+    
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateNode {
+        ptr,
+        x: 123,
+        next: Default::default(),
+    });
+    client.queue_operation(SetNode {
+        node: alisa::LoadingPtr::new(ptr),
+    });
+    client.tick();
+
+    drop(client);
+
+    let client = alisa::Client::<Project>::local_with_storage(storage).unwrap();
+    assert!(client.get(ptr).is_some());
+    assert_eq!(client.get(ptr).unwrap().x, 123);
+
+}
+
 #[test]
 fn object_load() {
 
@@ -140,7 +165,7 @@ fn object_load() {
 #[test]
 fn project_load_collab() {
 
-    let mut server = TestingServer::<Project>::new("project_load_collab.test"); 
+    let mut server = TestingServer::<Project>::new(); 
 
     // Make sure Alice gets keys
     server.tick_alice();
@@ -167,7 +192,7 @@ fn project_load_collab() {
 #[test]
 fn object_load_collab() {
 
-    let mut server = TestingServer::<Project>::new("project_load_collab.test"); 
+    let mut server = TestingServer::<Project>::new(); 
 
     // Make sure Alice gets keys
     server.tick_alice();
@@ -0,0 +1,315 @@
+
+use alisa::ABFValue;
+
+mod v1 {
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Project {
+        pub name: String,
+        pub thing: alisa::LoadingPtr<Thing>
+    }
+
+    alisa::project_set_property_operation!(Project, name, String);
+    alisa::project_set_property_operation!(Project, thing, alisa::LoadingPtr<Thing>);
+
+    #[derive(Default)]
+    pub struct Objects {
+        things: alisa::ObjList<Thing>
+    }
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Thing {
+        pub x: i32
+    }
+
+    #[derive(alisa::Serializable, Default)]
+    pub struct CreateThing {
+        pub ptr: alisa::Ptr<Thing>,
+        pub x: i32
+    }
+
+    impl alisa::Operation for CreateThing {
+        type Project = Project;
+        const NAME: &'static str = "CreateThing";
+
+        fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+            recorder.add_obj(self.ptr, Thing {
+                x: self.x
+            })
+        }
+    }
+
+    impl alisa::Object for Thing {
+        type Project = Project;
+
+        const TYPE_ID: u16 = 0;
+
+        fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+            &objects.things
+        }
+
+        fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+            &mut objects.things
+        }
+    }
+
+    impl alisa::Project for Project {
+
+        type Objects = Objects;
+        type ActionContext = ();
+
+        fn empty() -> Self {
+            Self::default()
+        }
+
+        const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+            alisa::ObjectKind::from::<Thing>()
+        ];
+        const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+            alisa::OperationKind::from::<SetName>(),
+            alisa::OperationKind::from::<SetThing>(),
+            alisa::OperationKind::from::<CreateThing>(),
+        ];
+
+    }
+
+}
+
+mod v2 {
+
+    use super::rename_key;
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Project {
+        pub title: String,
+        pub thing: alisa::LoadingPtr<Thing>
+    }
+
+    #[derive(Default)]
+    pub struct Objects {
+        things: alisa::ObjList<Thing>
+    }
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Thing {
+        pub position: i32
+    }
+
+    alisa::object_set_property_operation!(Thing, position, i32);
+
+    impl alisa::Object for Thing {
+        type Project = Project;
+
+        const TYPE_ID: u16 = 0;
+        const MIGRATIONS: &'static [alisa::Migration] = &[
+            |data| rename_key(data, "x", "position")
+        ];
+
+        fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+            &objects.things
+        }
+
+        fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+            &mut objects.things
+        }
+    }
+
+    impl alisa::Project for Project {
+
+        type Objects = Objects;
+        type ActionContext = ();
+
+        fn empty() -> Self {
+            Self::default()
+        }
+
+        const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+            alisa::ObjectKind::from::<Thing>()
+        ];
+        const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+            alisa::OperationKind::from::<SetThingPosition>(),
+        ];
+        const MIGRATIONS: &'static [alisa::Migration] = &[
+            |data| rename_key(data, "name", "title")
+        ];
+
+    }
+
+}
+
+mod v1_1 {
+
+    use super::rename_key;
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Project {
+        pub name: String,
+        pub thing: alisa::LoadingPtr<Thing>
+    }
+
+    #[derive(Default)]
+    pub struct Objects {
+        things: alisa::ObjList<Thing>
+    }
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Thing {
+        pub position: i32
+    }
+
+    impl alisa::Object for Thing {
+        type Project = Project;
+
+        const TYPE_ID: u16 = 0;
+        const MIGRATIONS: &'static [alisa::Migration] = &[
+            |data| rename_key(data, "x", "position")
+        ];
+
+        fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+            &objects.things
+        }
+
+        fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+            &mut objects.things
+        }
+    }
+
+    impl alisa::Project for Project {
+
+        type Objects = Objects;
+        type ActionContext = ();
+
+        fn empty() -> Self {
+            Self::default()
+        }
+
+        const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+            alisa::ObjectKind::from::<Thing>()
+        ];
+        const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[];
+
+    }
+
+}
+
+mod broken {
+
+    #[derive(Clone, alisa::Serializable, Default)]
+    pub struct Project {
+        pub name: String
+    }
+
+    #[derive(Default)]
+    pub struct Objects {
+    }
+
+    impl alisa::Project for Project {
+
+        type Objects = Objects;
+        type ActionContext = ();
+
+        fn empty() -> Self {
+            Self::default()
+        }
+
+        const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[];
+        const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[];
+        const MIGRATIONS: &'static [alisa::Migration] = &[
+            |_| None
+        ];
+
+    }
+
+}
+
+fn rename_key(data: ABFValue, from: &str, to: &str) -> Option<ABFValue> {
+    let ABFValue::Map(entries) = data else {
+        return None;
+    };
+    Some(ABFValue::Map(entries.into_vec().into_iter().map(|(key, value)| (if key == from { to.to_owned() } else { key }, value)).collect()))
+}
+
+fn create_v1_project(storage: alisa::verter::MemoryStorage) -> alisa::Ptr<v1::Thing> {
+    let mut client = alisa::Client::<v1::Project>::local_with_storage(storage).unwrap();
+    let ptr = client.next_ptr();
+    client.queue_operation(v1::CreateThing {
+        ptr,
+        x: 123
+    });
+    client.queue_operation(v1::SetThing {
+        thing: alisa::LoadingPtr::new(ptr)
+    });
+    client.queue_operation(v1::SetName {
+        name: "Migrated".to_owned()
+    });
+    client.tick();
+    ptr
+}
+
+#[test]
+fn migration() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let ptr = create_v1_project(storage.clone()).any().key();
+
+    let mut client = alisa::Client::<v2::Project>::local_with_storage(storage.clone()).unwrap();
+    assert_eq!(client.title, "Migrated");
+    let ptr = alisa::Ptr::<v2::Thing>::from_key(ptr);
+    assert_eq!(client.get(ptr).unwrap().position, 123);
+
+    // Objects saved after the migration must be readable without migrating them again
+    client.queue_operation(v2::SetThingPosition {
+        ptr,
+        position_value: 456
+    });
+    client.tick();
+    drop(client);
+
+    let client = alisa::Client::<v2::Project>::local_with_storage(storage.clone()).unwrap();
+    assert_eq!(client.title, "Migrated");
+    assert_eq!(client.get(ptr).unwrap().position, 456);
+
+}
+
+#[test]
+fn refuse_newer_version() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    create_v1_project(storage.clone());
+    drop(alisa::Client::<v2::Project>::local_with_storage(storage.clone()).unwrap());
+
+    let bytes = storage.bytes();
+    assert!(alisa::Client::<v1::Project>::local_with_storage(storage.clone()).is_none());
+    // The file must be left untouched
+    assert_eq!(storage.bytes(), bytes);
+
+}
+
+#[test]
+fn refuse_newer_object_version() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    create_v1_project(storage.clone());
+    // Only the object schema changed, so the project version in the file stays the same
+    drop(alisa::Client::<v1_1::Project>::local_with_storage(storage.clone()).unwrap());
+
+    let bytes = storage.bytes();
+    assert!(alisa::Client::<v1::Project>::local_with_storage(storage.clone()).is_none());
+    assert_eq!(storage.bytes(), bytes);
+
+}
+
+#[test]
+fn refuse_failed_migration() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    create_v1_project(storage.clone());
+
+    let bytes = storage.bytes();
+    assert!(alisa::Client::<broken::Project>::local_with_storage(storage.clone()).is_none());
+    // The project must not be replaced by an empty one
+    assert_eq!(storage.bytes(), bytes);
+    let client = alisa::Client::<v1::Project>::local_with_storage(storage.clone()).unwrap();
+    assert_eq!(client.name, "Migrated");
+
+}
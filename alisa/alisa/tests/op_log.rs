@@ -0,0 +1,178 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+struct Project {
+    n: i32
+}
+
+#[derive(alisa::Serializable, Default)]
+struct Set {
+    n: i32
+}
+
+impl alisa::Operation for Set {
+    type Project = Project;
+    const NAME: &'static str = "Set";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.project_mut().n = self.n;
+        true
+    }
+
+}
+
+#[derive(alisa::Serializable, Default)]
+struct Add {
+    n: i32
+}
+
+impl alisa::Operation for Add {
+    type Project = Project;
+    const NAME: &'static str = "Add";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.project_mut().n += self.n;
+        true
+    }
+
+}
+
+impl alisa::Project for Project {
+
+    type Objects = ();
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<Set>(),
+        alisa::OperationKind::from::<Add>(),
+    ];
+}
+
+fn send_operation<O: alisa::Operation<Project = Project>>(server: &mut alisa::Server<Project>, id: alisa::ClientId, client: &mut alisa::Client<Project>, operation: O) {
+    client.queue_operation(operation);
+    client.tick();
+    for message in client.take_messages() {
+        server.receive_message(id, &message);
+    }
+    for message in std::mem::take(server.get_msgs_to_send_mut(id).unwrap()) {
+        client.receive_message(&message);
+    }
+}
+
+#[test]
+fn persistent_log() {
+    let path = "op_log.test";
+    let log_path = alisa::op_log_path(path.as_ref());
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(&log_path);
+
+    let mut server = alisa::Server::<Project>::new(path).unwrap();
+    let (id, welcome) = server.add_client();
+    let mut client = alisa::Client::<Project>::collab(&welcome).unwrap();
+    send_operation(&mut server, id, &mut client, Set { n: 5 });
+    send_operation(&mut server, id, &mut client, Add { n: 2 });
+    assert_eq!(server.seq(), 2);
+    assert_eq!(client.session().unwrap().last_seq, 2);
+    drop(server);
+
+    let log = alisa::read_op_log(path).unwrap();
+    assert_eq!(log.iter().map(|op| (op.seq, op.operation.as_str(), op.client)).collect::<Vec<_>>(), vec![(1, "Set", id), (2, "Add", id)]);
+    assert!(log.iter().all(|op| op.success));
+
+    // Simulate the server crashing while writing a record
+    let mut log_data = std::fs::read(&log_path).unwrap();
+    log_data.extend_from_slice(&[100, 0, 0, 0, 1, 2]);
+    std::fs::write(&log_path, log_data).unwrap();
+
+    // The sequence numbers should continue where they left off after restarting the server
+    let mut server = alisa::Server::<Project>::new(path).unwrap();
+    assert_eq!(server.seq(), 2);
+    let (new_id, welcome) = server.add_client();
+    assert_ne!(new_id, id);
+    assert_eq!(welcome.seq, 2);
+    let mut client = alisa::Client::<Project>::collab(&welcome).unwrap();
+    assert_eq!(client.n, 7);
+    send_operation(&mut server, new_id, &mut client, Add { n: 1 });
+    assert_eq!(server.seq(), 3);
+    assert_eq!(server.project().n, 8);
+    drop(server);
+
+    let log = alisa::read_op_log(path).unwrap();
+    assert_eq!(log.iter().map(|op| op.seq).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(log_path).unwrap();
+}
+
+#[test]
+fn late_joiner_catch_up() {
+    let mut server = TestingServer::<Project>::new();
+    let snapshot = server.snapshot();
+
+    server.alice().queue_operation(Set { n: 5 });
+    server.tick_alice();
+    server.stabilize();
+    server.bob().queue_operation(Add { n: 3 });
+    server.tick_bob();
+    server.stabilize();
+
+    let carol = server.add_client_from_snapshot(&snapshot).unwrap();
+    server.stabilize();
+    assert_eq!(server.client(carol).n, 8);
+
+    server.client(carol).queue_operation(Add { n: 2 });
+    server.tick_client(carol);
+    server.stabilize();
+    assert_eq!(server.alice().n, 10);
+    assert_eq!(server.bob().n, 10);
+    assert_eq!(server.client(carol).n, 10);
+}
+
+#[test]
+fn refuse_snapshot_from_future() {
+    let mut server = TestingServer::<Project>::new();
+    let mut snapshot = server.snapshot();
+    snapshot.seq = 100;
+    assert!(server.add_client_from_snapshot(&snapshot).is_none());
+}
+
+#[test]
+fn log_compaction() {
+    let path = "op_log_compaction.test";
+    let log_path = alisa::op_log_path(path.as_ref());
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(&log_path);
+
+    let mut server = alisa::Server::<Project>::new(path).unwrap();
+    let (id, welcome) = server.add_client();
+    let mut client = alisa::Client::<Project>::collab(&welcome).unwrap();
+    for _ in 0..10000 {
+        send_operation(&mut server, id, &mut client, Add { n: 1 });
+    }
+    assert_eq!(server.project().n, 10000);
+    drop(server);
+
+    // Only the recent operations are left in the log
+    let log = alisa::read_op_log(path).unwrap();
+    assert!(log.len() < 10000);
+    assert_eq!(log.last().unwrap().seq, 10000);
+    assert!(log.windows(2).all(|ops| ops[1].seq == ops[0].seq + 1));
+
+    // The server still knows which client IDs were handed out and where the sequence numbers left off
+    let mut server = alisa::Server::<Project>::new(path).unwrap();
+    assert_eq!(server.seq(), 10000);
+    assert_eq!(server.project().n, 10000);
+    let (new_id, _) = server.add_client();
+    assert_ne!(new_id, id);
+    drop(server);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(log_path).unwrap();
+}
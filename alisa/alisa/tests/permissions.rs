@@ -0,0 +1,252 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+    n: i32
+}
+
+#[derive(Default)]
+pub struct Objects {
+    things: alisa::ObjList<Thing>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Thing {
+    x: i32,
+    /// Locked things can only be modified by Alice
+    locked: bool
+}
+
+impl alisa::Object for Thing {
+
+    type Project = Project;
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.things
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.things
+    }
+}
+
+alisa::object_set_property_operation!(Thing, x, i32);
+
+#[derive(alisa::Serializable, Default)]
+pub struct CreateThing {
+    ptr: alisa::Ptr<Thing>,
+    x: i32,
+    locked: bool
+}
+
+impl alisa::Operation for CreateThing {
+
+    type Project = Project;
+    const NAME: &'static str = "CreateThing";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Self::Project>) -> bool {
+        recorder.add_obj(self.ptr, Thing {
+            x: self.x,
+            locked: self.locked
+        })
+    }
+}
+
+/// Creates a thing and locks it afterwards, like operations that create objects before attaching them to something
+#[derive(alisa::Serializable, Default)]
+pub struct CreateThingThenLock {
+    ptr: alisa::Ptr<Thing>
+}
+
+impl alisa::Operation for CreateThingThenLock {
+
+    type Project = Project;
+    const NAME: &'static str = "CreateThingThenLock";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Self::Project>) -> bool {
+        if !recorder.add_obj(self.ptr, Thing::default()) {
+            return false;
+        }
+        let Some(thing) = recorder.get_obj_mut(self.ptr) else { return false; };
+        thing.locked = true;
+        true
+    }
+}
+
+#[derive(alisa::Serializable, Default)]
+struct SetN {
+    n: i32
+}
+
+impl alisa::Operation for SetN {
+    type Project = Project;
+    const NAME: &'static str = "SetN";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.project_mut().n = self.n;
+        true
+    }
+
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Thing>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateThing>(),
+        alisa::OperationKind::from::<CreateThingThenLock>(),
+        alisa::OperationKind::from::<SetThingX>(),
+        alisa::OperationKind::from::<SetN>()
+    ];
+}
+
+/// Alice can do anything. Everyone else can only touch unlocked things.
+struct AliceOwnsLockedThings;
+
+const ALICE: alisa::ClientId = alisa::ClientId(1);
+
+impl AliceOwnsLockedThings {
+
+    fn is_locked(context: &alisa::ProjectContext<Project>, ptr: alisa::AnyPtr) -> bool {
+        context.obj_list::<Thing>().get(alisa::Ptr::from_key(ptr.key())).map(|thing| thing.locked).unwrap_or(false)
+    }
+
+}
+
+impl alisa::Permissions<Project> for AliceOwnsLockedThings {
+
+    fn can_modify(&self, client: alisa::ClientId, context: &alisa::ProjectContext<Project>, ptr: Option<alisa::AnyPtr>) -> bool {
+        match ptr {
+            _ if client == ALICE => true,
+            Some(ptr) => !Self::is_locked(context, ptr),
+            None => false
+        }
+    }
+
+    fn can_load(&self, client: alisa::ClientId, context: &alisa::ProjectContext<Project>, ptr: alisa::AnyPtr) -> bool {
+        client == ALICE || !Self::is_locked(context, ptr)
+    }
+
+}
+
+fn setup() -> (TestingServer<Project>, alisa::Ptr<Thing>, alisa::Ptr<Thing>) {
+    let mut server = TestingServer::<Project>::new();
+    server.set_permissions(AliceOwnsLockedThings);
+
+    let locked = server.alice().next_ptr();
+    let unlocked = server.alice().next_ptr();
+    server.alice().queue_operation(CreateThing { ptr: locked, x: 1, locked: true });
+    server.alice().queue_operation(CreateThing { ptr: unlocked, x: 2, locked: false });
+    server.tick_alice();
+    server.stabilize();
+
+    (server, locked, unlocked)
+}
+
+#[test]
+fn modify() {
+    let (mut server, locked, unlocked) = setup();
+
+    // Bob can modify unlocked things...
+    server.bob().queue_operation(SetThingX { ptr: unlocked, x_value: 20 });
+    server.tick_bob();
+    server.stabilize();
+    assert_eq!(server.alice().get(unlocked).unwrap().x, 20);
+
+    // ...but not locked ones. His change gets undone once the server rejects it.
+    server.bob().queue_operation(SetThingX { ptr: locked, x_value: 10 });
+    server.tick_bob();
+    assert_eq!(server.bob().get(locked).unwrap().x, 10);
+    server.stabilize();
+    assert_eq!(server.bob().get(locked).unwrap().x, 1);
+    assert_eq!(server.alice().get(locked).unwrap().x, 1);
+
+    // Alice can modify anything
+    server.alice().queue_operation(SetThingX { ptr: locked, x_value: 100 });
+    server.tick_alice();
+    server.stabilize();
+    assert_eq!(server.bob().get(locked).unwrap().x, 100);
+}
+
+#[test]
+fn modify_project() {
+    let (mut server, _, unlocked) = setup();
+
+    // Bob can't modify the project itself, but that doesn't affect his other operations
+    server.bob().queue_operation(SetThingX { ptr: unlocked, x_value: 5 });
+    server.bob().queue_operation(SetN { n: 5 });
+    server.tick_bob();
+    server.stabilize();
+    assert_eq!(server.alice().get(unlocked).unwrap().x, 5);
+    assert_eq!(server.alice().n, 0);
+    assert_eq!(server.bob().n, 0);
+    assert_eq!(server.bob().get(unlocked).unwrap().x, 5);
+}
+
+#[test]
+fn create() {
+    let (mut server, _, _) = setup();
+
+    // The testing server doesn't map keys between clients, so skip the keys Alice already used
+    server.bob().next_ptr::<Thing>();
+    server.bob().next_ptr::<Thing>();
+
+    let new_locked = server.bob().next_ptr();
+    let new_unlocked = server.bob().next_ptr();
+    server.bob().queue_operation(CreateThing { ptr: new_locked, x: 3, locked: true });
+    server.bob().queue_operation(CreateThing { ptr: new_unlocked, x: 4, locked: false });
+    server.tick_bob();
+    server.stabilize();
+
+    assert!(server.bob().get(new_locked).is_none());
+    assert_eq!(server.bob().get(new_unlocked).unwrap().x, 4);
+}
+
+#[test]
+fn create_then_modify() {
+    let (mut server, _, _) = setup();
+
+    server.bob().next_ptr::<Thing>();
+    server.bob().next_ptr::<Thing>();
+
+    // Created objects are checked once the operation is done, so Bob can't sneak in a locked thing by locking it after creating it
+    let new_thing = server.bob().next_ptr();
+    server.bob().queue_operation(CreateThingThenLock { ptr: new_thing });
+    server.tick_bob();
+    server.stabilize();
+    assert!(server.bob().get(new_thing).is_none());
+    assert!(server.alice().get(new_thing).is_none());
+
+    // Alice can
+    let alice_thing = server.alice().next_ptr();
+    server.alice().queue_operation(CreateThingThenLock { ptr: alice_thing });
+    server.tick_alice();
+    server.stabilize();
+    assert!(server.alice().get(alice_thing).unwrap().locked);
+}
+
+#[test]
+fn load() {
+    let (mut server, locked, unlocked) = setup();
+
+    let carol = server.add_client();
+    server.client(carol).request_load(locked);
+    server.client(carol).request_load(unlocked);
+    server.tick_client(carol);
+    server.stabilize();
+
+    assert!(server.client(carol).get(locked).is_none());
+    assert_eq!(server.client(carol).get(unlocked).unwrap().x, 2);
+}
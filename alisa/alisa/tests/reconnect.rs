@@ -0,0 +1,160 @@
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+struct Project {
+    n: i32
+}
+
+#[derive(alisa::Serializable, Default)]
+struct Set {
+    n: i32
+}
+
+impl alisa::Operation for Set {
+    type Project = Project;
+    const NAME: &'static str = "Set";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.project_mut().n = self.n;
+        true
+    }
+
+}
+
+#[derive(alisa::Serializable, Default)]
+struct Add {
+    n: i32
+}
+
+impl alisa::Operation for Add {
+    type Project = Project;
+    const NAME: &'static str = "Add";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.project_mut().n += self.n;
+        true
+    }
+
+}
+
+impl alisa::Project for Project {
+
+    type Objects = ();
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<Set>(),
+        alisa::OperationKind::from::<Add>(),
+    ];
+}
+
+#[test]
+fn offline_operations() {
+    let mut server = TestingServer::<Project>::new();
+
+    server.disconnect(0);
+    assert!(server.alice().is_offline());
+
+    // Work done while offline is only performed locally
+    server.alice().queue_operation(Add { n: 5 });
+    server.bob().queue_operation(Set { n: 10 });
+    server.tick_alice();
+    server.tick_bob();
+    server.stabilize();
+    assert_eq!(server.alice().n, 5);
+    assert_eq!(server.bob().n, 10);
+
+    // Once reconnected, Alice's work is rebased on top of Bob's
+    assert!(server.reconnect(0));
+    server.stabilize();
+    assert!(!server.alice().is_offline());
+    assert_eq!(server.alice().n, 15);
+    assert_eq!(server.bob().n, 15);
+}
+
+#[test]
+fn lost_confirmation() {
+    let mut server = TestingServer::<Project>::new();
+
+    // The server receives Alice's operation, but she loses the connection before the confirmation arrives
+    server.alice().queue_operation(Add { n: 1 });
+    server.send_alice_messages();
+    server.disconnect(0);
+    server.stabilize();
+    assert_eq!(server.bob().n, 1);
+
+    server.alice().queue_operation(Add { n: 2 });
+    server.bob().queue_operation(Add { n: 3 });
+    server.tick_alice();
+    server.tick_bob();
+    server.stabilize();
+    assert_eq!(server.alice().n, 3);
+    assert_eq!(server.bob().n, 4);
+
+    // The operation the server already performed must not be performed twice
+    assert!(server.reconnect(0));
+    server.stabilize();
+    assert_eq!(server.alice().n, 6);
+    assert_eq!(server.bob().n, 6);
+}
+
+#[test]
+fn lost_operation() {
+    let mut server = TestingServer::<Project>::new();
+
+    // Alice's operation never reaches the server
+    server.alice().queue_operation(Add { n: 1 });
+    server.tick_alice();
+    server.disconnect(0);
+    server.stabilize();
+    assert_eq!(server.alice().n, 1);
+    assert_eq!(server.bob().n, 0);
+
+    assert!(server.reconnect(0));
+    server.stabilize();
+    assert_eq!(server.alice().n, 1);
+    assert_eq!(server.bob().n, 1);
+}
+
+#[test]
+fn resume_late_joiner() {
+    let mut server = TestingServer::<Project>::new();
+
+    let carol = server.add_client();
+    server.disconnect(carol);
+    server.bob().queue_operation(Add { n: 1 });
+    server.tick_bob();
+    server.stabilize();
+    assert_eq!(server.client(carol).n, 0);
+
+    assert!(server.reconnect(carol));
+    server.stabilize();
+    assert_eq!(server.client(carol).n, 1);
+}
+
+#[test]
+fn expired_session() {
+    let mut server = TestingServer::<Project>::new();
+    server.server_mut().set_session_timeout(std::time::Duration::ZERO);
+
+    server.disconnect(0);
+    server.alice().queue_operation(Add { n: 1 });
+    server.tick_alice();
+
+    // The next message the server receives reaps the session
+    server.bob().queue_operation(Add { n: 2 });
+    server.tick_bob();
+    server.stabilize();
+    assert!(!server.server().has_session(server.client_id(0)));
+
+    // Alice has to join again from scratch
+    assert!(!server.reconnect(0));
+    let carol = server.add_client();
+    assert_eq!(server.client(carol).n, 2);
+}
@@ -0,0 +1,187 @@
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+    n: i32
+}
+
+alisa::project_set_property_operation!(Project, n, i32);
+
+#[derive(Default)]
+pub struct Objects {
+    nodes: alisa::ObjList<Node>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Node {
+    x: i32,
+    next: alisa::LoadingPtr<Node>
+}
+
+alisa::object_set_property_operation!(Node, x, i32);
+alisa::object_set_property_operation!(Node, next, alisa::LoadingPtr<Node>);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateNode {
+    ptr: alisa::Ptr<Node>,
+    x: i32
+}
+
+impl alisa::Operation for CreateNode {
+    type Project = Project;
+    const NAME: &'static str = "CreateNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Node {
+            x: self.x,
+            next: alisa::LoadingPtr::default(),
+        })
+    }
+}
+
+#[derive(alisa::Serializable, Default)]
+struct DeleteNode {
+    ptr: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for DeleteNode {
+    type Project = Project;
+    const NAME: &'static str = "DeleteNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.delete_obj(self.ptr).is_some()
+    }
+}
+
+/// Add a node's value to the project's, touching both
+#[derive(alisa::Serializable, Default)]
+struct AddToProject {
+    ptr: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for AddToProject {
+    type Project = Project;
+    const NAME: &'static str = "AddToProject";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        let Some(x) = recorder.get_obj(self.ptr).map(|node| node.x) else { return false; };
+        recorder.project_mut().n += x;
+        true
+    }
+}
+
+/// Swap the values of two nodes, touching both of them
+#[derive(alisa::Serializable, Default)]
+struct SwapX {
+    a: alisa::Ptr<Node>,
+    b: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for SwapX {
+    type Project = Project;
+    const NAME: &'static str = "SwapX";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        let Some(a) = recorder.get_obj(self.a).map(|node| node.x) else { return false; };
+        let Some(b) = recorder.get_obj(self.b).map(|node| node.x) else { return false; };
+        let Some(node_a) = recorder.get_obj_mut(self.a) else { return false; };
+        node_a.x = b;
+        let Some(node_b) = recorder.get_obj_mut(self.b) else { return false; };
+        node_b.x = a;
+        true
+    }
+}
+
+impl alisa::Object for Node {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.nodes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.nodes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Node>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<SetN>(),
+        alisa::OperationKind::from::<CreateNode>(),
+        alisa::OperationKind::from::<DeleteNode>(),
+        alisa::OperationKind::from::<SetNodeX>(),
+        alisa::OperationKind::from::<SetNodeNext>(),
+        alisa::OperationKind::from::<AddToProject>(),
+        alisa::OperationKind::from::<SwapX>(),
+    ];
+
+}
+
+fn simulation(clients: usize, seed: u64) -> alisa::Simulation<Project> {
+    let mut simulation = alisa::Simulation::new(clients, seed);
+    simulation.fuzz::<SetN>();
+    simulation.fuzz::<CreateNode>();
+    simulation.fuzz::<DeleteNode>();
+    simulation.fuzz::<SetNodeX>();
+    simulation.fuzz::<SetNodeNext>();
+    simulation.fuzz::<AddToProject>();
+    simulation.fuzz::<SwapX>();
+    assert!(simulation.unregistered_operations().is_empty());
+    simulation
+}
+
+#[test]
+fn random_operations_converge() {
+
+    for seed in 0..32 {
+        let mut simulation = simulation(3, seed);
+        simulation.run(400);
+        simulation.settle();
+        if let Err(err) = simulation.check_convergence() {
+            panic!("seed {}: {}", seed, err);
+        }
+    }
+
+}
+
+#[test]
+fn random_operations_converge_with_disconnects() {
+
+    for seed in 0..32 {
+        let mut simulation = simulation(3, seed);
+        simulation.set_disconnect_chance(0.02);
+        simulation.run(400);
+        simulation.settle();
+        if let Err(err) = simulation.check_convergence() {
+            panic!("seed {}: {}", seed, err);
+        }
+    }
+
+}
+
+#[test]
+fn custom_generators() {
+
+    let mut simulation = alisa::Simulation::<Project>::new(2, 7);
+    simulation.generate(|fuzzer| Some(CreateNode { ptr: fuzzer.new_ptr(), x: fuzzer.rng().below(10) as i32 }));
+    simulation.generate(|fuzzer| Some(SetNodeX { ptr: fuzzer.loaded_ptr()?, x_value: 3 }));
+    assert_eq!(simulation.unregistered_operations().len(), 5);
+
+    simulation.run(200);
+    simulation.settle();
+    assert!(simulation.check_convergence().is_ok());
+    assert!(simulation.client(0).project().n == simulation.server().project().n);
+
+}
@@ -0,0 +1,281 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+    n: i32,
+    first: alisa::LoadingPtr<Node>
+}
+
+alisa::project_set_property_operation!(Project, n, i32);
+alisa::project_set_property_operation!(Project, first, alisa::LoadingPtr<Node>);
+
+#[derive(Default)]
+pub struct Objects {
+    nodes: alisa::ObjList<Node>
+}
+
+/// Nodes reference each other through plain `Ptr`s, which snapshots follow too
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Node {
+    x: i32,
+    next: alisa::Ptr<Node>
+}
+
+alisa::object_set_property_operation!(Node, x, i32);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateNode {
+    ptr: alisa::Ptr<Node>,
+    x: i32,
+    next: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for CreateNode {
+    type Project = Project;
+    const NAME: &'static str = "CreateNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Node {
+            x: self.x,
+            next: self.next
+        })
+    }
+}
+
+#[derive(alisa::Serializable, Default)]
+struct DeleteNode {
+    ptr: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for DeleteNode {
+    type Project = Project;
+    const NAME: &'static str = "DeleteNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.delete_obj(self.ptr).is_some()
+    }
+}
+
+impl alisa::Object for Node {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.nodes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.nodes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Node>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<SetN>(),
+        alisa::OperationKind::from::<SetFirst>(),
+        alisa::OperationKind::from::<SetNodeX>(),
+        alisa::OperationKind::from::<CreateNode>(),
+        alisa::OperationKind::from::<DeleteNode>(),
+        alisa::OperationKind::from_invertible::<alisa::RestoreSnapshot<Self>>(),
+    ];
+
+}
+
+struct Nodes {
+    a: alisa::Ptr<Node>,
+    b: alisa::Ptr<Node>,
+    c: alisa::Ptr<Node>
+}
+
+/// Set up the project for snapshot "before", then change it and take snapshot "after"
+fn edit(client: &mut alisa::Client<Project>) -> Nodes {
+    let nodes = Nodes {
+        a: client.next_ptr(),
+        b: client.next_ptr(),
+        c: client.next_ptr()
+    };
+    client.queue_operation(CreateNode { ptr: nodes.b, x: 2, next: alisa::Ptr::null() });
+    client.queue_operation(CreateNode { ptr: nodes.a, x: 1, next: nodes.b });
+    client.queue_operation(SetFirst { first: alisa::LoadingPtr::new(nodes.a) });
+    client.queue_operation(SetN { n: 5 });
+    client.tick();
+    client.create_snapshot("before").unwrap();
+
+    client.queue_operation(DeleteNode { ptr: nodes.b });
+    client.queue_operation(SetNodeX { ptr: nodes.a, x_value: 10 });
+    client.queue_operation(CreateNode { ptr: nodes.c, x: 3, next: nodes.a });
+    client.queue_operation(SetFirst { first: alisa::LoadingPtr::new(nodes.c) });
+    client.tick();
+    client.create_snapshot("after").unwrap();
+
+    nodes
+}
+
+fn assert_before(client: &alisa::Client<Project>, nodes: &Nodes) {
+    assert_eq!(client.n, 5);
+    assert_eq!(client.first.ptr(), nodes.a);
+    assert_eq!(client.get(nodes.a).unwrap().x, 1);
+    assert_eq!(client.get(nodes.b).unwrap().x, 2);
+    assert!(client.get(nodes.c).is_none());
+}
+
+#[test]
+fn list_and_diff() {
+
+    let mut client = alisa::Client::<Project>::local_with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+    let nodes = edit(&mut client);
+
+    let snapshots = client.snapshots();
+    assert_eq!(snapshots.iter().map(|info| info.name.as_str()).collect::<Vec<_>>(), ["before", "after"]);
+    assert_ne!(snapshots[0].id, snapshots[1].id);
+
+    let before = client.snapshot(snapshots[0].id).unwrap();
+    let after = client.snapshot(snapshots[1].id).unwrap();
+    assert_eq!(before.project().unwrap().n, 5);
+    assert_eq!(before.get(nodes.b).unwrap().x, 2);
+    assert_eq!(before.data(nodes.b.any()).unwrap().get("x").and_then(alisa::ABFValue::as_i32), Some(2));
+    assert!(before.data(nodes.c.any()).is_none());
+
+    let diff = before.diff(&after);
+    assert!(diff.project);
+    assert_eq!(diff.added, [nodes.c.any()]);
+    assert_eq!(diff.removed, [nodes.b.any()]);
+    assert_eq!(diff.modified, [nodes.a.any()]);
+    assert!(after.diff(&client.capture_snapshot().unwrap()).is_empty());
+
+    assert!(client.delete_snapshot(snapshots[0].id));
+    assert!(!client.delete_snapshot(snapshots[0].id));
+    assert_eq!(client.snapshots(), [snapshots[1].clone()]);
+
+}
+
+#[test]
+fn restore_as_new_project() {
+
+    let mut client = alisa::Client::<Project>::local_with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+    let nodes = edit(&mut client);
+    let before = client.snapshots()[0].id;
+    let before = client.snapshot(before).unwrap();
+
+    let storage = alisa::verter::MemoryStorage::new();
+    before.save_to_storage(storage.clone()).unwrap();
+    // Existing projects are never overwritten
+    assert!(before.save_to_storage(storage.clone()).is_none());
+
+    let mut restored = alisa::Client::<Project>::local_with_storage(storage).unwrap();
+    restored.request_load(nodes.b);
+    restored.tick();
+    assert_before(&restored, &nodes);
+
+    // Keys handed out after the snapshot was taken aren't reused
+    assert!(restored.next_key() > nodes.c.key());
+
+}
+
+#[test]
+fn restore_into_project() {
+
+    let storage = alisa::verter::MemoryStorage::new();
+    let mut client = alisa::Client::<Project>::local_with_storage(storage.clone()).unwrap();
+    let nodes = edit(&mut client);
+    let snapshots = client.snapshots();
+    let before = client.snapshot(snapshots[0].id).unwrap();
+    let after = client.snapshot(snapshots[1].id).unwrap();
+
+    let operation = client.restore_snapshot_operation(&before).unwrap();
+    client.queue_action(alisa::Action::single((), operation));
+    client.tick();
+    assert_before(&client, &nodes);
+    assert!(before.diff(&client.capture_snapshot().unwrap()).is_empty());
+
+    // Restoring a snapshot can be undone
+    client.undo();
+    client.tick();
+    assert!(after.diff(&client.capture_snapshot().unwrap()).is_empty());
+    client.redo();
+    client.tick();
+    drop(client);
+
+    let mut client = alisa::Client::<Project>::local_with_storage(storage).unwrap();
+    client.request_load(nodes.b);
+    client.tick();
+    assert_before(&client, &nodes);
+
+}
+
+#[test]
+fn snapshots_survive_compaction() {
+    let path = "snapshots.test";
+
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    let nodes = edit(&mut client);
+    let before = client.snapshots()[0].id;
+    client.queue_operation(DeleteNode { ptr: nodes.a });
+    client.queue_operation(DeleteNode { ptr: nodes.c });
+    client.tick();
+    assert!(client.compact().unwrap() > 0);
+    drop(client);
+
+    assert!(alisa::check_file::<Project>(path, false).unwrap().is_ok());
+
+    let mut client = alisa::Client::<Project>::local(path).unwrap();
+    let before = client.snapshot(before).unwrap();
+    assert_eq!(before.get(nodes.a).unwrap().x, 1);
+    assert_eq!(before.get(nodes.b).unwrap().x, 2);
+    drop(client);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn restore_on_server() {
+
+    let mut server = TestingServer::<Project>::new();
+    // Make sure Alice gets some keys in her keychain
+    server.tick_alice();
+    server.stabilize();
+
+    let a = server.alice().next_ptr();
+    let b = server.alice().next_ptr();
+    server.alice().queue_operation(CreateNode { ptr: a, x: 1, next: alisa::Ptr::null() });
+    server.alice().queue_operation(SetFirst { first: alisa::LoadingPtr::new(a) });
+    server.alice().queue_operation(SetN { n: 5 });
+    server.tick_alice();
+    server.stabilize();
+    let snapshot = server.server_mut().create_snapshot("checkpoint").unwrap();
+
+    server.bob().queue_operation(SetNodeX { ptr: a, x_value: 3 });
+    server.alice().queue_operation(CreateNode { ptr: b, x: 2, next: a });
+    server.alice().queue_operation(SetFirst { first: alisa::LoadingPtr::new(b) });
+    server.tick_bob();
+    server.tick_alice();
+    server.stabilize();
+    assert_eq!(server.bob().get(b).unwrap().x, 2);
+
+    let snapshot = server.server_mut().snapshot(snapshot.id).unwrap();
+    assert!(server.server_mut().restore_snapshot(alisa::ClientId::SERVER, &snapshot));
+    server.stabilize();
+
+    for client in [server.alice(), server.bob()] {
+        assert_eq!(client.n, 5);
+        assert_eq!(client.first.ptr(), a);
+        assert_eq!(client.get(a).unwrap().x, 1);
+        assert!(client.get(b).is_none());
+    }
+    assert!(snapshot.diff(&server.server_mut().capture_snapshot().unwrap()).is_empty());
+
+}
@@ -1,6 +1,4 @@
 
-use std::path::PathBuf;
-
 struct TestingClient<P: alisa::Project> {
     id: alisa::ClientId,
     client: alisa::Client<P> 
@@ -21,24 +19,21 @@ impl<P: alisa::Project> TestingClient<P> {
 pub struct TestingServer<P: alisa::Project> {
     server: alisa::Server<P>,
 
-    clients: Vec<TestingClient<P>>,
-
-    path: PathBuf
+    clients: Vec<TestingClient<P>>
 }
 
 impl<P: alisa::Project> TestingServer<P> {
 
-    pub fn new<Path: Into<PathBuf>>(path: Path) -> Self {
-        let path = path.into();
-        let mut server = alisa::Server::new(path.clone()).unwrap();
+    pub fn new() -> Self {
+        // Keep the project in memory, so tests don't interfere with each other through the file system
+        let mut server = alisa::Server::with_storage(alisa::verter::MemoryStorage::new()).unwrap();
 
         let alice = TestingClient::new(&mut server);
         let bob = TestingClient::new(&mut server);
 
         let mut server = Self {
             server,
-            clients: vec![alice, bob],
-            path
+            clients: vec![alice, bob]
         };
 
         server.stabilize();
@@ -128,6 +123,75 @@ impl<P: alisa::Project> TestingServer<P> {
         }
     }
 
+    /// Simulate a client losing its connection to the server. Messages that were in flight are lost.
+    #[allow(unused)]
+    pub fn disconnect(&mut self, id: usize) {
+        let client = &mut self.clients[id];
+        self.server.disconnect_client(client.id);
+        client.client.disconnect();
+    }
+
+    /// Resume the session of a disconnected client. Returns false if the server refused to resume the session.
+    #[allow(unused)]
+    pub fn reconnect(&mut self, id: usize) -> bool {
+        let session = self.clients[id].client.session().unwrap();
+        self.server.resume_client(&session).is_ok()
+    }
+
+    /// Restrict what the clients may modify and load
+    #[allow(unused)]
+    pub fn set_permissions<Perms: alisa::Permissions<P> + 'static>(&mut self, permissions: Perms) {
+        self.server.set_permissions(permissions);
+    }
+
+    /// Get a snapshot of the project, as a new client would receive it
+    #[allow(unused)]
+    pub fn snapshot(&mut self) -> alisa::WelcomeMessage {
+        self.server.add_client().1
+    }
+
+    /// Add a client that joins from a snapshot of the project. Returns None if the server refused to catch the client up.
+    #[allow(unused)]
+    pub fn add_client_from_snapshot(&mut self, snapshot: &alisa::WelcomeMessage) -> Option<usize> {
+        let id = self.server.add_client_from_snapshot(snapshot.seq)?;
+        self.clients.push(TestingClient {
+            client: alisa::Client::collab_from_snapshot(snapshot, id).unwrap(),
+            id
+        });
+        Some(self.clients.len() - 1)
+    }
+
+    #[allow(unused)]
+    pub fn server(&self) -> &alisa::Server<P> {
+        &self.server
+    }
+
+    #[allow(unused)]
+    pub fn server_mut(&mut self) -> &mut alisa::Server<P> {
+        &mut self.server
+    }
+
+    /// The ID the server assigned to a client
+    #[allow(unused)]
+    pub fn client_id(&self, id: usize) -> alisa::ClientId {
+        self.clients[id].id
+    }
+
+    /// Tick a client and take the messages it wants to send, without delivering them
+    #[allow(unused)]
+    pub fn take_client_messages(&mut self, id: usize) -> Vec<alisa::Message> {
+        self.tick_client(id);
+        self.clients[id].client.take_messages()
+    }
+
+    /// Deliver messages to the server as if a client sent them
+    #[allow(unused)]
+    pub fn send_client_messages(&mut self, id: usize, messages: Vec<alisa::Message>) {
+        for message in messages {
+            self.server.receive_message(self.clients[id].id, &message);
+        }
+    }
+
     #[allow(unused)]
     pub fn add_client(&mut self) -> usize {
         self.clients.push(TestingClient::new(&mut self.server));
@@ -136,10 +200,10 @@ impl<P: alisa::Project> TestingServer<P> {
 
 }
 
-impl<P: alisa::Project> Drop for TestingServer<P> {
+impl<P: alisa::Project> Default for TestingServer<P> {
 
-    fn drop(&mut self) {
-        let _ = std::fs::remove_file(&self.path);
+    fn default() -> Self {
+        Self::new()
     }
 
 }
@@ -0,0 +1,254 @@
+
+mod test_server;
+use test_server::TestingServer;
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Project {
+
+}
+
+#[derive(Default)]
+pub struct Objects {
+    nodes: alisa::ObjList<Node>
+}
+
+#[derive(Clone, alisa::Serializable, Default)]
+pub struct Node {
+    x: i32,
+    next: alisa::LoadingPtr<Node>
+}
+
+alisa::object_set_property_operation!(Node, x, i32);
+
+#[derive(alisa::Serializable, Default)]
+struct CreateNode {
+    ptr: alisa::Ptr<Node>,
+    x: i32,
+    next: alisa::Ptr<Node>
+}
+
+impl alisa::Operation for CreateNode {
+    type Project = Project;
+    const NAME: &'static str = "CreateNode";
+
+    fn perform(&self, recorder: &mut alisa::Recorder<'_, Project>) -> bool {
+        recorder.add_obj(self.ptr, Node {
+            x: self.x,
+            next: alisa::LoadingPtr::new(self.next),
+        })
+    }
+}
+
+impl alisa::Object for Node {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 0;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.nodes
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.nodes
+    }
+}
+
+impl alisa::Project for Project {
+
+    type Objects = Objects;
+    type ActionContext = ();
+
+    fn empty() -> Self {
+        Self {}
+    }
+
+    const OBJECTS: &'static [alisa::ObjectKind<Self>] = &[
+        alisa::ObjectKind::from::<Node>()
+    ];
+    const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
+        alisa::OperationKind::from::<CreateNode>(),
+        alisa::OperationKind::from::<SetNodeX>(),
+    ];
+
+}
+
+/// Have Alice create a node pointing to another node, returning the two nodes
+fn create_chain(server: &mut TestingServer<Project>) -> (alisa::Ptr<Node>, alisa::Ptr<Node>) {
+    // Make sure Alice gets some keys in her keychain
+    server.tick_alice();
+    server.stabilize();
+
+    let head = server.alice().next_ptr();
+    let tail = server.alice().next_ptr();
+    server.alice().queue_operation(CreateNode {
+        ptr: tail,
+        x: 2,
+        next: alisa::Ptr::null(),
+    });
+    server.alice().queue_operation(CreateNode {
+        ptr: head,
+        x: 1,
+        next: tail,
+    });
+    server.tick_alice();
+    server.stabilize();
+
+    (head, tail)
+}
+
+#[test]
+fn unload_cascades() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (head, tail) = create_chain(&mut server);
+
+    let carol = server.add_client();
+    server.client(carol).request_load(head);
+    server.tick_client(carol);
+    server.stabilize();
+    assert!(server.client(carol).get(head).is_some());
+    assert!(server.client(carol).get(tail).is_some());
+
+    // The tail is still referenced by the head, so it can't be unloaded on its own
+    server.client(carol).request_unload(tail);
+    server.tick_client(carol);
+    server.stabilize();
+    assert!(server.client(carol).get(tail).is_some());
+
+    // Unloading the head takes the tail with it
+    server.client(carol).request_unload(head);
+    server.tick_client(carol);
+    server.stabilize();
+    assert!(server.client(carol).get_ref(head).is_none());
+    assert!(server.client(carol).get_ref(tail).is_none());
+    assert!(!server.server().client_has_loaded(server.client_id(carol), head.any()));
+    assert!(!server.server().client_has_loaded(server.client_id(carol), tail.any()));
+
+    // Alice still has the nodes, so the server keeps them around
+    assert!(server.server().has_loaded(head.any()));
+
+    // Carol can load the nodes again
+    server.client(carol).request_load(head);
+    server.tick_client(carol);
+    server.stabilize();
+    assert_eq!(server.client(carol).get(head).unwrap().x, 1);
+    assert_eq!(server.client(carol).get(tail).unwrap().x, 2);
+
+}
+
+#[test]
+fn unload_waits_for_confirmation() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (head, _) = create_chain(&mut server);
+
+    server.alice().queue_operation(SetNodeX {
+        ptr: head,
+        x_value: 10,
+    });
+    server.alice().request_unload(head);
+    server.tick_alice();
+
+    // The operation hasn't been confirmed yet, so Alice has to hold on to the node
+    assert!(server.alice().get(head).is_some());
+
+    // Once it's confirmed, Alice asks the server to stop sending her changes to the node on the next tick
+    server.stabilize();
+    server.tick_alice();
+    assert!(server.alice().get(head).is_some());
+    server.stabilize();
+    assert!(server.alice().get_ref(head).is_none());
+    assert_eq!(server.bob().get(head).unwrap().x, 10);
+
+}
+
+#[test]
+fn server_unloads_unused_objects() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (head, tail) = create_chain(&mut server);
+
+    for client in 0..2 {
+        server.client(client).request_unload(head);
+        server.tick_client(client);
+    }
+    server.stabilize();
+    assert!(!server.server().has_loaded(head.any()));
+    assert!(!server.server().has_loaded(tail.any()));
+
+    // The server loads the nodes again when someone needs them
+    server.bob().request_load(head);
+    server.tick_bob();
+    server.stabilize();
+    assert!(server.server().has_loaded(head.any()));
+    assert_eq!(server.bob().get(head).unwrap().x, 1);
+    assert_eq!(server.bob().get(tail).unwrap().x, 2);
+
+}
+
+#[test]
+fn lru_policy() {
+
+    let mut client = alisa::Client::<Project>::local_with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+
+    let a = client.next_ptr();
+    let b = client.next_ptr();
+    let c = client.next_ptr();
+    for (ptr, x) in [(a, 1), (b, 2)] {
+        client.queue_operation(CreateNode {
+            ptr,
+            x,
+            next: alisa::Ptr::null(),
+        });
+    }
+    client.add_unload_policy(alisa::LruUnloadPolicy::<Node>::new(2));
+    client.tick();
+
+    // Using A makes B the least recently used node, so it gets unloaded when C is created
+    client.get(a);
+    client.queue_operation(CreateNode {
+        ptr: c,
+        x: 3,
+        next: alisa::Ptr::null(),
+    });
+    client.tick();
+    assert!(client.get_ref(a).is_loaded());
+    assert!(client.get_ref(b).is_none());
+    assert!(client.get_ref(c).is_loaded());
+
+    // Loading B again pushes out A
+    client.request_load(b);
+    client.tick();
+    assert_eq!(client.get(b).unwrap().x, 2);
+    assert!(client.get_ref(a).is_none());
+    assert!(client.get_ref(c).is_loaded());
+
+}
+
+#[test]
+fn expired_sessions_release_objects() {
+
+    let mut server = TestingServer::<Project>::new();
+    let (head, tail) = create_chain(&mut server);
+    let carol = server.add_client();
+    server.client(carol).request_load(head);
+    server.tick_client(carol);
+    server.stabilize();
+
+    for client in 0..2 {
+        server.client(client).request_unload(head);
+        server.tick_client(client);
+    }
+    server.stabilize();
+
+    // Carol might still resume her session, so the server keeps her objects around
+    server.disconnect(carol);
+    server.server_mut().expire_sessions();
+    assert!(server.server().has_loaded(head.any()));
+
+    server.server_mut().set_session_timeout(std::time::Duration::ZERO);
+    server.server_mut().expire_sessions();
+    assert!(!server.server().has_loaded(head.any()));
+    assert!(!server.server().has_loaded(tail.any()));
+
+}
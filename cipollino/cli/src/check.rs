@@ -0,0 +1,40 @@
+
+use std::{path::PathBuf, process::ExitCode};
+
+pub fn check(path: PathBuf, repair: bool) -> ExitCode {
+    if !path.is_file() {
+        eprintln!("{} does not exist.", path.display());
+        return ExitCode::FAILURE;
+    }
+    let report = match project::alisa::check_file::<project::Project>(&path, repair) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("could not check {}: {:?}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for problem in &report.problems {
+        println!("{}", problem);
+    }
+
+    if report.is_ok() {
+        println!("{} is healthy.", path.display());
+        return ExitCode::SUCCESS;
+    }
+
+    println!("found {} problem(s) in {}.", report.problems.len(), path.display());
+    if !repair {
+        println!("run again with --repair to fix them.");
+        return ExitCode::FAILURE;
+    }
+
+    if !report.quarantined_chains.is_empty() {
+        println!("cut short {} damaged page chain(s).", report.quarantined_chains.len());
+    }
+    if report.rebuilt_free_list {
+        println!("rebuilt the list of deleted pages.");
+    }
+    println!("repaired {}.", path.display());
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,126 @@
+
+use std::{fmt::{self, Write}, path::PathBuf, process::ExitCode};
+
+use project::{alisa::{ABFValue, Object}, AudioBlock, AudioClip, AudioInstance, AudioLayer, Clip, ClipInner, Color, Fill, Folder, Frame, Layer, LayerGroup, MotionKey, Palette, PaletteInner, Stroke};
+
+const INDENT: &str = "    ";
+
+fn object_type_name(obj_type: u16) -> &'static str {
+    match obj_type {
+        Stroke::TYPE_ID => "Stroke",
+        Frame::TYPE_ID => "Frame",
+        Layer::TYPE_ID => "Layer",
+        Clip::TYPE_ID => "Clip",
+        ClipInner::TYPE_ID => "ClipInner",
+        Folder::TYPE_ID => "Folder",
+        LayerGroup::TYPE_ID => "LayerGroup",
+        Fill::TYPE_ID => "Fill",
+        Palette::TYPE_ID => "Palette",
+        PaletteInner::TYPE_ID => "PaletteInner",
+        Color::TYPE_ID => "Color",
+        AudioLayer::TYPE_ID => "AudioLayer",
+        AudioClip::TYPE_ID => "AudioClip",
+        AudioBlock::TYPE_ID => "AudioBlock",
+        AudioInstance::TYPE_ID => "AudioInstance",
+        MotionKey::TYPE_ID => "MotionKey",
+        _ => "Unknown"
+    }
+}
+
+/// Values that don't contain other values are printed on one line
+fn is_scalar(value: &ABFValue) -> bool {
+    !matches!(value, ABFValue::Array(..) | ABFValue::Map(..) | ABFValue::IndexedEnum(..) | ABFValue::NamedEnum(..))
+}
+
+fn write_value(out: &mut String, value: &ABFValue, depth: usize) -> fmt::Result {
+    match value {
+        ABFValue::Bool(val) => write!(out, "{}", val),
+        ABFValue::PositiveInt(val) => write!(out, "{}", val),
+        ABFValue::U8(val) => write!(out, "{}", val),
+        ABFValue::U16(val) => write!(out, "{}", val),
+        ABFValue::U32(val) => write!(out, "{}", val),
+        ABFValue::U64(val) => write!(out, "{}", val),
+        ABFValue::I8(val) => write!(out, "{}", val),
+        ABFValue::I16(val) => write!(out, "{}", val),
+        ABFValue::I32(val) => write!(out, "{}", val),
+        ABFValue::I64(val) => write!(out, "{}", val),
+        ABFValue::F32(val) => write!(out, "{}", val),
+        ABFValue::F64(val) => write!(out, "{}", val),
+        ABFValue::Str(val) => write!(out, "{:?}", val),
+        ABFValue::Binary(data) => write!(out, "<{} bytes>", data.len()),
+        ABFValue::ObjPtr(obj_type, key) => write!(out, "{}({})", object_type_name(*obj_type), key),
+        ABFValue::Array(values) if values.is_empty() => write!(out, "[]"),
+        ABFValue::Array(values) if values.iter().all(is_scalar) => {
+            write!(out, "[")?;
+            for (idx, value) in values.iter().enumerate() {
+                if idx > 0 {
+                    write!(out, ", ")?;
+                }
+                write_value(out, value, depth)?;
+            }
+            write!(out, "]")
+        },
+        ABFValue::Array(values) => {
+            writeln!(out, "[")?;
+            for value in values {
+                write!(out, "{}", INDENT.repeat(depth + 1))?;
+                write_value(out, value, depth + 1)?;
+                writeln!(out, ",")?;
+            }
+            write!(out, "{}]", INDENT.repeat(depth))
+        },
+        ABFValue::Map(values) if values.is_empty() => write!(out, "{{}}"),
+        ABFValue::Map(values) => {
+            writeln!(out, "{{")?;
+            for (name, value) in values {
+                write!(out, "{}{}: ", INDENT.repeat(depth + 1), name)?;
+                write_value(out, value, depth + 1)?;
+                writeln!(out, ",")?;
+            }
+            write!(out, "{}}}", INDENT.repeat(depth))
+        },
+        ABFValue::IndexedUnitEnum(variant) => write!(out, "#{}", variant),
+        ABFValue::IndexedEnum(variant, value) => {
+            write!(out, "#{} ", variant)?;
+            write_value(out, value, depth)
+        },
+        ABFValue::NamedUnitEnum(variant) => write!(out, "{}", variant),
+        ABFValue::NamedEnum(variant, value) => {
+            write!(out, "{} ", variant)?;
+            write_value(out, value, depth)
+        },
+    }
+}
+
+/// Print the data of an object as text, or the data of the project if no key is given.
+/// The data is printed as it is stored in the file, upgraded to the current format.
+pub fn dump(path: PathBuf, key: Option<u64>) -> ExitCode {
+    let Some(mut client) = crate::project_file::open(&path) else {
+        return ExitCode::FAILURE;
+    };
+    let Some(snapshot) = client.capture_snapshot() else {
+        eprintln!("could not read {}.", path.display());
+        return ExitCode::FAILURE;
+    };
+
+    let data = match key {
+        Some(key) => {
+            let object = snapshot.objects()
+                .find(|ptr| ptr.key() == key)
+                .and_then(|ptr| Some((ptr, snapshot.data(ptr)?)));
+            let Some((ptr, data)) = object else {
+                eprintln!("there is no object with key {} in {}.", key, path.display());
+                return ExitCode::FAILURE;
+            };
+            println!("{}({})", object_type_name(ptr.obj_type()), key);
+            data
+        },
+        None => snapshot.project_data()
+    };
+
+    let mut text = String::new();
+    // Writing to a string never fails
+    let _ = write_value(&mut text, data, 0);
+    println!("{}", text);
+    ExitCode::SUCCESS
+}
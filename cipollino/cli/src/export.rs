@@ -0,0 +1,360 @@
+
+//! Exports the contents of a clip as JSON, for pipeline scripts that want to read clips without going through Alisa.
+//!
+//! The JSON document is an `ExportedClip`. Every struct below maps onto a JSON object with the same field names.
+//! Lists of layers and scene objects are ordered from top to bottom, the same way they are shown in the editor.
+//! Colors are `[r, g, b]` arrays with components between 0 and 1, and positions are in canvas units.
+//! Objects that could not be loaded are left out.
+//!
+//! An abbreviated example:
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "key": 12,
+//!   "name": "Intro",
+//!   "width": 1920,
+//!   "height": 1080,
+//!   "length": 100,
+//!   "framerate": 24.0,
+//!   "background_color": [1.0, 1.0, 1.0],
+//!   "colors": [{ "key": 20, "name": "Skin", "color": [0.9, 0.7, 0.6] }],
+//!   "layers": [
+//!     {
+//!       "type": "layer",
+//!       "key": 30,
+//!       "name": "Layer",
+//!       "frames": [
+//!         {
+//!           "key": 31,
+//!           "time": 0,
+//!           "scene": [
+//!             {
+//!               "type": "stroke",
+//!               "key": 32,
+//!               "color": [0.0, 0.0, 0.0],
+//!               "color_key": null,
+//!               "width": 5.0,
+//!               "brush": 0,
+//!               "points": [{ "prev": [0.0, 0.0, 1.0], "pt": [0.0, 0.0, 1.0], "next": [10.0, 0.0, 1.0] }]
+//!             }
+//!           ]
+//!         }
+//!       ],
+//!       "motion_keys": [
+//!         { "key": 33, "time": 0, "position": [0.0, 0.0], "rotation": 0.0, "scale": [1.0, 1.0], "opacity": 1.0, "easing": "ease_in_out" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::{path::PathBuf, process::ExitCode};
+
+use project::{alisa, Client, Easing, LayerPtr, SceneObjPtr, SceneObjectColor, StrokeBrush};
+
+use crate::project_file;
+
+/// The version of the export format, increased whenever it changes in a way that could break existing scripts
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(serde::Serialize)]
+struct ExportedClip {
+    format_version: u32,
+    key: u64,
+    name: String,
+    width: u32,
+    height: u32,
+    /// The length of the clip, in frames
+    length: u32,
+    /// The number of frames per second
+    framerate: f32,
+    background_color: [f32; 3],
+    /// The colors defined in the clip itself, sorted by name. Palette colors are not included.
+    colors: Vec<ExportedColor>,
+    layers: Vec<ExportedLayer>
+}
+
+#[derive(serde::Serialize)]
+struct ExportedColor {
+    key: u64,
+    name: String,
+    color: [f32; 3]
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportedLayer {
+    Layer {
+        key: u64,
+        name: String,
+        /// Sorted by time
+        frames: Vec<ExportedFrame>,
+        /// Sorted by time
+        motion_keys: Vec<ExportedMotionKey>
+    },
+    Group {
+        key: u64,
+        name: String,
+        layers: Vec<ExportedLayer>
+    },
+    Audio {
+        key: u64,
+        name: String,
+        /// Sorted by start time
+        instances: Vec<ExportedAudioInstance>
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ExportedFrame {
+    key: u64,
+    /// The frame on which the keyframe starts. It lasts until the next keyframe in the layer.
+    time: i32,
+    scene: Vec<ExportedSceneObject>
+}
+
+/// A key in the motion of a layer. Between two keys, the layer's contents are scaled, rotated and then moved by values interpolated with the easing of the earlier key.
+#[derive(serde::Serialize)]
+struct ExportedMotionKey {
+    key: u64,
+    time: i32,
+    position: [f32; 2],
+    /// In degrees
+    rotation: f32,
+    scale: [f32; 2],
+    opacity: f32,
+    /// One of `linear`, `ease_in`, `ease_out`, `ease_in_out` or `hold`
+    easing: &'static str
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportedSceneObject {
+    Stroke {
+        key: u64,
+        color: [f32; 3],
+        /// The clip or palette color the stroke uses, if any
+        color_key: Option<u64>,
+        width: f32,
+        /// The index of the builtin brush
+        brush: usize,
+        /// The Bézier points of the stroke. Each of `prev`, `pt` and `next` is `[x, y, pressure]`.
+        points: Vec<ExportedStrokePoint>
+    },
+    Fill {
+        key: u64,
+        color: [f32; 3],
+        /// The clip or palette color the fill uses, if any
+        color_key: Option<u64>,
+        /// The outlines of the fill, as lists of Bézier points. Each of `prev`, `pt` and `next` is `[x, y]`.
+        paths: Vec<Vec<ExportedFillPoint>>
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ExportedStrokePoint {
+    prev: [f32; 3],
+    pt: [f32; 3],
+    next: [f32; 3]
+}
+
+#[derive(serde::Serialize)]
+struct ExportedFillPoint {
+    prev: [f32; 2],
+    pt: [f32; 2],
+    next: [f32; 2]
+}
+
+#[derive(serde::Serialize)]
+struct ExportedAudioInstance {
+    key: u64,
+    /// The key of the audio clip played
+    clip: u64,
+    /// The start of the instance on the timeline, in seconds
+    start: f32,
+    /// The end of the instance on the timeline, in seconds
+    end: f32,
+    /// The time in the audio clip at which playback starts, in seconds
+    offset: f32
+}
+
+fn export_color(client: &Client, color: &SceneObjectColor) -> ([f32; 3], Option<u64>) {
+    match client.get(color.color) {
+        Some(clip_color) => (clip_color.color, Some(color.color.ptr().key())),
+        None => (color.backup, None)
+    }
+}
+
+fn export_scene(client: &Client, scene: &alisa::ChildList<SceneObjPtr>) -> Vec<ExportedSceneObject> {
+    scene.iter().filter_map(|obj| match obj {
+        SceneObjPtr::Stroke(stroke_ptr) => {
+            let stroke = client.get(stroke_ptr)?;
+            let (color, color_key) = export_color(client, &stroke.color);
+            let StrokeBrush::Builtin(brush) = stroke.brush;
+            Some(ExportedSceneObject::Stroke {
+                key: stroke_ptr.key(),
+                color,
+                color_key,
+                width: stroke.width,
+                brush,
+                points: stroke.stroke.0.path.pts.iter().map(|pt| ExportedStrokePoint {
+                    prev: [pt.prev.pt.x, pt.prev.pt.y, pt.prev.pressure],
+                    pt: [pt.pt.pt.x, pt.pt.pt.y, pt.pt.pressure],
+                    next: [pt.next.pt.x, pt.next.pt.y, pt.next.pressure]
+                }).collect()
+            })
+        },
+        SceneObjPtr::Fill(fill_ptr) => {
+            let fill = client.get(fill_ptr)?;
+            let (color, color_key) = export_color(client, &fill.color);
+            Some(ExportedSceneObject::Fill {
+                key: fill_ptr.key(),
+                color,
+                color_key,
+                paths: fill.paths.0.paths.iter().map(|path| path.pts.iter().map(|pt| ExportedFillPoint {
+                    prev: [pt.prev.x, pt.prev.y],
+                    pt: [pt.pt.x, pt.pt.y],
+                    next: [pt.next.x, pt.next.y]
+                }).collect()).collect()
+            })
+        }
+    }).collect()
+}
+
+fn easing_name(easing: Easing) -> &'static str {
+    match easing {
+        Easing::Linear => "linear",
+        Easing::EaseIn => "ease_in",
+        Easing::EaseOut => "ease_out",
+        Easing::EaseInOut => "ease_in_out",
+        Easing::Hold => "hold"
+    }
+}
+
+fn export_layers(client: &Client, layers: &alisa::ChildList<LayerPtr>) -> Vec<ExportedLayer> {
+    layers.iter().filter_map(|layer| match layer {
+        LayerPtr::Layer(layer_ptr) => {
+            let layer = client.get(layer_ptr)?;
+            let mut frames: Vec<_> = layer.frames.iter()
+                .filter_map(|frame_ptr| {
+                    let frame = client.get(frame_ptr.ptr())?;
+                    Some(ExportedFrame {
+                        key: frame_ptr.ptr().key(),
+                        time: frame.time,
+                        scene: export_scene(client, &frame.scene)
+                    })
+                })
+                .collect();
+            frames.sort_by_key(|frame| frame.time);
+            let mut motion_keys: Vec<_> = layer.motion_keys.iter()
+                .filter_map(|key_ptr| {
+                    let motion_key = client.get(key_ptr.ptr())?;
+                    Some(ExportedMotionKey {
+                        key: key_ptr.ptr().key(),
+                        time: motion_key.time,
+                        position: motion_key.position,
+                        rotation: motion_key.rotation,
+                        scale: motion_key.scale,
+                        opacity: motion_key.opacity,
+                        easing: easing_name(motion_key.easing)
+                    })
+                })
+                .collect();
+            motion_keys.sort_by_key(|motion_key| motion_key.time);
+            Some(ExportedLayer::Layer {
+                key: layer_ptr.key(),
+                name: layer.name.clone(),
+                frames,
+                motion_keys
+            })
+        },
+        LayerPtr::LayerGroup(group_ptr) => {
+            let group = client.get(group_ptr)?;
+            Some(ExportedLayer::Group {
+                key: group_ptr.key(),
+                name: group.name.clone(),
+                layers: export_layers(client, &group.layers)
+            })
+        },
+        LayerPtr::AudioLayer(audio_layer_ptr) => {
+            let audio_layer = client.get(audio_layer_ptr)?;
+            let mut instances: Vec<_> = audio_layer.audio_instances.iter()
+                .filter_map(|instance_ptr| {
+                    let instance = client.get(instance_ptr.ptr())?;
+                    Some(ExportedAudioInstance {
+                        key: instance_ptr.ptr().key(),
+                        clip: instance.clip.key(),
+                        start: instance.start,
+                        end: instance.end,
+                        offset: instance.offset
+                    })
+                })
+                .collect();
+            instances.sort_by(|a, b| a.start.total_cmp(&b.start));
+            Some(ExportedLayer::Audio {
+                key: audio_layer_ptr.key(),
+                name: audio_layer.name.clone(),
+                instances
+            })
+        }
+    }).collect()
+}
+
+/// Find a clip, load it and convert its contents to JSON
+pub fn export_clip(client: &mut Client, clip: &str) -> Option<String> {
+    let (clip_ptr, inner_ptr) = project_file::load_clip(client, clip)?;
+    let (clip, inner) = (client.get(clip_ptr)?, client.get(inner_ptr)?);
+
+    let mut colors: Vec<_> = inner.colors.iter()
+        .filter_map(|color_ptr| {
+            let color = client.get(color_ptr.ptr())?;
+            Some(ExportedColor {
+                key: color_ptr.ptr().key(),
+                name: color.name.clone(),
+                color: color.color
+            })
+        })
+        .collect();
+    colors.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let exported = ExportedClip {
+        format_version: FORMAT_VERSION,
+        key: clip_ptr.key(),
+        name: clip.name.clone(),
+        width: inner.width,
+        height: inner.height,
+        length: inner.length,
+        framerate: inner.framerate,
+        background_color: inner.background_color,
+        colors,
+        layers: export_layers(client, &inner.layers)
+    };
+
+    match serde_json::to_string_pretty(&exported) {
+        Ok(json) => Some(json),
+        Err(err) => {
+            eprintln!("could not export clip {}: {}", clip.name, err);
+            None
+        }
+    }
+}
+
+/// Write the contents of a clip as JSON to `output`, or print it if no output file is given
+pub fn export(path: PathBuf, clip: String, output: Option<PathBuf>) -> ExitCode {
+    let Some(mut client) = project_file::open(&path) else {
+        return ExitCode::FAILURE;
+    };
+    let Some(json) = export_clip(&mut client, &clip) else {
+        return ExitCode::FAILURE;
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(&output, json) {
+                eprintln!("could not write {}: {}", output.display(), err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => println!("{}", json)
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,44 @@
+
+use std::{path::PathBuf, process::ExitCode};
+
+use project::{alisa, Asset, AudioClip, Client, Clip, Folder, Palette};
+
+use crate::project_file;
+
+type AssetList<A> = alisa::UnorderedChildList<alisa::OwningPtr<A>>;
+
+/// The assets in a list that are loaded, sorted by name
+fn sorted_assets<'a, A: Asset>(client: &'a Client, assets: &AssetList<A>) -> Vec<(alisa::Ptr<A>, &'a A)> {
+    let mut assets: Vec<_> = assets.iter()
+        .filter_map(|asset| Some((asset.ptr(), client.get(asset.ptr())?)))
+        .collect();
+    assets.sort_by(|(_, a), (_, b)| a.name().cmp(b.name()));
+    assets
+}
+
+fn list_assets<A: Asset>(client: &Client, assets: &AssetList<A>, kind: &str, path: &str) {
+    for (ptr, asset) in sorted_assets(client, assets) {
+        println!("{:<8} {:<20} {}{}", kind, ptr.key(), path, asset.name());
+    }
+}
+
+fn list_folder(client: &Client, folders: &AssetList<Folder>, clips: &AssetList<Clip>, palettes: &AssetList<Palette>, audio_clips: &AssetList<AudioClip>, path: &str) {
+    for (ptr, folder) in sorted_assets(client, folders) {
+        let folder_path = format!("{}{}/", path, folder.name);
+        println!("{:<8} {:<20} {}", "folder", ptr.key(), folder_path);
+        list_folder(client, &folder.folders, &folder.clips, &folder.palettes, &folder.audio_clips, &folder_path);
+    }
+    list_assets(client, clips, "clip", path);
+    list_assets(client, palettes, "palette", path);
+    list_assets(client, audio_clips, "audio", path);
+}
+
+/// Print every asset in the project, one per line, along with its kind and key
+pub fn list(path: PathBuf) -> ExitCode {
+    let Some(client) = project_file::open(&path) else {
+        return ExitCode::FAILURE;
+    };
+
+    list_folder(&client, &client.folders, &client.clips, &client.palettes, &client.audio_clips, "");
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,68 @@
+
+use clap::Parser;
+use std::{path::PathBuf, process::ExitCode};
+
+mod check;
+mod list;
+mod dump;
+mod stats;
+mod export;
+mod project_file;
+
+#[cfg(test)]
+mod test;
+
+#[derive(clap::Parser)]
+#[command(about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Check the integrity of a project file
+    Check {
+        path: PathBuf,
+        /// Fix any problems found in the file
+        #[arg(long)]
+        repair: bool
+    },
+    /// List the folders, clips, palettes and audio clips in a project, along with their keys
+    List {
+        path: PathBuf
+    },
+    /// Print the data of an object as text
+    Dump {
+        path: PathBuf,
+        /// The key of the object. Prints the project itself if no key is given.
+        key: Option<u64>
+    },
+    /// Print statistics about a clip
+    Stats {
+        path: PathBuf,
+        /// The key of the clip, or its path in the project, such as `Scenes/Intro`
+        clip: String
+    },
+    /// Export the contents of a clip as JSON
+    Export {
+        path: PathBuf,
+        /// The key of the clip, or its path in the project, such as `Scenes/Intro`
+        clip: String,
+        /// The file to write the JSON to. Prints it if no file is given.
+        #[arg(long, short)]
+        output: Option<PathBuf>
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Check { path, repair } => check::check(path, repair),
+        Command::List { path } => list::list(path),
+        Command::Dump { path, key } => dump::dump(path, key),
+        Command::Stats { path, clip } => stats::stats(path, clip),
+        Command::Export { path, clip, output } => export::export(path, clip, output),
+    }
+}
@@ -0,0 +1,58 @@
+
+use std::path::Path;
+
+use project::{alisa, Client, Clip, ClipInner};
+
+/// Open a project file for inspection.
+/// Unlike `Client::local`, this refuses to create a new project if there is no file at `path`.
+pub fn open(path: &Path) -> Option<Client> {
+    if !path.is_file() {
+        eprintln!("{} does not exist.", path.display());
+        return None;
+    }
+    let client = Client::local(path);
+    if client.is_none() {
+        eprintln!("could not open {}. it might be damaged or saved by a newer version of Cipollino.", path.display());
+    }
+    client
+}
+
+/// Find a clip by its key or by its path in the asset tree, such as `Scenes/Intro`
+pub fn find_clip(client: &Client, clip: &str) -> Option<alisa::Ptr<Clip>> {
+    if let Ok(key) = clip.parse() {
+        let ptr = alisa::Ptr::from_key(key);
+        if client.get(ptr).is_some() {
+            return Some(ptr);
+        }
+    }
+
+    let (folder_path, name) = clip.rsplit_once('/').unwrap_or(("", clip));
+    let mut folders = &client.folders;
+    let mut clips = &client.clips;
+    for folder_name in folder_path.split('/').filter(|name| !name.is_empty()) {
+        let folder = folders.iter()
+            .filter_map(|folder| client.get(folder.ptr()))
+            .find(|folder| folder.name == folder_name)?;
+        folders = &folder.folders;
+        clips = &folder.clips;
+    }
+    clips.iter()
+        .map(|clip| clip.ptr())
+        .find(|clip| client.get(*clip).is_some_and(|clip| clip.name == name))
+}
+
+/// Find a clip and load its contents
+pub fn load_clip(client: &mut Client, clip: &str) -> Option<(alisa::Ptr<Clip>, alisa::Ptr<ClipInner>)> {
+    let Some(clip_ptr) = find_clip(client, clip) else {
+        eprintln!("there is no clip {}.", clip);
+        return None;
+    };
+    let inner_ptr = client.get(clip_ptr)?.inner.ptr();
+    client.request_load(inner_ptr);
+    client.tick();
+    if client.get(inner_ptr).is_none() {
+        eprintln!("could not load the contents of clip {}.", clip);
+        return None;
+    }
+    Some((clip_ptr, inner_ptr))
+}
@@ -0,0 +1,100 @@
+
+use std::{path::PathBuf, process::ExitCode};
+
+use project::{alisa, Client, LayerPtr, SceneObjPtr};
+
+use crate::project_file;
+
+#[derive(Default)]
+struct ClipStats {
+    layers: usize,
+    layer_groups: usize,
+    audio_layers: usize,
+    audio_instances: usize,
+    frames: usize,
+    motion_keys: usize,
+    strokes: usize,
+    stroke_points: usize,
+    fills: usize
+}
+
+impl ClipStats {
+
+    fn count_layers(&mut self, client: &Client, layers: &alisa::ChildList<LayerPtr>) {
+        for layer in layers.iter() {
+            match layer {
+                LayerPtr::Layer(layer_ptr) => {
+                    let Some(layer) = client.get(layer_ptr) else { continue; };
+                    self.layers += 1;
+                    for frame in layer.frames.iter() {
+                        if let Some(frame) = client.get(frame.ptr()) {
+                            self.frames += 1;
+                            self.count_scene(client, &frame.scene);
+                        }
+                    }
+                    self.motion_keys += layer.motion_keys.iter().count();
+                },
+                LayerPtr::LayerGroup(group_ptr) => {
+                    let Some(group) = client.get(group_ptr) else { continue; };
+                    self.layer_groups += 1;
+                    self.count_layers(client, &group.layers);
+                },
+                LayerPtr::AudioLayer(audio_layer_ptr) => {
+                    let Some(audio_layer) = client.get(audio_layer_ptr) else { continue; };
+                    self.audio_layers += 1;
+                    self.audio_instances += audio_layer.audio_instances.iter().count();
+                }
+            }
+        }
+    }
+
+    fn count_scene(&mut self, client: &Client, scene: &alisa::ChildList<SceneObjPtr>) {
+        for obj in scene.iter() {
+            match obj {
+                SceneObjPtr::Stroke(stroke_ptr) => {
+                    if let Some(stroke) = client.get(stroke_ptr) {
+                        self.strokes += 1;
+                        self.stroke_points += stroke.stroke.0.path.pts.len();
+                    }
+                },
+                SceneObjPtr::Fill(fill_ptr) => {
+                    if client.get(fill_ptr).is_some() {
+                        self.fills += 1;
+                    }
+                }
+            }
+        }
+    }
+
+}
+
+/// Print the size and timing of a clip, and how much it contains
+pub fn stats(path: PathBuf, clip: String) -> ExitCode {
+    let Some(mut client) = project_file::open(&path) else {
+        return ExitCode::FAILURE;
+    };
+    let Some((clip_ptr, inner_ptr)) = project_file::load_clip(&mut client, &clip) else {
+        return ExitCode::FAILURE;
+    };
+    let (Some(clip), Some(inner)) = (client.get(clip_ptr), client.get(inner_ptr)) else {
+        return ExitCode::FAILURE;
+    };
+
+    let mut stats = ClipStats::default();
+    stats.count_layers(&client, &inner.layers);
+
+    println!("clip             {} ({})", clip.name, clip_ptr.key());
+    println!("size             {}x{}", inner.width, inner.height);
+    println!("length           {} frames at {} fps", inner.length, inner.framerate);
+    println!("layers           {}", stats.layers);
+    println!("layer groups     {}", stats.layer_groups);
+    println!("audio layers     {}", stats.audio_layers);
+    println!("audio instances  {}", stats.audio_instances);
+    println!("frames           {}", stats.frames);
+    println!("motion keys      {}", stats.motion_keys);
+    println!("strokes          {}", stats.strokes);
+    println!("stroke points    {}", stats.stroke_points);
+    println!("fills            {}", stats.fills);
+    println!("colors           {}", inner.colors.iter().count());
+    ExitCode::SUCCESS
+}
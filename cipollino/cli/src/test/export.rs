@@ -0,0 +1,74 @@
+use project::{Client, Clip, CreateLayer, CreateMotionKey, Easing, Layer, LayerParent, LayerTreeData, MotionKeyTreeData, Ptr};
+
+use crate::export::export_clip;
+
+use super::{create_clip, create_folder, memory_client};
+
+fn create_layer(client: &Client, clip: Ptr<Clip>, name: &str) -> Ptr<Layer> {
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateLayer {
+        ptr,
+        parent: LayerParent::Clip(clip),
+        idx: 0,
+        data: LayerTreeData {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    });
+    ptr
+}
+
+fn create_motion_key(client: &Client, layer: Ptr<Layer>, time: i32, x: f32, easing: Easing) {
+    client.queue_operation(CreateMotionKey {
+        ptr: client.next_ptr(),
+        parent: layer,
+        idx: (),
+        data: MotionKeyTreeData {
+            time,
+            position: [x, 0.0],
+            easing,
+            ..Default::default()
+        }
+    });
+}
+
+#[test]
+fn export() {
+    let mut client = memory_client();
+    let scenes = create_folder(&client, Ptr::null(), "Scenes");
+    let intro = create_clip(&client, scenes, "Intro");
+    // Each layer is created on top of the previous one
+    let background = create_layer(&client, intro, "Background");
+    let characters = create_layer(&client, intro, "Characters");
+    create_motion_key(&client, characters, 20, 30.0, Easing::Linear);
+    create_motion_key(&client, characters, 0, 10.0, Easing::Hold);
+    client.tick();
+
+    let json = export_clip(&mut client, "Scenes/Intro").unwrap();
+    let exported: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(exported["format_version"], 1);
+    assert_eq!(exported["key"], intro.key());
+    assert_eq!(exported["name"], "Intro");
+
+    // Layers are listed from top to bottom
+    let layers = exported["layers"].as_array().unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0]["type"], "layer");
+    assert_eq!(layers[0]["key"], characters.key());
+    assert_eq!(layers[0]["name"], "Characters");
+    assert_eq!(layers[1]["key"], background.key());
+    assert_eq!(layers[1]["name"], "Background");
+
+    // Motion keys are sorted by time
+    let motion_keys = layers[0]["motion_keys"].as_array().unwrap();
+    assert_eq!(motion_keys.len(), 2);
+    assert_eq!(motion_keys[0]["time"], 0);
+    assert_eq!(motion_keys[0]["position"], serde_json::json!([10.0, 0.0]));
+    assert_eq!(motion_keys[0]["easing"], "hold");
+    assert_eq!(motion_keys[1]["time"], 20);
+    assert_eq!(motion_keys[1]["position"], serde_json::json!([30.0, 0.0]));
+    assert_eq!(motion_keys[1]["easing"], "linear");
+    assert!(layers[1]["motion_keys"].as_array().unwrap().is_empty());
+
+    assert!(export_clip(&mut client, "Scenes/Outro").is_none());
+}
@@ -0,0 +1,36 @@
+use project::{alisa, Client, Clip, ClipTreeData, CreateClip, CreateFolder, Folder, FolderTreeData, Ptr};
+
+mod project_file;
+mod export;
+
+/// A local client for an empty project kept in memory
+fn memory_client() -> Client {
+    Client::local_with_storage(alisa::verter::MemoryStorage::new()).unwrap()
+}
+
+fn create_folder(client: &Client, parent: Ptr<Folder>, name: &str) -> Ptr<Folder> {
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateFolder {
+        ptr,
+        parent,
+        data: FolderTreeData {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    });
+    ptr
+}
+
+fn create_clip(client: &Client, parent: Ptr<Folder>, name: &str) -> Ptr<Clip> {
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateClip {
+        ptr,
+        parent,
+        data: ClipTreeData {
+            name: name.to_owned(),
+            inner_ptr: client.next_ptr(),
+            ..Default::default()
+        }
+    });
+    ptr
+}
@@ -0,0 +1,36 @@
+use project::Ptr;
+
+use crate::project_file::find_clip;
+
+use super::{create_clip, create_folder, memory_client};
+
+#[test]
+fn find_clips() {
+    let mut client = memory_client();
+    let scenes = create_folder(&client, Ptr::null(), "Scenes");
+    let forest = create_folder(&client, scenes, "Forest");
+    let title = create_clip(&client, Ptr::null(), "Title");
+    let intro = create_clip(&client, scenes, "Intro");
+    let walk = create_clip(&client, forest, "Walk");
+    // A clip with the same name in another folder
+    let other_walk = create_clip(&client, Ptr::null(), "Walk");
+    client.tick();
+
+    // By key
+    assert_eq!(find_clip(&client, &title.key().to_string()), Some(title));
+    assert_eq!(find_clip(&client, &walk.key().to_string()), Some(walk));
+
+    // By path
+    assert_eq!(find_clip(&client, "Title"), Some(title));
+    assert_eq!(find_clip(&client, "Scenes/Intro"), Some(intro));
+    assert_eq!(find_clip(&client, "Scenes/Forest/Walk"), Some(walk));
+    assert_eq!(find_clip(&client, "Walk"), Some(other_walk));
+
+    // Missing clips
+    assert_eq!(find_clip(&client, "Outro"), None);
+    assert_eq!(find_clip(&client, "Scenes/Walk"), None);
+    assert_eq!(find_clip(&client, "Forest/Walk"), None);
+    assert_eq!(find_clip(&client, "Scenes/Forest/Walk/Extra"), None);
+    assert_eq!(find_clip(&client, &scenes.key().to_string()), None);
+    assert_eq!(find_clip(&client, "123456"), None);
+}
@@ -0,0 +1,57 @@
+
+use crate::PanelContext;
+
+use super::Window;
+
+/// Window showing the result of compacting the project file
+pub struct CompactProjectWindow {
+    reclaimed_bytes: Option<u64>
+}
+
+impl CompactProjectWindow {
+
+    pub fn new(reclaimed_bytes: Option<u64>) -> Self {
+        Self {
+            reclaimed_bytes
+        }
+    }
+
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+impl Window for CompactProjectWindow {
+
+    fn title(&self) -> String {
+        "Compact Project".to_owned()
+    }
+
+    fn render(&mut self, ui: &mut pierro::UI, close: &mut bool, _ctx: &mut PanelContext) {
+        match self.reclaimed_bytes {
+            Some(reclaimed_bytes) => pierro::label(ui, format!("Reclaimed {} of disk space.", format_bytes(reclaimed_bytes))),
+            None => pierro::label(ui, "Could not compact the project file."),
+        };
+        pierro::v_spacing(ui, 3.0);
+        if pierro::button(ui, "Ok").mouse_clicked() {
+            *close = true;
+        }
+    }
+
+    fn unique(&self) -> bool {
+        true
+    }
+
+}
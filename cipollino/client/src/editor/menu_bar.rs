@@ -1,7 +1,7 @@
 
 use crate::{splash::SplashScreen, AppState};
 
-use super::{Editor, SettingsWindow};
+use super::{CompactProjectWindow, Editor, SettingsWindow};
 use crate::ExportDialog;
 
 impl Editor {
@@ -13,6 +13,12 @@ impl Editor {
                 if pierro::menu_button(ui, "Export").mouse_clicked() {
                     self.state.editor.open_window(ExportDialog::new());
                 }
+                if self.state.project.client.is_local() {
+                    if pierro::menu_button(ui, "Compact Project").mouse_clicked() {
+                        let reclaimed_bytes = self.state.project.client.compact();
+                        self.state.editor.open_window(CompactProjectWindow::new(reclaimed_bytes));
+                    }
+                }
                 if self.state.project.client.is_collab() {
                     if pierro::menu_button(ui, "Disconnect").mouse_clicked() {
                         *next_app_state = Some(AppState::SplashScreen(SplashScreen::new()));
@@ -2,9 +2,8 @@
 use std::path::PathBuf;
 
 use alisa::Children;
-use project::{deep_load_clip, Client, Fill, Frame, Message, Ptr, Stroke, WelcomeMessage, PROTOCOL_VERSION};
+use project::{deep_load_clip, AudioBlock, Client, ClipInner, ConnectionRefused, Fill, Frame, Message, Ptr, Stroke, WelcomeMessage, PROTOCOL_VERSION};
 
-use crate::splash::SplashScreen;
 use crate::{AppState, AppSystems, DockingLayoutPref, EditorPanel, PanelContext};
 
 mod socket;
@@ -14,6 +13,7 @@ mod state;
 pub use state::*;
 
 mod undo_redo;
+pub use undo_redo::*;
 
 mod selection;
 pub use selection::*;
@@ -29,6 +29,12 @@ pub use window::*;
 mod settings;
 pub use settings::*;
 
+mod compact;
+pub use compact::*;
+
+mod reconnect;
+use reconnect::*;
+
 mod presence;
 pub use presence::*;
 
@@ -49,12 +55,23 @@ pub struct Editor {
     docking: pierro::DockingState<EditorPanel>,
     windows: pierro::WindowManager<WindowInstance>,
     socket: Option<Socket>,
+    /// The state of reconnecting to the collab server, if the connection was lost
+    reconnect: Option<Reconnect>,
     redraw_requests: u32 
 }
 
+/// How many clips to keep loaded. The contents of the least recently used clips are unloaded beyond this.
+const LOADED_CLIPS: usize = 16;
+/// How many blocks of audio to keep loaded
+const LOADED_AUDIO_BLOCKS: usize = 512;
+
 impl Editor {
 
-    fn new(client: Client, socket: Option<Socket>, systems: &mut AppSystems) -> Self {
+    fn new(mut client: Client, socket: Option<Socket>, systems: &mut AppSystems) -> Self {
+        // Keep long sessions from holding every clip and sound they ever touched in memory
+        client.add_unload_policy(alisa::LruUnloadPolicy::<ClipInner>::new(LOADED_CLIPS));
+        client.add_unload_policy(alisa::LruUnloadPolicy::<AudioBlock>::new(LOADED_AUDIO_BLOCKS));
+
         let mut editor = Self {
             state: State {
                 project: ProjectState::new(client),
@@ -64,6 +81,7 @@ impl Editor {
             docking: systems.prefs.get::<DockingLayoutPref>(),
             windows: pierro::WindowManager::new(),
             socket,
+            reconnect: None,
             redraw_requests: 3
         };
 
@@ -78,10 +96,18 @@ impl Editor {
     }
 
     pub fn local(path: PathBuf, systems: &mut AppSystems) -> Option<Self> {
-        Some(Self::new(Client::local(path)?, None, systems))
+        let mut client = Client::local(path)?;
+        let undo_history_depth = systems.prefs.get::<UndoHistoryDepth>();
+        if undo_history_depth > 0 {
+            client.enable_persistent_history(undo_history_depth as usize);
+        }
+        Some(Self::new(client, None, systems))
     }
 
     pub fn collab(socket: Socket, welcome_msg: &alisa::ABFValue, systems: &mut AppSystems) -> Result<Self, String> {
+        if let Some(refused) = ConnectionRefused::from_message(welcome_msg) {
+            return Err(refused.reason);
+        }
         let welcome_msg = alisa::deserialize::<WelcomeMessage>(welcome_msg).ok_or("Invalid server protocol.".to_owned())?;
         let mut editor = Self::new(Client::collab(&welcome_msg.collab).ok_or("Invalid server protocol.".to_owned())?, Some(socket), systems);
         editor.state.editor.other_clients = welcome_msg.presence.into_iter().collect();
@@ -229,6 +255,9 @@ impl Editor {
                             };
                             Self::receive_message(&submsg, &mut self.state);
                         }
+                    } else if self.reconnect.as_mut().is_some_and(|reconnect| reconnect.take_reply(&msg)) {
+                        // The reply to an attempt to reconnect is handled by `tick_reconnect`
+                        break;
                     } else {
                         if let Some(msg) = alisa::deserialize::<Message>(&msg) {
                             Self::receive_message(&msg, &mut self.state);
@@ -267,11 +296,12 @@ impl Editor {
 
             self.state.editor.presence.update(socket);
 
-            if socket.closed() {
-                let msg = "Collab server disconnected.".to_owned();
-                *next_app_state = Some(AppState::SplashScreen(SplashScreen::new_with_error(msg)));
-            }
+        }
 
+        self.tick_reconnect(ui.input().delta_time, next_app_state, systems);
+        if self.reconnect.is_some() {
+            // Keep redrawing, so that reconnection attempts happen even if the user isn't doing anything
+            ui.request_redraw();
         }
 
         // Update the project client
@@ -0,0 +1,107 @@
+
+use project::{ConnectMessage, ConnectionRefused};
+
+use crate::{splash::SplashScreen, AppState, AppSystems};
+
+use super::{Editor, Socket};
+
+/// How long to keep trying to reconnect to the collab server before giving up, in seconds
+const RECONNECT_TIMEOUT: f32 = 60.0;
+/// The time between attempts to reconnect to the collab server, in seconds
+const RECONNECT_INTERVAL: f32 = 2.0;
+
+/// The state of reconnecting to the collab server after losing the connection
+pub(super) struct Reconnect {
+    /// The time left before giving up
+    time_left: f32,
+    /// The time left until the next attempt
+    next_attempt: f32,
+    /// Did the server let us know our session expired? If so, we join the project again from scratch instead of resuming the session.
+    rejoin: bool,
+    /// The server's reply to the latest attempt, if it refused the connection or welcomed us back as a new client
+    reply: Option<alisa::ABFValue>
+}
+
+impl Reconnect {
+
+    /// Hold on to a message the server sent outside of a message array, if it's the reply to an attempt to reconnect.
+    /// Returns true if the message was taken.
+    pub(super) fn take_reply(&mut self, msg: &alisa::ABFValue) -> bool {
+        if self.rejoin || ConnectionRefused::from_message(msg).is_some() {
+            self.reply = Some(msg.clone());
+            return true;
+        }
+        false
+    }
+
+}
+
+impl Editor {
+
+    /// Resume the collab session if the connection to the server was lost.
+    /// While reconnecting, the editor keeps working offline, and the work done is sent to the server once the session is resumed.
+    /// If the session expired, the editor joins the project again from scratch, and the work done offline is lost.
+    pub(super) fn tick_reconnect(&mut self, delta_time: f32, next_app_state: &mut Option<AppState>, systems: &mut AppSystems) {
+        if let Some(reply) = self.reconnect.as_mut().and_then(|reconnect| reconnect.reply.take()) {
+            if let Some(refused) = ConnectionRefused::from_message(&reply) {
+                if !refused.session_expired {
+                    *next_app_state = Some(AppState::SplashScreen(SplashScreen::new_with_error(refused.reason)));
+                    return;
+                }
+                if let Some(reconnect) = &mut self.reconnect {
+                    reconnect.rejoin = true;
+                    reconnect.next_attempt = 0.0;
+                }
+            } else if let Some(socket) = self.socket.take() {
+                *next_app_state = Some(match Editor::collab(socket, &reply, systems) {
+                    Ok(editor) => AppState::Editor(editor),
+                    Err(msg) => AppState::SplashScreen(SplashScreen::new_with_error(msg))
+                });
+                return;
+            }
+        }
+
+        let Some(socket) = &mut self.socket else { return; };
+        let client = &mut self.state.project.client;
+
+        if !socket.closed() {
+            if !client.is_offline() {
+                self.reconnect = None;
+            }
+            return;
+        }
+
+        let reconnect = self.reconnect.get_or_insert_with(|| {
+            client.disconnect();
+            self.state.editor.other_clients.clear();
+            Reconnect {
+                time_left: RECONNECT_TIMEOUT,
+                next_attempt: 0.0,
+                rejoin: false,
+                reply: None
+            }
+        });
+
+        reconnect.time_left -= delta_time;
+        if reconnect.time_left < 0.0 {
+            let msg = "Collab server disconnected.".to_owned();
+            *next_app_state = Some(AppState::SplashScreen(SplashScreen::new_with_error(msg)));
+            return;
+        }
+
+        reconnect.next_attempt -= delta_time;
+        if reconnect.next_attempt > 0.0 {
+            return;
+        }
+        reconnect.next_attempt = RECONNECT_INTERVAL;
+
+        if let Ok(mut new_socket) = Socket::new(socket.url(), socket.credentials().cloned()) {
+            new_socket.send_data(alisa::serialize(&ConnectMessage {
+                resume: if reconnect.rejoin { None } else { client.session() },
+                credentials: socket.credentials().cloned()
+            }));
+            *socket = new_socket;
+        }
+    }
+
+}
@@ -6,13 +6,16 @@ use project::{Client, ClipInner, Frame, Layer, LayerPtr, Ptr, SceneObjPtr, Strok
 use super::EditorState;
 
 pub struct SceneRenderList {
-    pub objs: Vec<SceneObjPtr>
+    pub objs: Vec<SceneObjPtr>,
+    /// The motion transform of the layer each object in `objs` is on
+    pub transforms: Vec<elic::Mat4>
 }
 
 impl SceneRenderList {
 
-    fn get_frame_render_list(&mut self, frame: &Frame) {
+    fn get_frame_render_list(&mut self, frame: &Frame, transform: elic::Mat4) {
         self.objs.extend(frame.scene.iter().rev());
+        self.transforms.resize(self.objs.len(), transform);
     }
 
     fn get_layer_render_list(&mut self, client: &Client, editor: &EditorState, layer: &Layer, layer_ptr: Ptr<Layer>, time: i32) {
@@ -22,7 +25,7 @@ impl SceneRenderList {
 
         let Some(frame_ptr) = layer.frame_at(client, time) else { return; };
         if let Some(frame) = client.get(frame_ptr) {
-            self.get_frame_render_list(frame);
+            self.get_frame_render_list(frame, layer.motion_at(client, time).transform());
         }
     }
 
@@ -47,6 +50,7 @@ impl SceneRenderList {
     pub fn make(client: &Client, editor: &EditorState, clip: &ClipInner, time: i32) -> Self {
         let mut list = SceneRenderList {
             objs: Vec::new(),
+            transforms: Vec::new()
         };
         list.get_layer_list_render_list(client, editor, &clip.layers, time);
         list
@@ -0,0 +1,20 @@
+
+use crate::{AppSystems, UndoHistoryDepth};
+
+pub(super) fn history(ui: &mut pierro::UI, systems: &mut AppSystems) {
+
+    pierro::key_value_layout(ui, |builder| {
+        builder.labeled("Saved Undo Steps:", |ui| {
+            let mut depth = systems.prefs.get::<UndoHistoryDepth>();
+            let prev_depth = depth;
+            pierro::DragValue::new(&mut depth)
+                .with_max(1000)
+                .render(ui);
+            if depth != prev_depth {
+                systems.prefs.set::<UndoHistoryDepth>(&depth);
+            }
+        });
+    });
+    pierro::label(ui, "0 turns off saving the undo history in project files.");
+
+}
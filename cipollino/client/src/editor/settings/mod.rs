@@ -9,10 +9,14 @@ pub use appearance::*;
 mod shortcuts;
 use shortcuts::*;
 
+mod history;
+use history::*;
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum SettingsTab {
     Appearance,
-    Shortcuts
+    Shortcuts,
+    History
 }
 
 pub struct SettingsWindow {
@@ -62,6 +66,7 @@ impl Window for SettingsWindow {
                 |ui| {
                     self.settings_tab_button(ui, "Appearance", SettingsTab::Appearance);
                     self.settings_tab_button(ui, "Shortcuts", SettingsTab::Shortcuts);
+                    self.settings_tab_button(ui, "History", SettingsTab::History);
                 }
             );
             pierro::v_line(ui);
@@ -71,6 +76,7 @@ impl Window for SettingsWindow {
                         match self.tab {
                             SettingsTab::Appearance => appearance(ui, ctx.systems),
                             SettingsTab::Shortcuts => shortcuts(ui, ctx.systems),
+                            SettingsTab::History => history(ui, ctx.systems),
                         }
                     });
                 });
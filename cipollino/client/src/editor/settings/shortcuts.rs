@@ -67,6 +67,7 @@ pub(super) fn shortcuts(ui: &mut pierro::UI, systems: &mut AppSystems) {
             pierro::label(ui, "Tools");
         });
         shortcut!(builder, shortcut_occurences, "Select", SelectToolShortcut);
+        shortcut!(builder, shortcut_occurences, "Node Edit", NodeToolShortcut);
         shortcut!(builder, shortcut_occurences, "Pencil", PencilToolShortcut);
         shortcut!(builder, shortcut_occurences, "Eraser", EraserToolShortcut);
         shortcut!(builder, shortcut_occurences, "Bucket", BucketToolShortcut);
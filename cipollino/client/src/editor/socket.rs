@@ -1,7 +1,7 @@
 
 use std::sync::{Arc, Mutex};
 
-use project::Message;
+use project::{Credentials, Message, COMPRESSION_THRESHOLD};
 
 #[derive(PartialEq, Eq)]
 enum SocketState {
@@ -11,6 +11,9 @@ enum SocketState {
 }
 
 pub struct Socket {
+    url: String,
+    /// The credentials used to log in, kept so we can log in again when reconnecting
+    credentials: Option<Credentials>,
     sender: ewebsock::WsSender,
     state: Arc<Mutex<SocketState>>,
     error: Arc<Mutex<Option<String>>>,
@@ -21,7 +24,7 @@ pub struct Socket {
 
 impl Socket {
 
-    pub fn new(url: &str) -> Result<Self, String> {
+    pub fn new(url: &str, credentials: Option<Credentials>) -> Result<Self, String> {
         let (sender, receiver) = ewebsock::connect(url, ewebsock::Options::default())?;        
 
         let state = Arc::new(Mutex::new(SocketState::None));
@@ -64,6 +67,8 @@ impl Socket {
         });
 
         Ok(Self {
+            url: url.to_owned(),
+            credentials,
             sender,
             state: state_copy,
             error: error_copy,
@@ -87,11 +92,19 @@ impl Socket {
     }
 
     pub fn send_data(&mut self, data: project::alisa::ABFValue) {
-        let data = alisa::encode_abf(&data);
+        let data = alisa::encode_abf_compressed(&data, COMPRESSION_THRESHOLD);
         let msg = ewebsock::WsMessage::Binary(data);
         self.sender.send(msg);
     }
 
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn credentials(&self) -> Option<&Credentials> {
+        self.credentials.as_ref()
+    }
+
     pub fn opened(&self) -> bool {
         *self.state.lock().unwrap() == SocketState::Opened
     }
@@ -1,7 +1,7 @@
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use project::SceneObjPtr;
+use project::{Fill, Ptr, SceneObjPtr, Stroke};
 
 
 /// The state of the scene edit preview.
@@ -26,6 +26,13 @@ pub struct ScenePreview {
     /// Hide some objects in the scene
     pub hide: HashSet<SceneObjPtr>,
 
+    /// Meshes rendered in place of hidden strokes, using the stroke's color and brush.
+    /// Used to show what is left of strokes being cut by the eraser, or strokes being reshaped by the node tool.
+    pub stroke_replacements: HashMap<Ptr<Stroke>, Vec<malvina::StrokeMesh>>,
+
+    /// Meshes rendered in place of hidden fills, using the fill's color
+    pub fill_replacements: HashMap<Ptr<Fill>, malvina::FillMesh>,
+
     /// By default, previews should dissapear unless they are explicitly requested.
     /// At the end of the frame, if this flag is false, all previews will be removed.
     pub keep_preview: bool
@@ -39,6 +46,8 @@ impl ScenePreview {
             stroke_preview: None,
             fill_preview: None,
             hide: HashSet::new(),
+            stroke_replacements: HashMap::new(),
+            fill_replacements: HashMap::new(),
             keep_preview: false,
         }
     }
@@ -48,6 +57,8 @@ impl ScenePreview {
             self.stroke_preview = None;
             self.fill_preview = None;
             self.hide.clear();
+            self.stroke_replacements.clear();
+            self.fill_replacements.clear();
             self.selection_transform = elic::Mat4::IDENTITY;
         }
         self.keep_preview = false;
@@ -1,8 +1,27 @@
 
 use project::ActionContext;
 
+use crate::UserPref;
+
 use super::{Editor, EditorState};
 
+/// The number of actions kept in the undo/redo history saved in the project file.
+/// 0 turns the persistent history off.
+pub struct UndoHistoryDepth;
+
+impl UserPref for UndoHistoryDepth {
+    type Type = u32;
+
+    fn default() -> Self::Type {
+        0
+    }
+
+    fn name() -> &'static str {
+        "undo_history_depth"
+    }
+
+}
+
 impl EditorState {
 
     pub fn action_context<S: Into<String>>(&self, name: S) -> ActionContext {
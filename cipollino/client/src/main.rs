@@ -28,6 +28,7 @@ use export::*;
 
 use std::path::PathBuf;
 use clap::Parser;
+use project::{ConnectMessage, Credentials};
 use splash::SplashScreen;
 
 pub enum AppState {
@@ -69,7 +70,10 @@ struct Args {
     #[arg(long)]
     project: Option<PathBuf>,
     #[arg(long)]
-    url: Option<String>
+    url: Option<String>,
+    /// The access token to log in with, for servers that require it
+    #[arg(long)]
+    token: Option<String>
 }
 
 fn main() {
@@ -88,7 +92,12 @@ fn main() {
         }
     } else if let Some(url) = args.url {
 
-        let mut socket = Socket::new(url.as_str()).unwrap(); 
+        let credentials = args.token.map(Credentials::Token);
+        let mut socket = Socket::new(url.as_str(), credentials.clone()).unwrap(); 
+        socket.send_data(alisa::serialize(&ConnectMessage {
+            resume: None,
+            credentials
+        }));
 
         let mut welcome_msg = None;
         while welcome_msg.is_none() {
@@ -99,7 +108,7 @@ fn main() {
         }
         let welcome_msg = welcome_msg.unwrap();
 
-        let editor = Editor::collab(socket, &welcome_msg, &mut systems).expect("invalid server protocol");
+        let editor = Editor::collab(socket, &welcome_msg, &mut systems).expect("could not join collab session");
 
         App {
             state: AppState::Editor(editor),
@@ -17,6 +17,9 @@ pub use tool_settings::*;
 mod colors;
 pub use colors::*;
 
+mod motion;
+pub use motion::*;
+
 #[cfg(debug_assertions)]
 mod debug;
 #[cfg(debug_assertions)]
@@ -28,6 +31,7 @@ pub const PANEL_KINDS: &'static [PanelKind] = &[
     PanelKind::of::<ScenePanel>(),
     PanelKind::of::<ToolSettings>(),
     PanelKind::of::<ColorsPanel>(),
+    PanelKind::of::<MotionPanel>(),
 
     #[cfg(debug_assertions)]
     PanelKind::of::<DebugPanel>()
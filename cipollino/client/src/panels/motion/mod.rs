@@ -0,0 +1,203 @@
+
+use project::{Action, Client, ClipInner, CreateMotionKey, DeleteMotionKey, Easing, MotionKey, MotionKeyTreeData, Ptr, SetMotionKeyEasing, SetMotionKeyOpacity, SetMotionKeyPosition, SetMotionKeyRotation, SetMotionKeyScale, SetMotionKeyTime};
+
+use crate::EditorState;
+
+use super::{Panel, PanelContext};
+
+#[derive(Default)]
+pub struct MotionPanel {
+    /// The values of the key being edited, shown until the edit is applied
+    preview: Option<(Ptr<MotionKey>, MotionKey)>
+}
+
+impl MotionPanel {
+
+    /// Add a motion key to the active layer at the current frame, keeping the layer where it currently is
+    pub fn add_key(client: &Client, editor: &mut EditorState, clip: &ClipInner) {
+        if editor.locked_layers.contains(&editor.active_layer) {
+            return;
+        }
+        let Some(layer) = client.get(editor.active_layer) else { return; };
+        let time = clip.frame_idx(editor.time);
+        if layer.motion_key_at(client, time).is_some() {
+            return;
+        }
+
+        editor.playing = false;
+        let motion = layer.motion_at(client, time);
+        client.queue_action(Action::single(editor.action_context("New Motion Key"), CreateMotionKey {
+            ptr: client.next_ptr(),
+            parent: editor.active_layer,
+            idx: (),
+            data: MotionKeyTreeData {
+                time,
+                position: motion.position.into(),
+                rotation: motion.rotation,
+                scale: motion.scale.into(),
+                opacity: motion.opacity,
+                easing: Easing::default()
+            },
+        }));
+    }
+
+    fn key_settings(&mut self, ui: &mut pierro::UI, client: &Client, editor: &mut EditorState, clip: &ClipInner, key_ptr: Ptr<MotionKey>, key: &MotionKey) {
+        let mut values = match &self.preview {
+            Some((preview_ptr, preview)) if *preview_ptr == key_ptr => preview.clone(),
+            _ => key.clone()
+        };
+        let mut editing = false;
+
+        pierro::key_value_layout(ui, |builder| {
+            builder.labeled("Frame:", |ui| {
+                let resp = pierro::DragValue::new(&mut values.time)
+                    .with_min(0)
+                    .with_max(clip.length as i32 - 1)
+                    .render(ui);
+                editing |= resp.editing;
+                let time_free = client.get(key.layer)
+                    .map(|layer| layer.motion_key_at(client, values.time).is_none())
+                    .unwrap_or(false);
+                if resp.done_editing && time_free {
+                    client.queue_action(Action::single(editor.action_context("Set Motion Key Frame"), SetMotionKeyTime {
+                        ptr: key_ptr,
+                        time_value: values.time
+                    }));
+                    editor.jump_to((values.time as f32 + 0.5) * clip.frame_len());
+                }
+            });
+            builder.labeled("Position:", |ui| {
+                pierro::horizontal_fit_centered(ui, |ui| {
+                    let x_resp = pierro::DragValue::new(&mut values.position[0]).render(ui);
+                    pierro::h_spacing(ui, 3.0);
+                    let y_resp = pierro::DragValue::new(&mut values.position[1]).render(ui);
+                    editing |= x_resp.editing || y_resp.editing;
+                    if x_resp.done_editing || y_resp.done_editing {
+                        client.queue_action(Action::single(editor.action_context("Set Motion Key Position"), SetMotionKeyPosition {
+                            ptr: key_ptr,
+                            position_value: values.position
+                        }));
+                    }
+                });
+            });
+            builder.labeled("Rotation:", |ui| {
+                let resp = pierro::DragValue::new(&mut values.rotation).render(ui);
+                editing |= resp.editing;
+                if resp.done_editing {
+                    client.queue_action(Action::single(editor.action_context("Set Motion Key Rotation"), SetMotionKeyRotation {
+                        ptr: key_ptr,
+                        rotation_value: values.rotation
+                    }));
+                }
+            });
+            builder.labeled("Scale:", |ui| {
+                pierro::horizontal_fit_centered(ui, |ui| {
+                    let x_resp = pierro::DragValue::new(&mut values.scale[0]).render(ui);
+                    pierro::h_spacing(ui, 3.0);
+                    let y_resp = pierro::DragValue::new(&mut values.scale[1]).render(ui);
+                    editing |= x_resp.editing || y_resp.editing;
+                    if x_resp.done_editing || y_resp.done_editing {
+                        client.queue_action(Action::single(editor.action_context("Set Motion Key Scale"), SetMotionKeyScale {
+                            ptr: key_ptr,
+                            scale_value: values.scale
+                        }));
+                    }
+                });
+            });
+            builder.labeled("Opacity:", |ui| {
+                let resp = pierro::DragValue::new(&mut values.opacity)
+                    .with_min(0.0)
+                    .with_max(1.0)
+                    .render(ui);
+                editing |= resp.editing;
+                if resp.done_editing {
+                    client.queue_action(Action::single(editor.action_context("Set Motion Key Opacity"), SetMotionKeyOpacity {
+                        ptr: key_ptr,
+                        opacity_value: values.opacity
+                    }));
+                }
+            });
+            builder.labeled("Easing:", |ui| {
+                pierro::dropdown(ui, key.easing.name(), |ui| {
+                    for easing in Easing::ALL {
+                        if pierro::menu_button(ui, easing.name()).mouse_clicked() && easing != key.easing {
+                            client.queue_action(Action::single(editor.action_context("Set Motion Key Easing"), SetMotionKeyEasing {
+                                ptr: key_ptr,
+                                easing_value: easing
+                            }));
+                        }
+                    }
+                });
+            });
+            builder.labeled("", |ui| {
+                if pierro::button(ui, "Delete Key").mouse_clicked() {
+                    client.queue_action(Action::single(editor.action_context("Delete Motion Key"), DeleteMotionKey {
+                        ptr: key_ptr
+                    }));
+                }
+            });
+        });
+
+        self.preview = if editing {
+            Some((key_ptr, values))
+        } else {
+            None
+        };
+    }
+
+}
+
+impl Panel for MotionPanel {
+    const NAME: &'static str = "Motion";
+
+    fn title(&self) -> String {
+        "Motion".to_owned()
+    }
+
+    fn render(&mut self, ui: &mut pierro::UI, context: &mut PanelContext) {
+        let project = &context.project;
+        let editor = &mut context.editor;
+
+        let Some(clip) = project.client.get(editor.open_clip) else {
+            pierro::centered(ui, |ui| {
+                pierro::label(ui, "No clip open.");
+            });
+            return;
+        };
+        let Some(clip_inner) = project.client.get(clip.inner) else {
+            pierro::centered(ui, |ui| {
+                pierro::label(ui, "Clip loading...");
+            });
+            return;
+        };
+        let Some(layer) = project.client.get(editor.active_layer) else {
+            pierro::centered(ui, |ui| {
+                pierro::label(ui, "No layer selected.");
+            });
+            return;
+        };
+
+        let time = clip_inner.frame_idx(editor.time);
+        let key = layer.motion_key_at(&project.client, time)
+            .and_then(|key_ptr| Some((key_ptr, project.client.get(key_ptr)?)));
+
+        pierro::scroll_area(ui, |ui| {
+            pierro::margin(ui, pierro::Margin::same(3.0), |ui| {
+                match key {
+                    Some((key_ptr, key)) => {
+                        self.key_settings(ui, &project.client, editor, clip_inner, key_ptr, key);
+                    },
+                    None => {
+                        self.preview = None;
+                        pierro::label(ui, "No motion key on this frame.");
+                        pierro::v_spacing(ui, 3.0);
+                        if pierro::button(ui, "Add Key").mouse_clicked() {
+                            Self::add_key(&project.client, editor, clip_inner);
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+}
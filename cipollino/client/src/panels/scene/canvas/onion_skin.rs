@@ -7,11 +7,11 @@ impl ScenePanel {
 
     fn render_onion_skin_frame(rndr: &mut malvina::LayerRenderer, client: &Client, editor: &EditorState, clip: &ClipInner, time: i32, color: elic::Color) {
         let render_list = SceneRenderList::make(client, editor, clip, time);
-        for scene_obj in render_list.objs {
+        for (scene_obj, transform) in render_list.objs.into_iter().zip(render_list.transforms) {
             match scene_obj {
                 SceneObjPtr::Stroke(stroke_ptr) => {
                     if let Some(stroke) = editor.mesh_cache.get_stroke(stroke_ptr) {
-                        rndr.render_stroke(&stroke.mesh, color, editor.scene_obj_transform(stroke_ptr), None);
+                        rndr.render_stroke(&stroke.mesh, color, transform * editor.scene_obj_transform(stroke_ptr), None);
                     }
                 },
                 SceneObjPtr::Fill(_fill_ptr) => {} // Fills shouldn't be rendered in the onion skin
@@ -13,12 +13,12 @@ impl ScenePanel {
             match scene_obj {
                 SceneObjPtr::Stroke(stroke_ptr) => {
                     if let Some(stroke) = editor.mesh_cache.get_stroke(*stroke_ptr) {
-                        rndr.render_stroke(&stroke.mesh, idx as u32 + 1, editor.scene_obj_transform(*stroke_ptr), None);
+                        rndr.render_stroke(&stroke.mesh, idx as u32 + 1, render_list.transforms[idx] * editor.scene_obj_transform(*stroke_ptr), None);
                     }
                 },
                 SceneObjPtr::Fill(fill_ptr) => {
                     if let Some(fill) = editor.mesh_cache.get_fill(*fill_ptr) {
-                        rndr.render_fill(&fill.mesh, idx as u32 + 1, editor.scene_obj_transform(*fill_ptr));
+                        rndr.render_fill(&fill.mesh, idx as u32 + 1, render_list.transforms[idx] * editor.scene_obj_transform(*fill_ptr));
                     }
                 }
             }
@@ -5,7 +5,7 @@ use crate::{get_brush_texture, get_color_value, BuiltinBrushTextures, EditorStat
 impl ScenePanel {
     
     pub(super) fn render_selection(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &EditorState, render_list: &SceneRenderList) {
-        for scene_obj in render_list.objs.iter() {
+        for (scene_obj, transform) in render_list.objs.iter().zip(render_list.transforms.iter()) {
             match scene_obj {
                 SceneObjPtr::Stroke(stroke_ptr) => {
                     if !editor.selection.selected(*stroke_ptr) {
@@ -13,14 +13,14 @@ impl ScenePanel {
                     }
                     let Some(stroke) = editor.mesh_cache.get_stroke(*stroke_ptr) else { continue; };
                     let texture = get_brush_texture(stroke.brush, brushes);
-                    rndr.render_stroke_selection(&stroke.mesh, get_color_value(&stroke.color, client), editor.scene_obj_transform(*stroke_ptr), Some(texture));
+                    rndr.render_stroke_selection(&stroke.mesh, get_color_value(&stroke.color, client), *transform * editor.scene_obj_transform(*stroke_ptr), Some(texture));
                 },
                 SceneObjPtr::Fill(fill_ptr) => {
                     if !editor.selection.selected(*fill_ptr) {
                         continue;
                     }
                     let Some(fill) = editor.mesh_cache.get_fill(*fill_ptr) else { continue; }; 
-                    rndr.render_fill_selection(&fill.mesh, get_color_value(&fill.color, client), editor.scene_obj_transform(*fill_ptr));
+                    rndr.render_fill_selection(&fill.mesh, get_color_value(&fill.color, client), *transform * editor.scene_obj_transform(*fill_ptr));
                 }
             }
         }
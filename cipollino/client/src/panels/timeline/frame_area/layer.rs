@@ -1,7 +1,7 @@
 
 use std::f32;
 
-use project::{Action, ClipInner, CreateFrame, FrameTreeData, Layer, Ptr};
+use project::{Action, ClipInner, CreateFrame, Easing, FrameTreeData, Layer, Ptr};
 
 use crate::{EditorState, ProjectState, TimelinePanel};
 
@@ -58,6 +58,51 @@ impl FrameDot {
 
 }
 
+/// A motion key, drawn as a small square along the bottom of its layer
+pub(super) struct MotionKeyMarker {
+    pub layer_idx: usize,
+    pub time: i32,
+    /// The time of the next key, if the layer moves between this key and the next one
+    pub tween_end: Option<i32>,
+    pub current: bool
+}
+
+impl MotionKeyMarker {
+
+    const SIZE: f32 = 5.0;
+
+    fn rect(layer_idx: usize, time: i32) -> pierro::Rect {
+        pierro::Rect::center_size(
+            TimelinePanel::FRAME_SIZE * pierro::vec2(time as f32 + 0.5, layer_idx as f32 + 1.0) - pierro::Vec2::Y * (Self::SIZE * 0.5 + 1.0),
+            pierro::Vec2::splat(Self::SIZE)
+        )
+    }
+
+    pub fn paint(self, painter: &mut pierro::Painter, rect: pierro::Rect, text_color: pierro::Color, accent_color: pierro::Color) {
+        let marker_rect = Self::rect(self.layer_idx, self.time).shift(rect.tl());
+
+        if let Some(tween_end) = self.tween_end {
+            let end_rect = Self::rect(self.layer_idx, tween_end).shift(rect.tl());
+            let line_rect = pierro::Rect::min_max(
+                pierro::vec2(marker_rect.center().x, marker_rect.center().y - 0.5),
+                pierro::vec2(end_rect.center().x, end_rect.center().y + 0.5)
+            );
+            painter.rect(pierro::PaintRect::new(line_rect, text_color.with_alpha(0.5)));
+        }
+
+        let color = if self.current {
+            accent_color
+        } else {
+            text_color
+        };
+        painter.rect(
+            pierro::PaintRect::new(marker_rect, color)
+                .with_rounding(pierro::Rounding::same(1.0))
+        );
+    }
+
+}
+
 impl FrameArea {
     
     pub(super) fn drag_to_frame_offset(drag: f32) -> i32 {
@@ -79,8 +124,41 @@ impl FrameArea {
 
         let layer_editable = !editor.locked_layers.contains(&layer_ptr);
 
+        // Motion keys
+        let curr_frame = clip.frame_idx(editor.time);
+        let mut motion_keys: Vec<(i32, Easing)> = layer.motion_keys.iter()
+            .filter_map(|key_ptr| project.client.get(key_ptr.ptr()))
+            .map(|key| (key.time, key.easing))
+            .collect();
+        motion_keys.sort_by_key(|(time, _)| *time);
+        let mut mouse_over_motion_key = false;
+        for i in 0..motion_keys.len() {
+            let (time, easing) = motion_keys[i];
+            let tween_end = motion_keys.get(i + 1)
+                .filter(|_| easing != Easing::Hold)
+                .map(|(next_time, _)| *next_time);
+
+            if let Some(mouse_pos) = frame_area.mouse_pos(ui) {
+                let interaction_rect = pierro::Margin::same(2.0).grow(MotionKeyMarker::rect(layer_idx, time));
+                if interaction_rect.contains(mouse_pos) {
+                    mouse_over_motion_key = true;
+                    if frame_area.mouse_clicked() {
+                        editor.active_layer = layer_ptr;
+                        editor.jump_to((time as f32 + 0.5) * clip.frame_len());
+                    }
+                }
+            }
+
+            paint_commands.motion_keys.push(MotionKeyMarker {
+                layer_idx,
+                time,
+                tween_end,
+                current: time == curr_frame
+            });
+        }
+
         let mut frames_to_render = Vec::new();
-        let mut mouse_over_frame = false;
+        let mut mouse_over_frame = mouse_over_motion_key;
         for frame_ptr in layer.frames.iter() {
             if let Some(frame) = project.client.get(frame_ptr) {
 
@@ -106,7 +184,7 @@ impl FrameArea {
 
                 frames_to_render.push((display_time, frame.scene.as_slice().is_empty(), layer_editable && (selected || in_selection_rect)));
 
-                if let Some(mouse_pos) = frame_area.mouse_pos(ui) {
+                if let Some(mouse_pos) = frame_area.mouse_pos(ui).filter(|_| !mouse_over_motion_key) {
                     if frame_interaction_rect.contains(mouse_pos) {
                         mouse_over_frame = true;
                         if frame_area.mouse_clicked() {
@@ -4,7 +4,7 @@ use project::ClipInner;
 
 use crate::{EditorState, LayerRenderList, ProjectState, RenderLayerKind};
 
-use super::TimelinePanel;
+use super::{InbetweenDialog, TimelinePanel};
 
 mod paint;
 mod layer;
@@ -119,6 +119,13 @@ impl FrameArea {
             self.drag_stopped(project, editor, clip, render_list);
         }
 
+        pierro::context_menu(ui, &frame_area, |ui| {
+            if pierro::menu_button(ui, "Inbetween...").mouse_clicked() {
+                editor.open_window(InbetweenDialog::new(&project.client, &editor.selection));
+                pierro::close_context_menu(ui, frame_area.id);
+            }
+        });
+
         // Painting the frame area contents 
         let n_layers = render_list.len();
         let clip_length = clip.length;
@@ -1,14 +1,15 @@
 
 use crate::{panels::timeline::frame_area::audio::AudioInstanceBar, TimelinePanel};
 
-use super::{layer::FrameDot, FrameArea};
+use super::{layer::{FrameDot, MotionKeyMarker}, FrameArea};
 
-/// Commands for painting frame dots, audio clips, etc in the timeline's frame area.
+/// Commands for painting frame dots, motion keys, audio clips, etc in the timeline's frame area.
 /// This is necessary because painting happens after the UI tree is constructed,
 /// so we can't use any borrowed data in the paint callback. Using a command
 /// queue gets around this problem.
 pub(super) struct PaintCommands {
     pub frame_dots: Vec<FrameDot>,
+    pub motion_keys: Vec<MotionKeyMarker>,
     pub audio_bars: Vec<AudioInstanceBar>
 }
 
@@ -17,6 +18,7 @@ impl PaintCommands {
     pub fn new() -> Self {
         Self {
             frame_dots: Vec::new(),
+            motion_keys: Vec::new(),
             audio_bars: Vec::new()
         }
     }
@@ -25,6 +27,9 @@ impl PaintCommands {
         for frame_dot in self.frame_dots {
             frame_dot.paint(painter, rect, text_color, accent_color);
         }
+        for motion_key in self.motion_keys {
+            motion_key.paint(painter, rect, text_color, accent_color);
+        }
         for audio_bar in self.audio_bars {
             audio_bar.paint(painter, rect, framerate, accent_color);
         }
@@ -1,7 +1,7 @@
 
 use project::{Action, AudioLayerTreeData, Clip, ClipInner, CreateAudioLayer, CreateFrame, CreateLayer, CreateLayerGroup, FrameTreeData, LayerGroupTreeData, LayerParent, LayerTreeData, Ptr, SetClipInnerLength};
 
-use crate::{EditorState, ProjectState};
+use crate::{EditorState, MotionPanel, ProjectState};
 
 use super::TimelinePanel;
 
@@ -100,6 +100,11 @@ impl TimelinePanel {
             ui.pop_style();
         }
 
+        // Add motion key
+        if pierro::icon_button(ui, pierro::icons::DIAMOND).mouse_clicked() {
+            MotionPanel::add_key(&project.client, editor, clip);
+        }
+
         // Play buttons
         pierro::centered_horizontal(ui, |ui| {
             ui.with_style::<pierro::theme::WidgetStroke, _, _>(pierro::Stroke::new(divider_color, widget_stroke.width), |ui| {
@@ -0,0 +1,233 @@
+
+use project::{alisa, Action, Client, CreateFrame, Frame, FrameTreeData, Ptr, SceneObjPtr, SceneObjPtrTreeData, Stroke};
+
+use crate::{PanelContext, Selection, Window};
+
+mod strokes;
+use strokes::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StrokeMatching {
+    /// Match the strokes of the two frames in the order they appear in the frames
+    ByOrder,
+    /// Match strokes paired up by the user
+    Custom
+}
+
+impl StrokeMatching {
+
+    fn name(&self) -> &'static str {
+        match self {
+            StrokeMatching::ByOrder => "By Order",
+            StrokeMatching::Custom => "Custom",
+        }
+    }
+
+}
+
+/// Dialog for generating inbetween frames between two selected frames on the same layer
+pub struct InbetweenDialog {
+    /// The start and end frames, if the selection was valid when the dialog was opened
+    frames: Option<(Ptr<Frame>, Ptr<Frame>)>,
+    count: u32,
+    matching: StrokeMatching,
+    /// Stroke pairs chosen by the user, from the start frame to the end frame
+    pairs: Vec<(Ptr<Stroke>, Ptr<Stroke>)>,
+    /// A stroke on the start frame waiting to be paired with a stroke on the end frame
+    pending_start: Option<Ptr<Stroke>>
+}
+
+fn frame_strokes(frame: &Frame) -> impl Iterator<Item = Ptr<Stroke>> + '_ {
+    frame.scene.iter().filter_map(|obj| match obj {
+        SceneObjPtr::Stroke(stroke) => Some(stroke),
+        _ => None
+    })
+}
+
+impl InbetweenDialog {
+
+    pub fn new(client: &Client, selection: &Selection) -> Self {
+        let frames: Vec<(Ptr<Frame>, &Frame)> = selection.iter::<Frame>()
+            .filter_map(|ptr| Some((ptr, client.get(ptr)?)))
+            .collect();
+        let frames = match frames.as_slice() {
+            [(a_ptr, a), (b_ptr, b)] if a.layer == b.layer && a.time != b.time => {
+                if a.time < b.time {
+                    Some((*a_ptr, *b_ptr))
+                } else {
+                    Some((*b_ptr, *a_ptr))
+                }
+            },
+            _ => None
+        };
+        Self {
+            frames,
+            count: 1,
+            matching: StrokeMatching::ByOrder,
+            pairs: Vec::new(),
+            pending_start: None
+        }
+    }
+
+    /// The pairs of strokes to interpolate between, in the order of the strokes on the start frame
+    fn stroke_pairs(&self, start: &Frame, end: &Frame) -> Vec<(Ptr<Stroke>, Ptr<Stroke>)> {
+        match self.matching {
+            StrokeMatching::ByOrder => frame_strokes(start).zip(frame_strokes(end)).collect(),
+            StrokeMatching::Custom => frame_strokes(start).filter_map(|start_stroke| {
+                self.pairs.iter()
+                    .find(|(pair_start, pair_end)| *pair_start == start_stroke && frame_strokes(end).any(|stroke| stroke == *pair_end))
+                    .copied()
+            }).collect()
+        }
+    }
+
+    /// Pair up the selected stroke.
+    /// A stroke on the start frame is held until a stroke on the end frame is selected to pair it with.
+    fn pair_selected(&mut self, client: &Client, selection: &Selection, start_ptr: Ptr<Frame>, end_ptr: Ptr<Frame>) {
+        for stroke_ptr in selection.iter::<Stroke>() {
+            let Some(stroke) = client.get(stroke_ptr) else { continue; };
+            if stroke.frame == start_ptr {
+                self.pending_start = Some(stroke_ptr);
+                return;
+            }
+            if stroke.frame == end_ptr {
+                if let Some(pending_start) = self.pending_start.take() {
+                    self.pairs.retain(|(pair_start, pair_end)| *pair_start != pending_start && *pair_end != stroke_ptr);
+                    self.pairs.push((pending_start, stroke_ptr));
+                }
+                return;
+            }
+        }
+    }
+
+    /// Add a frame of interpolated strokes at each of the given times to an action
+    fn create(client: &Client, action: &mut Action, start: &Frame, end: &Frame, pairs: &[(Ptr<Stroke>, Ptr<Stroke>)], times: &[i32]) {
+        let gap = end.time - start.time;
+        for time in times {
+            let t = (time - start.time) as f32 / gap as f32;
+            let children = pairs.iter().filter_map(|(start_stroke, end_stroke)| {
+                let start_stroke = client.get(*start_stroke)?;
+                let end_stroke = client.get(*end_stroke)?;
+                let data = inbetween_stroke(start_stroke, end_stroke, t)?;
+                let ptr = Ptr::from_key(client.next_key());
+                Some((SceneObjPtr::Stroke(ptr), SceneObjPtrTreeData::Stroke(ptr, data)))
+            }).collect();
+            action.push(CreateFrame {
+                ptr: client.next_ptr(),
+                layer: start.layer,
+                data: FrameTreeData {
+                    time: *time,
+                    scene: alisa::ChildListTreeData {
+                        children
+                    },
+                },
+            });
+        }
+    }
+
+}
+
+impl Window for InbetweenDialog {
+
+    fn title(&self) -> String {
+        "Inbetween Frames".to_owned()
+    }
+
+    fn render<'ctx>(&mut self, ui: &mut pierro::UI, close: &mut bool, ctx: &mut PanelContext<'ctx>) {
+        let client = &ctx.project.client;
+
+        let frames = self.frames.and_then(|(start_ptr, end_ptr)| Some((start_ptr, client.get(start_ptr)?, end_ptr, client.get(end_ptr)?)));
+        let Some((start_ptr, start, end_ptr, end)) = frames else {
+            pierro::label(ui, "Select two frames on the same layer to inbetween.");
+            pierro::v_spacing(ui, 3.0);
+            if pierro::button(ui, "Ok").mouse_clicked() {
+                *close = true;
+            }
+            return;
+        };
+        let Some(layer) = client.get(start.layer) else { return; };
+
+        let gap = end.time - start.time;
+        if gap < 2 {
+            pierro::label(ui, "There is no room for inbetweens between the selected frames.");
+            pierro::v_spacing(ui, 3.0);
+            if pierro::button(ui, "Ok").mouse_clicked() {
+                *close = true;
+            }
+            return;
+        }
+        self.count = self.count.clamp(1, gap as u32 - 1);
+
+        pierro::key_value_layout(ui, |builder| {
+            builder.labeled("Inbetweens:", |ui| {
+                pierro::DragValue::new(&mut self.count)
+                    .with_min(1)
+                    .with_max(gap as u32 - 1)
+                    .render(ui);
+            });
+            builder.labeled("Match Strokes:", |ui| {
+                pierro::dropdown(ui, self.matching.name(), |ui| {
+                    for matching in [StrokeMatching::ByOrder, StrokeMatching::Custom] {
+                        if pierro::menu_button(ui, matching.name()).mouse_clicked() {
+                            self.matching = matching;
+                        }
+                    }
+                });
+            });
+        });
+
+        if self.matching == StrokeMatching::Custom {
+            pierro::v_spacing(ui, 5.0);
+            pierro::label(ui, "Select a stroke on the first frame, then a stroke on the last frame, pairing each one.");
+            pierro::v_spacing(ui, 3.0);
+            pierro::horizontal_fit_centered(ui, |ui| {
+                if pierro::button(ui, "Pair Selected Stroke").mouse_clicked() {
+                    self.pair_selected(client, &ctx.editor.selection, start_ptr, end_ptr);
+                }
+                pierro::h_spacing(ui, 3.0);
+                if pierro::button(ui, "Clear Pairs").mouse_clicked() {
+                    self.pairs.clear();
+                    self.pending_start = None;
+                }
+            });
+            pierro::v_spacing(ui, 3.0);
+            if self.pending_start.is_some() {
+                pierro::label(ui, "Waiting for a stroke on the last frame...");
+            }
+        }
+
+        let pairs = self.stroke_pairs(start, end);
+        let n_strokes = frame_strokes(start).count().max(frame_strokes(end).count());
+        let times: Vec<i32> = (1..=self.count as i32)
+            .map(|k| start.time + ((k * gap) as f32 / (self.count + 1) as f32).round() as i32)
+            .filter(|time| layer.frame_exactly_at(client, *time).is_none())
+            .collect();
+
+        pierro::v_spacing(ui, 5.0);
+        pierro::label(ui, format!("{} of {} strokes matched.", pairs.len(), n_strokes));
+        if times.len() < self.count as usize {
+            pierro::label(ui, format!("{} frames already exist and will be skipped.", self.count as usize - times.len()));
+        }
+        let locked = ctx.editor.locked_layers.contains(&start.layer);
+        if locked {
+            pierro::label(ui, "The layer is locked.");
+        }
+
+        pierro::v_spacing(ui, 5.0);
+        pierro::vertical_centered(ui, |ui| {
+            if pierro::button(ui, "Create").mouse_clicked() {
+                if !locked && !times.is_empty() {
+                    let mut action = Action::new(ctx.editor.action_context("Inbetween Frames"));
+                    Self::create(client, &mut action, start, end, &pairs, &times);
+                    client.queue_action(action);
+                }
+                *close = true;
+            }
+        });
+    }
+
+    fn unique(&self) -> bool {
+        true
+    }
+
+}
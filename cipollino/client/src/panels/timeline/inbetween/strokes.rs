@@ -0,0 +1,15 @@
+
+use project::{Stroke, StrokeData, StrokeTreeData};
+
+/// Interpolate between two strokes, `t` of the way from `start` to `end`.
+/// The stroke's points, pressure and width are interpolated, while the color and brush are taken from `start`.
+pub(super) fn inbetween_stroke(start: &Stroke, end: &Stroke, t: f32) -> Option<StrokeTreeData> {
+    let (start_path, end_path) = start.stroke.0.path.match_points(&end.stroke.0.path, |pt| pt.pt);
+    let path = start_path.lerp(&end_path, t)?;
+    Some(StrokeTreeData {
+        stroke: StrokeData(malvina::Stroke { path }),
+        color: start.color,
+        width: start.width + (end.width - start.width) * t,
+        brush: start.brush
+    })
+}
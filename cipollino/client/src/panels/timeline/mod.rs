@@ -12,6 +12,8 @@ mod header;
 mod framebar;
 mod layers;
 mod frame_area;
+mod inbetween;
+use inbetween::InbetweenDialog;
 
 pub struct TimelinePanel {
     layers_width: f32,
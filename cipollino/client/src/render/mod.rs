@@ -2,40 +2,65 @@
 mod builtin_brushes;
 pub use builtin_brushes::*;
 
-use project::{Client, ClipInner, Fill, Frame, Layer, LayerPtr, Ptr, SceneObjPtr, Stroke};
+use project::{Client, ClipInner, Fill, Frame, Layer, LayerPtr, MotionState, Ptr, SceneObjPtr, Stroke};
 use crate::{get_brush_texture, get_color_value, EditorState};
 
-fn render_stroke(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &mut EditorState, stroke_ptr: Ptr<Stroke>) {
+/// The color of an object on a layer with the given motion
+fn motion_color(color: elic::Color, motion: &MotionState) -> elic::Color {
+    color.with_alpha(color.a * motion.opacity.clamp(0.0, 1.0))
+}
+
+fn render_stroke(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &mut EditorState, stroke_ptr: Ptr<Stroke>, motion: &MotionState) {
     if editor.mesh_cache.get_stroke(stroke_ptr).is_none() {
         editor.mesh_cache.calculate_stroke_mesh(stroke_ptr, client, rndr.device());
     }
 
     let Some(stroke_mesh) = editor.mesh_cache.get_stroke(stroke_ptr) else { return; };
     let texture = get_brush_texture(stroke_mesh.brush, brushes);
-    rndr.render_stroke(&stroke_mesh.mesh, get_color_value(&stroke_mesh.color, client), editor.scene_obj_transform(stroke_ptr), Some(texture));
+    rndr.render_stroke(&stroke_mesh.mesh, motion_color(get_color_value(&stroke_mesh.color, client), motion), motion.transform() * editor.scene_obj_transform(stroke_ptr), Some(texture));
 }
 
-fn render_fill(rndr: &mut malvina::LayerRenderer, client: &Client, editor: &mut EditorState, fill_ptr: Ptr<Fill>) {
+fn render_fill(rndr: &mut malvina::LayerRenderer, client: &Client, editor: &mut EditorState, fill_ptr: Ptr<Fill>, motion: &MotionState) {
     if editor.mesh_cache.get_fill(fill_ptr).is_none() {
         editor.mesh_cache.calculate_fill_mesh(fill_ptr, client, rndr.device());
     }
 
     if let Some(fill) = editor.mesh_cache.get_fill(fill_ptr) {
-        rndr.render_fill(&fill.mesh, get_color_value(&fill.color, client), editor.scene_obj_transform(fill_ptr)); 
+        rndr.render_fill(&fill.mesh, motion_color(get_color_value(&fill.color, client), motion), motion.transform() * editor.scene_obj_transform(fill_ptr)); 
+    }
+}
+
+/// Render the preview shown in place of a hidden object, if there is one
+fn render_replacement(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &EditorState, scene_obj: SceneObjPtr, motion: &MotionState) {
+    match scene_obj {
+        SceneObjPtr::Stroke(stroke_ptr) => {
+            let Some(meshes) = editor.preview.stroke_replacements.get(&stroke_ptr) else { return; };
+            let Some(stroke) = client.get(stroke_ptr) else { return; };
+            let texture = get_brush_texture(stroke.brush, brushes);
+            for mesh in meshes {
+                rndr.render_stroke(mesh, motion_color(get_color_value(&stroke.color, client), motion), motion.transform() * editor.scene_obj_transform(stroke_ptr), Some(texture));
+            }
+        },
+        SceneObjPtr::Fill(fill_ptr) => {
+            let Some(mesh) = editor.preview.fill_replacements.get(&fill_ptr) else { return; };
+            let Some(fill) = client.get(fill_ptr) else { return; };
+            rndr.render_fill(mesh, motion_color(get_color_value(&fill.color, client), motion), motion.transform() * editor.scene_obj_transform(fill_ptr));
+        }
     }
 }
 
-fn render_frame(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &mut EditorState, frame: &Frame, editor_view: bool) {
+fn render_frame(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTextures, client: &Client, editor: &mut EditorState, frame: &Frame, motion: &MotionState, editor_view: bool) {
     for scene_child in frame.scene.iter().rev() {
         if editor_view && editor.preview.hide.contains(&scene_child) {
+            render_replacement(rndr, brushes, client, editor, scene_child, motion);
             continue;
         }
         match scene_child {
             SceneObjPtr::Stroke(stroke_ptr) => {
-                render_stroke(rndr, brushes, client, editor, stroke_ptr);
+                render_stroke(rndr, brushes, client, editor, stroke_ptr, motion);
             },
             SceneObjPtr::Fill(fill_ptr) => {
-                render_fill(rndr, client, editor, fill_ptr);
+                render_fill(rndr, client, editor, fill_ptr, motion);
             }
         }
     }
@@ -54,7 +79,8 @@ fn render_layer(rndr: &mut malvina::LayerRenderer, brushes: &BuiltinBrushTexture
 
     if let Some(frame_ptr) = layer.frame_at(client, time) {
         if let Some(frame) = client.get(frame_ptr) {
-            render_frame(rndr, brushes, client, editor, frame, editor_view);
+            let motion = layer.motion_at(client, time);
+            render_frame(rndr, brushes, client, editor, frame, &motion, editor_view);
         }
     } 
 
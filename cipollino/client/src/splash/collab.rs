@@ -1,13 +1,25 @@
 
+use project::{ConnectMessage, ConnectionRefused, Credentials, ProjectListing};
+
 use crate::{AppState, AppSystems, Editor, Socket};
 
 use super::SplashScreenState;
 
 pub(super) struct CollabScreen {
     url: String,
+    /// Only needed for servers that require logging in
+    username: String,
+    /// The password, or an access token if no username is given
+    password: String,
     socket: Option<Socket>,
     error: String,
 
+    /// The URL of the server whose projects are listed, for servers that host a directory of projects
+    projects_url: String,
+    /// The socket used to fetch the project listing
+    listing_socket: Option<Socket>,
+    projects: Vec<ProjectListing>,
+
     connection_icon_idx: usize,
     connection_icon_timer: f32
 }
@@ -44,18 +56,122 @@ fn cleanup_connect_error_message(msg: String, url: &str) -> String {
     msg
 }
 
+/// Percent-encode a project name so it can be put in a URL path
+fn encode_project_name(name: &str) -> String {
+    let mut encoded = String::new();
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
 impl CollabScreen {
 
     pub fn new() -> Self {
         Self {
             url: String::new(),
+            username: String::new(),
+            password: String::new(),
             socket: None,
             error: String::new(),
+            projects_url: String::new(),
+            listing_socket: None,
+            projects: Vec::new(),
             connection_icon_timer: 0.0,
             connection_icon_idx: 0
         }
     }
 
+    fn credentials(&self) -> Option<Credentials> {
+        match (self.username.is_empty(), self.password.is_empty()) {
+            (_, true) => None,
+            (true, false) => Some(Credentials::Token(self.password.clone())),
+            (false, false) => Some(Credentials::Password {
+                username: self.username.clone(),
+                password: self.password.clone()
+            })
+        }
+    }
+
+    fn connect(&mut self) {
+        self.error.clear();
+        match Socket::new(self.url.as_str(), self.credentials()) {
+            Ok(mut new_socket) => {
+                new_socket.send_data(alisa::serialize(&ConnectMessage {
+                    resume: None,
+                    credentials: self.credentials()
+                }));
+                self.socket = Some(new_socket);
+            },
+            Err(msg) => {
+                self.error = cleanup_connect_error_message(msg, &self.url);
+            },
+        }
+    }
+
+    fn find_projects(&mut self) {
+        self.error.clear();
+        self.projects.clear();
+        self.projects_url = self.url.trim().trim_end_matches('/').trim_end_matches("/projects").to_owned();
+        match Socket::new(&format!("{}/projects", self.projects_url), None) {
+            Ok(mut socket) => {
+                // Servers that require logging in only list their projects to users who can log in
+                socket.send_data(alisa::serialize(&ConnectMessage {
+                    resume: None,
+                    credentials: self.credentials()
+                }));
+                self.listing_socket = Some(socket);
+            },
+            Err(msg) => self.error = cleanup_connect_error_message(msg, &self.url),
+        }
+    }
+
+    fn render_project_listing(&mut self, ui: &mut pierro::UI) {
+        if let Some(socket) = &mut self.listing_socket {
+            if let Some(data) = socket.receive() {
+                if let Some(refused) = ConnectionRefused::from_message(&data) {
+                    self.error = refused.reason;
+                } else {
+                    match alisa::deserialize::<Vec<ProjectListing>>(&data) {
+                        Some(projects) if projects.is_empty() => self.error = "The server has no projects.".to_owned(),
+                        Some(projects) => self.projects = projects,
+                        None => self.error = "Server does not host multiple projects.".to_owned(),
+                    }
+                }
+                self.listing_socket = None;
+            } else if let Some(err) = socket.take_error() {
+                self.error = cleanup_connect_error_message(err, &self.url);
+                self.listing_socket = None;
+            } else if socket.closed() {
+                self.error = "Server does not host multiple projects.".to_owned();
+                self.listing_socket = None;
+            } else {
+                ui.request_redraw();
+            }
+        }
+
+        let mut selected = None;
+        for project in &self.projects {
+            let label = match project.clients {
+                0 => project.name.clone(),
+                1 => format!("{} (1 person connected)", project.name),
+                n => format!("{} ({} people connected)", project.name, n),
+            };
+            if pierro::button(ui, label).mouse_clicked() {
+                selected = Some(project.name.clone());
+            }
+        }
+        if let Some(name) = selected {
+            self.url = format!("{}/projects/{}", self.projects_url, encode_project_name(&name));
+            self.projects.clear();
+            self.connect();
+        }
+    }
+
     pub fn render(&mut self, ui: &mut pierro::UI, next_state: &mut Option<SplashScreenState>, next_app_state: &mut Option<AppState>, systems: &mut AppSystems) {
 
         // Back button
@@ -71,6 +187,12 @@ impl CollabScreen {
                 builder.labeled("URL:", |ui| {
                     pierro::text_edit(ui, &mut self.url);
                 });
+                builder.labeled("Username:", |ui| {
+                    pierro::text_edit(ui, &mut self.username);
+                });
+                builder.labeled("Password/Token:", |ui| {
+                    pierro::text_edit(ui, &mut self.password);
+                });
             });
             pierro::v_spacing(ui, 10.0);
             pierro::error_label(ui, &self.error);
@@ -109,16 +231,13 @@ impl CollabScreen {
                 }
             } else {
                 if pierro::button(ui, "Connect").mouse_clicked() {
-                    self.error.clear();
-                    match Socket::new(self.url.as_str()) {
-                        Ok(new_socket) => {
-                            self.socket = Some(new_socket);
-                        },
-                        Err(msg) => {
-                            self.error = cleanup_connect_error_message(msg, &self.url);
-                        },
-                    }
+                    self.connect();
+                }
+                if self.listing_socket.is_none() && pierro::button(ui, "Find Projects").mouse_clicked() {
+                    self.find_projects();
                 }
+                pierro::v_spacing(ui, 10.0);
+                self.render_project_listing(ui);
             }
         });
 
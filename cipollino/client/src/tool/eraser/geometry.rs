@@ -0,0 +1,31 @@
+use elic::{BezierRegion, Vec2};
+
+/// The part of the canvas erased by the eraser at one point in time
+#[derive(Clone, Copy)]
+pub struct EraserCircle {
+    pub center: Vec2,
+    pub radius: f32
+}
+
+impl EraserCircle {
+
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self {
+            center,
+            radius
+        }
+    }
+
+    /// The circles covering the movement of the eraser from `from` to `to`, not including the circle at `from`.
+    /// Consecutive circles overlap, so that nothing is left behind between them.
+    pub fn along(from: Vec2, to: Vec2, radius: f32) -> impl Iterator<Item = Self> {
+        let spacing = radius * 0.5;
+        let steps = if spacing > 0.0 { (from.distance(to) / spacing).ceil().max(1.0) as usize } else { 1 };
+        (1..=steps).map(move |i| Self::new(from.lerp(to, i as f32 / steps as f32), radius))
+    }
+
+    pub fn region(&self) -> BezierRegion {
+        BezierRegion::circle(self.center, self.radius)
+    }
+
+}
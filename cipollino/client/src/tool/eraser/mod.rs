@@ -1,12 +1,27 @@
 
-use project::{Action, Client, DeleteFill, DeleteStroke, SceneObjPtr};
+use std::collections::HashMap;
 
-use crate::{keyboard_shortcut, EditorState};
+use project::{Action, Client, CreateStroke, DeleteFill, DeleteStroke, Fill, FillPaths, Ptr, SceneObjPtr, SetFillPaths, SetStrokeStroke, Stroke, StrokeData, StrokeTreeData};
+
+use crate::{get_brush_settings, keyboard_shortcut, AppSystems, EditorState, ProjectState, RendererState};
 
 use super::{Tool, ToolContext};
 
+mod geometry;
+use geometry::*;
+
+mod prefs;
+use prefs::*;
+
+mod settings;
+
 pub struct EraserTool {
     to_delete: Vec<SceneObjPtr>,
+    /// When cutting, the pieces left of each stroke the eraser has passed over
+    cut_strokes: HashMap<Ptr<Stroke>, Vec<malvina::Stroke>>,
+    /// When cutting, what is left of each fill the eraser has passed over
+    cut_fills: HashMap<Ptr<Fill>, malvina::FillPaths>,
+    cutting: bool,
     prev_pt: elic::Vec2
 }
 
@@ -15,6 +30,9 @@ impl Default for EraserTool {
     fn default() -> Self {
         Self {
             to_delete: Vec::new(),
+            cut_strokes: HashMap::new(),
+            cut_fills: HashMap::new(),
+            cutting: false,
             prev_pt: elic::Vec2::ZERO
         }
     }
@@ -69,33 +87,187 @@ impl EraserTool {
         false
     }
 
+    /// Cut a series of circles out of the pieces of a stroke.
+    /// Returns `None` if none of the circles touch the stroke.
+    fn cut_stroke(pieces: &[malvina::Stroke], circles: &[EraserCircle]) -> Option<Vec<malvina::Stroke>> {
+        let mut result: Option<Vec<malvina::Stroke>> = None;
+        for circle in circles {
+            let curr = result.as_deref().unwrap_or(pieces);
+            let region = circle.region();
+            let cuts: Vec<_> = curr.iter().map(|piece| piece.path.cut(&region, |pt| pt.pt)).collect();
+            if cuts.iter().all(Option::is_none) {
+                continue;
+            }
+            let mut next = Vec::new();
+            for (piece, cut) in curr.iter().zip(cuts) {
+                match cut {
+                    Some(cut) => next.extend(cut.into_iter().map(|path| malvina::Stroke { path })),
+                    None => next.push(piece.clone())
+                }
+            }
+            result = Some(next);
+        }
+        result
+    }
+
+    /// Cut a series of circles out of a fill.
+    /// Returns `None` if none of the circles change the fill.
+    fn cut_fill(paths: &malvina::FillPaths, circles: &[EraserCircle]) -> Option<malvina::FillPaths> {
+        let mut result: Option<malvina::FillPaths> = None;
+        for circle in circles {
+            let curr = elic::BezierRegion::new(result.as_ref().unwrap_or(paths).paths.clone());
+            let region = circle.region();
+            if curr.overlaps(&region) {
+                result = Some(malvina::FillPaths { paths: curr.difference(&region).contours });
+            }
+        }
+        result
+    }
+
+    /// Cut the circles out of every object that can be modified, and show what is left of them in the preview
+    fn cut(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, circles: &[EraserCircle]) {
+        let client = &ctx.project.client;
+        for obj in ctx.modifiable_objs {
+            match *obj {
+                SceneObjPtr::Stroke(ptr) => {
+                    let Some(stroke) = client.get(ptr) else { continue; };
+                    let pieces = self.cut_strokes.get(&ptr).map(Vec::as_slice).unwrap_or(std::slice::from_ref(&stroke.stroke.0));
+                    let Some(pieces) = Self::cut_stroke(pieces, circles) else { continue; };
+
+                    let meshes = pieces.iter()
+                        .map(|piece| malvina::StrokeMesh::new(ctx.device, piece, stroke.width, get_brush_settings(stroke.brush)))
+                        .collect();
+                    editor.preview.stroke_replacements.insert(ptr, meshes);
+                    editor.preview.hide.insert(*obj);
+                    self.cut_strokes.insert(ptr, pieces);
+                },
+                SceneObjPtr::Fill(ptr) => {
+                    let Some(fill) = client.get(ptr) else { continue; };
+                    let paths = self.cut_fills.get(&ptr).unwrap_or(&fill.paths.0);
+                    let Some(paths) = Self::cut_fill(paths, circles) else { continue; };
+
+                    editor.preview.fill_replacements.insert(ptr, malvina::FillMesh::new(ctx.device, &paths));
+                    editor.preview.hide.insert(*obj);
+                    self.cut_fills.insert(ptr, paths);
+                }
+            }
+        }
+    }
+
+    /// Replace the objects that were cut with what is left of them, in a single action
+    fn apply_cuts(&mut self, editor: &mut EditorState, ctx: &mut ToolContext) {
+        if self.cut_strokes.is_empty() && self.cut_fills.is_empty() {
+            return;
+        }
+
+        let client = &ctx.project.client;
+        let mut action = Action::new(editor.action_context("Erase"));
+
+        // The extra pieces of a stroke are inserted right above it, in order.
+        // Going through the strokes from the bottom of their frame up keeps the indices of the strokes left to process valid.
+        let mut strokes: Vec<_> = self.cut_strokes.drain().filter_map(|(ptr, pieces)| {
+            let stroke = client.get(ptr)?;
+            let frame = client.get(stroke.frame)?;
+            let idx = frame.scene.as_slice().iter().position(|obj| *obj == SceneObjPtr::Stroke(ptr))?;
+            Some((ptr, stroke, idx, pieces))
+        }).collect();
+        strokes.sort_by_key(|(_, _, idx, _)| std::cmp::Reverse(*idx));
+
+        for (ptr, stroke, idx, pieces) in strokes {
+            let mut pieces = pieces.into_iter();
+            let Some(first_piece) = pieces.next() else {
+                action.push(DeleteStroke {
+                    ptr
+                });
+                continue;
+            };
+            action.push(SetStrokeStroke {
+                ptr,
+                stroke_value: StrokeData(first_piece)
+            });
+            for (i, piece) in pieces.enumerate() {
+                action.push(CreateStroke {
+                    ptr: client.next_ptr(),
+                    parent: stroke.frame,
+                    idx: idx + i,
+                    data: StrokeTreeData {
+                        stroke: StrokeData(piece),
+                        color: stroke.color,
+                        width: stroke.width,
+                        brush: stroke.brush
+                    }
+                });
+            }
+        }
+
+        for (ptr, paths) in self.cut_fills.drain() {
+            if paths.paths.is_empty() {
+                action.push(DeleteFill {
+                    ptr
+                });
+            } else {
+                action.push(SetFillPaths {
+                    ptr,
+                    paths_value: FillPaths(paths)
+                });
+            }
+        }
+
+        client.queue_action(action);
+    }
+
 }
 
 impl Tool for EraserTool {
     const ICON: &'static str = pierro::icons::ERASER;
     type Shortcut = EraserToolShortcut;
 
-    fn mouse_clicked(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, _pos: elic::Vec2) {
+    fn mouse_clicked(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
+        if ctx.systems.prefs.get::<EraserCutPref>() {
+            let radius = ctx.systems.prefs.get::<EraserRadiusPref>();
+            self.cut(editor, ctx, &[EraserCircle::new(pos, radius)]);
+            self.apply_cuts(editor, ctx);
+            return;
+        }
+
         if let Some((x, y)) = ctx.picking_mouse_pos {
             let Some(scene_obj) = ctx.pick(x, y) else { return; };
             if !ctx.modifiable_objs.contains(&scene_obj) {
                 return;
             }
-            let mut action = Action::new(editor.action_context("Delete")); 
+            let mut action = Action::new(editor.action_context("Delete"));
             Self::delete_obj(&mut action, scene_obj);
             ctx.project.client.queue_action(action);
         }
     }
 
     fn tick(&mut self, editor: &mut EditorState, _ctx: &mut ToolContext) {
-        if !self.to_delete.is_empty() {
+        // If the user undo/redoes while erasing, drop the cuts made so far
+        if (editor.will_undo || editor.will_redo) && self.cutting {
+            editor.will_undo = false;
+            self.cut_strokes.clear();
+            self.cut_fills.clear();
+            self.cutting = false;
+        }
+
+        if !self.to_delete.is_empty() || self.cutting {
             editor.preview.keep_preview = true;
         }
     }
 
     fn mouse_drag_started(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
-        self.to_delete.clear(); 
+        self.to_delete.clear();
         self.prev_pt = pos;
+
+        if ctx.systems.prefs.get::<EraserCutPref>() {
+            self.cut_strokes.clear();
+            self.cut_fills.clear();
+            self.cutting = true;
+            let radius = ctx.systems.prefs.get::<EraserRadiusPref>();
+            self.cut(editor, ctx, &[EraserCircle::new(pos, radius)]);
+            return;
+        }
+
         if let Some((x, y)) = ctx.picking_mouse_pos {
             let Some(scene_obj) = ctx.pick(x, y) else { return; };
             if !ctx.modifiable_objs.contains(&scene_obj) {
@@ -107,10 +279,16 @@ impl Tool for EraserTool {
     }
 
     fn mouse_dragged(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
-        for obj in ctx.modifiable_objs {
-            if self.should_erase(*obj, &ctx.project.client, pos) {
-                self.to_delete.push(*obj);
-                editor.preview.hide.insert(*obj);
+        if self.cutting {
+            let radius = ctx.systems.prefs.get::<EraserRadiusPref>();
+            let circles: Vec<_> = EraserCircle::along(self.prev_pt, pos, radius).collect();
+            self.cut(editor, ctx, &circles);
+        } else {
+            for obj in ctx.modifiable_objs {
+                if self.should_erase(*obj, &ctx.project.client, pos) {
+                    self.to_delete.push(*obj);
+                    editor.preview.hide.insert(*obj);
+                }
             }
         }
 
@@ -118,6 +296,12 @@ impl Tool for EraserTool {
     }
 
     fn mouse_drag_stopped(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, _pos: elic::Vec2) {
+        if self.cutting {
+            self.apply_cuts(editor, ctx);
+            self.cutting = false;
+            return;
+        }
+
         let mut action = Action::new(editor.action_context("Delete"));
         for obj in &self.to_delete {
             Self::delete_obj(&mut action, *obj);
@@ -126,4 +310,20 @@ impl Tool for EraserTool {
         self.to_delete.clear();
     }
 
+    fn settings(&mut self, ui: &mut pierro::UI, _project: &ProjectState, _editor: &mut EditorState, systems: &mut AppSystems, _renderer: &mut Option<RendererState>) {
+        self.settings(ui, systems);
+    }
+
+    fn render_overlay(&self, ctx: &mut ToolContext, rndr: &mut malvina::LayerRenderer, accent_color: elic::Color) {
+        if !self.cutting {
+            return;
+        }
+
+        let radius = ctx.systems.prefs.get::<EraserRadiusPref>();
+        let circle_pt = |i: usize| self.prev_pt + elic::Vec2::from_angle(i as f32 / 32.0 * std::f32::consts::TAU) * radius;
+        for i in 0..32 {
+            rndr.overlay_line(circle_pt(i), circle_pt(i + 1), accent_color);
+        }
+    }
+
 }
@@ -0,0 +1,30 @@
+
+use crate::UserPref;
+
+pub(super) enum EraserCutPref {}
+
+impl UserPref for EraserCutPref {
+    type Type = bool;
+
+    fn default() -> bool {
+        false
+    }
+
+    fn name() -> &'static str {
+        "eraser_cut"
+    }
+}
+
+pub(super) enum EraserRadiusPref {}
+
+impl UserPref for EraserRadiusPref {
+    type Type = f32;
+
+    fn default() -> f32 {
+        10.0
+    }
+
+    fn name() -> &'static str {
+        "eraser_radius"
+    }
+}
@@ -0,0 +1,37 @@
+
+use crate::AppSystems;
+use super::{EraserCutPref, EraserRadiusPref, EraserTool};
+
+impl EraserTool {
+
+    pub(super) fn settings(&mut self, ui: &mut pierro::UI, systems: &mut AppSystems) {
+        pierro::scroll_area(ui, |ui| {
+            pierro::margin(ui, pierro::Margin::same(3.0), |ui| {
+                pierro::key_value_layout(ui, |builder| {
+                    builder.labeled("Cut Strokes:", |ui| {
+                        let mut cut = systems.prefs.get::<EraserCutPref>();
+                        let prev_cut = cut;
+                        pierro::checkbox(ui, &mut cut);
+                        if cut != prev_cut {
+                            systems.prefs.set::<EraserCutPref>(&cut);
+                        }
+                    });
+                    if systems.prefs.get::<EraserCutPref>() {
+                        builder.labeled("Radius:", |ui| {
+                            let mut radius = systems.prefs.get::<EraserRadiusPref>();
+                            let prev_radius = radius;
+                            pierro::DragValue::new(&mut radius)
+                                .with_min(1.0)
+                                .with_max(200.0)
+                                .render(ui);
+                            if radius != prev_radius {
+                                systems.prefs.set::<EraserRadiusPref>(&radius);
+                            }
+                        });
+                    }
+                });
+            });
+        });
+    }
+
+}
@@ -6,6 +6,9 @@ pub use util::*;
 mod select;
 pub use select::*;
 
+mod node;
+pub use node::*;
+
 mod pencil;
 pub use pencil::*;
 
@@ -0,0 +1,471 @@
+
+use elic::{BezierPath, BezierPoint, BezierSegment};
+use project::{Action, Client, DeleteFill, DeleteStroke, Fill, FillPaths, Ptr, SceneObjPtr, SetFillPaths, SetStrokeStroke, Stroke, StrokeData};
+
+use crate::{get_brush_settings, keyboard_shortcut, AppSystems, EditorState, ProjectState, RendererState};
+
+use super::{Tool, ToolContext};
+
+mod points;
+use points::*;
+
+mod settings;
+
+/// A working copy of an object whose points are being edited
+#[derive(Clone)]
+enum NodeObj {
+    Stroke(Ptr<Stroke>, malvina::Stroke),
+    Fill(Ptr<Fill>, malvina::FillPaths)
+}
+
+impl NodeObj {
+
+    fn load(client: &Client, obj: SceneObjPtr) -> Option<Self> {
+        match obj {
+            SceneObjPtr::Stroke(ptr) => Some(Self::Stroke(ptr, client.get(ptr)?.stroke.0.clone())),
+            SceneObjPtr::Fill(ptr) => Some(Self::Fill(ptr, client.get(ptr)?.paths.0.clone()))
+        }
+    }
+
+    fn scene_obj(&self) -> SceneObjPtr {
+        match self {
+            NodeObj::Stroke(ptr, _) => SceneObjPtr::Stroke(*ptr),
+            NodeObj::Fill(ptr, _) => SceneObjPtr::Fill(*ptr)
+        }
+    }
+
+    fn n_paths(&self) -> usize {
+        match self {
+            NodeObj::Stroke(..) => 1,
+            NodeObj::Fill(_, fill) => fill.paths.len()
+        }
+    }
+
+    /// The positions of the points of a path
+    fn points(&self, path: usize) -> Vec<BezierPoint<elic::Vec2>> {
+        match self {
+            NodeObj::Stroke(_, stroke) => stroke.path.pts.iter().map(|pt| pt.map(|pt| pt.pt)).collect(),
+            NodeObj::Fill(_, fill) => fill.paths.get(path).map(|path| path.pts.clone()).unwrap_or_default()
+        }
+    }
+
+    /// The segments making up a path, as they are drawn.
+    /// Fill paths are closed with a straight segment.
+    fn segments(&self, path: usize) -> Vec<BezierSegment<elic::Vec2>> {
+        match self {
+            NodeObj::Stroke(_, stroke) => stroke.path.iter_segments().map(|segment| segment.map(|pt| pt.pt)).collect(),
+            NodeObj::Fill(_, fill) => fill.paths.get(path).map(BezierPath::closed_segments).unwrap_or_default()
+        }
+    }
+
+    fn move_handle(&mut self, node: NodeRef, handle: Handle, pos: elic::Vec2, break_tangent: bool) {
+        match self {
+            NodeObj::Stroke(_, stroke) => {
+                let Some(pt) = stroke.path.pts.get_mut(node.pt) else { return; };
+                move_handle(pt, handle, pos, break_tangent);
+            },
+            NodeObj::Fill(_, fill) => {
+                let Some(pt) = fill.paths.get_mut(node.path).and_then(|path| path.pts.get_mut(node.pt)) else { return; };
+                move_handle(pt, handle, pos, break_tangent);
+            }
+        }
+    }
+
+    /// Insert a point into the segment of a path starting at the point `idx`, returning the index of the new point
+    fn insert_point(&mut self, path: usize, idx: usize, t: f32) -> usize {
+        match self {
+            NodeObj::Stroke(_, stroke) => insert_point(&mut stroke.path, idx, t),
+            NodeObj::Fill(_, fill) => insert_point(&mut fill.paths[path], idx, t)
+        }
+    }
+
+    fn delete_point(&mut self, node: NodeRef) {
+        match self {
+            NodeObj::Stroke(_, stroke) => {
+                if node.pt < stroke.path.pts.len() {
+                    stroke.path.pts.remove(node.pt);
+                }
+            },
+            NodeObj::Fill(_, fill) => {
+                let Some(path) = fill.paths.get_mut(node.path) else { return; };
+                if node.pt < path.pts.len() {
+                    path.pts.remove(node.pt);
+                }
+                // A path needs at least 3 points to cover any area
+                if path.pts.len() < 3 {
+                    fill.paths.remove(node.path);
+                }
+            }
+        }
+    }
+
+    fn pressure(&self, node: NodeRef) -> Option<f32> {
+        match self {
+            NodeObj::Stroke(_, stroke) => stroke.path.pts.get(node.pt).map(|pt| pt.pt.pressure),
+            NodeObj::Fill(..) => None
+        }
+    }
+
+    fn set_pressure(&mut self, node: NodeRef, pressure: f32) {
+        if let NodeObj::Stroke(_, stroke) = self {
+            if let Some(pt) = stroke.path.pts.get_mut(node.pt) {
+                // Shift the pressure of the handles along with the point, so the pressure changes as smoothly around it as before
+                let delta = pressure - pt.pt.pressure;
+                pt.prev.pressure += delta;
+                pt.pt.pressure = pressure;
+                pt.next.pressure += delta;
+            }
+        }
+    }
+
+    /// Show the working copy in place of the object
+    fn preview(&self, editor: &mut EditorState, client: &Client, device: &pierro::wgpu::Device) {
+        match self {
+            NodeObj::Stroke(ptr, stroke) => {
+                let Some(stroke_obj) = client.get(*ptr) else { return; };
+                let mesh = malvina::StrokeMesh::new(device, stroke, stroke_obj.width, get_brush_settings(stroke_obj.brush));
+                editor.preview.stroke_replacements.insert(*ptr, vec![mesh]);
+            },
+            NodeObj::Fill(ptr, fill) => {
+                editor.preview.fill_replacements.insert(*ptr, malvina::FillMesh::new(device, fill));
+            }
+        }
+        editor.preview.hide.insert(self.scene_obj());
+    }
+
+    /// Replace the object with the working copy, deleting it if no points are left
+    fn apply(self, action: &mut Action) {
+        match self {
+            NodeObj::Stroke(ptr, stroke) => {
+                if stroke.path.pts.is_empty() {
+                    action.push(DeleteStroke {
+                        ptr
+                    });
+                } else {
+                    action.push(SetStrokeStroke {
+                        ptr,
+                        stroke_value: StrokeData(stroke)
+                    });
+                }
+            },
+            NodeObj::Fill(ptr, fill) => {
+                if fill.paths.is_empty() {
+                    action.push(DeleteFill {
+                        ptr
+                    });
+                } else {
+                    action.push(SetFillPaths {
+                        ptr,
+                        paths_value: FillPaths(fill)
+                    });
+                }
+            }
+        }
+    }
+
+}
+
+/// A point of one of the paths of an object
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct NodeRef {
+    obj: SceneObjPtr,
+    path: usize,
+    pt: usize
+}
+
+struct NodeDrag {
+    node: NodeRef,
+    handle: Handle,
+    /// The object being edited, with the changes made during the drag
+    obj: NodeObj,
+    /// The offset from the mouse to the dragged handle, so the handle doesn't jump to the mouse when grabbed off-center
+    offset: elic::Vec2
+}
+
+pub struct NodeTool {
+    /// The selected objects, whose points are shown
+    objs: Vec<NodeObj>,
+    /// The point whose handles are shown and whose pressure is edited in the settings
+    selected_point: Option<NodeRef>,
+    drag: Option<NodeDrag>,
+    /// The pressure being entered in the settings, previewed until the user is done editing it
+    pressure_edit: Option<f32>
+}
+
+impl Default for NodeTool {
+
+    fn default() -> Self {
+        Self {
+            objs: Vec::new(),
+            selected_point: None,
+            drag: None,
+            pressure_edit: None
+        }
+    }
+
+}
+
+keyboard_shortcut!(NodeToolShortcut, A, pierro::KeyModifiers::empty());
+
+impl NodeTool {
+
+    const RADIUS: f32 = 3.5;
+    const INTERACTION_RADIUS: f32 = 2.0 * Self::RADIUS;
+    /// How many lines each segment of a path is drawn with in the overlay
+    const SEGMENT_RESOLUTION: usize = 16;
+
+    fn obj(&self, obj: SceneObjPtr) -> Option<&NodeObj> {
+        self.objs.iter().find(|node_obj| node_obj.scene_obj() == obj)
+    }
+
+    fn load_objs(&mut self, editor: &EditorState, ctx: &ToolContext) {
+        let strokes = editor.selection.iter::<Stroke>().map(SceneObjPtr::Stroke);
+        let fills = editor.selection.iter::<Fill>().map(SceneObjPtr::Fill);
+        self.objs = strokes.chain(fills)
+            .filter(|obj| ctx.modifiable_objs.contains(obj))
+            .filter_map(|obj| NodeObj::load(&ctx.project.client, obj))
+            .collect();
+
+        if self.selected_point.map(|node| self.obj(node.obj).is_none()).unwrap_or(false) {
+            self.selected_point = None;
+            self.pressure_edit = None;
+        }
+    }
+
+    /// The handles of the selected point that shape a segment of its path, along with their positions
+    fn selected_handles(&self) -> Vec<(NodeRef, Handle, elic::Vec2)> {
+        let Some(node) = self.selected_point else { return Vec::new(); };
+        let Some(obj) = self.obj(node.obj) else { return Vec::new(); };
+        let pts = obj.points(node.path);
+        let Some(pt) = pts.get(node.pt) else { return Vec::new(); };
+
+        // The first and last points of a path only have a segment on one side.
+        // The segment closing a fill path is straight, so it has no handles either.
+        // Handles sitting right on the anchor are left out, so the anchor can still be grabbed.
+        let mut handles = Vec::new();
+        if node.pt > 0 {
+            handles.push((node, Handle::Prev, pt.prev));
+        }
+        if node.pt + 1 < pts.len() {
+            handles.push((node, Handle::Next, pt.next));
+        }
+        handles.retain(|(_, _, handle_pos)| handle_pos.distance(pt.pt) > 0.001);
+        handles
+    }
+
+    /// Find the handle under the mouse.
+    /// The handles of the selected point take priority over the anchors.
+    fn handle_at(&self, pos: elic::Vec2, zoom: f32) -> Option<(NodeRef, Handle)> {
+        let radius = Self::INTERACTION_RADIUS / zoom;
+
+        for (node, handle, handle_pos) in self.selected_handles() {
+            if handle_pos.distance(pos) < radius {
+                return Some((node, handle));
+            }
+        }
+
+        let mut closest = None;
+        let mut closest_dist = radius;
+        for obj in &self.objs {
+            for path in 0..obj.n_paths() {
+                for (idx, pt) in obj.points(path).iter().enumerate() {
+                    let dist = pt.pt.distance(pos);
+                    if dist < closest_dist {
+                        closest_dist = dist;
+                        closest = Some(NodeRef { obj: obj.scene_obj(), path, pt: idx });
+                    }
+                }
+            }
+        }
+        closest.map(|node| (node, Handle::Anchor))
+    }
+
+    /// Find the point on the paths of the selected objects closest to the mouse.
+    /// Returns the object, path, index of the segment and the parameter along it.
+    fn curve_at(&self, pos: elic::Vec2, zoom: f32) -> Option<(usize, usize, usize, f32)> {
+        let mut closest = None;
+        let mut closest_dist = Self::INTERACTION_RADIUS / zoom;
+        for (obj_idx, obj) in self.objs.iter().enumerate() {
+            for path in 0..obj.n_paths() {
+                let Some((segment, t, dist)) = closest_on_segments(&obj.segments(path), pos) else { continue; };
+                if dist < closest_dist {
+                    closest_dist = dist;
+                    closest = Some((obj_idx, path, segment, t));
+                }
+            }
+        }
+        closest
+    }
+
+    fn pick_obj(editor: &mut EditorState, ctx: &mut ToolContext) {
+        if let Some((x, y)) = ctx.picking_mouse_pos {
+            if let Some(obj) = ctx.pick(x, y) {
+                if ctx.modifiable_objs.contains(&obj) {
+                    editor.selection.extend_select_scene_obj(obj);
+                }
+            }
+        }
+    }
+
+    fn apply(editor: &mut EditorState, client: &Client, obj: NodeObj, action_name: &str) {
+        let mut action = Action::new(editor.action_context(action_name));
+        obj.apply(&mut action);
+        client.queue_action(action);
+    }
+
+    /// Delete the selected point from its path
+    fn delete_selected_point(&mut self, editor: &mut EditorState, client: &Client) {
+        let Some(node) = self.selected_point.take() else { return; };
+        let Some(mut obj) = self.obj(node.obj).cloned() else { return; };
+        obj.delete_point(node);
+        Self::apply(editor, client, obj, "Delete point");
+        editor.selection.keep_selection();
+        self.pressure_edit = None;
+    }
+
+    /// Set the pressure of the selected stroke point
+    fn apply_pressure(&mut self, editor: &mut EditorState, client: &Client, pressure: f32) {
+        let Some(node) = self.selected_point else { return; };
+        let Some(mut obj) = self.obj(node.obj).cloned() else { return; };
+        obj.set_pressure(node, pressure);
+        Self::apply(editor, client, obj, "Set point pressure");
+    }
+
+    fn render_path(rndr: &mut malvina::LayerRenderer, segments: &[BezierSegment<elic::Vec2>], color: elic::Color) {
+        for segment in segments {
+            for i in 0..Self::SEGMENT_RESOLUTION {
+                let t0 = i as f32 / Self::SEGMENT_RESOLUTION as f32;
+                let t1 = (i + 1) as f32 / Self::SEGMENT_RESOLUTION as f32;
+                rndr.overlay_line(segment.sample(t0), segment.sample(t1), color);
+            }
+        }
+    }
+
+}
+
+impl Tool for NodeTool {
+
+    const ICON: &'static str = pierro::icons::BEZIER_CURVE;
+
+    type Shortcut = NodeToolShortcut;
+
+    fn tick(&mut self, editor: &mut EditorState, ctx: &mut ToolContext) {
+        if self.drag.is_some() && (editor.will_undo || editor.will_redo) {
+            editor.will_undo = false;
+            self.drag = None;
+        }
+
+        // Keep the points in sync with the project, except for the object being dragged
+        self.load_objs(editor, ctx);
+        let client = &ctx.project.client;
+        if let Some(drag) = &self.drag {
+            if let Some(obj) = self.objs.iter_mut().find(|obj| obj.scene_obj() == drag.obj.scene_obj()) {
+                *obj = drag.obj.clone();
+            }
+            drag.obj.preview(editor, client, ctx.device);
+            editor.preview.keep_preview = true;
+        }
+
+        if let (Some(node), Some(pressure)) = (self.selected_point, self.pressure_edit) {
+            if let Some(obj) = self.objs.iter_mut().find(|obj| obj.scene_obj() == node.obj) {
+                obj.set_pressure(node, pressure);
+                obj.preview(editor, client, ctx.device);
+                editor.preview.keep_preview = true;
+            }
+        }
+    }
+
+    fn mouse_clicked(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
+        self.pressure_edit = None;
+
+        if let Some((node, _)) = self.handle_at(pos, ctx.cam_zoom) {
+            editor.selection.keep_selection();
+            self.selected_point = Some(node);
+            if ctx.key_modifiers.contains(pierro::KeyModifiers::OPTION) {
+                self.delete_selected_point(editor, &ctx.project.client);
+            }
+            return;
+        }
+
+        if let Some((obj_idx, path, segment, t)) = self.curve_at(pos, ctx.cam_zoom) {
+            editor.selection.keep_selection();
+            let mut obj = self.objs[obj_idx].clone();
+            let pt = obj.insert_point(path, segment, t);
+            self.selected_point = Some(NodeRef { obj: obj.scene_obj(), path, pt });
+            Self::apply(editor, &ctx.project.client, obj, "Insert point");
+            return;
+        }
+
+        self.selected_point = None;
+        Self::pick_obj(editor, ctx);
+    }
+
+    fn mouse_drag_started(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
+        self.pressure_edit = None;
+
+        if let Some((node, handle)) = self.handle_at(pos, ctx.cam_zoom) {
+            let Some(obj) = self.obj(node.obj).cloned() else { return; };
+            let Some(pt) = obj.points(node.path).get(node.pt).copied() else { return; };
+            editor.selection.keep_selection();
+            self.selected_point = Some(node);
+            self.drag = Some(NodeDrag {
+                node,
+                handle,
+                obj,
+                offset: handle.pos(&pt) - pos
+            });
+            return;
+        }
+
+        self.selected_point = None;
+    }
+
+    fn mouse_dragged(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, pos: elic::Vec2) {
+        let Some(drag) = &mut self.drag else { return; };
+        let break_tangent = ctx.key_modifiers.contains(pierro::KeyModifiers::OPTION);
+        drag.obj.move_handle(drag.node, drag.handle, pos + drag.offset, break_tangent);
+        drag.obj.preview(editor, &ctx.project.client, ctx.device);
+    }
+
+    fn mouse_drag_stopped(&mut self, editor: &mut EditorState, ctx: &mut ToolContext, _pos: elic::Vec2) {
+        let Some(drag) = self.drag.take() else { return; };
+        let action_name = match drag.handle {
+            Handle::Anchor => "Move point",
+            Handle::Prev | Handle::Next => "Move handle"
+        };
+        editor.selection.keep_selection();
+        Self::apply(editor, &ctx.project.client, drag.obj, action_name);
+    }
+
+    fn settings(&mut self, ui: &mut pierro::UI, project: &ProjectState, editor: &mut EditorState, _systems: &mut AppSystems, _renderer: &mut Option<RendererState>) {
+        self.settings(ui, project, editor);
+    }
+
+    fn render_overlay(&self, _ctx: &mut ToolContext, rndr: &mut malvina::LayerRenderer, accent_color: elic::Color) {
+        for obj in &self.objs {
+            for path in 0..obj.n_paths() {
+                Self::render_path(rndr, &obj.segments(path), accent_color);
+            }
+        }
+
+        if let Some(anchor) = self.selected_point.and_then(|node| self.obj(node.obj)?.points(node.path).get(node.pt).map(|pt| pt.pt)) {
+            for (_, _, handle_pos) in self.selected_handles() {
+                rndr.overlay_line(anchor, handle_pos, accent_color);
+                rndr.overlay_circle(handle_pos, Self::RADIUS - 1.0, accent_color);
+            }
+        }
+
+        for obj in &self.objs {
+            for path in 0..obj.n_paths() {
+                for (idx, pt) in obj.points(path).iter().enumerate() {
+                    let selected = self.selected_point == Some(NodeRef { obj: obj.scene_obj(), path, pt: idx });
+                    rndr.overlay_circle(pt.pt, Self::RADIUS, accent_color);
+                    if !selected {
+                        rndr.overlay_circle(pt.pt, Self::RADIUS - 1.0, elic::Color::WHITE);
+                    }
+                }
+            }
+        }
+    }
+
+}
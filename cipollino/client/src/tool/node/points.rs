@@ -0,0 +1,138 @@
+
+use elic::{BezierPath, BezierPoint, BezierSegment, Linear, Vec2};
+
+/// A type of point the node tool can edit the position of
+pub(super) trait NodePoint: Linear {
+
+    fn pos(&self) -> Vec2;
+    fn set_pos(&mut self, pos: Vec2);
+
+}
+
+impl NodePoint for Vec2 {
+
+    fn pos(&self) -> Vec2 {
+        *self
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        *self = pos;
+    }
+
+}
+
+impl NodePoint for malvina::StrokePoint {
+
+    fn pos(&self) -> Vec2 {
+        self.pt
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.pt = pos;
+    }
+
+}
+
+/// The part of a bezier point that can be dragged around
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Handle {
+    Anchor,
+    Prev,
+    Next
+}
+
+impl Handle {
+
+    pub(super) fn pos<T: NodePoint>(&self, pt: &BezierPoint<T>) -> Vec2 {
+        match self {
+            Handle::Anchor => pt.pt.pos(),
+            Handle::Prev => pt.prev.pos(),
+            Handle::Next => pt.next.pos()
+        }
+    }
+
+}
+
+/// Move part of a bezier point to a new position.
+/// Moving the anchor brings the handles along with it.
+/// Moving a handle keeps the opposite handle pointing the other way, so smooth points stay smooth, unless `break_tangent` is set.
+pub(super) fn move_handle<T: NodePoint>(pt: &mut BezierPoint<T>, handle: Handle, pos: Vec2, break_tangent: bool) {
+    let anchor = pt.pt.pos();
+    let (moved, opposite) = match handle {
+        Handle::Anchor => {
+            let delta = pos - anchor;
+            pt.prev.set_pos(pt.prev.pos() + delta);
+            pt.pt.set_pos(pos);
+            pt.next.set_pos(pt.next.pos() + delta);
+            return;
+        },
+        Handle::Prev => (&mut pt.prev, &mut pt.next),
+        Handle::Next => (&mut pt.next, &mut pt.prev)
+    };
+
+    moved.set_pos(pos);
+    if break_tangent {
+        return;
+    }
+    let opposite_length = opposite.pos().distance(anchor);
+    let dir = anchor - pos;
+    if opposite_length < 0.001 || dir.length() < 0.001 {
+        return;
+    }
+    opposite.set_pos(anchor + dir.normalize() * opposite_length);
+}
+
+/// The segment of a path starting at the point `idx`.
+/// For closed paths, the segment starting at the last point runs straight back to the first point.
+fn segment<T: NodePoint>(path: &BezierPath<T>, idx: usize) -> BezierSegment<T> {
+    match path.pts.get(idx + 1) {
+        Some(next) => BezierSegment::from_points(path.pts[idx].clone(), next.clone()),
+        None => BezierSegment::straight(path.pts[idx].pt.clone(), path.pts[0].pt.clone())
+    }
+}
+
+/// Split the segment starting at the point `idx` in two, without changing the shape of the path.
+/// Returns the index of the new point.
+pub(super) fn insert_point<T: NodePoint>(path: &mut BezierPath<T>, idx: usize, t: f32) -> usize {
+    let (before, after) = segment(path, idx).split(t);
+    path.pts[idx].next = before.b0;
+    if let Some(next) = path.pts.get_mut(idx + 1) {
+        next.prev = after.a1;
+    }
+    path.pts.insert(idx + 1, BezierPoint::new(before.a1, before.p1, after.b0));
+    idx + 1
+}
+
+/// Find the point on a list of segments closest to `pos`.
+/// Returns the index of the segment, the parameter along it and the distance to the point.
+pub(super) fn closest_on_segments(segments: &[BezierSegment<Vec2>], pos: Vec2) -> Option<(usize, f32, f32)> {
+    const SAMPLES: usize = 32;
+
+    let mut closest: Option<(usize, f32, f32)> = None;
+    for (idx, segment) in segments.iter().enumerate() {
+        let dist = |t: f32| segment.sample(t).distance(pos);
+
+        // Find roughly where the closest point is, then refine it
+        let mut t = (0..=SAMPLES)
+            .map(|i| i as f32 / SAMPLES as f32)
+            .min_by(|a, b| dist(*a).total_cmp(&dist(*b)))
+            .unwrap_or(0.0);
+        let mut step = 0.5 / SAMPLES as f32;
+        for _ in 0..16 {
+            let before = (t - step).max(0.0);
+            let after = (t + step).min(1.0);
+            if dist(before) < dist(t) {
+                t = before;
+            } else if dist(after) < dist(t) {
+                t = after;
+            }
+            step *= 0.5;
+        }
+
+        let dist = dist(t);
+        if closest.map(|(_, _, closest_dist)| dist < closest_dist).unwrap_or(true) {
+            closest = Some((idx, t, dist));
+        }
+    }
+    closest
+}
@@ -0,0 +1,41 @@
+
+use crate::{EditorState, ProjectState};
+use super::NodeTool;
+
+impl NodeTool {
+
+    pub(super) fn settings(&mut self, ui: &mut pierro::UI, project: &ProjectState, editor: &mut EditorState) {
+        let Some(node) = self.selected_point else { return; };
+        let Some(obj) = self.obj(node.obj) else { return; };
+        let pressure = obj.pressure(node);
+
+        pierro::scroll_area(ui, |ui| {
+            pierro::margin(ui, pierro::Margin::same(3.0), |ui| {
+                pierro::key_value_layout(ui, |builder| {
+                    if let Some(pressure) = pressure {
+                        builder.labeled("Pressure:", |ui| {
+                            let mut pressure = self.pressure_edit.unwrap_or(pressure);
+                            let resp = pierro::DragValue::new(&mut pressure)
+                                .with_min(0.0)
+                                .with_max(1.0)
+                                .render(ui);
+                            if resp.editing {
+                                self.pressure_edit = Some(pressure);
+                            }
+                            if resp.done_editing {
+                                self.pressure_edit = None;
+                                self.apply_pressure(editor, &project.client, pressure);
+                            }
+                        });
+                    }
+                    builder.labeled("", |ui| {
+                        if pierro::button(ui, "Delete Point").mouse_clicked() {
+                            self.delete_selected_point(editor, &project.client);
+                        }
+                    });
+                });
+            });
+        });
+    }
+
+}
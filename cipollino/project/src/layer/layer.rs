@@ -1,5 +1,5 @@
 
-use crate::{Client, Frame, Objects, Project};
+use crate::{Client, Frame, MotionKey, Objects, Project};
 use super::{LayerPtr, LayerParent};
 
 #[derive(alisa::Serializable, Clone)]
@@ -8,7 +8,8 @@ pub struct Layer {
 
     pub name: String,
 
-    pub frames: alisa::UnorderedChildList<alisa::OwningPtr<Frame>>
+    pub frames: alisa::UnorderedChildList<alisa::OwningPtr<Frame>>,
+    pub motion_keys: alisa::UnorderedChildList<alisa::OwningPtr<MotionKey>>
 }
 
 impl Default for Layer {
@@ -17,7 +18,8 @@ impl Default for Layer {
         Self {
             parent: LayerParent::Clip(alisa::Ptr::null()),
             name: "Layer".to_owned(),
-            frames: alisa::UnorderedChildList::new()
+            frames: alisa::UnorderedChildList::new(),
+            motion_keys: alisa::UnorderedChildList::new()
         }
     }
 
@@ -27,6 +29,9 @@ impl alisa::Object for Layer {
     type Project = Project;
 
     const TYPE_ID: u16 = 2;
+    const MIGRATIONS: &'static [alisa::Migration] = &[
+        add_motion_keys
+    ];
 
     fn list(objects: &Objects) -> &alisa::ObjList<Self> {
         &objects.layers
@@ -40,14 +45,16 @@ impl alisa::Object for Layer {
 #[derive(alisa::Serializable)]
 pub struct LayerTreeData {
     pub name: String,
-    pub frames: alisa::UnorderedChildListTreeData<alisa::OwningPtr<Frame>>
+    pub frames: alisa::UnorderedChildListTreeData<alisa::OwningPtr<Frame>>,
+    pub motion_keys: alisa::UnorderedChildListTreeData<alisa::OwningPtr<MotionKey>>
 }
 
 impl Default for LayerTreeData {
     fn default() -> Self {
         Self {
             name: "Layer".to_owned(),
-            frames: alisa::UnorderedChildListTreeData::default()
+            frames: alisa::UnorderedChildListTreeData::default(),
+            motion_keys: alisa::UnorderedChildListTreeData::default()
         }
     }
 }
@@ -77,7 +84,8 @@ impl alisa::TreeObj for Layer {
         let layer = Layer {
             parent,
             name: data.name.clone(),
-            frames: data.frames.instance(ptr, recorder)
+            frames: data.frames.instance(ptr, recorder),
+            motion_keys: data.motion_keys.instance(ptr, recorder)
         };
         recorder.add_obj(ptr, layer);
     }
@@ -85,12 +93,25 @@ impl alisa::TreeObj for Layer {
     fn collect_data(&self, objects: &Objects) -> Self::TreeData {
         LayerTreeData {
             name: self.name.clone(),
-            frames: self.frames.collect_data(objects)
+            frames: self.frames.collect_data(objects),
+            motion_keys: self.motion_keys.collect_data(objects)
         }
     }
 
 }
 
+/// Layers saved before motion keys were added have no motion keys
+fn add_motion_keys(data: alisa::ABFValue) -> Option<alisa::ABFValue> {
+    let alisa::ABFValue::Map(entries) = data else {
+        return None;
+    };
+    let mut entries = entries.into_vec();
+    if !entries.iter().any(|(key, _)| key == "motion_keys") {
+        entries.push(("motion_keys".to_owned(), alisa::ABFValue::Array(Box::new([]))));
+    }
+    Some(alisa::ABFValue::Map(entries.into_boxed_slice()))
+}
+
 alisa::tree_object_operations!(Layer);
 alisa::object_set_property_operation!(Layer, name, String);
 
@@ -20,6 +20,9 @@ pub use color::*;
 mod audio_instance;
 pub use audio_instance::*;
 
+mod motion;
+pub use motion::*;
+
 mod protocol;
 pub use protocol::*;
 
@@ -0,0 +1,43 @@
+
+/// How the motion of a layer progresses from one key to the next
+#[derive(Clone, Copy, Default, PartialEq, Eq, alisa::Serializable)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Start slowly and speed up towards the next key
+    EaseIn,
+    /// Start quickly and slow down towards the next key
+    EaseOut,
+    /// Speed up, then slow down towards the next key
+    EaseInOut,
+    /// Stay still until the next key
+    Hold
+}
+
+impl Easing {
+
+    pub const ALL: [Easing; 5] = [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::Hold];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease In",
+            Easing::EaseOut => "Ease Out",
+            Easing::EaseInOut => "Ease In-Out",
+            Easing::Hold => "Hold"
+        }
+    }
+
+    /// Map the fraction of time elapsed between two keys to the fraction of the motion completed
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Hold => 0.0
+        }
+    }
+
+}
@@ -0,0 +1,94 @@
+
+use crate::{Client, Layer};
+
+use super::MotionKey;
+
+/// The transform and opacity of a layer at a point in time
+#[derive(Clone, Copy)]
+pub struct MotionState {
+    pub position: elic::Vec2,
+    /// The rotation of the layer, in degrees
+    pub rotation: f32,
+    pub scale: elic::Vec2,
+    pub opacity: f32
+}
+
+impl MotionState {
+
+    pub const IDENTITY: Self = Self {
+        position: elic::Vec2::ZERO,
+        rotation: 0.0,
+        scale: elic::Vec2::ONE,
+        opacity: 1.0
+    };
+
+    pub fn of_key(key: &MotionKey) -> Self {
+        Self {
+            position: elic::vec2(key.position[0], key.position[1]),
+            rotation: key.rotation,
+            scale: elic::vec2(key.scale[0], key.scale[1]),
+            opacity: key.opacity
+        }
+    }
+
+    pub fn lerp(&self, other: &MotionState, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(other.position, t),
+            rotation: self.rotation + (other.rotation - self.rotation) * t,
+            scale: self.scale.lerp(other.scale, t),
+            opacity: self.opacity + (other.opacity - self.opacity) * t
+        }
+    }
+
+    /// The transform to apply to the contents of the layer.
+    /// The layer is scaled, then rotated, then moved.
+    pub fn transform(&self) -> elic::Mat4 {
+        elic::Mat4::translate(self.position) * elic::Mat4::rotate(self.rotation.to_radians()) * elic::Mat4::scale(self.scale)
+    }
+
+}
+
+impl Default for MotionState {
+
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+
+}
+
+impl Layer {
+
+    /// The motion key exactly at time `t`
+    pub fn motion_key_at(&self, client: &Client, t: i32) -> Option<alisa::Ptr<MotionKey>> {
+        self.motion_keys.iter()
+            .map(|key_ptr| key_ptr.ptr())
+            .find(|key_ptr| client.get(*key_ptr).map(|key| key.time == t).unwrap_or(false))
+    }
+
+    /// Interpolate the layer's motion keys at time `t`.
+    /// Before the first key and after the last key, the layer stays at that key.
+    pub fn motion_at(&self, client: &Client, t: i32) -> MotionState {
+        let mut before: Option<&MotionKey> = None;
+        let mut after: Option<&MotionKey> = None;
+        for key_ptr in self.motion_keys.iter() {
+            let Some(key) = client.get(key_ptr.ptr()) else { continue; };
+            if key.time <= t {
+                if before.map(|before| key.time > before.time).unwrap_or(true) {
+                    before = Some(key);
+                }
+            } else if after.map(|after| key.time < after.time).unwrap_or(true) {
+                after = Some(key);
+            }
+        }
+
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let t = (t - before.time) as f32 / (after.time - before.time) as f32;
+                MotionState::of_key(before).lerp(&MotionState::of_key(after), before.easing.apply(t))
+            },
+            (Some(key), None) | (None, Some(key)) => MotionState::of_key(key),
+            (None, None) => MotionState::IDENTITY
+        }
+    }
+
+}
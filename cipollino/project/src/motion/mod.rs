@@ -0,0 +1,131 @@
+
+use crate::{Layer, Objects, Project};
+
+mod easing;
+pub use easing::*;
+
+mod interpolate;
+pub use interpolate::*;
+
+/// A key in the motion of a layer.
+/// Between two keys, the layer's transform and opacity are interpolated using the easing of the earlier key.
+#[derive(alisa::Serializable, Clone)]
+pub struct MotionKey {
+    pub layer: alisa::Ptr<Layer>,
+    pub time: i32,
+    pub position: [f32; 2],
+    /// The rotation of the layer, in degrees
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub opacity: f32,
+    pub easing: Easing
+}
+
+impl Default for MotionKey {
+
+    fn default() -> Self {
+        Self {
+            layer: alisa::Ptr::null(),
+            time: 0,
+            position: [0.0; 2],
+            rotation: 0.0,
+            scale: [1.0; 2],
+            opacity: 1.0,
+            easing: Easing::default()
+        }
+    }
+
+}
+
+impl alisa::Object for MotionKey {
+    type Project = Project;
+
+    const TYPE_ID: u16 = 15;
+
+    fn list(objects: &Objects) -> &alisa::ObjList<Self> {
+        &objects.motion_keys
+    }
+
+    fn list_mut(objects: &mut Objects) -> &mut alisa::ObjList<Self> {
+        &mut objects.motion_keys
+    }
+}
+
+#[derive(alisa::Serializable)]
+pub struct MotionKeyTreeData {
+    pub time: i32,
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub opacity: f32,
+    pub easing: Easing
+}
+
+impl Default for MotionKeyTreeData {
+
+    fn default() -> Self {
+        Self {
+            time: 0,
+            position: [0.0; 2],
+            rotation: 0.0,
+            scale: [1.0; 2],
+            opacity: 1.0,
+            easing: Easing::default()
+        }
+    }
+
+}
+
+impl alisa::TreeObj for MotionKey {
+    type ParentPtr = alisa::Ptr<Layer>;
+    type ChildList = alisa::UnorderedChildList<alisa::OwningPtr<MotionKey>>;
+    type TreeData = MotionKeyTreeData;
+
+    fn child_list<'a>(parent: Self::ParentPtr, context: &'a alisa::ProjectContext<Self::Project>) -> Option<&'a Self::ChildList> {
+        Some(&context.obj_list().get(parent)?.motion_keys)
+    }
+
+    fn child_list_mut<'a>(parent: Self::ParentPtr, recorder: &'a mut alisa::Recorder<Self::Project>) -> Option<&'a mut Self::ChildList> {
+        Some(&mut recorder.get_obj_mut(parent)?.motion_keys)
+    }
+
+    fn parent(&self) -> Self::ParentPtr {
+        self.layer
+    }
+
+    fn parent_mut(&mut self) -> &mut Self::ParentPtr {
+        &mut self.layer
+    }
+
+    fn instance(data: &Self::TreeData, ptr: alisa::Ptr<Self>, parent: Self::ParentPtr, recorder: &mut alisa::Recorder<Self::Project>) {
+        recorder.add_obj(ptr, MotionKey {
+            layer: parent,
+            time: data.time,
+            position: data.position,
+            rotation: data.rotation,
+            scale: data.scale,
+            opacity: data.opacity,
+            easing: data.easing
+        });
+    }
+
+    fn collect_data(&self, _objects: &Objects) -> Self::TreeData {
+        MotionKeyTreeData {
+            time: self.time,
+            position: self.position,
+            rotation: self.rotation,
+            scale: self.scale,
+            opacity: self.opacity,
+            easing: self.easing
+        }
+    }
+
+}
+
+alisa::tree_object_creation_operations!(MotionKey);
+alisa::object_set_property_operation!(MotionKey, time, i32);
+alisa::object_set_property_operation!(MotionKey, position, [f32; 2]);
+alisa::object_set_property_operation!(MotionKey, rotation, f32);
+alisa::object_set_property_operation!(MotionKey, scale, [f32; 2]);
+alisa::object_set_property_operation!(MotionKey, opacity, f32);
+alisa::object_set_property_operation!(MotionKey, easing, Easing);
@@ -1,5 +1,5 @@
 
-use crate::{AddBlockToAudioClip, AddPaletteToClip, AudioBlock, AudioClip, AudioInstance, AudioLayer, Clip, ClipInner, ClipTreeData, Color, CreateAudioClip, CreateAudioInstance, CreateAudioLayer, CreateClip, CreateClipInner, CreateColor, CreateFill, CreateFolder, CreateFrame, CreateLayer, CreateLayerGroup, CreatePalette, CreatePaletteInner, CreateStroke, DeleteAudioClip, DeleteAudioInstance, DeleteAudioLayer, DeleteClip, DeleteColor, DeleteFill, DeleteFolder, DeleteFrame, DeleteLayer, DeleteLayerGroup, DeletePalette, DeleteStroke, Fill, Folder, Frame, Layer, LayerGroup, LayerParent, LayerTreeData, Palette, PaletteInner, RemovePaletteFromClip, RenameAudioClip, RenameClip, RenameFolder, RenamePalette, SetAudioInstanceBounds, SetAudioInstanceOffset, SetAudioLayerName, SetClipInnerBackgroundColor, SetClipInnerFramerate, SetClipInnerHeight, SetClipInnerLength, SetClipInnerWidth, SetColorColor, SetColorName, SetFillColor, SetFillPaths, SetFrameTime, SetLayerGroupName, SetLayerName, SetStrokeColor, SetStrokeStroke, Stroke, TransferAudioClip, TransferAudioLayer, TransferClip, TransferFolder, TransferLayer, TransferLayerGroup, TransferPalette};
+use crate::{AddBlockToAudioClip, AddPaletteToClip, AudioBlock, AudioClip, AudioInstance, AudioLayer, Clip, ClipInner, ClipTreeData, Color, CreateAudioClip, CreateAudioInstance, CreateAudioLayer, CreateClip, CreateClipInner, CreateColor, CreateFill, CreateFolder, CreateFrame, CreateLayer, CreateLayerGroup, CreateMotionKey, CreatePalette, CreatePaletteInner, CreateStroke, DeleteAudioClip, DeleteAudioInstance, DeleteAudioLayer, DeleteClip, DeleteColor, DeleteFill, DeleteFolder, DeleteFrame, DeleteLayer, DeleteLayerGroup, DeleteMotionKey, DeletePalette, DeleteStroke, Fill, Folder, Frame, Layer, LayerGroup, LayerParent, LayerTreeData, MotionKey, Palette, PaletteInner, RemovePaletteFromClip, RenameAudioClip, RenameClip, RenameFolder, RenamePalette, SetAudioInstanceBounds, SetAudioInstanceOffset, SetAudioLayerName, SetClipInnerBackgroundColor, SetClipInnerFramerate, SetClipInnerHeight, SetClipInnerLength, SetClipInnerWidth, SetColorColor, SetColorName, SetFillColor, SetFillPaths, SetFrameTime, SetLayerGroupName, SetLayerName, SetMotionKeyEasing, SetMotionKeyOpacity, SetMotionKeyPosition, SetMotionKeyRotation, SetMotionKeyScale, SetMotionKeyTime, SetStrokeColor, SetStrokeStroke, Stroke, TransferAudioClip, TransferAudioLayer, TransferClip, TransferFolder, TransferLayer, TransferLayerGroup, TransferPalette};
 
 #[derive(alisa::Serializable, Clone)]
 pub struct Project {
@@ -38,10 +38,11 @@ pub struct Objects {
     pub audio_layers: alisa::ObjList<AudioLayer>,
     pub audio_clips: alisa::ObjList<AudioClip>,
     pub audio_blocks: alisa::ObjList<AudioBlock>,
-    pub audio_instances: alisa::ObjList<AudioInstance>
+    pub audio_instances: alisa::ObjList<AudioInstance>,
+    pub motion_keys: alisa::ObjList<MotionKey>
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default, alisa::Serializable)]
 pub struct ActionContext {
     pub name: String,
     pub open_clip: alisa::Ptr<Clip>,
@@ -93,6 +94,9 @@ impl alisa::Project for Project {
         alisa::verter::Config {
             magic_bytes: b"CIPOLINO",
             page_size: 64,
+            journal: true,
+            // Older builds of Cipollino can't read files with checksums
+            checksums: false,
         } 
     }
 
@@ -112,79 +116,94 @@ impl alisa::Project for Project {
         alisa::ObjectKind::from::<AudioClip>(),
         alisa::ObjectKind::from::<AudioBlock>(),
         alisa::ObjectKind::from::<AudioInstance>(),
+        alisa::ObjectKind::from::<MotionKey>(),
     ];
 
     const OPERATIONS: &'static [alisa::OperationKind<Self>] = &[
-        alisa::OperationKind::from::<CreateFolder>(),
-        alisa::OperationKind::from::<DeleteFolder>(),
-        alisa::OperationKind::from::<RenameFolder>(),
-        alisa::OperationKind::from::<TransferFolder>(),
+        alisa::OperationKind::from_invertible::<CreateFolder>(),
+        alisa::OperationKind::from_invertible::<DeleteFolder>(),
+        alisa::OperationKind::from_invertible::<RenameFolder>(),
+        alisa::OperationKind::from_invertible::<TransferFolder>(),
 
-        alisa::OperationKind::from::<CreateClip>(),
-        alisa::OperationKind::from::<DeleteClip>(),
-        alisa::OperationKind::from::<RenameClip>(),
-        alisa::OperationKind::from::<TransferClip>(),
+        alisa::OperationKind::from_invertible::<CreateClip>(),
+        alisa::OperationKind::from_invertible::<DeleteClip>(),
+        alisa::OperationKind::from_invertible::<RenameClip>(),
+        alisa::OperationKind::from_invertible::<TransferClip>(),
 
+        // Queued on its own when creating a clip rather than as part of an action, so it never ends up in the persistent undo/redo history
         alisa::OperationKind::from::<CreateClipInner>(),
-        alisa::OperationKind::from::<SetClipInnerWidth>(),
-        alisa::OperationKind::from::<SetClipInnerHeight>(),
-        alisa::OperationKind::from::<SetClipInnerLength>(),
-        alisa::OperationKind::from::<SetClipInnerFramerate>(),
-        alisa::OperationKind::from::<SetClipInnerBackgroundColor>(),
-        alisa::OperationKind::from::<AddPaletteToClip>(),
-        alisa::OperationKind::from::<RemovePaletteFromClip>(),
+        alisa::OperationKind::from_invertible::<SetClipInnerWidth>(),
+        alisa::OperationKind::from_invertible::<SetClipInnerHeight>(),
+        alisa::OperationKind::from_invertible::<SetClipInnerLength>(),
+        alisa::OperationKind::from_invertible::<SetClipInnerFramerate>(),
+        alisa::OperationKind::from_invertible::<SetClipInnerBackgroundColor>(),
+        alisa::OperationKind::from_invertible::<AddPaletteToClip>(),
+        alisa::OperationKind::from_invertible::<RemovePaletteFromClip>(),
         
-        alisa::OperationKind::from::<CreateLayer>(),
-        alisa::OperationKind::from::<DeleteLayer>(),
-        alisa::OperationKind::from::<TransferLayer>(),
-        alisa::OperationKind::from::<SetLayerName>(),
-
-        alisa::OperationKind::from::<CreateLayerGroup>(),
-        alisa::OperationKind::from::<DeleteLayerGroup>(),
-        alisa::OperationKind::from::<TransferLayerGroup>(),
-        alisa::OperationKind::from::<SetLayerGroupName>(),
-
-        alisa::OperationKind::from::<CreateFrame>(),
-        alisa::OperationKind::from::<DeleteFrame>(),
-        alisa::OperationKind::from::<SetFrameTime>(),
-
-        alisa::OperationKind::from::<CreateStroke>(),
-        alisa::OperationKind::from::<DeleteStroke>(),
-        alisa::OperationKind::from::<SetStrokeStroke>(),
-        alisa::OperationKind::from::<SetStrokeColor>(),
-
-        alisa::OperationKind::from::<CreateFill>(),
-        alisa::OperationKind::from::<DeleteFill>(),
-        alisa::OperationKind::from::<SetFillPaths>(),
-        alisa::OperationKind::from::<SetFillColor>(),
-
-        alisa::OperationKind::from::<CreatePalette>(),
-        alisa::OperationKind::from::<DeletePalette>(),
-        alisa::OperationKind::from::<RenamePalette>(),
-        alisa::OperationKind::from::<TransferPalette>(),
-
+        alisa::OperationKind::from_invertible::<CreateLayer>(),
+        alisa::OperationKind::from_invertible::<DeleteLayer>(),
+        alisa::OperationKind::from_invertible::<TransferLayer>(),
+        alisa::OperationKind::from_invertible::<SetLayerName>(),
+
+        alisa::OperationKind::from_invertible::<CreateLayerGroup>(),
+        alisa::OperationKind::from_invertible::<DeleteLayerGroup>(),
+        alisa::OperationKind::from_invertible::<TransferLayerGroup>(),
+        alisa::OperationKind::from_invertible::<SetLayerGroupName>(),
+
+        alisa::OperationKind::from_invertible::<CreateFrame>(),
+        alisa::OperationKind::from_invertible::<DeleteFrame>(),
+        alisa::OperationKind::from_invertible::<SetFrameTime>(),
+
+        alisa::OperationKind::from_invertible::<CreateStroke>(),
+        alisa::OperationKind::from_invertible::<DeleteStroke>(),
+        alisa::OperationKind::from_invertible::<SetStrokeStroke>(),
+        alisa::OperationKind::from_invertible::<SetStrokeColor>(),
+
+        alisa::OperationKind::from_invertible::<CreateFill>(),
+        alisa::OperationKind::from_invertible::<DeleteFill>(),
+        alisa::OperationKind::from_invertible::<SetFillPaths>(),
+        alisa::OperationKind::from_invertible::<SetFillColor>(),
+
+        alisa::OperationKind::from_invertible::<CreatePalette>(),
+        alisa::OperationKind::from_invertible::<DeletePalette>(),
+        alisa::OperationKind::from_invertible::<RenamePalette>(),
+        alisa::OperationKind::from_invertible::<TransferPalette>(),
+
+        // Queued on its own when creating a palette rather than as part of an action, so it never ends up in the persistent undo/redo history
         alisa::OperationKind::from::<CreatePaletteInner>(),
 
-        alisa::OperationKind::from::<CreateColor>(),
-        alisa::OperationKind::from::<DeleteColor>(),
-        alisa::OperationKind::from::<SetColorColor>(),
-        alisa::OperationKind::from::<SetColorName>(),
-
-        alisa::OperationKind::from::<CreateAudioLayer>(),
-        alisa::OperationKind::from::<DeleteAudioLayer>(),
-        alisa::OperationKind::from::<TransferAudioLayer>(),
-        alisa::OperationKind::from::<SetAudioLayerName>(),
-
-        alisa::OperationKind::from::<CreateAudioClip>(),
-        alisa::OperationKind::from::<DeleteAudioClip>(),
-        alisa::OperationKind::from::<TransferAudioClip>(),
-        alisa::OperationKind::from::<RenameAudioClip>(),
+        alisa::OperationKind::from_invertible::<CreateColor>(),
+        alisa::OperationKind::from_invertible::<DeleteColor>(),
+        alisa::OperationKind::from_invertible::<SetColorColor>(),
+        alisa::OperationKind::from_invertible::<SetColorName>(),
+
+        alisa::OperationKind::from_invertible::<CreateAudioLayer>(),
+        alisa::OperationKind::from_invertible::<DeleteAudioLayer>(),
+        alisa::OperationKind::from_invertible::<TransferAudioLayer>(),
+        alisa::OperationKind::from_invertible::<SetAudioLayerName>(),
+
+        alisa::OperationKind::from_invertible::<CreateAudioClip>(),
+        alisa::OperationKind::from_invertible::<DeleteAudioClip>(),
+        alisa::OperationKind::from_invertible::<TransferAudioClip>(),
+        alisa::OperationKind::from_invertible::<RenameAudioClip>(),
+        // Queued on its own when importing audio rather than as part of an action, so it never ends up in the persistent undo/redo history
         alisa::OperationKind::from::<AddBlockToAudioClip>(),
 
-        alisa::OperationKind::from::<CreateAudioInstance>(),
-        alisa::OperationKind::from::<DeleteAudioInstance>(),
-        alisa::OperationKind::from::<SetAudioInstanceBounds>(),
-        alisa::OperationKind::from::<SetAudioInstanceOffset>()
+        alisa::OperationKind::from_invertible::<CreateAudioInstance>(),
+        alisa::OperationKind::from_invertible::<DeleteAudioInstance>(),
+        alisa::OperationKind::from_invertible::<SetAudioInstanceBounds>(),
+        alisa::OperationKind::from_invertible::<SetAudioInstanceOffset>(),
+
+        alisa::OperationKind::from_invertible::<alisa::RestoreSnapshot<Self>>(),
+
+        alisa::OperationKind::from_invertible::<CreateMotionKey>(),
+        alisa::OperationKind::from_invertible::<DeleteMotionKey>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyTime>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyPosition>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyRotation>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyScale>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyOpacity>(),
+        alisa::OperationKind::from_invertible::<SetMotionKeyEasing>()
     ];
 
 }
@@ -23,7 +23,52 @@ pub enum Message {
     Collab(alisa::Message),
     Presence(PresenceData),
     PresenceUpdate(ClientId, PresenceData),
-    Disconnect(ClientId)
+    Disconnect(ClientId),
+    /// Ask the server to store a snapshot of the project under the given name
+    CreateSnapshot(String),
+    /// Ask the server for the snapshots stored in the project
+    ListSnapshots,
+    /// The snapshots stored in the project, sent in response to `CreateSnapshot` and `ListSnapshots`
+    Snapshots(Vec<SnapshotListing>),
+    /// Ask the server to bring the project back to the state of a snapshot, given the snapshot's ID
+    RestoreSnapshot(u64)
+}
+
+/// How a user proves who they are to a server that requires it
+#[derive(alisa::Serializable, Clone)]
+pub enum Credentials {
+    Token(String),
+    Password {
+        username: String,
+        password: String
+    }
+}
+
+/// The first message a client sends after connecting to the server
+#[derive(alisa::Serializable, Default)]
+pub struct ConnectMessage {
+    /// The session to resume, if the client is reconnecting after losing its connection
+    pub resume: Option<alisa::Session>,
+    pub credentials: Option<Credentials>
+}
+
+/// Sent by the server instead of a `WelcomeMessage` if it refuses the connection
+#[derive(alisa::Serializable, Default)]
+pub struct ConnectionRefused {
+    pub reason: String,
+    /// Was the connection refused because the session the client tried to resume expired?
+    /// If so, the client can still join again from scratch.
+    pub session_expired: bool
+}
+
+impl ConnectionRefused {
+
+    /// Try to read a connection refusal from the first message the server sent
+    pub fn from_message(msg: &alisa::ABFValue) -> Option<Self> {
+        msg.get("reason")?;
+        alisa::deserialize(msg)
+    }
+
 }
 
 #[derive(alisa::Serializable, Default)]
@@ -33,4 +78,38 @@ pub struct WelcomeMessage {
     pub presence: Vec<(ClientId, PresenceData)>
 }
 
-pub const PROTOCOL_VERSION: u64 = 1;
+/// A project hosted by a server that hosts a directory of projects.
+/// The server lists them at `/projects`, both as JSON over HTTP and in a single message over a websocket.
+/// Websocket clients start by sending a `ConnectMessage` with their credentials, and get a `ConnectionRefused` instead of the listing if they can't log in.
+#[derive(alisa::Serializable, Default, Clone)]
+pub struct ProjectListing {
+    pub name: String,
+    /// The number of clients connected to the project
+    pub clients: u64
+}
+
+/// A snapshot stored in a project hosted by a server
+#[derive(alisa::Serializable, Default, Clone)]
+pub struct SnapshotListing {
+    pub id: u64,
+    pub name: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub created: u64
+}
+
+impl From<alisa::SnapshotInfo> for SnapshotListing {
+
+    fn from(info: alisa::SnapshotInfo) -> Self {
+        Self {
+            id: info.id,
+            name: info.name,
+            created: info.created
+        }
+    }
+
+}
+
+pub const PROTOCOL_VERSION: u64 = 7;
+
+/// Binary data in messages at least this many bytes long, such as stroke geometry, is compressed
+pub const COMPRESSION_THRESHOLD: usize = 512;
@@ -0,0 +1,117 @@
+use alisa::Object;
+use project::*;
+
+/// Create a project with a layer holding motion keys with the given times, horizontal positions and easings
+fn layer_with_keys(keys: &[(i32, f32, Easing)]) -> (Client, alisa::Ptr<Layer>) {
+    let mut client = Client::local_with_storage(alisa::verter::MemoryStorage::new()).unwrap();
+    let clip = client.next_ptr();
+    let layer = client.next_ptr();
+    client.queue_operation(CreateClip {
+        ptr: clip,
+        parent: alisa::Ptr::null(),
+        data: ClipTreeData {
+            inner_ptr: client.next_ptr(),
+            ..Default::default()
+        }
+    });
+    client.queue_operation(CreateLayer {
+        ptr: layer,
+        parent: LayerParent::Clip(clip),
+        idx: 0,
+        data: LayerTreeData::default()
+    });
+    for (time, x, easing) in keys {
+        client.queue_operation(CreateMotionKey {
+            ptr: client.next_ptr(),
+            parent: layer,
+            idx: (),
+            data: MotionKeyTreeData {
+                time: *time,
+                position: [*x, 0.0],
+                easing: *easing,
+                ..Default::default()
+            }
+        });
+    }
+    client.tick();
+    (client, layer)
+}
+
+fn x_at(client: &Client, layer: alisa::Ptr<Layer>, t: i32) -> f32 {
+    client.get(layer).unwrap().motion_at(client, t).position.x
+}
+
+#[test]
+fn no_keys() {
+    let (client, layer) = layer_with_keys(&[]);
+    let motion = client.get(layer).unwrap().motion_at(&client, 5);
+    assert_eq!((motion.position.x, motion.position.y), (0.0, 0.0));
+    assert_eq!(motion.rotation, 0.0);
+    assert_eq!((motion.scale.x, motion.scale.y), (1.0, 1.0));
+    assert_eq!(motion.opacity, 1.0);
+}
+
+#[test]
+fn keyframe_lookup() {
+    let (client, layer) = layer_with_keys(&[(20, 30.0, Easing::Linear), (0, 10.0, Easing::Linear), (10, 20.0, Easing::Hold)]);
+
+    // Exactly on a key
+    assert_eq!(x_at(&client, layer, 0), 10.0);
+    assert_eq!(x_at(&client, layer, 10), 20.0);
+    assert_eq!(x_at(&client, layer, 20), 30.0);
+    assert!(client.get(layer).unwrap().motion_key_at(&client, 10).is_some());
+    assert!(client.get(layer).unwrap().motion_key_at(&client, 5).is_none());
+
+    // Between keys, using the easing of the earlier key
+    assert_eq!(x_at(&client, layer, 5), 15.0);
+    assert_eq!(x_at(&client, layer, 15), 20.0);
+    assert_eq!(x_at(&client, layer, 19), 20.0);
+}
+
+#[test]
+fn before_first_and_after_last_key() {
+    let (client, layer) = layer_with_keys(&[(5, 10.0, Easing::Linear), (15, 20.0, Easing::EaseIn)]);
+    assert_eq!(x_at(&client, layer, -10), 10.0);
+    assert_eq!(x_at(&client, layer, 4), 10.0);
+    assert_eq!(x_at(&client, layer, 16), 20.0);
+    assert_eq!(x_at(&client, layer, 100), 20.0);
+}
+
+#[test]
+fn eased_interpolation() {
+    let (client, layer) = layer_with_keys(&[(0, 0.0, Easing::EaseIn), (10, 100.0, Easing::Linear)]);
+    assert_eq!(x_at(&client, layer, 5), 25.0);
+}
+
+#[test]
+fn easing_endpoints() {
+    for easing in Easing::ALL {
+        assert_eq!(easing.apply(0.0), 0.0, "{}", easing.name());
+        let end = if easing == Easing::Hold { 0.0 } else { 1.0 };
+        assert_eq!(easing.apply(1.0), end, "{}", easing.name());
+
+        // Times outside of the range between the keys are clamped
+        assert_eq!(easing.apply(-1.0), 0.0, "{}", easing.name());
+        assert_eq!(easing.apply(2.0), end, "{}", easing.name());
+    }
+}
+
+#[test]
+fn easing_midpoints() {
+    assert_eq!(Easing::Linear.apply(0.5), 0.5);
+    assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+    assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    assert_eq!(Easing::Hold.apply(0.5), 0.0);
+}
+
+#[test]
+fn layers_from_before_motion_keys_migrate() {
+    let old_layer = alisa::ABFValue::Map(Box::new([
+        ("name".to_owned(), alisa::ABFValue::Str("Old Layer".into())),
+        ("frames".to_owned(), alisa::ABFValue::Array(Box::new([])))
+    ]));
+    let layer = alisa::migrate(old_layer, 0, Layer::MIGRATIONS).unwrap();
+    assert!(layer.get("motion_keys").and_then(|keys| keys.as_array()).is_some_and(|keys| keys.is_empty()));
+    assert_eq!(layer.get("name").and_then(|name| name.as_string()), Some("Old Layer"));
+}
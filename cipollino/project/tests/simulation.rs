@@ -0,0 +1,63 @@
+
+use project::*;
+
+macro_rules! fuzz_operations {
+    ($simulation: ident, $($operation: ty),* $(,)?) => {
+        $(
+            $simulation.fuzz::<$operation>();
+        )*
+    };
+}
+
+fn simulation(clients: usize, seed: u64) -> alisa::Simulation<Project> {
+    let mut simulation = alisa::Simulation::new(clients, seed);
+    fuzz_operations!(simulation,
+        CreateFolder, DeleteFolder, RenameFolder, TransferFolder,
+        CreateClip, DeleteClip, RenameClip, TransferClip,
+        CreateClipInner, SetClipInnerWidth, SetClipInnerHeight, SetClipInnerLength, SetClipInnerFramerate, SetClipInnerBackgroundColor, AddPaletteToClip, RemovePaletteFromClip,
+        CreateLayer, DeleteLayer, TransferLayer, SetLayerName,
+        CreateLayerGroup, DeleteLayerGroup, TransferLayerGroup, SetLayerGroupName,
+        CreateFrame, DeleteFrame, SetFrameTime,
+        CreateStroke, DeleteStroke, SetStrokeStroke, SetStrokeColor,
+        CreateFill, DeleteFill, SetFillPaths, SetFillColor,
+        CreatePalette, DeletePalette, RenamePalette, TransferPalette,
+        CreatePaletteInner,
+        CreateColor, DeleteColor, SetColorColor, SetColorName,
+        CreateAudioLayer, DeleteAudioLayer, TransferAudioLayer, SetAudioLayerName,
+        CreateAudioClip, DeleteAudioClip, TransferAudioClip, RenameAudioClip, AddBlockToAudioClip,
+        CreateAudioInstance, DeleteAudioInstance, SetAudioInstanceBounds, SetAudioInstanceOffset,
+        CreateMotionKey, DeleteMotionKey, SetMotionKeyTime, SetMotionKeyPosition, SetMotionKeyRotation, SetMotionKeyScale, SetMotionKeyOpacity, SetMotionKeyEasing,
+        alisa::RestoreSnapshot<Project>,
+    );
+    assert!(simulation.unregistered_operations().is_empty(), "operations missing from the simulation: {:?}", simulation.unregistered_operations());
+    simulation
+}
+
+#[test]
+fn random_operations_converge() {
+
+    for seed in 0..16 {
+        let mut simulation = simulation(3, seed);
+        simulation.run(400);
+        simulation.settle();
+        if let Err(err) = simulation.check_convergence() {
+            panic!("seed {}: {}", seed, err);
+        }
+    }
+
+}
+
+#[test]
+fn random_operations_converge_with_disconnects() {
+
+    for seed in 0..16 {
+        let mut simulation = simulation(3, seed);
+        simulation.set_disconnect_chance(0.02);
+        simulation.run(400);
+        simulation.settle();
+        if let Err(err) = simulation.check_convergence() {
+            panic!("seed {}: {}", seed, err);
+        }
+    }
+
+}
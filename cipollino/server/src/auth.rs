@@ -0,0 +1,149 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use argon2::{password_hash::{rand_core::OsRng, SaltString}, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use base64::Engine;
+use project::Credentials;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// What a user can do with a part of the project
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// The user can't open it
+    None,
+    /// The user can open it, but not modify it
+    #[default]
+    ReadOnly,
+    Edit
+}
+
+#[derive(serde::Deserialize)]
+pub struct User {
+    pub name: String,
+    /// The user's password, hashed with Argon2 and stored as a PHC string. See `hash_password`.
+    #[serde(default)]
+    pub password_hash: Option<String>,
+    /// The SHA-256 hash of the user's token, in hex. See `hash_token`.
+    #[serde(default)]
+    pub token_hash: Option<String>,
+    /// The user's access to everything not covered by `folders` or `clips`
+    #[serde(default)]
+    pub access: Access,
+    /// The user's access to folders and everything inside them, by the folder's path, e.g. "Characters/Bob"
+    #[serde(default)]
+    pub folders: HashMap<String, Access>,
+    /// The user's access to clips, by the clip's path, e.g. "Characters/Bob/Walk Cycle"
+    #[serde(default)]
+    pub clips: HashMap<String, Access>
+}
+
+impl User {
+
+    fn check_password(&self, password: &str) -> bool {
+        let Some(password_hash) = self.password_hash.as_ref().and_then(|hash| PasswordHash::new(hash).ok()) else {
+            return false;
+        };
+        Argon2::default().verify_password(password.as_bytes(), &password_hash).is_ok()
+    }
+
+    /// Compare the hash of a token with the user's token hash, taking the same time no matter where they differ
+    fn check_token_hash(&self, token_hash: &str) -> bool {
+        let Some(user_token_hash) = &self.token_hash else {
+            return false;
+        };
+        user_token_hash.to_ascii_lowercase().as_bytes().ct_eq(token_hash.as_bytes()).into()
+    }
+
+}
+
+/// Hash a password for the `password_hash` of a user in the auth config
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default().hash_password(password.as_bytes(), &salt).map(|hash| hash.to_string()).map_err(|err| err.to_string())
+}
+
+/// Hash a token for the `token_hash` of a user in the auth config.
+/// Tokens are meant to be long and random, so a fast hash is enough to keep them from leaking along with the config.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The auth config file, as it's stored on disk
+#[derive(serde::Deserialize)]
+struct AuthConfigFile {
+    users: Vec<User>
+}
+
+/// The users allowed to connect to the server, loaded from a JSON config file
+pub struct AuthConfig {
+    users: Vec<Arc<User>>
+}
+
+impl AuthConfig {
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let config: AuthConfigFile = serde_json::from_str(&data).map_err(|err| err.to_string())?;
+
+        // Catch mistakes in the config up front, instead of refusing every login
+        for user in &config.users {
+            if let Some(password_hash) = &user.password_hash {
+                PasswordHash::new(password_hash).map_err(|err| format!("invalid password hash for user {}: {}", user.name, err))?;
+            }
+            if let Some(token_hash) = &user.token_hash {
+                if token_hash.len() != 64 || !token_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("invalid token hash for user {}", user.name));
+                }
+            }
+        }
+
+        Ok(Self {
+            users: config.users.into_iter().map(Arc::new).collect()
+        })
+    }
+
+    /// Find the user the credentials belong to
+    pub fn authenticate(&self, credentials: &Credentials) -> Option<Arc<User>> {
+        match credentials {
+            Credentials::Token(token) => {
+                let token_hash = hash_token(token);
+                self.users.iter().find(|user| user.check_token_hash(&token_hash)).cloned()
+            },
+            Credentials::Password { username, password } => {
+                self.users.iter().find(|user| user.name == *username && user.check_password(password)).cloned()
+            }
+        }
+    }
+
+}
+
+/// Find the user a client logs in as.
+/// Returns None if the server doesn't require logging in, or the reason the client is turned away if its credentials are missing or invalid.
+pub fn log_in(auth: Option<&AuthConfig>, credentials: Option<&Credentials>) -> Result<Option<Arc<User>>, String> {
+    let Some(auth) = auth else {
+        return Ok(None);
+    };
+    let Some(credentials) = credentials else {
+        return Err("This server requires logging in.".to_owned());
+    };
+    auth.authenticate(credentials).map(Some).ok_or("Invalid username, password or token.".to_owned())
+}
+
+/// Read the credentials from the `Authorization` header of an HTTP request, either a `Bearer` token or a `Basic` username and password
+pub fn credentials_from_header(header: &str) -> Option<Credentials> {
+    let (scheme, value) = header.trim().split_once(' ')?;
+    let value = value.trim();
+    if scheme.eq_ignore_ascii_case("bearer") {
+        return Some(Credentials::Token(value.to_owned()));
+    }
+    if scheme.eq_ignore_ascii_case("basic") {
+        let decoded = base64::engine::general_purpose::STANDARD.decode(value).ok()?;
+        let (username, password) = std::str::from_utf8(&decoded).ok()?.split_once(':')?;
+        return Some(Credentials::Password {
+            username: username.to_owned(),
+            password: password.to_owned()
+        });
+    }
+    None
+}
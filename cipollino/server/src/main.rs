@@ -7,20 +7,114 @@ use tokio::sync::Mutex;
 mod server;
 use server::*;
 
+mod auth;
+use auth::*;
+
+mod permissions;
+use permissions::*;
+
+mod projects;
+use projects::*;
+
+#[cfg(test)]
+mod test;
+
 #[derive(clap::Parser)]
 #[command(about, long_about = None)]
 struct Args {
     #[arg(long, default_value = "8000")]
     port: u16,
+    /// The project to host
     #[arg(long, default_value = "project.cip")]
-    path: PathBuf
+    path: PathBuf,
+    /// A directory of projects to host instead of a single one.
+    /// Clients connect to `/projects/<name>` to open `<name>.cip`, and `/projects` lists the available projects.
+    /// With `--auth`, listing the projects requires logging in too.
+    #[arg(long)]
+    dir: Option<PathBuf>,
+    /// A JSON file listing the users that can connect and what they can access.
+    /// Without it, anyone can connect and edit anything.
+    #[arg(long)]
+    auth: Option<PathBuf>,
+    /// Read a password from stdin and print its hash, for the `password_hash` of a user in the auth config
+    #[arg(long)]
+    hash_password: bool,
+    /// Read a token from stdin and print its hash, for the `token_hash` of a user in the auth config
+    #[arg(long)]
+    hash_token: bool
+}
+
+/// Read a single line from stdin, without the line break
+fn read_secret() -> String {
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line).expect("could not read from stdin");
+    line.trim_end_matches(['\r', '\n']).to_owned()
+}
+
+/// The routes of a server hosting a directory of projects
+fn project_routes(projects: Arc<Mutex<Projects>>, auth: Option<Arc<AuthConfig>>) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let list_projects = {
+        let projects = projects.clone();
+        warp::path!("projects").and(warp::get()).and(warp::header::optional::<String>("authorization")).then(move |authorization: Option<String>| {
+            let projects = projects.clone();
+            let auth = auth.clone();
+            async move {
+                let credentials = authorization.as_deref().and_then(credentials_from_header);
+                match log_in(auth.as_deref(), credentials.as_ref()) {
+                    Ok(_) => warp::reply::with_status(warp::reply::json(&projects.lock().await.list_json()), warp::http::StatusCode::OK),
+                    Err(reason) => warp::reply::with_status(warp::reply::json(&serde_json::json!({ "error": reason })), warp::http::StatusCode::UNAUTHORIZED)
+                }
+            }
+        })
+    };
+
+    let list_projects_socket = {
+        let projects = projects.clone();
+        warp::path!("projects").and(warp::ws()).map(move |socket: warp::ws::Ws| {
+            let projects = projects.clone();
+            socket.on_upgrade(move |socket| {
+                Projects::send_list(projects, socket)
+            })
+        })
+    };
+
+    let project_server = warp::path!("projects" / String).and(warp::ws()).map(move |name: String, socket: warp::ws::Ws| {
+        let projects = projects.clone();
+        socket.on_upgrade(move |socket| {
+            Projects::handle_connection(projects, name, socket)
+        })
+    });
+
+    project_server.or(list_projects_socket).or(list_projects)
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
 
-    let server = Arc::new(Mutex::new(Server::new(args.path)));
+    if args.hash_password {
+        println!("{}", hash_password(&read_secret()).unwrap_or_else(|err| panic!("could not hash password: {}", err)));
+        return;
+    }
+    if args.hash_token {
+        println!("{}", hash_token(&read_secret()));
+        return;
+    }
+
+    let auth = args.auth.map(|path| Arc::new(AuthConfig::load(&path).unwrap_or_else(|err| panic!("could not load auth config: {}", err))));
+
+    let address = std::net::IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1));
+    let address = std::net::SocketAddr::new(address, args.port);
+
+    if let Some(dir) = args.dir {
+        let projects = Arc::new(Mutex::new(Projects::new(dir, auth.clone())));
+        warp::serve(project_routes(projects, auth))
+            .run(address)
+            .await;
+        return;
+    }
+
+    let server = Arc::new(Mutex::new(Server::new(args.path, auth).expect("could not open project")));
 
     let websocket_server = warp::ws().map(move |socket: warp::ws::Ws| {
         let server = server.clone();
@@ -31,9 +125,6 @@ async fn main() {
 
     let routes = websocket_server;
 
-    let address = std::net::IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1));
-    let address = std::net::SocketAddr::new(address, args.port);
-
     warp::serve(routes)
         .run(address)
         .await;
@@ -0,0 +1,173 @@
+
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use project::{alisa::{self, Object}, AudioBlock, AudioClip, AudioInstance, AudioLayer, Clip, ClipInner, ClientId, Color, ColorParent, Fill, Folder, Frame, Layer, LayerGroup, LayerParent, MotionKey, Palette, PaletteInner, Project, Ptr, Stroke};
+
+use crate::{Access, User};
+
+/// The part of the project whose access settings apply to an object
+enum Owner {
+    Root,
+    Folder(Ptr<Folder>),
+    Clip(Ptr<Clip>)
+}
+
+type Context<'a> = alisa::ProjectContext<'a, Project>;
+
+fn folder_owner(folder: Ptr<Folder>) -> Owner {
+    if folder.is_null() {
+        Owner::Root
+    } else {
+        Owner::Folder(folder)
+    }
+}
+
+fn layer_parent_owner(context: &Context, parent: LayerParent) -> Option<Owner> {
+    match parent {
+        LayerParent::Clip(clip) => Some(Owner::Clip(clip)),
+        LayerParent::LayerGroup(group) => layer_parent_owner(context, context.obj_list().get(group)?.parent),
+    }
+}
+
+fn frame_owner(context: &Context, frame: Ptr<Frame>) -> Option<Owner> {
+    let layer = context.obj_list().get(frame)?.layer;
+    layer_parent_owner(context, context.obj_list().get(layer)?.parent)
+}
+
+/// Find which part of the project an object belongs to.
+/// Returns None if an object along the way isn't loaded.
+fn owner(context: &Context, ptr: alisa::AnyPtr) -> Option<Owner> {
+    let key = ptr.key();
+    let obj_type = ptr.obj_type();
+    if obj_type == Folder::TYPE_ID {
+        Some(Owner::Folder(Ptr::from_key(key)))
+    } else if obj_type == Clip::TYPE_ID {
+        Some(Owner::Clip(Ptr::from_key(key)))
+    } else if obj_type == ClipInner::TYPE_ID {
+        let inner = Ptr::<ClipInner>::from_key(key);
+        context.obj_list::<Clip>().iter().find(|(_, clip)| clip.inner.ptr() == inner).map(|(clip, _)| Owner::Clip(clip))
+    } else if obj_type == Layer::TYPE_ID {
+        layer_parent_owner(context, context.obj_list::<Layer>().get(Ptr::from_key(key))?.parent)
+    } else if obj_type == LayerGroup::TYPE_ID {
+        layer_parent_owner(context, context.obj_list::<LayerGroup>().get(Ptr::from_key(key))?.parent)
+    } else if obj_type == AudioLayer::TYPE_ID {
+        layer_parent_owner(context, context.obj_list::<AudioLayer>().get(Ptr::from_key(key))?.parent)
+    } else if obj_type == AudioInstance::TYPE_ID {
+        let layer = context.obj_list::<AudioInstance>().get(Ptr::from_key(key))?.layer;
+        layer_parent_owner(context, context.obj_list().get(layer)?.parent)
+    } else if obj_type == MotionKey::TYPE_ID {
+        let layer = context.obj_list::<MotionKey>().get(Ptr::from_key(key))?.layer;
+        layer_parent_owner(context, context.obj_list().get(layer)?.parent)
+    } else if obj_type == Frame::TYPE_ID {
+        frame_owner(context, Ptr::from_key(key))
+    } else if obj_type == Stroke::TYPE_ID {
+        frame_owner(context, context.obj_list::<Stroke>().get(Ptr::from_key(key))?.frame)
+    } else if obj_type == Fill::TYPE_ID {
+        frame_owner(context, context.obj_list::<Fill>().get(Ptr::from_key(key))?.frame)
+    } else if obj_type == Palette::TYPE_ID {
+        Some(folder_owner(context.obj_list::<Palette>().get(Ptr::from_key(key))?.folder))
+    } else if obj_type == PaletteInner::TYPE_ID {
+        let palette = context.obj_list::<PaletteInner>().get(Ptr::from_key(key))?.palette;
+        Some(folder_owner(context.obj_list().get(palette)?.folder))
+    } else if obj_type == Color::TYPE_ID {
+        match context.obj_list::<Color>().get(Ptr::from_key(key))?.parent {
+            ColorParent::Clip(clip) => Some(Owner::Clip(clip)),
+            ColorParent::Palette(palette) => Some(folder_owner(context.obj_list().get(palette)?.folder)),
+        }
+    } else if obj_type == AudioClip::TYPE_ID {
+        Some(folder_owner(context.obj_list::<AudioClip>().get(Ptr::from_key(key))?.folder))
+    } else if obj_type == AudioBlock::TYPE_ID {
+        let block = Ptr::<AudioBlock>::from_key(key);
+        context.obj_list::<AudioClip>().iter().find(|(_, clip)| clip.blocks.iter().any(|(_, clip_block)| clip_block.ptr() == block)).map(|(_, clip)| folder_owner(clip.folder))
+    } else {
+        None
+    }
+}
+
+/// The names of the folders from the root of the project down to `folder`.
+/// Returns None if one of the folders isn't loaded.
+fn folder_names(context: &Context, mut folder: Ptr<Folder>) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    while !folder.is_null() {
+        let folder_data = context.obj_list().get(folder)?;
+        names.push(folder_data.name.clone());
+        folder = folder_data.parent;
+    }
+    names.reverse();
+    Some(names)
+}
+
+impl User {
+
+    /// The access to the folder with the given path, taking the folders it's inside of into account
+    fn folder_access(&self, names: &[String]) -> Access {
+        for depth in (1..=names.len()).rev() {
+            if let Some(access) = self.folders.get(&names[..depth].join("/")) {
+                return *access;
+            }
+        }
+        self.access
+    }
+
+    /// The access to a part of the project. Returns None if an object needed to find the access isn't loaded.
+    fn owner_access(&self, context: &Context, owner: Owner) -> Option<Access> {
+        match owner {
+            Owner::Root => Some(self.access),
+            Owner::Folder(folder) => Some(self.folder_access(&folder_names(context, folder)?)),
+            Owner::Clip(clip) => {
+                let clip = context.obj_list().get(clip)?;
+                let mut names = folder_names(context, clip.folder)?;
+                names.push(clip.name.clone());
+                if let Some(access) = self.clips.get(&names.join("/")) {
+                    return Some(*access);
+                }
+                Some(self.folder_access(&names[..(names.len() - 1)]))
+            }
+        }
+    }
+
+    /// The access to an object, or to the project itself if `ptr` is None.
+    /// If the access can't be found because some of the objects the object belongs to aren't loaded, the user can't access the object at all, since the folder or clip it is in might be off limits.
+    fn object_access(&self, context: &Context, ptr: Option<alisa::AnyPtr>) -> Access {
+        let owner = match ptr {
+            Some(ptr) => owner(context, ptr),
+            None => Some(Owner::Root),
+        };
+        owner.and_then(|owner| self.owner_access(context, owner)).unwrap_or(Access::None)
+    }
+
+}
+
+/// Restricts what each client can modify and open, based on the user they logged in as
+pub struct UserPermissions {
+    users: Arc<Mutex<HashMap<ClientId, Arc<User>>>>
+}
+
+impl UserPermissions {
+
+    pub fn new(users: Arc<Mutex<HashMap<ClientId, Arc<User>>>>) -> Self {
+        Self {
+            users
+        }
+    }
+
+    fn access(&self, client: ClientId, context: &Context, ptr: Option<alisa::AnyPtr>) -> Access {
+        let Some(user) = self.users.lock().unwrap().get(&client).cloned() else {
+            return Access::None;
+        };
+        user.object_access(context, ptr)
+    }
+
+}
+
+impl alisa::Permissions<Project> for UserPermissions {
+
+    fn can_modify(&self, client: ClientId, context: &Context, ptr: Option<alisa::AnyPtr>) -> bool {
+        self.access(client, context, ptr) == Access::Edit
+    }
+
+    fn can_load(&self, client: ClientId, context: &Context, ptr: alisa::AnyPtr) -> bool {
+        self.access(client, context, Some(ptr)) != Access::None
+    }
+
+}
@@ -0,0 +1,148 @@
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use project::{alisa, ProjectListing};
+use tokio::sync::Mutex;
+use warp::ws;
+
+use crate::{log_in, AuthConfig, Client, Server};
+
+/// How long a project stays open after everyone disconnected from it.
+/// Keeps the project open for as long as the server keeps the sessions of disconnected clients, so that they can resume them.
+const CLOSE_DELAY: Duration = alisa::SESSION_TIMEOUT;
+
+struct OpenProject {
+    server: Arc<Mutex<Server>>,
+    /// The number of connections to the project, including ones that haven't finished joining yet
+    connections: usize
+}
+
+/// A directory of projects hosted by the server.
+/// Projects are opened when the first client connects to them, and closed once everyone leaves.
+pub struct Projects {
+    dir: PathBuf,
+    auth: Option<Arc<AuthConfig>>,
+    open: HashMap<String, OpenProject>
+}
+
+impl Projects {
+
+    pub fn new(dir: PathBuf, auth: Option<Arc<AuthConfig>>) -> Self {
+        Self {
+            dir,
+            auth,
+            open: HashMap::new()
+        }
+    }
+
+    /// The path of the project with the given name, if it exists
+    fn project_path(&self, name: &str) -> Option<PathBuf> {
+        // Don't let clients reach outside the projects directory
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return None;
+        }
+        let path = self.dir.join(format!("{}.cip", name));
+        path.is_file().then_some(path)
+    }
+
+    pub fn list(&self) -> Vec<ProjectListing> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut projects = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "cip"))
+            .filter_map(|path| path.file_stem()?.to_str().map(str::to_owned))
+            .filter(|name| self.project_path(name).is_some())
+            .map(|name| ProjectListing {
+                clients: self.open.get(&name).map(|project| project.connections as u64).unwrap_or(0),
+                name
+            })
+            .collect::<Vec<_>>();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        projects
+    }
+
+    /// Get the server for a project, opening it if nobody is connected to it yet
+    fn connect(&mut self, name: &str) -> Option<Arc<Mutex<Server>>> {
+        if let Some(project) = self.open.get_mut(name) {
+            project.connections += 1;
+            return Some(project.server.clone());
+        }
+
+        let server = Arc::new(Mutex::new(Server::new(self.project_path(name)?, self.auth.clone())?));
+        println!("Opened project {}.", name);
+        self.open.insert(name.to_owned(), OpenProject {
+            server: server.clone(),
+            connections: 1
+        });
+        Some(server)
+    }
+
+    fn disconnect(&mut self, name: &str) {
+        if let Some(project) = self.open.get_mut(name) {
+            project.connections = project.connections.saturating_sub(1);
+        }
+    }
+
+    /// Close the project if nobody is connected to it
+    fn close_if_unused(&mut self, name: &str) {
+        if self.open.get(name).is_some_and(|project| project.connections == 0) {
+            self.open.remove(name);
+            println!("Closed project {}.", name);
+        }
+    }
+
+    /// The project listing as JSON
+    pub fn list_json(&self) -> serde_json::Value {
+        self.list().into_iter().map(|project| serde_json::json!({
+            "name": project.name,
+            "clients": project.clients
+        })).collect()
+    }
+
+    /// Send the project listing over a websocket, for clients that can't make HTTP requests.
+    /// Like when connecting to a project, the client starts by sending a `ConnectMessage` with its credentials.
+    pub async fn send_list(projects: Arc<Mutex<Self>>, socket: ws::WebSocket) {
+        let (sender, mut receiver) = socket.split();
+        let Some(connect_msg) = Server::read_connect_message(&mut receiver).await else { return; };
+        let mut client = Client::new(sender);
+        let auth = projects.lock().await.auth.clone();
+        if let Err(reason) = log_in(auth.as_deref(), connect_msg.credentials.as_ref()) {
+            Server::refuse(&mut client, reason, false).await;
+            return;
+        }
+
+        let list = projects.lock().await.list();
+        client.send(alisa::serialize(&list)).await;
+        client.close().await;
+    }
+
+    pub async fn handle_connection(projects: Arc<Mutex<Self>>, name: String, socket: ws::WebSocket) {
+        let Some(name) = percent_encoding::percent_decode_str(&name).decode_utf8().ok().map(|name| name.into_owned()) else { return; };
+
+        // Check who the client is before opening the project, so that strangers can't keep projects open
+        let (sender, mut receiver) = socket.split();
+        let Some(connect_msg) = Server::read_connect_message(&mut receiver).await else { return; };
+        let mut client = Client::new(sender);
+        let auth = projects.lock().await.auth.clone();
+        if let Err(reason) = log_in(auth.as_deref(), connect_msg.credentials.as_ref()) {
+            Server::refuse(&mut client, reason, false).await;
+            return;
+        }
+
+        let Some(server) = projects.lock().await.connect(&name) else {
+            println!("Could not open project {}.", name);
+            return;
+        };
+
+        Server::handle_client(server, client, receiver, connect_msg).await;
+
+        projects.lock().await.disconnect(&name);
+        tokio::time::sleep(CLOSE_DELAY).await;
+        projects.lock().await.close_if_unused(&name);
+    }
+
+}
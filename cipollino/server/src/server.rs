@@ -1,14 +1,20 @@
 
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use project::{alisa::{self, ABFValue}, ClientId, Message, PresenceData, WelcomeMessage, PROTOCOL_VERSION};
+use project::{alisa::{self, ABFValue}, ClientId, ConnectMessage, ConnectionRefused, Message, PresenceData, SnapshotListing, WelcomeMessage, COMPRESSION_THRESHOLD, PROTOCOL_VERSION};
 use warp::ws;
 use futures::SinkExt;
 use tokio::sync::Mutex;
 
+use crate::{log_in, Access, AuthConfig, User, UserPermissions};
+
 pub struct Server {
     server: project::Server,
-    clients: HashMap<ClientId, Client>
+    clients: HashMap<ClientId, Client>,
+    /// The users that can connect. If None, anyone can connect.
+    auth: Option<Arc<AuthConfig>>,
+    /// The user each client logged in as
+    users: Arc<std::sync::Mutex<HashMap<ClientId, Arc<User>>>>
 }
 
 pub struct Client {
@@ -18,20 +24,64 @@ pub struct Client {
 
 impl Client {
 
-    async fn send(&mut self, msg: ABFValue) -> bool {
-        let data = alisa::encode_abf(&msg);
+    pub fn new(sender: futures::stream::SplitSink<ws::WebSocket, ws::Message>) -> Self {
+        Self {
+            sender,
+            presence: Default::default()
+        }
+    }
+
+    pub async fn send(&mut self, msg: ABFValue) -> bool {
+        let data = alisa::encode_abf_compressed(&msg, COMPRESSION_THRESHOLD);
         self.sender.send(ws::Message::binary(data)).await.is_ok()
     }
 
+    pub async fn close(&mut self) {
+        let _ = self.sender.close().await;
+    }
+
 }
 
 impl Server {
 
-    pub fn new(path: PathBuf) -> Self {
-        Self {
-            server: project::Server::new(path).expect("could not open project"),
-            clients: HashMap::new()
+    pub fn new(path: PathBuf, auth: Option<Arc<AuthConfig>>) -> Option<Self> {
+        let mut server = project::Server::new(path)?;
+        let users = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        if auth.is_some() {
+            server.set_permissions(UserPermissions::new(users.clone()));
+        }
+        Some(Self {
+            server,
+            clients: HashMap::new(),
+            auth,
+            users
+        })
+    }
+
+    /// Check the credentials the client connected with.
+    /// Returns the reason the connection is refused if they're invalid.
+    fn authenticate(&self, connect_msg: &ConnectMessage) -> Result<Option<Arc<User>>, String> {
+        let Some(user) = log_in(self.auth.as_deref(), connect_msg.credentials.as_ref())? else {
+            return Ok(None);
+        };
+
+        // Make sure nobody takes over another user's session
+        // Expired sessions no longer have a user, but they can't be resumed anyway
+        if let Some(session) = connect_msg.resume.as_ref().filter(|session| self.server.has_session(session.id)) {
+            let session_user = self.users.lock().unwrap().get(&session.id).map(|user| user.name.clone());
+            if session_user.as_ref() != Some(&user.name) {
+                return Err("The session belongs to a different user.".to_owned());
+            }
         }
+
+        Ok(Some(user))
+    }
+
+    /// Forget the sessions that expired, along with the users they were logged in as
+    fn expire_sessions(&mut self) {
+        self.server.expire_sessions();
+        let server = &self.server;
+        self.users.lock().unwrap().retain(|client_id, _| server.has_session(*client_id));
     }
 
     async fn process_message(&mut self, client_id: ClientId, msg: &Message) {
@@ -50,10 +100,48 @@ impl Server {
                     client.presence = presence_data.clone();
                 }
             },
+            Message::CreateSnapshot(name) => {
+                if self.can_manage_snapshots(client_id) {
+                    if let Some(snapshot) = self.server.create_snapshot(name) {
+                        println!("Created snapshot {}.", snapshot.name);
+                    }
+                }
+                self.send_snapshots(client_id).await;
+            },
+            Message::ListSnapshots => {
+                self.send_snapshots(client_id).await;
+            },
+            Message::RestoreSnapshot(id) => {
+                if !self.can_manage_snapshots(client_id) {
+                    return;
+                }
+                let Some(snapshot) = self.server.snapshot(*id) else { return; };
+                if self.server.restore_snapshot(client_id, &snapshot) {
+                    println!("Restored snapshot {}.", id);
+                } else {
+                    println!("Could not restore snapshot {}.", id);
+                }
+            },
             _ => {}
         }
     }
 
+    /// Restoring a snapshot changes the whole project, so managing snapshots requires being able to edit the project itself
+    fn can_manage_snapshots(&self, client_id: ClientId) -> bool {
+        if self.auth.is_none() {
+            return true;
+        }
+        self.users.lock().unwrap().get(&client_id).is_some_and(|user| user.access == Access::Edit)
+    }
+
+    async fn send_snapshots(&mut self, client_id: ClientId) {
+        let snapshots = self.server.snapshots().into_iter().map(SnapshotListing::from).collect();
+        let msg = self.server.serialize(client_id, &Message::Snapshots(snapshots));
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.send(msg).await;
+        }
+    }
+
     async fn receive_message(&mut self, client_id: ClientId, msg: alisa::ABFValue) {
         if let Some(msgs) = msg.as_array() {
             for submsg in msgs {
@@ -65,8 +153,16 @@ impl Server {
             self.process_message(client_id, &msg).await;
         }
 
-        // Send outgoing messages
-        for (client_id, msgs) in self.server.take_all_msgs_to_send() {
+        self.expire_sessions();
+        self.send_outgoing_messages().await;
+    }
+
+    async fn send_outgoing_messages(&mut self) {
+        let msgs_to_send = self.server.take_all_msgs_to_send();
+        if let Some(err) = self.server.take_log_error() {
+            println!("Could not write to the operation log: {}", err);
+        }
+        for (client_id, msgs) in msgs_to_send {
             if let Some(client) = self.clients.get_mut(&client_id) {
                 if !msgs.is_empty() {
                     client.send(
@@ -81,27 +177,94 @@ impl Server {
         }
     }
 
+    /// Let a client know why its connection was refused
+    pub async fn refuse(client: &mut Client, reason: String, session_expired: bool) {
+        println!("Refused connection: {}", reason);
+        client.send(alisa::serialize(&ConnectionRefused { reason, session_expired })).await;
+    }
+
+    /// Add a client that connected to the server, either by resuming its session or by joining from scratch.
+    /// Returns `None` if the connection was refused.
+    async fn add_client(&mut self, mut client: Client, connect_msg: ConnectMessage) -> Option<ClientId> {
+        self.expire_sessions();
+        let user = match self.authenticate(&connect_msg) {
+            Ok(user) => user,
+            Err(reason) => {
+                Self::refuse(&mut client, reason, false).await;
+                return None;
+            },
+        };
+
+        let client_id = if let Some(session) = connect_msg.resume {
+            match self.server.resume_client(&session) {
+                Ok(()) => {},
+                Err(alisa::ResumeError::Expired) => {
+                    // The client can fall back to joining from scratch
+                    Self::refuse(&mut client, format!("The session of client {:?} expired.", session.id), true).await;
+                    return None;
+                },
+                Err(alisa::ResumeError::StillConnected) => {
+                    // The client keeps retrying until we notice its old connection was closed
+                    println!("Refused connection: client {:?} is still connected.", session.id);
+                    return None;
+                }
+            }
+            println!("Client {:?} reconnected.", session.id);
+
+            // Let the client know who else is here, since it might have missed some presence updates
+            for (other_client_id, other_client) in &self.clients {
+                client.send(self.server.serialize(session.id, &Message::PresenceUpdate(*other_client_id, other_client.presence.clone()))).await;
+            }
+
+            session.id
+        } else {
+            match &user {
+                Some(user) => println!("{} connected.", user.name),
+                None => println!("New client connected.")
+            }
+            let (client_id, welcome_msg) = self.server.add_client();
+            if let Some(user) = user {
+                self.users.lock().unwrap().insert(client_id, user);
+            }
+            let welcome_msg = WelcomeMessage {
+                collab: welcome_msg,
+                version: PROTOCOL_VERSION,
+                presence: self.clients.iter().map(|(id, client)| (*id, client.presence.clone())).collect(),
+            };
+            client.send(self.server.serialize(client_id, &welcome_msg)).await;
+            client_id
+        };
+
+        self.clients.insert(client_id, client);
+
+        // Send the operations a resumed client missed
+        self.send_outgoing_messages().await;
+
+        Some(client_id)
+    }
+
+    /// Read the first message a client sends, telling us whether it's joining or resuming a session
+    pub async fn read_connect_message(receiver: &mut futures::stream::SplitStream<ws::WebSocket>) -> Option<ConnectMessage> {
+        use futures::StreamExt;
+        match receiver.next().await {
+            Some(Ok(msg)) => alisa::parse_abf(msg.as_bytes()).and_then(|msg| alisa::deserialize::<ConnectMessage>(&msg)),
+            _ => None
+        }
+    }
+
     pub async fn handle_connection(server_arc: Arc<Mutex<Self>>, socket: ws::WebSocket) {
         use futures::StreamExt;
-        println!("New client connected.");
 
-        let mut server = server_arc.lock().await;
         let (sender, mut receiver) = socket.split();
-        let (client_id, welcome_msg) = server.server.add_client();
-        let welcome_msg = WelcomeMessage {
-            collab: welcome_msg,
-            version: PROTOCOL_VERSION,
-            presence: server.clients.iter().map(|(id, client)| (*id, client.presence.clone())).collect(),
-        };
+        let Some(connect_msg) = Self::read_connect_message(&mut receiver).await else { return; };
+        Self::handle_client(server_arc, Client::new(sender), receiver, connect_msg).await;
+    }
 
-        let mut client = Client {
-            sender,
-            presence: Default::default() 
-        };
+    /// Serve a client that already sent its connect message, until it disconnects
+    pub async fn handle_client(server_arc: Arc<Mutex<Self>>, client: Client, mut receiver: futures::stream::SplitStream<ws::WebSocket>, connect_msg: ConnectMessage) {
+        use futures::StreamExt;
 
-        client.send(server.server.serialize(client_id, &welcome_msg)).await;
-        server.clients.insert(client_id, client);
-        drop(server);
+        let Some(client_id) = server_arc.lock().await.add_client(client, connect_msg).await else { return; };
 
         while let Some(Ok(msg)) = receiver.next().await {
             let data = msg.as_bytes();
@@ -118,6 +281,9 @@ impl Server {
         let clients = &mut server.clients;
         let server = &mut server.server;
 
+        // Keep the client's session around, so it can resume it if it reconnects
+        server.disconnect_client(client_id);
+
         // Tell the other clients this client disconnected
         for (other_client, client) in clients {
             client.send(server.serialize(*other_client, &Message::Disconnect(client_id))).await; 
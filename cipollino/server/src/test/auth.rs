@@ -0,0 +1,91 @@
+use project::Credentials;
+
+use crate::{credentials_from_header, hash_password, hash_token, log_in, AuthConfig};
+
+use super::load_auth_config;
+
+fn password(username: &str, password: &str) -> Credentials {
+    Credentials::Password {
+        username: username.to_owned(),
+        password: password.to_owned()
+    }
+}
+
+fn config() -> AuthConfig {
+    load_auth_config("users", serde_json::json!([
+        {
+            "name": "alice",
+            "password_hash": hash_password("hunter2").unwrap()
+        },
+        {
+            "name": "bob",
+            "token_hash": hash_token("bobs-token")
+        }
+    ])).unwrap()
+}
+
+#[test]
+fn password_login() {
+    let config = config();
+    assert_eq!(config.authenticate(&password("alice", "hunter2")).unwrap().name, "alice");
+    assert!(config.authenticate(&password("alice", "hunter3")).is_none());
+    assert!(config.authenticate(&password("bob", "hunter2")).is_none());
+    assert!(config.authenticate(&password("carol", "hunter2")).is_none());
+}
+
+#[test]
+fn token_login() {
+    let config = config();
+    assert_eq!(config.authenticate(&Credentials::Token("bobs-token".to_owned())).unwrap().name, "bob");
+    assert!(config.authenticate(&Credentials::Token("alices-token".to_owned())).is_none());
+    assert!(config.authenticate(&Credentials::Token(String::new())).is_none());
+}
+
+#[test]
+fn uppercase_token_hash() {
+    let config = load_auth_config("uppercase", serde_json::json!([
+        {
+            "name": "bob",
+            "token_hash": hash_token("bobs-token").to_ascii_uppercase()
+        }
+    ])).unwrap();
+    assert_eq!(config.authenticate(&Credentials::Token("bobs-token".to_owned())).unwrap().name, "bob");
+}
+
+#[test]
+fn invalid_hashes() {
+    assert!(load_auth_config("bad-password", serde_json::json!([{ "name": "alice", "password_hash": "hunter2" }])).is_err());
+    assert!(load_auth_config("bad-token", serde_json::json!([{ "name": "bob", "token_hash": "abc" }])).is_err());
+    assert!(load_auth_config("bad-token-chars", serde_json::json!([{ "name": "bob", "token_hash": "z".repeat(64) }])).is_err());
+}
+
+#[test]
+fn log_in_without_auth() {
+    assert!(log_in(None, None).unwrap().is_none());
+    assert!(log_in(None, Some(&password("alice", "whatever"))).unwrap().is_none());
+}
+
+#[test]
+fn log_in_with_auth() {
+    let config = config();
+    assert!(log_in(Some(&config), None).is_err());
+    assert!(log_in(Some(&config), Some(&password("alice", "hunter3"))).is_err());
+    assert_eq!(log_in(Some(&config), Some(&password("alice", "hunter2"))).unwrap().unwrap().name, "alice");
+}
+
+#[test]
+fn authorization_header() {
+    assert!(matches!(credentials_from_header("Bearer bobs-token"), Some(Credentials::Token(token)) if token == "bobs-token"));
+    assert!(matches!(credentials_from_header("bearer  bobs-token "), Some(Credentials::Token(token)) if token == "bobs-token"));
+
+    // "alice:hunter2:extra" in base64. Passwords can contain colons, usernames can't.
+    assert!(matches!(
+        credentials_from_header("Basic YWxpY2U6aHVudGVyMjpleHRyYQ=="),
+        Some(Credentials::Password { username, password }) if username == "alice" && password == "hunter2:extra"
+    ));
+
+    assert!(credentials_from_header("Basic not-base64!").is_none());
+    assert!(credentials_from_header("Basic YWxpY2U=").is_none()); // "alice", without a password
+    assert!(credentials_from_header("Digest something").is_none());
+    assert!(credentials_from_header("bobs-token").is_none());
+}
@@ -0,0 +1,14 @@
+use crate::AuthConfig;
+
+mod auth;
+mod permissions;
+mod projects;
+
+/// Load an auth config with the given users, by writing it to a temporary file
+fn load_auth_config(name: &str, users: serde_json::Value) -> Result<AuthConfig, String> {
+    let path = std::env::temp_dir().join(format!("cipollino-server-test-{}-{}.json", name, std::process::id()));
+    std::fs::write(&path, serde_json::json!({ "users": users }).to_string()).unwrap();
+    let config = AuthConfig::load(&path);
+    std::fs::remove_file(path).unwrap();
+    config
+}
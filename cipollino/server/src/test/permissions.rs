@@ -0,0 +1,327 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use project::{alisa::{self, Object, Permissions}, AddBlockToAudioClip, AudioBlock, AudioClip, AudioClipTreeData, Client, Clip, ClipTreeData, CreateAudioClip, CreateClip, CreateFolder, CreateLayer, Folder, FolderTreeData, Frame, FrameTreeData, Layer, LayerParent, LayerTreeData, Ptr, RenameAudioClip, RenameClip, RenameFolder, SetLayerName};
+
+use crate::{Access, User, UserPermissions};
+
+/// A project laid out like this, with a layer holding a frame in each clip:
+/// - Title
+/// - Characters/
+///   - Intro
+///   - Theme (audio, with one block)
+///   - Bob/
+///     - Walk Cycle
+///     - Run Cycle
+struct TestProject {
+    storage: alisa::verter::MemoryStorage,
+    characters: Ptr<Folder>,
+    bob: Ptr<Folder>,
+    title: Ptr<Clip>,
+    intro: Ptr<Clip>,
+    walk_cycle: Ptr<Clip>,
+    run_cycle: Ptr<Clip>,
+    walk_layer: Ptr<Layer>,
+    walk_frame: Ptr<Frame>,
+    run_layer: Ptr<Layer>,
+    theme: Ptr<AudioClip>,
+    theme_block: Ptr<AudioBlock>
+}
+
+fn create_folder(client: &Client, parent: Ptr<Folder>, name: &str) -> Ptr<Folder> {
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateFolder {
+        ptr,
+        parent,
+        data: FolderTreeData {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    });
+    ptr
+}
+
+fn create_clip(client: &Client, parent: Ptr<Folder>, name: &str) -> Ptr<Clip> {
+    let ptr = client.next_ptr();
+    client.queue_operation(CreateClip {
+        ptr,
+        parent,
+        data: ClipTreeData {
+            name: name.to_owned(),
+            inner_ptr: client.next_ptr(),
+            ..Default::default()
+        }
+    });
+    ptr
+}
+
+/// Create a layer holding a single frame in a clip
+fn create_layer(client: &Client, clip: Ptr<Clip>) -> (Ptr<Layer>, Ptr<Frame>) {
+    let layer = client.next_ptr();
+    let frame = client.next_ptr();
+    client.queue_operation(CreateLayer {
+        ptr: layer,
+        parent: LayerParent::Clip(clip),
+        idx: 0,
+        data: LayerTreeData {
+            frames: alisa::UnorderedChildListTreeData {
+                children: vec![(alisa::OwningPtr::new(frame), FrameTreeData::default())]
+            },
+            ..Default::default()
+        }
+    });
+    (layer, frame)
+}
+
+impl TestProject {
+
+    fn new() -> Self {
+        let storage = alisa::verter::MemoryStorage::new();
+        let mut client = Client::local_with_storage(storage.clone()).unwrap();
+
+        let characters = create_folder(&client, Ptr::null(), "Characters");
+        let bob = create_folder(&client, characters, "Bob");
+        let title = create_clip(&client, Ptr::null(), "Title");
+        let intro = create_clip(&client, characters, "Intro");
+        let walk_cycle = create_clip(&client, bob, "Walk Cycle");
+        let run_cycle = create_clip(&client, bob, "Run Cycle");
+        create_layer(&client, title);
+        create_layer(&client, intro);
+        let (walk_layer, walk_frame) = create_layer(&client, walk_cycle);
+        let (run_layer, _) = create_layer(&client, run_cycle);
+
+        let theme = client.next_ptr();
+        let theme_block = client.next_ptr();
+        client.queue_operation(CreateAudioClip {
+            ptr: theme,
+            parent: characters,
+            data: AudioClipTreeData {
+                name: "Theme".to_owned(),
+                ..Default::default()
+            }
+        });
+        client.queue_operation(AddBlockToAudioClip {
+            ptr: theme_block,
+            clip: theme,
+            length: 4,
+            data: Box::new([0; 4])
+        });
+        client.tick();
+
+        Self {
+            storage,
+            characters,
+            bob,
+            title,
+            intro,
+            walk_cycle,
+            run_cycle,
+            walk_layer,
+            walk_frame,
+            run_layer,
+            theme,
+            theme_block
+        }
+    }
+
+}
+
+/// Read-only by default, except for the Characters folder, which they can edit.
+/// Bob's folder is read-only again, except for the walk cycle, and the intro is off limits.
+fn animator() -> User {
+    serde_json::from_value(serde_json::json!({
+        "name": "animator",
+        "access": "read_only",
+        "folders": {
+            "Characters": "edit",
+            "Characters/Bob": "read_only"
+        },
+        "clips": {
+            "Characters/Bob/Walk Cycle": "edit",
+            "Characters/Intro": "none"
+        }
+    })).unwrap()
+}
+
+const ANIMATOR: alisa::ClientId = alisa::ClientId(1);
+
+fn animator_permissions() -> UserPermissions {
+    UserPermissions::new(Arc::new(Mutex::new(HashMap::from([(ANIMATOR, Arc::new(animator()))]))))
+}
+
+fn access<O: Object>(permissions: &UserPermissions, client: &Client, ptr: Ptr<O>) -> Access {
+    let context = client.context();
+    if permissions.can_modify(ANIMATOR, &context, Some(ptr.any())) {
+        Access::Edit
+    } else if permissions.can_load(ANIMATOR, &context, ptr.any()) {
+        Access::ReadOnly
+    } else {
+        Access::None
+    }
+}
+
+fn open(project: &TestProject) -> Client {
+    let mut client = Client::local_with_storage(project.storage.clone()).unwrap();
+    client.request_load(project.characters);
+    client.request_load(project.bob);
+    for clip in [project.title, project.intro, project.walk_cycle, project.run_cycle] {
+        client.request_load(clip);
+    }
+    client.request_load(project.walk_layer);
+    client.request_load(project.walk_frame);
+    client.request_load(project.run_layer);
+    client.request_load(project.theme);
+    client.request_load(project.theme_block);
+    client.tick();
+    client
+}
+
+#[test]
+fn nested_folders() {
+    let project = TestProject::new();
+    let client = open(&project);
+    let permissions = animator_permissions();
+
+    assert_eq!(access(&permissions, &client, project.title), Access::ReadOnly);
+    assert_eq!(access(&permissions, &client, project.characters), Access::Edit);
+    assert_eq!(access(&permissions, &client, project.theme), Access::Edit);
+    assert_eq!(access(&permissions, &client, project.theme_block), Access::Edit);
+
+    // The rule for Bob's folder takes precedence over the one for the folder it's in
+    assert_eq!(access(&permissions, &client, project.bob), Access::ReadOnly);
+    assert_eq!(access(&permissions, &client, project.run_cycle), Access::ReadOnly);
+    assert_eq!(access(&permissions, &client, project.run_layer), Access::ReadOnly);
+}
+
+#[test]
+fn clip_rules() {
+    let project = TestProject::new();
+    let client = open(&project);
+    let permissions = animator_permissions();
+
+    assert_eq!(access(&permissions, &client, project.walk_cycle), Access::Edit);
+    assert_eq!(access(&permissions, &client, project.walk_layer), Access::Edit);
+    assert_eq!(access(&permissions, &client, project.walk_frame), Access::Edit);
+    assert_eq!(access(&permissions, &client, project.intro), Access::None);
+}
+
+#[test]
+fn project_access() {
+    let project = TestProject::new();
+    let client = open(&project);
+    let permissions = animator_permissions();
+    assert!(!permissions.can_modify(ANIMATOR, &client.context(), None));
+
+    // Clients that didn't log in can't do anything
+    assert!(!permissions.can_load(alisa::ClientId(2), &client.context(), project.title.any()));
+}
+
+#[test]
+fn owner_not_loaded() {
+    let project = TestProject::new();
+    let mut client = Client::local_with_storage(project.storage.clone()).unwrap();
+    client.request_load(project.walk_frame);
+    client.tick();
+    assert!(client.get(project.walk_frame).is_some());
+    assert!(client.get(project.walk_layer).is_none());
+
+    // The layer the frame is in might be in a clip that's off limits, so the animator can't access the frame at all
+    let permissions = animator_permissions();
+    assert_eq!(access(&permissions, &client, project.walk_frame), Access::None);
+}
+
+/// Apply the animator's operations through a server enforcing their permissions, passing messages around until nothing is left to send
+fn sync(server: &mut alisa::Server<project::Project>, client: &mut Client) {
+    loop {
+        client.tick();
+        let to_server = client.take_messages();
+        let to_client = server.get_msgs_to_send_mut(ANIMATOR).map(std::mem::take).unwrap_or_default();
+        if to_server.is_empty() && to_client.is_empty() {
+            break;
+        }
+        for msg in to_server {
+            server.receive_message(ANIMATOR, &msg);
+        }
+        for msg in to_client {
+            client.receive_message(&msg);
+        }
+    }
+}
+
+#[test]
+fn denied_operations() {
+    let project = TestProject::new();
+    let mut server = alisa::Server::with_storage(project.storage.clone()).unwrap();
+    server.set_permissions(animator_permissions());
+    let (id, welcome) = server.add_client();
+    assert_eq!(id, ANIMATOR);
+    let mut client = Client::collab(&welcome).unwrap();
+
+    // The folders and clips are sent along with the project, but the animator can only open the clips they have access to
+    let intro_inner = client.get(project.intro).unwrap().inner.ptr();
+    let walk_inner = client.get(project.walk_cycle).unwrap().inner.ptr();
+    client.request_load(intro_inner);
+    client.request_load(walk_inner);
+    client.request_load(project.run_layer);
+    sync(&mut server, &mut client);
+    assert!(client.get(intro_inner).is_none());
+    assert!(client.get(walk_inner).is_some());
+    assert!(client.get(project.run_layer).is_some());
+
+    // Read-only objects can't be modified
+    client.queue_operation(RenameFolder { ptr: project.bob, name: "Robert".to_owned() });
+    client.queue_operation(SetLayerName { ptr: project.run_layer, name_value: "Legs".to_owned() });
+    sync(&mut server, &mut client);
+    assert_eq!(client.get(project.bob).unwrap().name, "Bob");
+    assert_eq!(client.get(project.run_layer).unwrap().name, "Layer");
+
+    // Editable ones can, including objects created inside of them
+    client.queue_operation(RenameAudioClip { ptr: project.theme, name: "Main Theme".to_owned() });
+    let (layer, frame) = create_layer(&client, project.walk_cycle);
+    sync(&mut server, &mut client);
+    assert_eq!(client.get(project.theme).unwrap().name, "Main Theme");
+    assert!(client.get(layer).unwrap().frames.iter().any(|child| child.ptr() == frame));
+    assert!(client.get(frame).is_some());
+}
+
+/// Can edit everything except for Bob's folder, which they can't even see
+fn editor() -> User {
+    serde_json::from_value(serde_json::json!({
+        "name": "editor",
+        "access": "edit",
+        "folders": {
+            "Characters/Bob": "none"
+        }
+    })).unwrap()
+}
+
+#[test]
+fn restore_snapshot() {
+    let project = TestProject::new();
+
+    // Rename a clip inside and a clip outside of Bob's folder after taking a snapshot
+    let mut client = Client::local_with_storage(project.storage.clone()).unwrap();
+    let snapshot = client.create_snapshot("Before renaming").unwrap();
+    client.queue_operation(RenameClip { ptr: project.walk_cycle, name: "Walk".to_owned() });
+    client.queue_operation(RenameClip { ptr: project.title, name: "Opening".to_owned() });
+    client.tick();
+    drop(client);
+
+    let clip_names = |storage: &alisa::verter::MemoryStorage| {
+        let client = Client::local_with_storage(storage.clone()).unwrap();
+        (client.get(project.walk_cycle).unwrap().name.clone(), client.get(project.title).unwrap().name.clone())
+    };
+
+    let mut server = alisa::Server::with_storage(project.storage.clone()).unwrap();
+    server.set_permissions(UserPermissions::new(Arc::new(Mutex::new(HashMap::from([(ANIMATOR, Arc::new(editor()))])))));
+    let (id, _) = server.add_client();
+    assert_eq!(id, ANIMATOR);
+
+    // Restoring the snapshot would rename the walk cycle back, which the editor isn't allowed to touch, so nothing is restored
+    let snapshot = server.snapshot(snapshot.id).unwrap();
+    assert!(!server.restore_snapshot(id, &snapshot));
+    assert_eq!(clip_names(&project.storage), ("Walk".to_owned(), "Opening".to_owned()));
+
+    // The server itself can restore anything
+    assert!(server.restore_snapshot(alisa::ClientId::SERVER, &snapshot));
+    assert_eq!(clip_names(&project.storage), ("Walk Cycle".to_owned(), "Title".to_owned()));
+}
@@ -0,0 +1,180 @@
+use std::{path::PathBuf, sync::Arc};
+
+use project::{alisa, ConnectMessage, ConnectionRefused, Credentials, ProjectListing};
+use tokio::sync::Mutex;
+use warp::{test::WsClient, ws::Message, Filter};
+
+use crate::{hash_token, project_routes, AuthConfig, Projects};
+
+use super::load_auth_config;
+
+/// A directory of projects, next to a project clients shouldn't be able to reach:
+/// - outside.cip
+/// - projects/
+///   - Short Film.cip
+///   - Teaser.cip
+///   - .hidden.cip
+///   - notes.txt
+struct TestDir {
+    root: PathBuf
+}
+
+impl TestDir {
+
+    fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("cipollino-server-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("projects")).unwrap();
+        for path in ["outside.cip", "projects/Short Film.cip", "projects/Teaser.cip", "projects/.hidden.cip"] {
+            project::Client::local(root.join(path)).unwrap();
+        }
+        std::fs::write(root.join("projects/notes.txt"), "").unwrap();
+        Self {
+            root
+        }
+    }
+
+    fn routes(&self, auth: Option<AuthConfig>) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone + Send + Sync + 'static {
+        let auth = auth.map(Arc::new);
+        project_routes(Arc::new(Mutex::new(Projects::new(self.root.join("projects"), auth.clone()))), auth)
+    }
+
+    /// Did the server open the project? Opening a project creates its operation log next to it.
+    fn opened(&self, path: &str) -> bool {
+        alisa::op_log_path(&self.root.join(path)).exists()
+    }
+
+}
+
+impl Drop for TestDir {
+
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+
+}
+
+fn auth_config(name: &str) -> AuthConfig {
+    load_auth_config(name, serde_json::json!([
+        {
+            "name": "director",
+            "token_hash": hash_token("directors-token"),
+            "access": "edit"
+        }
+    ])).unwrap()
+}
+
+fn token(token: &str) -> Option<Credentials> {
+    Some(Credentials::Token(token.to_owned()))
+}
+
+/// Open a websocket and send the connect message.
+/// Returns the socket along with the first message the server replies with, or None if it closes the connection instead.
+async fn connect<F>(routes: &F, path: &str, credentials: Option<Credentials>) -> (WsClient, Option<alisa::ABFValue>)
+    where F: Filter + Clone + Send + Sync + 'static, F::Extract: warp::Reply + Send {
+    let mut socket = warp::test::ws().path(path).handshake(routes.clone()).await.unwrap();
+    let connect_msg = ConnectMessage {
+        resume: None,
+        credentials
+    };
+    socket.send(Message::binary(alisa::encode_abf(&alisa::serialize(&connect_msg)))).await;
+    let reply = socket.recv().await.ok().and_then(|msg| alisa::parse_abf(msg.as_bytes()));
+    (socket, reply)
+}
+
+fn is_welcome(msg: &Option<alisa::ABFValue>) -> bool {
+    msg.as_ref().is_some_and(|msg| ConnectionRefused::from_message(msg).is_none() && msg.get("collab").is_some())
+}
+
+fn is_refusal(msg: &Option<alisa::ABFValue>) -> bool {
+    msg.as_ref().is_some_and(|msg| ConnectionRefused::from_message(msg).is_some())
+}
+
+fn listed_names(msg: &Option<alisa::ABFValue>) -> Vec<String> {
+    let projects = alisa::deserialize::<Vec<ProjectListing>>(msg.as_ref().unwrap()).unwrap();
+    projects.into_iter().map(|project| project.name).collect()
+}
+
+#[tokio::test]
+async fn open_project() {
+    let dir = TestDir::new("open-project");
+    let routes = dir.routes(None);
+    let (_socket, reply) = connect(&routes, "/projects/Short%20Film", None).await;
+    assert!(is_welcome(&reply));
+    assert!(dir.opened("projects/Short Film.cip"));
+    assert!(!dir.opened("projects/Teaser.cip"));
+}
+
+#[tokio::test]
+async fn project_names_stay_inside_the_directory() {
+    let dir = TestDir::new("project-names");
+    let routes = dir.routes(None);
+
+    for path in ["/projects/..%2Foutside", "/projects/..%5Coutside", "/projects/.hidden", "/projects/notes", "/projects/Missing"] {
+        let (_socket, reply) = connect(&routes, path, None).await;
+        assert!(reply.is_none(), "{}", path);
+    }
+    assert!(!dir.opened("outside.cip"));
+    assert!(!dir.opened("projects/.hidden.cip"));
+}
+
+#[tokio::test]
+async fn listing() {
+    let dir = TestDir::new("listing");
+    let routes = dir.routes(None);
+
+    let (_teaser, reply) = connect(&routes, "/projects/Teaser", None).await;
+    assert!(is_welcome(&reply));
+
+    // Hidden projects and other files aren't listed
+    let response = warp::test::request().path("/projects").reply(&routes).await;
+    assert_eq!(response.status(), 200);
+    let listing: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(listing, serde_json::json!([
+        { "name": "Short Film", "clients": 0 },
+        { "name": "Teaser", "clients": 1 }
+    ]));
+
+    let (_socket, reply) = connect(&routes, "/projects", None).await;
+    assert_eq!(listed_names(&reply), vec!["Short Film", "Teaser"]);
+}
+
+#[tokio::test]
+async fn listing_requires_logging_in() {
+    let dir = TestDir::new("listing-auth");
+    let routes = dir.routes(Some(auth_config("listing-auth")));
+
+    let list = |authorization: Option<&str>| {
+        let mut request = warp::test::request().path("/projects");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        request.reply(&routes)
+    };
+    assert_eq!(list(None).await.status(), 401);
+    assert_eq!(list(Some("Bearer wrong-token")).await.status(), 401);
+    assert_eq!(list(Some("Bearer directors-token")).await.status(), 200);
+
+    let (_socket, reply) = connect(&routes, "/projects", None).await;
+    assert!(is_refusal(&reply));
+    let (_socket, reply) = connect(&routes, "/projects", token("wrong-token")).await;
+    assert!(is_refusal(&reply));
+    let (_socket, reply) = connect(&routes, "/projects", token("directors-token")).await;
+    assert_eq!(listed_names(&reply), vec!["Short Film", "Teaser"]);
+}
+
+#[tokio::test]
+async fn log_in_before_opening_project() {
+    let dir = TestDir::new("open-auth");
+    let routes = dir.routes(Some(auth_config("open-auth")));
+
+    let (_socket, reply) = connect(&routes, "/projects/Teaser", None).await;
+    assert!(is_refusal(&reply));
+    let (_socket, reply) = connect(&routes, "/projects/Teaser", token("wrong-token")).await;
+    assert!(is_refusal(&reply));
+    assert!(!dir.opened("projects/Teaser.cip"));
+
+    let (_socket, reply) = connect(&routes, "/projects/Teaser", token("directors-token")).await;
+    assert!(is_welcome(&reply));
+    assert!(dir.opened("projects/Teaser.cip"));
+}
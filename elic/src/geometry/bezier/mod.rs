@@ -7,6 +7,15 @@ pub use segment::*;
 mod path;
 pub use path::*;
 
+mod region;
+pub use region::*;
+
+/// Intersections closer than this in both parameters are treated as the same intersection
+pub(crate) const INTERSECTION_MERGE_DISTANCE: f32 = 0.001;
+
+/// Parameter ranges shorter than this are treated as empty
+pub(crate) const MIN_RANGE: f32 = 0.0001;
+
 #[derive(Clone, Copy, Default)]
 pub struct BezierPoint<T: Linear> {
     pub prev: T,
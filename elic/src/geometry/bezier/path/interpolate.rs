@@ -0,0 +1,120 @@
+
+use crate::{Linear, Vec2};
+
+use super::{BezierPath, BezierPoint};
+
+/// Points closer than this fraction of their paths' arc length are matched up with each other
+const MATCH_TOLERANCE: f32 = 0.001;
+
+/// The fraction of the arc length of a path at each of its points
+fn length_fractions(path: &BezierPath<Vec2>) -> Vec<f32> {
+    let mut lengths = vec![0.0];
+    for segment in path.iter_segments() {
+        lengths.push(lengths[lengths.len() - 1] + segment.length());
+    }
+    let total_length = lengths[lengths.len() - 1];
+    let n_segments = path.n_segments().max(1) as f32;
+    lengths.iter().enumerate().map(|(i, length)| {
+        if total_length > 0.0 {
+            length / total_length
+        } else {
+            i as f32 / n_segments
+        }
+    }).collect()
+}
+
+/// The parameter at a fraction of the arc length of a path
+fn t_at_length_fraction(path: &BezierPath<Vec2>, fraction: f32) -> f32 {
+    let length = path.length();
+    if length > 0.0 {
+        path.t_at_length(fraction * length)
+    } else {
+        fraction * path.n_segments() as f32
+    }
+}
+
+impl<T: Linear> BezierPath<T> {
+
+    /// Insert a point at each of a list of increasing parameters, without changing the shape of the path.
+    /// Paths with fewer than two points are returned unchanged.
+    pub fn subdivide(&self, ts: &[f32]) -> Self {
+        let n_segments = self.n_segments();
+        if n_segments == 0 {
+            return self.clone();
+        }
+
+        let mut ts = ts.iter().copied().peekable();
+        let mut segments = Vec::new();
+        for (i, segment) in self.iter_segments().enumerate() {
+            let mut local_ts = Vec::new();
+            while let Some(t) = ts.next_if(|t| *t < (i + 1) as f32 || i == n_segments - 1) {
+                local_ts.push((t - i as f32).clamp(0.0, 1.0));
+            }
+            segments.extend(segment.split_at(&local_ts));
+        }
+
+        let mut path = Self::from_segments(&segments);
+        let last_idx = path.pts.len() - 1;
+        path.pts[0].prev = self.pts[0].prev.clone();
+        path.pts[last_idx].next = self.pts[self.pts.len() - 1].next.clone();
+        path
+    }
+
+    /// Insert points into this path and `other` until they have the same number of points,
+    /// with corresponding points at the same fraction of the arc length along each path.
+    /// `pos` gives the position of a point on the paths. A path with a single point is repeated to match the other path.
+    pub fn match_points<F: Fn(&T) -> Vec2>(&self, other: &Self, pos: F) -> (Self, Self) {
+        if self.pts.is_empty() || other.pts.is_empty() {
+            return (self.clone(), other.clone());
+        }
+        if self.pts.len() == 1 {
+            return (Self { pts: vec![self.pts[0].clone(); other.pts.len()] }, other.clone());
+        }
+        if other.pts.len() == 1 {
+            return (self.clone(), Self { pts: vec![other.pts[0].clone(); self.pts.len()] });
+        }
+
+        let self_pos = self.map(|pt| pos(pt));
+        let other_pos = other.map(|pt| pos(pt));
+        let self_fractions = length_fractions(&self_pos);
+        let other_fractions = length_fractions(&other_pos);
+
+        // Walk along both paths, adding a point to one path wherever the other has a point it doesn't
+        let mut self_ts = Vec::new();
+        let mut other_ts = Vec::new();
+        let mut i = 0;
+        let mut j = 0;
+        while i < self_fractions.len() || j < other_fractions.len() {
+            let self_fraction = self_fractions.get(i).copied().unwrap_or(f32::INFINITY);
+            let other_fraction = other_fractions.get(j).copied().unwrap_or(f32::INFINITY);
+            if (self_fraction - other_fraction).abs() < MATCH_TOLERANCE {
+                i += 1;
+                j += 1;
+            } else if self_fraction < other_fraction {
+                other_ts.push(t_at_length_fraction(&other_pos, self_fraction));
+                i += 1;
+            } else {
+                self_ts.push(t_at_length_fraction(&self_pos, other_fraction));
+                j += 1;
+            }
+        }
+
+        (self.subdivide(&self_ts), other.subdivide(&other_ts))
+    }
+
+    /// Interpolate between this path and another path with the same number of points, point by point.
+    /// Returns None if the paths have different numbers of points.
+    pub fn lerp(&self, other: &Self, t: f32) -> Option<Self> {
+        if self.pts.len() != other.pts.len() {
+            return None;
+        }
+        Some(Self {
+            pts: self.pts.iter().zip(other.pts.iter()).map(|(a, b)| BezierPoint::new(
+                T::lerp(a.prev.clone(), b.prev.clone(), t),
+                T::lerp(a.pt.clone(), b.pt.clone(), t),
+                T::lerp(a.next.clone(), b.next.clone(), t)
+            )).collect()
+        })
+    }
+
+}
@@ -0,0 +1,42 @@
+
+use crate::Vec2;
+
+use super::super::INTERSECTION_MERGE_DISTANCE;
+use super::BezierPath;
+
+impl BezierPath<Vec2> {
+
+    /// The parameters at which two paths cross or touch, as pairs of (parameter on `self`, parameter on `other`).
+    /// Segment `i` of a path covers the parameters `i..(i + 1)`. The pairs are sorted by the parameter on `self`.
+    pub fn intersect_ts(&self, other: &BezierPath<Vec2>) -> Vec<(f32, f32)> {
+        let other_segments: Vec<_> = other.iter_segments().collect();
+        let mut ts = Vec::new();
+        for (i, segment) in self.iter_segments().enumerate() {
+            let bounds = segment.hull_bounds();
+            for (j, other_segment) in other_segments.iter().enumerate() {
+                let other_bounds = other_segment.hull_bounds();
+                if bounds.left() > other_bounds.right() || other_bounds.left() > bounds.right() || bounds.top() > other_bounds.bottom() || other_bounds.top() > bounds.bottom() {
+                    continue;
+                }
+                ts.extend(segment.intersect_ts(other_segment).into_iter().map(|(t0, t1)| (i as f32 + t0, j as f32 + t1)));
+            }
+        }
+
+        // Intersections where segments meet are found on both segments
+        ts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut merged: Vec<(f32, f32)> = Vec::new();
+        for (t0, t1) in ts {
+            let duplicate = merged.iter().any(|(m0, m1)| (m0 - t0).abs() < INTERSECTION_MERGE_DISTANCE && (m1 - t1).abs() < INTERSECTION_MERGE_DISTANCE);
+            if !duplicate {
+                merged.push((t0, t1));
+            }
+        }
+        merged
+    }
+
+    /// The points at which two paths cross or touch
+    pub fn intersections(&self, other: &BezierPath<Vec2>) -> Vec<Vec2> {
+        self.intersect_ts(other).into_iter().map(|(t, _)| self.sample(t)).collect()
+    }
+
+}
@@ -0,0 +1,47 @@
+
+use crate::Vec2;
+
+use super::BezierPath;
+
+impl BezierPath<Vec2> {
+
+    /// The arc length of the path
+    pub fn length(&self) -> f32 {
+        self.iter_segments().map(|segment| segment.length()).sum()
+    }
+
+    /// The arc length of the path from its start up to parameter `t`
+    pub fn length_at(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, self.n_segments() as f32);
+        self.iter_segments().enumerate().map(|(i, segment)| {
+            let segment_t = (t - i as f32).clamp(0.0, 1.0);
+            if segment_t <= 0.0 {
+                0.0
+            } else if segment_t >= 1.0 {
+                segment.length()
+            } else {
+                segment.length_between(0.0, segment_t)
+            }
+        }).sum()
+    }
+
+    /// The parameter at which the arc length from the start of the path is `length`.
+    /// Lengths outside of the path are clamped to its ends.
+    pub fn t_at_length(&self, length: f32) -> f32 {
+        let mut remaining = length;
+        for (i, segment) in self.iter_segments().enumerate() {
+            let segment_length = segment.length();
+            if remaining <= segment_length {
+                return i as f32 + segment.t_at_length(remaining);
+            }
+            remaining -= segment_length;
+        }
+        self.n_segments() as f32
+    }
+
+    /// The point on the path at arc length `length` from its start
+    pub fn sample_at_length(&self, length: f32) -> Vec2 {
+        self.sample(self.t_at_length(length))
+    }
+
+}
@@ -3,6 +3,17 @@ use crate::Linear;
 
 use super::{BezierPoint, BezierSegment};
 
+mod split;
+pub(crate) use split::segments_between;
+
+mod length;
+
+mod intersect;
+
+mod offset;
+
+mod interpolate;
+
 #[derive(Clone)]
 pub struct BezierPath<T: Linear> {
     pub pts: Vec<BezierPoint<T>>
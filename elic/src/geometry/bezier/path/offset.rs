@@ -0,0 +1,25 @@
+
+use crate::Vec2;
+
+use super::{BezierPath, BezierSegment};
+
+impl BezierPath<Vec2> {
+
+    /// Approximate the path at distance `dist` from this one, within `tolerance`.
+    /// Positive distances offset the path in the direction of `BezierSegment::sample_normal`.
+    /// Where neighbouring segments meet at a corner, the gap between their offsets is bridged with a straight line.
+    pub fn offset(&self, dist: f32, tolerance: f32) -> Self {
+        let mut segments: Vec<BezierSegment<Vec2>> = Vec::new();
+        for segment in self.iter_segments() {
+            let offset = segment.offset(dist, tolerance);
+            if let (Some(prev), Some(next)) = (segments.last(), offset.first()) {
+                if prev.p1.distance(next.p0) > tolerance {
+                    segments.push(BezierSegment::straight(prev.p1, next.p0));
+                }
+            }
+            segments.extend(offset);
+        }
+        Self::from_segments(&segments)
+    }
+
+}
@@ -0,0 +1,90 @@
+
+use crate::Linear;
+
+use super::super::MIN_RANGE;
+use super::{BezierPath, BezierPoint, BezierSegment};
+
+/// The parts of a list of consecutive segments between parameters `t0` and `t1`, where segment `i` covers `i..(i + 1)`
+pub(crate) fn segments_between<T: Linear>(segments: &[BezierSegment<T>], t0: f32, t1: f32) -> Vec<BezierSegment<T>> {
+    if segments.is_empty() || t1 - t0 < MIN_RANGE {
+        return Vec::new();
+    }
+    let first = (t0.floor().max(0.0) as usize).min(segments.len() - 1);
+    let last = (t1.ceil().max(1.0) as usize - 1).min(segments.len() - 1);
+    (first..=last).filter_map(|i| {
+        let a = (t0 - i as f32).max(0.0);
+        let b = (t1 - i as f32).min(1.0);
+        (b - a > MIN_RANGE).then(|| segments[i].subsegment(a, b))
+    }).collect()
+}
+
+impl<T: Linear> BezierPath<T> {
+
+    /// Join consecutive segments into a path.
+    /// The outer handles at the ends of the path are set to the ends themselves.
+    pub fn from_segments(segments: &[BezierSegment<T>]) -> Self {
+        let mut pts = Vec::new();
+        for (i, segment) in segments.iter().enumerate() {
+            let prev = if i == 0 { segment.p0.clone() } else { segments[i - 1].a1.clone() };
+            pts.push(BezierPoint::new(prev, segment.p0.clone(), segment.b0.clone()));
+        }
+        if let Some(last) = segments.last() {
+            pts.push(BezierPoint::new(last.a1.clone(), last.p1.clone(), last.p1.clone()));
+        }
+        Self { pts }
+    }
+
+    /// The number of segments in the path. Segment `i` covers the parameters `i..(i + 1)`.
+    pub fn n_segments(&self) -> usize {
+        self.pts.len().saturating_sub(1)
+    }
+
+    /// The same path, running from its last point to its first
+    pub fn reverse(&self) -> Self {
+        Self {
+            pts: self.pts.iter().rev().map(|pt| BezierPoint::new(pt.next.clone(), pt.pt.clone(), pt.prev.clone())).collect()
+        }
+    }
+
+    /// The part of the path between parameters `t0` and `t1`.
+    /// Where the range reaches the ends of the path, the outer handles of the path are kept.
+    pub fn subpath(&self, t0: f32, t1: f32) -> Self {
+        let n_segments = self.n_segments() as f32;
+        let t0 = t0.max(0.0);
+        let t1 = t1.min(n_segments);
+        let segments: Vec<_> = self.iter_segments().collect();
+        let mut path = Self::from_segments(&segments_between(&segments, t0, t1));
+        if path.pts.is_empty() {
+            return path;
+        }
+        if t0 == 0.0 {
+            path.pts[0].prev = self.pts[0].prev.clone();
+        }
+        if t1 == n_segments {
+            let last_idx = path.pts.len() - 1;
+            path.pts[last_idx].next = self.pts[self.pts.len() - 1].next.clone();
+        }
+        path
+    }
+
+    /// Split the path in two at parameter `t`
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        (self.subpath(0.0, t), self.subpath(t, self.n_segments() as f32))
+    }
+
+    /// Split the path at a list of increasing parameters, leaving out empty pieces
+    pub fn split_at(&self, ts: &[f32]) -> Vec<Self> {
+        let end = self.n_segments() as f32;
+        let mut paths = Vec::new();
+        let mut prev_t = 0.0;
+        for t in ts.iter().copied().chain(std::iter::once(end)) {
+            let path = self.subpath(prev_t, t);
+            if !path.pts.is_empty() {
+                paths.push(path);
+            }
+            prev_t = t;
+        }
+        paths
+    }
+
+}
@@ -0,0 +1,226 @@
+
+use crate::Vec2;
+
+use super::super::INTERSECTION_MERGE_DISTANCE;
+use super::{segments_between, BezierPath, BezierRegion, BezierSegment};
+
+#[derive(Clone, Copy)]
+enum BooleanOp {
+    Union,
+    Intersection,
+    Difference
+}
+
+impl BooleanOp {
+
+    /// Should a piece of the boundary of the first region be kept, given whether it is inside the second region?
+    fn keep_first(&self, inside_second: bool) -> bool {
+        match self {
+            BooleanOp::Union => !inside_second,
+            BooleanOp::Intersection => inside_second,
+            BooleanOp::Difference => !inside_second
+        }
+    }
+
+    /// Should a piece of the boundary of the second region be kept, given whether it is inside the first region?
+    fn keep_second(&self, inside_first: bool) -> bool {
+        match self {
+            BooleanOp::Union => !inside_first,
+            BooleanOp::Intersection => inside_first,
+            BooleanOp::Difference => inside_first
+        }
+    }
+
+}
+
+/// A closed contour of one of the regions, along with the points where the other region's contours cross it
+struct Contour<'a> {
+    path: &'a BezierPath<Vec2>,
+    segments: Vec<BezierSegment<Vec2>>,
+    /// Pairs of (parameter along the contour, index of the intersection)
+    splits: Vec<(f32, usize)>,
+    first_region: bool
+}
+
+/// A piece of a contour running from one intersection to another
+struct Edge {
+    start: usize,
+    end: usize,
+    segments: Vec<BezierSegment<Vec2>>
+}
+
+fn contours(region: &BezierRegion, first_region: bool) -> Vec<Contour<'_>> {
+    region.contours.iter()
+        .map(|path| Contour {
+            path,
+            segments: path.closed_segments(),
+            splits: Vec::new(),
+            first_region
+        })
+        // Contours with a single point don't cover any area
+        .filter(|contour| !contour.segments.is_empty())
+        .collect()
+}
+
+/// Find the intersections between the contours of the two regions, recording them in the contours' splits.
+/// Returns the position of each intersection.
+fn split_contours(a_contours: &mut [Contour], b_contours: &mut [Contour]) -> Vec<Vec2> {
+    let mut intersections = Vec::new();
+    for a in a_contours.iter_mut() {
+        let a_path = BezierPath::from_segments(&a.segments);
+        let a_len = a.segments.len() as f32;
+        for b in b_contours.iter_mut() {
+            let b_path = BezierPath::from_segments(&b.segments);
+            let b_len = b.segments.len() as f32;
+
+            // The start and end of a closed contour are the same point, so intersections found there are merged
+            let mut ts: Vec<(f32, f32)> = Vec::new();
+            for (ta, tb) in a_path.intersect_ts(&b_path) {
+                let ta = if a_len - ta < INTERSECTION_MERGE_DISTANCE { 0.0 } else { ta };
+                let tb = if b_len - tb < INTERSECTION_MERGE_DISTANCE { 0.0 } else { tb };
+                if !ts.iter().any(|(sa, sb)| (sa - ta).abs() < INTERSECTION_MERGE_DISTANCE && (sb - tb).abs() < INTERSECTION_MERGE_DISTANCE) {
+                    ts.push((ta, tb));
+                }
+            }
+
+            for (ta, tb) in ts {
+                let idx = intersections.len();
+                intersections.push(a_path.sample(ta));
+                a.splits.push((ta, idx));
+                b.splits.push((tb, idx));
+            }
+        }
+    }
+    intersections
+}
+
+/// The point halfway along the part of a closed contour between `t0` and `t1`, wrapping around its end
+fn contour_midpoint(segments: &[BezierSegment<Vec2>], t0: f32, t1: f32) -> Vec2 {
+    let len = segments.len() as f32;
+    let mut range_len = (t1 - t0).rem_euclid(len);
+    if range_len == 0.0 {
+        range_len = len;
+    }
+    let mid = (t0 + range_len * 0.5).rem_euclid(len);
+    let segment_idx = (mid.floor() as usize).min(segments.len() - 1);
+    segments[segment_idx].sample(mid - segment_idx as f32)
+}
+
+/// The part of a closed contour between `t0` and `t1`, wrapping around its end
+fn contour_between(segments: &[BezierSegment<Vec2>], t0: f32, t1: f32) -> Vec<BezierSegment<Vec2>> {
+    if t1 > t0 {
+        segments_between(segments, t0, t1)
+    } else {
+        let mut piece = segments_between(segments, t0, segments.len() as f32);
+        piece.extend(segments_between(segments, 0.0, t1));
+        piece
+    }
+}
+
+fn boolean(a: &BezierRegion, b: &BezierRegion, op: BooleanOp) -> BezierRegion {
+    let a_polygons = a.flatten();
+    let b_polygons = b.flatten();
+    let mut a_contours = contours(a, true);
+    let mut b_contours = contours(b, false);
+    let intersections = split_contours(&mut a_contours, &mut b_contours);
+
+    let keep = |contour: &Contour, pt: Vec2| {
+        if contour.first_region {
+            op.keep_first(b_polygons.contains(pt))
+        } else {
+            op.keep_second(a_polygons.contains(pt))
+        }
+    };
+
+    let mut result = Vec::new();
+    let mut edges = Vec::new();
+    for contour in a_contours.iter_mut().chain(b_contours.iter_mut()) {
+        // Contours that don't cross the other region are either entirely inside it or entirely outside of it
+        if contour.splits.is_empty() {
+            if keep(contour, contour_midpoint(&contour.segments, 0.0, 0.0)) {
+                result.push(contour.path.clone());
+            }
+            continue;
+        }
+
+        contour.splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for i in 0..contour.splits.len() {
+            let (t0, start) = contour.splits[i];
+            let (t1, end) = contour.splits[(i + 1) % contour.splits.len()];
+            if !keep(contour, contour_midpoint(&contour.segments, t0, t1)) {
+                continue;
+            }
+            let mut segments = contour_between(&contour.segments, t0, t1);
+            // Make sure the edges meeting at an intersection connect exactly
+            if let Some(first) = segments.first_mut() {
+                first.p0 = intersections[start];
+            }
+            if let Some(last) = segments.last_mut() {
+                last.p1 = intersections[end];
+            }
+            edges.push(Edge { start, end, segments });
+        }
+    }
+
+    // Link the edges into closed contours through the intersections they share
+    let mut edges_at = vec![Vec::new(); intersections.len()];
+    for (edge_idx, edge) in edges.iter().enumerate() {
+        edges_at[edge.start].push(edge_idx);
+        edges_at[edge.end].push(edge_idx);
+    }
+    let mut used = vec![false; edges.len()];
+    for first_edge in 0..edges.len() {
+        if used[first_edge] {
+            continue;
+        }
+
+        let mut segments = Vec::new();
+        let mut edge_idx = first_edge;
+        let mut entry = edges[first_edge].start;
+        loop {
+            used[edge_idx] = true;
+            let edge = &edges[edge_idx];
+            let exit = if entry == edge.start {
+                segments.extend(edge.segments.iter().copied());
+                edge.end
+            } else {
+                segments.extend(edge.segments.iter().rev().map(BezierSegment::reverse));
+                edge.start
+            };
+
+            let Some(next_edge) = edges_at[exit].iter().copied().find(|edge| !used[*edge]) else { break; };
+            edge_idx = next_edge;
+            entry = exit;
+        }
+
+        if segments.is_empty() {
+            continue;
+        }
+        let mut contour = BezierPath::from_segments(&segments);
+        if let Some(last) = segments.last() {
+            contour.pts[0].prev = last.a1;
+        }
+        result.push(contour);
+    }
+
+    BezierRegion::new(result)
+}
+
+impl BezierRegion {
+
+    /// The area covered by either region
+    pub fn union(&self, other: &BezierRegion) -> BezierRegion {
+        boolean(self, other, BooleanOp::Union)
+    }
+
+    /// The area covered by both regions
+    pub fn intersection(&self, other: &BezierRegion) -> BezierRegion {
+        boolean(self, other, BooleanOp::Intersection)
+    }
+
+    /// The area covered by this region but not the other one
+    pub fn difference(&self, other: &BezierRegion) -> BezierRegion {
+        boolean(self, other, BooleanOp::Difference)
+    }
+
+}
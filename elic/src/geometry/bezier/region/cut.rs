@@ -0,0 +1,65 @@
+
+use crate::{Linear, Rect, Vec2};
+
+use super::super::INTERSECTION_MERGE_DISTANCE;
+use super::{BezierPath, BezierRegion};
+
+impl<T: Linear> BezierPath<T> {
+
+    /// Cut the parts of the path inside a region out of it, keeping the parts outside as separate paths.
+    /// `pos` gives the position of a point on the path.
+    /// Returns `None` if the path doesn't pass through the region.
+    pub fn cut<F: Fn(&T) -> Vec2>(&self, region: &BezierRegion, pos: F) -> Option<Vec<Self>> {
+        let region_bounds = region.bounds()?;
+        if self.pts.is_empty() {
+            return None;
+        }
+        let path = self.map(&pos);
+        let bounds = Rect::bounds_all(path.pts.iter().flat_map(|pt| [pt.prev, pt.pt, pt.next]));
+        if !bounds.intersects(region_bounds) {
+            return None;
+        }
+        let polygons = region.flatten();
+        if path.pts.len() == 1 {
+            return polygons.contains(path.pts[0].pt).then(Vec::new);
+        }
+
+        let mut ts = Vec::new();
+        for contour in &region.contours {
+            let contour = BezierPath::from_segments(&contour.closed_segments());
+            ts.extend(path.intersect_ts(&contour).into_iter().map(|(t, _)| t));
+        }
+        ts.sort_by(f32::total_cmp);
+        ts.dedup_by(|a, b| (*a - *b).abs() < INTERSECTION_MERGE_DISTANCE);
+
+        // Between crossings, the path is either entirely inside the region or entirely outside of it.
+        // Neighbouring pieces outside the region, where the path only touches it, are kept together.
+        let mut bounds = vec![0.0];
+        bounds.extend(ts);
+        bounds.push(path.n_segments() as f32);
+        let mut pieces = Vec::new();
+        let mut piece_start = None;
+        let mut changed = false;
+        for range in bounds.windows(2) {
+            let outside = !polygons.contains(path.sample((range[0] + range[1]) * 0.5));
+            if outside {
+                piece_start.get_or_insert(range[0]);
+                continue;
+            }
+            changed = true;
+            if let Some(start) = piece_start.take() {
+                pieces.push(self.subpath(start, range[0]));
+            }
+        }
+        if !changed {
+            return None;
+        }
+        if let Some(start) = piece_start {
+            pieces.push(self.subpath(start, path.n_segments() as f32));
+        }
+        // Pieces too short to matter come out empty
+        pieces.retain(|piece| !piece.pts.is_empty());
+        Some(pieces)
+    }
+
+}
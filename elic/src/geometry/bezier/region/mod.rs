@@ -0,0 +1,108 @@
+
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{Rect, Vec2};
+
+use super::{BezierPath, BezierSegment};
+use super::path::segments_between;
+
+mod polygon;
+pub use polygon::*;
+
+mod boolean;
+
+mod cut;
+
+/// Contour ends closer than this are treated as already closed
+const CLOSED_DISTANCE: f32 = 0.0001;
+
+/// An area bounded by Bézier contours, filled using the even-odd rule.
+/// Each contour is closed by a straight line from its last point back to its first, unless they already coincide.
+#[derive(Clone, Default)]
+pub struct BezierRegion {
+    pub contours: Vec<BezierPath<Vec2>>
+}
+
+impl BezierRegion {
+
+    pub fn new(contours: Vec<BezierPath<Vec2>>) -> Self {
+        Self {
+            contours
+        }
+    }
+
+    /// A circle, approximated by four quarter arcs running counter-clockwise from its rightmost point
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        let handle = 4.0 / 3.0 * (FRAC_PI_2 / 4.0).tan() * radius;
+        let dirs = [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y, Vec2::X];
+        let segments: Vec<_> = dirs.windows(2).map(|dirs| BezierSegment {
+            p0: center + dirs[0] * radius,
+            b0: center + dirs[0] * radius + dirs[0].turn_ccw() * handle,
+            a1: center + dirs[1] * radius - dirs[1].turn_ccw() * handle,
+            p1: center + dirs[1] * radius
+        }).collect();
+        let mut contour = BezierPath::from_segments(&segments);
+        contour.pts[0].prev = segments[3].a1;
+        Self::new(vec![contour])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contours.iter().all(|contour| contour.pts.is_empty())
+    }
+
+    /// A box containing the whole region, or `None` if the region is empty
+    pub fn bounds(&self) -> Option<Rect> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(Rect::bounds_all(self.contours.iter().flat_map(|contour| contour.pts.iter()).flat_map(|pt| [pt.prev, pt.pt, pt.next])))
+    }
+
+    /// Approximate the region with polygons, for testing which points are inside it
+    pub fn flatten(&self) -> PolygonRegion {
+        PolygonRegion::from_contours(&self.contours)
+    }
+
+    /// Is the point inside the region?
+    /// To test many points, flatten the region once and use `PolygonRegion::contains` instead.
+    pub fn contains(&self, pt: Vec2) -> bool {
+        self.flatten().contains(pt)
+    }
+
+    /// Do the regions share any area, or touch?
+    /// If not, combining them leaves both unchanged.
+    pub fn overlaps(&self, other: &BezierRegion) -> bool {
+        let (Some(bounds), Some(other_bounds)) = (self.bounds(), other.bounds()) else { return false; };
+        if !bounds.intersects(other_bounds) {
+            return false;
+        }
+
+        let contours: Vec<_> = self.contours.iter().map(|contour| BezierPath::from_segments(&contour.closed_segments())).collect();
+        let other_contours: Vec<_> = other.contours.iter().map(|contour| BezierPath::from_segments(&contour.closed_segments())).collect();
+        if contours.iter().any(|contour| other_contours.iter().any(|other_contour| !contour.intersect_ts(other_contour).is_empty())) {
+            return true;
+        }
+
+        // If no contours cross, the regions only overlap if one has a contour inside the other
+        let polygons = self.flatten();
+        let other_polygons = other.flatten();
+        contours.iter().filter_map(|contour| contour.pts.first()).any(|pt| other_polygons.contains(pt.pt)) ||
+            other_contours.iter().filter_map(|contour| contour.pts.first()).any(|pt| polygons.contains(pt.pt))
+    }
+
+}
+
+impl BezierPath<Vec2> {
+
+    /// The segments of the path as a closed contour, including the straight line from its last point back to its first
+    pub fn closed_segments(&self) -> Vec<BezierSegment<Vec2>> {
+        let mut segments: Vec<_> = self.iter_segments().collect();
+        if let (Some(first), Some(last)) = (self.pts.first(), self.pts.last()) {
+            if first.pt.distance(last.pt) > CLOSED_DISTANCE {
+                segments.push(BezierSegment::straight(last.pt, first.pt));
+            }
+        }
+        segments
+    }
+
+}
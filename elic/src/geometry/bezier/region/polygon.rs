@@ -0,0 +1,46 @@
+
+use crate::Vec2;
+
+use super::BezierPath;
+
+/// The number of points each segment is approximated with when flattening a contour
+const FLATTEN_SAMPLES: usize = 16;
+
+/// An area bounded by closed polygons, filled using the even-odd rule
+#[derive(Clone, Default)]
+pub struct PolygonRegion {
+    pub polygons: Vec<Vec<Vec2>>
+}
+
+impl PolygonRegion {
+
+    /// Approximate closed Bézier contours with polygons
+    pub fn from_contours(contours: &[BezierPath<Vec2>]) -> Self {
+        Self {
+            polygons: contours.iter().map(|contour| {
+                contour.closed_segments().iter()
+                    .flat_map(|segment| (0..FLATTEN_SAMPLES).map(|i| segment.sample(i as f32 / FLATTEN_SAMPLES as f32)))
+                    .collect()
+            }).collect()
+        }
+    }
+
+    /// Is the point inside the region? A point is inside if a ray from it crosses the polygons an odd number of times.
+    pub fn contains(&self, pt: Vec2) -> bool {
+        let mut inside = false;
+        for polygon in &self.polygons {
+            for i in 0..polygon.len() {
+                let a = polygon[i];
+                let b = polygon[(i + 1) % polygon.len()];
+                if (a.y > pt.y) != (b.y > pt.y) {
+                    let x = a.x + (pt.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                    if x > pt.x {
+                        inside = !inside;
+                    }
+                }
+            }
+        }
+        inside
+    }
+
+}
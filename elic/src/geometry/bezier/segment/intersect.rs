@@ -0,0 +1,97 @@
+
+use crate::{Range, Vec2};
+use super::super::INTERSECTION_MERGE_DISTANCE;
+use super::BezierSegment;
+
+/// Pieces of a segment whose control points are all closer than this to their chord are treated as straight lines
+const FLATNESS_TOLERANCE: f32 = 0.0001;
+
+/// Stop subdividing after this many steps, even if the pieces are not flat yet
+const MAX_INTERSECT_DEPTH: u32 = 40;
+
+
+impl BezierSegment<Vec2> {
+
+    /// How far the control points are from the straight line between the ends of the segment
+    pub fn flatness(&self) -> f32 {
+        let chord = self.p1 - self.p0;
+        let chord_length = chord.length();
+        if chord_length < 0.00001 {
+            return self.p0.distance(self.b0).max(self.p0.distance(self.a1));
+        }
+        let dist_b0 = chord.cross(self.b0 - self.p0).abs() / chord_length;
+        let dist_a1 = chord.cross(self.a1 - self.p0).abs() / chord_length;
+        dist_b0.max(dist_a1)
+    }
+
+    /// The parameters at which two segments cross or touch, as pairs of (parameter on `self`, parameter on `other`).
+    /// The pairs are sorted by the parameter on `self`.
+    /// Segments that overlap along a stretch of their length don't have a meaningful list of intersections, so the result is unspecified for them.
+    pub fn intersect_ts(&self, other: &BezierSegment<Vec2>) -> Vec<(f32, f32)> {
+        let mut ts = Vec::new();
+        intersect_subdivided(self, Range::new(0.0, 1.0), other, Range::new(0.0, 1.0), 0, &mut ts);
+
+        ts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut merged: Vec<(f32, f32)> = Vec::new();
+        for (t0, t1) in ts {
+            let duplicate = merged.iter().any(|(m0, m1)| (m0 - t0).abs() < INTERSECTION_MERGE_DISTANCE && (m1 - t1).abs() < INTERSECTION_MERGE_DISTANCE);
+            if !duplicate {
+                merged.push((t0, t1));
+            }
+        }
+        merged
+    }
+
+}
+
+/// Find the intersections of two pieces of segments by splitting them until they are flat enough to be treated as lines.
+/// `a_range` and `b_range` are the parameters of the pieces on the original segments.
+fn intersect_subdivided(a: &BezierSegment<Vec2>, a_range: Range, b: &BezierSegment<Vec2>, b_range: Range, depth: u32, ts: &mut Vec<(f32, f32)>) {
+    let a_bounds = a.hull_bounds();
+    let b_bounds = b.hull_bounds();
+    if a_bounds.left() > b_bounds.right() || b_bounds.left() > a_bounds.right() || a_bounds.top() > b_bounds.bottom() || b_bounds.top() > a_bounds.bottom() {
+        return;
+    }
+
+    let a_flat = a.flatness() < FLATNESS_TOLERANCE;
+    let b_flat = b.flatness() < FLATNESS_TOLERANCE;
+    if (a_flat && b_flat) || depth >= MAX_INTERSECT_DEPTH {
+        if let Some((s, t)) = intersect_chords(a, b) {
+            ts.push((a_range.min + s * a_range.size(), b_range.min + t * b_range.size()));
+        }
+        return;
+    }
+
+    // Split the piece that is further from being flat
+    if !a_flat && (b_flat || a_bounds.size().max_component() >= b_bounds.size().max_component()) {
+        let (a0, a1) = a.split(0.5);
+        let mid = a_range.center();
+        intersect_subdivided(&a0, Range::new(a_range.min, mid), b, b_range, depth + 1, ts);
+        intersect_subdivided(&a1, Range::new(mid, a_range.max), b, b_range, depth + 1, ts);
+    } else {
+        let (b0, b1) = b.split(0.5);
+        let mid = b_range.center();
+        intersect_subdivided(a, a_range, &b0, Range::new(b_range.min, mid), depth + 1, ts);
+        intersect_subdivided(a, a_range, &b1, Range::new(mid, b_range.max), depth + 1, ts);
+    }
+}
+
+/// The intersection of the chords of two segments, as parameters along each chord
+fn intersect_chords(a: &BezierSegment<Vec2>, b: &BezierSegment<Vec2>) -> Option<(f32, f32)> {
+    let a_dir = a.p1 - a.p0;
+    let b_dir = b.p1 - b.p0;
+    let denom = a_dir.cross(b_dir);
+    if denom.abs() < 0.0000001 {
+        // Parallel chords only count if the pieces have shrunk down to the same point
+        return (a.p0.distance(b.p0) < FLATNESS_TOLERANCE).then_some((0.0, 0.0));
+    }
+    let offset = b.p0 - a.p0;
+    let s = offset.cross(b_dir) / denom;
+    let t = offset.cross(a_dir) / denom;
+    // Allow a little slack, so that intersections right at the ends of the pieces aren't missed
+    let slack = 0.0001;
+    if s < -slack || s > 1.0 + slack || t < -slack || t > 1.0 + slack {
+        return None;
+    }
+    Some((s.clamp(0.0, 1.0), t.clamp(0.0, 1.0)))
+}
@@ -0,0 +1,77 @@
+
+use crate::Vec2;
+use super::BezierSegment;
+
+/// Nodes and weights of the 8-point Gauss-Legendre quadrature on -1..1
+const GAUSS_LEGENDRE: [(f32, f32); 8] = [
+    (-0.960_289_9, 0.101_228_54),
+    (-0.796_666_5, 0.222_381_03),
+    (-0.525_532_4, 0.313_706_65),
+    (-0.183_434_64, 0.362_683_78),
+    (0.183_434_64, 0.362_683_78),
+    (0.525_532_4, 0.313_706_65),
+    (0.796_666_5, 0.222_381_03),
+    (0.960_289_9, 0.101_228_54)
+];
+
+/// The range of parameters is split into this many parts, each integrated separately
+const LENGTH_SUBDIVISIONS: usize = 4;
+
+impl BezierSegment<Vec2> {
+
+    /// The arc length of the segment between parameters `t0` and `t1`
+    pub fn length_between(&self, t0: f32, t1: f32) -> f32 {
+        let step = (t1 - t0) / LENGTH_SUBDIVISIONS as f32;
+        let mut length = 0.0;
+        for i in 0..LENGTH_SUBDIVISIONS {
+            let center = t0 + step * (i as f32 + 0.5);
+            for (node, weight) in GAUSS_LEGENDRE {
+                length += weight * self.sample_derivative(center + node * step * 0.5).length();
+            }
+        }
+        length * step * 0.5
+    }
+
+    /// The arc length of the segment
+    pub fn length(&self) -> f32 {
+        self.length_between(0.0, 1.0)
+    }
+
+    /// The parameter at which the arc length from the start of the segment is `length`.
+    /// Lengths outside of the segment are clamped to its ends.
+    pub fn t_at_length(&self, length: f32) -> f32 {
+        let total_length = self.length();
+        if length <= 0.0 || total_length <= 0.0 {
+            return 0.0;
+        }
+        if length >= total_length {
+            return 1.0;
+        }
+
+        // Newton's method, falling back to bisection whenever a step leaves the bracket around the solution
+        let mut lo = 0.0;
+        let mut hi = 1.0;
+        let mut t = length / total_length;
+        for _ in 0..32 {
+            let error = self.length_between(0.0, t) - length;
+            if error.abs() < total_length * 0.00001 {
+                break;
+            }
+            if error > 0.0 {
+                hi = t;
+            } else {
+                lo = t;
+            }
+            let speed = self.sample_derivative(t).length();
+            let next_t = t - error / speed;
+            t = if speed > 0.0 && next_t > lo && next_t < hi { next_t } else { (lo + hi) * 0.5 };
+        }
+        t
+    }
+
+    /// The point on the segment at arc length `length` from its start
+    pub fn sample_at_length(&self, length: f32) -> Vec2 {
+        self.sample(self.t_at_length(length))
+    }
+
+}
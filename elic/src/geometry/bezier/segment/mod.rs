@@ -1,5 +1,5 @@
 
-use crate::{Linear, Vec2};
+use crate::{Linear, Rect, Vec2};
 
 use super::BezierPoint;
 
@@ -10,6 +10,12 @@ mod bounds;
 
 mod parallel;
 
+mod length;
+
+mod intersect;
+
+mod offset;
+
 #[derive(Clone, Copy)]
 pub struct BezierSegment<T: Linear> {
     pub p0: T,
@@ -91,6 +97,50 @@ impl<T: Linear> BezierSegment<T> {
         (seg_0t, seg_t1) 
     }
 
+    /// The part of the segment between `t0` and `t1`, running backwards if `t1` is less than `t0`
+    pub fn subsegment(&self, t0: f32, t1: f32) -> BezierSegment<T> {
+        if t1 < t0 {
+            return self.subsegment(t1, t0).reverse();
+        }
+        let t0 = t0.clamp(0.0, 1.0);
+        let t1 = t1.clamp(0.0, 1.0);
+        if t0 >= 1.0 {
+            return BezierSegment {
+                p0: self.p1.clone(),
+                b0: self.p1.clone(),
+                a1: self.p1.clone(),
+                p1: self.p1.clone()
+            };
+        }
+        let segment = if t0 > 0.0 { self.split(t0).1 } else { self.clone() };
+        if t1 < 1.0 {
+            segment.split((t1 - t0) / (1.0 - t0)).0
+        } else {
+            segment
+        }
+    }
+
+    /// Split the segment at a list of increasing parameters
+    pub fn split_at(&self, ts: &[f32]) -> Vec<BezierSegment<T>> {
+        let mut segments = Vec::new();
+        let mut prev_t = 0.0;
+        for t in ts.iter().copied().chain(std::iter::once(1.0)) {
+            segments.push(self.subsegment(prev_t, t));
+            prev_t = t;
+        }
+        segments
+    }
+
+    /// The same curve, running from `p1` to `p0`
+    pub fn reverse(&self) -> BezierSegment<T> {
+        BezierSegment {
+            p0: self.p1.clone(),
+            b0: self.a1.clone(),
+            a1: self.b0.clone(),
+            p1: self.p0.clone()
+        }
+    }
+
 }
 
 impl BezierSegment<Vec2> {
@@ -102,5 +152,29 @@ impl BezierSegment<Vec2> {
     pub fn sample_normal(&self, t: f32) -> Vec2 {
         self.sample_tangent(t).turn_cw()
     }
+
+    pub fn sample_second_derivative(&self, t: f32) -> Vec2 {
+        let p0 = self.p0 * (6.0 * (1.0 - t));
+        let p1 = self.b0 * (18.0 * t - 12.0);
+        let p2 = self.a1 * (6.0 - 18.0 * t);
+        let p3 = self.p1 * (6.0 * t);
+        p0 + p1 + p2 + p3
+    }
+
+    /// The signed curvature of the segment. Positive where the segment turns counter-clockwise.
+    pub fn sample_curvature(&self, t: f32) -> f32 {
+        let d1 = self.sample_derivative(t);
+        let d2 = self.sample_second_derivative(t);
+        let speed = d1.length();
+        if speed < 0.00001 {
+            return 0.0;
+        }
+        d1.cross(d2) / (speed * speed * speed)
+    }
+
+    /// The bounding box of the control points, which always contains the segment
+    pub fn hull_bounds(&self) -> Rect {
+        Rect::bounds_all([self.p0, self.b0, self.a1, self.p1].into_iter())
+    }
     
 }
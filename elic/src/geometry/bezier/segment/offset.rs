@@ -0,0 +1,78 @@
+
+use crate::Vec2;
+use super::BezierSegment;
+
+/// The number of points checked along each piece of an offset curve when measuring its error
+const OFFSET_ERROR_SAMPLES: usize = 8;
+
+/// Stop splitting the segment after this many steps, even if the offset curve is not accurate enough yet
+const MAX_OFFSET_DEPTH: u32 = 8;
+
+impl BezierSegment<Vec2> {
+
+    /// The direction of the segment at `t`, even where the derivative vanishes, such as at an end with a zero-length handle
+    fn direction_at(&self, t: f32) -> Vec2 {
+        let derivative = self.sample_derivative(t);
+        if derivative.length() > 0.00001 {
+            return derivative.normalize();
+        }
+        // Look slightly further along the segment
+        let nudged_t = if t < 0.5 { t + 0.001 } else { t - 0.001 };
+        let derivative = self.sample_derivative(nudged_t);
+        if derivative.length() > 0.00001 {
+            return derivative.normalize();
+        }
+        let chord = self.p1 - self.p0;
+        if chord.length() > 0.00001 {
+            chord.normalize()
+        } else {
+            Vec2::X
+        }
+    }
+
+    /// The point at distance `dist` from the segment along its normal at `t`
+    fn offset_point(&self, t: f32, dist: f32) -> Vec2 {
+        self.sample(t) + self.direction_at(t).turn_cw() * dist
+    }
+
+    /// Approximate the curve at distance `dist` from the segment with a list of segments.
+    /// Positive distances offset the segment in the direction of `sample_normal`.
+    /// The approximation is split until every piece is within `tolerance` of the exact offset curve.
+    pub fn offset(&self, dist: f32, tolerance: f32) -> Vec<BezierSegment<Vec2>> {
+        let mut segments = Vec::new();
+        self.offset_range(0.0, 1.0, dist, tolerance, 0, &mut segments);
+        segments
+    }
+
+    fn offset_range(&self, t0: f32, t1: f32, dist: f32, tolerance: f32, depth: u32, segments: &mut Vec<BezierSegment<Vec2>>) {
+        let piece = self.subsegment(t0, t1);
+
+        // The derivative of the offset curve is the derivative of the segment scaled by (1 + dist * curvature),
+        // so scaling the handles by the same amount keeps the ends of the approximation tangent to the offset curve.
+        let scale_0 = (1.0 + dist * piece.sample_curvature(0.0)).max(0.0);
+        let scale_1 = (1.0 + dist * piece.sample_curvature(1.0)).max(0.0);
+        let p0 = piece.offset_point(0.0, dist);
+        let p1 = piece.offset_point(1.0, dist);
+        let approx = BezierSegment {
+            p0,
+            b0: p0 + (piece.b0 - piece.p0) * scale_0,
+            a1: p1 + (piece.a1 - piece.p1) * scale_1,
+            p1
+        };
+
+        let error = (1..OFFSET_ERROR_SAMPLES).map(|i| {
+            let t = i as f32 / OFFSET_ERROR_SAMPLES as f32;
+            (approx.sample(t).distance(piece.sample(t)) - dist.abs()).abs()
+        }).fold(0.0, f32::max);
+
+        if error <= tolerance || depth >= MAX_OFFSET_DEPTH {
+            segments.push(approx);
+            return;
+        }
+
+        let mid = (t0 + t1) * 0.5;
+        self.offset_range(t0, mid, dist, tolerance, depth + 1, segments);
+        self.offset_range(mid, t1, dist, tolerance, depth + 1, segments);
+    }
+
+}
@@ -70,6 +70,11 @@ impl Vec2 {
         self.x * other.x + self.y * other.y
     }
 
+    /// The z component of the 3D cross product. Positive when `other` points counter-clockwise of `self`.
+    pub fn cross(&self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
     pub fn map(&self, from: Rect, to: Rect) -> Self {
         vec2(
             map(self.x,from.x_range(), to.x_range()),
@@ -0,0 +1,295 @@
+
+use elic::{vec2, BezierPath, BezierPoint, BezierRegion, BezierSegment, Vec2};
+
+fn assert_close(a: f32, b: f32, tolerance: f32) {
+    assert!((a - b).abs() <= tolerance, "{} is not within {} of {}", a, tolerance, b);
+}
+
+fn assert_close_pt(a: Vec2, b: Vec2, tolerance: f32) {
+    assert!(a.distance(b) <= tolerance, "({}, {}) is not within {} of ({}, {})", a.x, a.y, tolerance, b.x, b.y);
+}
+
+fn curve() -> BezierSegment<Vec2> {
+    BezierSegment {
+        p0: vec2(0.0, 0.0),
+        b0: vec2(10.0, 40.0),
+        a1: vec2(60.0, -20.0),
+        p1: vec2(80.0, 30.0)
+    }
+}
+
+/// A path through a list of points, with straight segments between them
+fn polyline(pts: &[Vec2]) -> BezierPath<Vec2> {
+    let segments: Vec<_> = pts.windows(2).map(|pts| BezierSegment::straight(pts[0], pts[1])).collect();
+    BezierPath::from_segments(&segments)
+}
+
+/// A circle of four segments, running counter-clockwise from its rightmost point
+fn circle(center: Vec2, radius: f32) -> BezierPath<Vec2> {
+    let handle = radius * 0.552_284_8;
+    let pts = [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y, Vec2::X];
+    let segments: Vec<_> = pts.windows(2).map(|dirs| BezierSegment {
+        p0: center + dirs[0] * radius,
+        b0: center + dirs[0] * radius + dirs[0].turn_ccw() * handle,
+        a1: center + dirs[1] * radius - dirs[1].turn_ccw() * handle,
+        p1: center + dirs[1] * radius
+    }).collect();
+    BezierPath::from_segments(&segments)
+}
+
+#[test]
+fn subsegment_follows_curve() {
+    let segment = curve();
+    let sub = segment.subsegment(0.25, 0.75);
+    for i in 0..=10 {
+        let t = i as f32 / 10.0;
+        assert_close_pt(sub.sample(t), segment.sample(0.25 + t * 0.5), 0.001);
+    }
+
+    let reversed = segment.subsegment(0.75, 0.25);
+    assert_close_pt(reversed.p0, segment.sample(0.75), 0.001);
+    assert_close_pt(reversed.p1, segment.sample(0.25), 0.001);
+
+    let pieces = segment.split_at(&[0.2, 0.5, 0.9]);
+    assert_eq!(pieces.len(), 4);
+    assert_close_pt(pieces[1].sample(0.5), segment.sample(0.35), 0.001);
+    assert_close_pt(pieces[3].p1, segment.p1, 0.001);
+}
+
+#[test]
+fn split_path() {
+    let path = polyline(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)]);
+    assert_eq!(path.n_segments(), 3);
+
+    let (before, after) = path.split(1.5);
+    assert_eq!(before.n_segments(), 2);
+    assert_eq!(after.n_segments(), 2);
+    assert_close_pt(before.pts.last().unwrap().pt, vec2(10.0, 5.0), 0.001);
+    assert_close_pt(after.pts[0].pt, vec2(10.0, 5.0), 0.001);
+    assert_close_pt(after.pts.last().unwrap().pt, vec2(0.0, 10.0), 0.001);
+
+    let middle = path.subpath(0.5, 2.5);
+    assert_close_pt(middle.pts[0].pt, vec2(5.0, 0.0), 0.001);
+    assert_close_pt(middle.pts.last().unwrap().pt, vec2(5.0, 10.0), 0.001);
+
+    let pieces = path.split_at(&[0.5, 2.0]);
+    assert_eq!(pieces.len(), 3);
+    assert_eq!(pieces[2].n_segments(), 1);
+
+    let reversed = path.reverse();
+    assert_close_pt(reversed.sample(0.5), path.sample(2.5), 0.001);
+}
+
+#[test]
+fn subpath_keeps_outer_handles() {
+    let mut path = polyline(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(20.0, 0.0)]);
+    path.pts[0].prev = vec2(-5.0, 1.0);
+    path.pts[2].next = vec2(25.0, 1.0);
+
+    let start = path.subpath(0.0, 1.5);
+    assert_close_pt(start.pts[0].prev, vec2(-5.0, 1.0), 0.0001);
+    let end = path.subpath(0.5, 2.0);
+    assert_close_pt(end.pts.last().unwrap().next, vec2(25.0, 1.0), 0.0001);
+}
+
+#[test]
+fn arc_length() {
+    let line = BezierSegment::straight(vec2(0.0, 0.0), vec2(30.0, 40.0));
+    assert_close(line.length(), 50.0, 0.001);
+    assert_close_pt(line.sample_at_length(25.0), vec2(15.0, 20.0), 0.01);
+
+    // A quarter of a circle of radius 10, approximated by a single segment
+    let quarter = circle(Vec2::ZERO, 10.0).subpath(0.0, 1.0);
+    assert_close(quarter.length(), std::f32::consts::FRAC_PI_2 * 10.0, 0.01);
+
+    let circle = circle(Vec2::ZERO, 10.0);
+    let circumference = circle.length();
+    assert_close(circumference, std::f32::consts::TAU * 10.0, 0.05);
+    assert_close(circle.t_at_length(circumference * 0.5), 2.0, 0.001);
+    assert_close_pt(circle.sample_at_length(circumference * 0.25), vec2(0.0, 10.0), 0.01);
+    assert_close(circle.t_at_length(-1.0), 0.0, 0.0);
+    assert_close(circle.t_at_length(circumference * 2.0), 4.0, 0.0);
+
+    // Arc length parametrization of an uneven curve
+    let segment = curve();
+    let length = segment.length();
+    for i in 1..10 {
+        let target = length * i as f32 / 10.0;
+        let t = segment.t_at_length(target);
+        assert_close(segment.length_between(0.0, t), target, length * 0.0001);
+    }
+    let path = BezierPath::from_segments(&[segment]);
+    assert_close(path.length_at(0.5), segment.length_between(0.0, 0.5), 0.001);
+}
+
+#[test]
+fn segment_intersections() {
+    let line = BezierSegment::straight(vec2(0.0, 10.0), vec2(80.0, 10.0));
+    let segment = curve();
+    let ts = segment.intersect_ts(&line);
+    assert_eq!(ts.len(), 3);
+    for (t_segment, t_line) in ts {
+        assert_close_pt(segment.sample(t_segment), line.sample(t_line), 0.01);
+        assert_close(segment.sample(t_segment).y, 10.0, 0.01);
+    }
+
+    let far = BezierSegment::straight(vec2(0.0, 100.0), vec2(80.0, 100.0));
+    assert!(segment.intersect_ts(&far).is_empty());
+
+    // Two lines crossing
+    let a = BezierSegment::straight(vec2(0.0, 0.0), vec2(10.0, 10.0));
+    let b = BezierSegment::straight(vec2(0.0, 10.0), vec2(10.0, 0.0));
+    let ts = a.intersect_ts(&b);
+    assert_eq!(ts.len(), 1);
+    assert_close(ts[0].0, 0.5, 0.001);
+    assert_close(ts[0].1, 0.5, 0.001);
+}
+
+#[test]
+fn path_intersections() {
+    let a = circle(Vec2::ZERO, 10.0);
+    let b = circle(vec2(10.0, 0.0), 10.0);
+    let pts = a.intersections(&b);
+    assert_eq!(pts.len(), 2);
+    let expected_y = (100.0f32 - 25.0).sqrt();
+    for pt in pts {
+        assert_close(pt.x, 5.0, 0.01);
+        assert_close(pt.y.abs(), expected_y, 0.01);
+    }
+
+    // A line through the corner where two segments of a path meet is only counted once
+    let corner = polyline(&[vec2(0.0, 0.0), vec2(10.0, 10.0), vec2(20.0, 0.0)]);
+    let line = polyline(&[vec2(10.0, 0.0), vec2(10.0, 20.0)]);
+    let ts = corner.intersect_ts(&line);
+    assert_eq!(ts.len(), 1);
+    assert_close(ts[0].0, 1.0, 0.001);
+    assert_close(ts[0].1, 0.5, 0.001);
+}
+
+#[test]
+fn offset() {
+    let line = BezierSegment::straight(vec2(0.0, 0.0), vec2(10.0, 0.0));
+    let offset = line.offset(2.0, 0.01);
+    assert_eq!(offset.len(), 1);
+    // The normal of a line running right points down
+    assert_close_pt(offset[0].p0, vec2(0.0, -2.0), 0.001);
+    assert_close_pt(offset[0].p1, vec2(10.0, -2.0), 0.001);
+
+    // The normals of a counter-clockwise circle point outwards
+    let circle = circle(Vec2::ZERO, 10.0);
+    let bigger = circle.offset(5.0, 0.01);
+    let smaller = circle.offset(-5.0, 0.01);
+    for i in 0..=40 {
+        let t = i as f32 / 40.0 * bigger.n_segments() as f32;
+        assert_close(bigger.sample(t).length(), 15.0, 0.02);
+        let t = i as f32 / 40.0 * smaller.n_segments() as f32;
+        assert_close(smaller.sample(t).length(), 5.0, 0.02);
+    }
+
+    // Every point of the offset of an uneven curve is at the right distance from it
+    let segment = curve();
+    let offset = BezierPath::from_segments(&segment.offset(3.0, 0.01));
+    let closest_dist = |pt: Vec2| (0..=2000).map(|i| segment.sample(i as f32 / 2000.0).distance(pt)).fold(f32::INFINITY, f32::min);
+    for i in 0..=50 {
+        let t = i as f32 / 50.0 * offset.n_segments() as f32;
+        assert_close(closest_dist(offset.sample(t)), 3.0, 0.05);
+    }
+
+    // Corners between segments are bridged
+    let corner = polyline(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]);
+    let offset = corner.offset(1.0, 0.01);
+    assert_eq!(offset.n_segments(), 3);
+    assert_close_pt(offset.pts[0].pt, vec2(0.0, -1.0), 0.001);
+    assert_close_pt(offset.pts[3].pt, vec2(11.0, 10.0), 0.001);
+}
+
+#[test]
+fn subdivide_keeps_shape() {
+    let path = BezierPath::from_segments(&[curve(), BezierSegment::straight(curve().p1, vec2(100.0, 0.0))]);
+    let subdivided = path.subdivide(&[0.25, 0.5, 1.5]);
+    assert_eq!(subdivided.pts.len(), path.pts.len() + 3);
+    assert_close_pt(subdivided.pts[1].pt, path.sample(0.25), 0.001);
+    assert_close_pt(subdivided.pts[4].pt, path.sample(1.5), 0.001);
+    for i in 0..=20 {
+        let t = i as f32 / 20.0;
+        assert_close_pt(subdivided.sample(t), path.sample(0.25 * t), 0.001);
+        assert_close_pt(subdivided.sample(1.0 + t), path.sample(0.25 + 0.25 * t), 0.001);
+        assert_close_pt(subdivided.sample(4.0 + t), path.sample(1.5 + 0.5 * t), 0.001);
+    }
+
+    // Points are inserted even where the path already has one
+    assert_eq!(path.subdivide(&[1.0]).pts.len(), path.pts.len() + 1);
+}
+
+#[test]
+fn match_points() {
+    let a = polyline(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(20.0, 0.0), vec2(40.0, 0.0)]);
+    let b = polyline(&[vec2(0.0, 10.0), vec2(20.0, 10.0), vec2(40.0, 10.0)]);
+    let (a_matched, b_matched) = a.match_points(&b, |pt| *pt);
+
+    // a's point at 1/4 of the way along is added to b, and a's point at 1/2 of the way along is shared with b
+    assert_eq!(a_matched.pts.len(), 4);
+    assert_eq!(b_matched.pts.len(), 4);
+    for (a_pt, b_pt) in a_matched.pts.iter().zip(b_matched.pts.iter()) {
+        assert_close(a_pt.pt.x, b_pt.pt.x, 0.001);
+    }
+
+    let halfway = a_matched.lerp(&b_matched, 0.5).unwrap();
+    assert_close_pt(halfway.pts[1].pt, vec2(10.0, 5.0), 0.001);
+    assert_close_pt(halfway.sample(2.5), vec2(30.0, 5.0), 0.001);
+
+    // Matching against a curve keeps the shape of both paths
+    let curved = BezierPath::from_segments(&[curve()]);
+    let (a_matched, curved_matched) = a.match_points(&curved, |pt| *pt);
+    assert_eq!(a_matched.pts.len(), curved_matched.pts.len());
+    assert_close_pt(curved_matched.pts[1].pt, curve().sample_at_length(curve().length() * 0.25), 0.01);
+    assert_close_pt(curved_matched.pts[2].pt, curve().sample_at_length(curve().length() * 0.5), 0.01);
+    assert_close_pt(curved_matched.sample(1.5), curve().sample(curve().t_at_length(curve().length() * 0.25) * 0.5 + curve().t_at_length(curve().length() * 0.5) * 0.5), 0.01);
+
+    // Single points are repeated to match the other path
+    let dot = BezierPath { pts: vec![elic::BezierPoint::new(vec2(5.0, 5.0), vec2(5.0, 5.0), vec2(5.0, 5.0))] };
+    let (dot_matched, b_matched) = dot.match_points(&b, |pt| *pt);
+    assert_eq!(dot_matched.pts.len(), b.pts.len());
+    assert_eq!(b_matched.pts.len(), b.pts.len());
+
+    assert!(a.lerp(&b, 0.5).is_none());
+}
+
+#[test]
+fn cut_by_region() {
+    let circle = BezierRegion::circle(vec2(10.0, 0.0), 5.0);
+    let ends = |pieces: &[BezierPath<Vec2>]| pieces.iter().map(|piece| (piece.pts[0].pt, piece.pts[piece.pts.len() - 1].pt)).collect::<Vec<_>>();
+
+    // Passing through the region leaves a piece on either side
+    let pieces = polyline(&[vec2(0.0, 0.0), vec2(20.0, 0.0)]).cut(&circle, |pt| *pt).unwrap();
+    let pieces = ends(&pieces);
+    assert_eq!(pieces.len(), 2);
+    assert_close_pt(pieces[0].0, vec2(0.0, 0.0), 0.001);
+    assert_close_pt(pieces[0].1, vec2(5.0, 0.0), 0.01);
+    assert_close_pt(pieces[1].0, vec2(15.0, 0.0), 0.01);
+    assert_close_pt(pieces[1].1, vec2(20.0, 0.0), 0.001);
+
+    // Starting inside, and crossing back and forth
+    let pieces = polyline(&[vec2(10.0, 0.0), vec2(20.0, 0.0), vec2(20.0, 2.0), vec2(0.0, 2.0)]).cut(&circle, |pt| *pt).unwrap();
+    assert_eq!(pieces.len(), 2);
+    assert_close_pt(pieces[0].pts[0].pt, vec2(15.0, 0.0), 0.01);
+    assert_eq!(pieces[0].n_segments(), 3);
+    assert_close_pt(pieces[1].pts[0].pt, vec2(10.0 - 21.0f32.sqrt(), 2.0), 0.01);
+
+    // Entirely inside
+    assert!(polyline(&[vec2(8.0, 0.0), vec2(12.0, 0.0)]).cut(&circle, |pt| *pt).unwrap().is_empty());
+
+    // Missing the region, and only touching it
+    assert!(polyline(&[vec2(0.0, 10.0), vec2(20.0, 10.0)]).cut(&circle, |pt| *pt).is_none());
+    assert!(polyline(&[vec2(0.0, 5.0), vec2(20.0, 5.0)]).cut(&circle, |pt| *pt).is_none());
+
+    // Paths with a single point
+    let dot = |pt: Vec2| BezierPath { pts: vec![BezierPoint::new(pt, pt, pt)] };
+    assert!(dot(vec2(10.0, 1.0)).cut(&circle, |pt| *pt).unwrap().is_empty());
+    assert!(dot(vec2(20.0, 1.0)).cut(&circle, |pt| *pt).is_none());
+
+    // The position of the points can be found through a map
+    let pieces = polyline(&[vec2(0.0, 10.0), vec2(20.0, 10.0)]).cut(&circle, |pt| *pt - vec2(0.0, 10.0)).unwrap();
+    assert_eq!(pieces.len(), 2);
+    assert_close_pt(pieces[0].pts[1].pt, vec2(5.0, 10.0), 0.01);
+}
@@ -0,0 +1,206 @@
+
+use elic::{vec2, BezierPath, BezierRegion, BezierSegment, Vec2};
+
+fn polygon(pts: &[Vec2]) -> BezierPath<Vec2> {
+    let segments: Vec<_> = pts.windows(2).map(|pts| BezierSegment::straight(pts[0], pts[1])).collect();
+    BezierPath::from_segments(&segments)
+}
+
+fn square(min: Vec2, size: f32) -> BezierPath<Vec2> {
+    polygon(&[min, min + vec2(size, 0.0), min + vec2(size, size), min + vec2(0.0, size)])
+}
+
+fn circle(center: Vec2, radius: f32) -> BezierPath<Vec2> {
+    let handle = radius * 0.552_284_8;
+    let dirs = [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y, Vec2::X];
+    let segments: Vec<_> = dirs.windows(2).map(|dirs| BezierSegment {
+        p0: center + dirs[0] * radius,
+        b0: center + dirs[0] * radius + dirs[0].turn_ccw() * handle,
+        a1: center + dirs[1] * radius - dirs[1].turn_ccw() * handle,
+        p1: center + dirs[1] * radius
+    }).collect();
+    BezierPath::from_segments(&segments)
+}
+
+/// Check that a region covers the same area as `expected` on a grid of points,
+/// ignoring points too close to the boundaries for the result to be meaningful
+fn assert_covers<F: Fn(Vec2) -> bool, B: Fn(Vec2) -> bool>(region: &BezierRegion, expected: F, near_boundary: B) {
+    let polygons = region.flatten();
+    let mut checked = 0;
+    for x in -10..=40 {
+        for y in -10..=40 {
+            let pt = vec2(x as f32 + 0.37, y as f32 + 0.61);
+            if near_boundary(pt) {
+                continue;
+            }
+            assert_eq!(polygons.contains(pt), expected(pt), "wrong result at ({}, {})", pt.x, pt.y);
+            checked += 1;
+        }
+    }
+    assert!(checked > 1000);
+}
+
+fn in_square(pt: Vec2, min: Vec2, size: f32) -> bool {
+    pt.x > min.x && pt.y > min.y && pt.x < min.x + size && pt.y < min.y + size
+}
+
+fn near_square(pt: Vec2, min: Vec2, size: f32) -> bool {
+    let near = |a: f32, b: f32| (a - b).abs() < 0.1;
+    let in_x = pt.x > min.x - 0.1 && pt.x < min.x + size + 0.1;
+    let in_y = pt.y > min.y - 0.1 && pt.y < min.y + size + 0.1;
+    (in_y && (near(pt.x, min.x) || near(pt.x, min.x + size))) || (in_x && (near(pt.y, min.y) || near(pt.y, min.y + size)))
+}
+
+#[test]
+fn contains() {
+    let region = BezierRegion::new(vec![square(vec2(0.0, 0.0), 20.0), square(vec2(5.0, 5.0), 10.0)]);
+    assert!(region.contains(vec2(2.0, 2.0)));
+    assert!(!region.contains(vec2(10.0, 10.0)));
+    assert!(!region.contains(vec2(30.0, 10.0)));
+    assert!(BezierRegion::default().is_empty());
+    assert!(BezierRegion::default().bounds().is_none());
+    let bounds = region.bounds().unwrap();
+    assert_eq!((bounds.width(), bounds.height()), (20.0, 20.0));
+}
+
+#[test]
+fn overlapping_squares() {
+    let a_min = vec2(0.0, 0.0);
+    let b_min = vec2(10.0, 10.0);
+    let a = BezierRegion::new(vec![square(a_min, 20.0)]);
+    let b = BezierRegion::new(vec![square(b_min, 20.0)]);
+    let near_boundary = |pt: Vec2| near_square(pt, a_min, 20.0) || near_square(pt, b_min, 20.0);
+
+    let union = a.union(&b);
+    assert_eq!(union.contours.len(), 1);
+    assert_covers(&union, |pt| in_square(pt, a_min, 20.0) || in_square(pt, b_min, 20.0), near_boundary);
+
+    let intersection = a.intersection(&b);
+    assert_eq!(intersection.contours.len(), 1);
+    assert_covers(&intersection, |pt| in_square(pt, a_min, 20.0) && in_square(pt, b_min, 20.0), near_boundary);
+
+    let difference = a.difference(&b);
+    assert_eq!(difference.contours.len(), 1);
+    assert_covers(&difference, |pt| in_square(pt, a_min, 20.0) && !in_square(pt, b_min, 20.0), near_boundary);
+}
+
+#[test]
+fn separate_and_nested_regions() {
+    let a = BezierRegion::new(vec![square(vec2(0.0, 0.0), 10.0)]);
+    let b = BezierRegion::new(vec![square(vec2(20.0, 20.0), 10.0)]);
+    assert_eq!(a.union(&b).contours.len(), 2);
+    assert!(a.intersection(&b).is_empty());
+    assert_eq!(a.difference(&b).contours.len(), 1);
+
+    // Cutting a hole out of a region
+    let outer = BezierRegion::new(vec![square(vec2(0.0, 0.0), 30.0)]);
+    let inner = BezierRegion::new(vec![square(vec2(10.0, 10.0), 10.0)]);
+    let near_boundary = |pt: Vec2| near_square(pt, vec2(0.0, 0.0), 30.0) || near_square(pt, vec2(10.0, 10.0), 10.0);
+    let hole = outer.difference(&inner);
+    assert_eq!(hole.contours.len(), 2);
+    assert_covers(&hole, |pt| in_square(pt, vec2(0.0, 0.0), 30.0) && !in_square(pt, vec2(10.0, 10.0), 10.0), near_boundary);
+    assert_eq!(outer.union(&inner).contours.len(), 1);
+    assert!(inner.difference(&outer).is_empty());
+
+    // The empty region
+    let empty = BezierRegion::default();
+    assert_eq!(a.union(&empty).contours.len(), 1);
+    assert!(a.intersection(&empty).is_empty());
+    assert!(empty.difference(&a).is_empty());
+}
+
+#[test]
+fn curved_regions() {
+    let a = BezierRegion::new(vec![circle(vec2(10.0, 15.0), 10.0)]);
+    let b = BezierRegion::new(vec![circle(vec2(20.0, 15.0), 10.0)]);
+    let in_a = |pt: Vec2| pt.distance(vec2(10.0, 15.0)) < 10.0;
+    let in_b = |pt: Vec2| pt.distance(vec2(20.0, 15.0)) < 10.0;
+    let near_boundary = |pt: Vec2| (pt.distance(vec2(10.0, 15.0)) - 10.0).abs() < 0.1 || (pt.distance(vec2(20.0, 15.0)) - 10.0).abs() < 0.1;
+
+    assert_covers(&a.union(&b), |pt| in_a(pt) || in_b(pt), near_boundary);
+    assert_covers(&a.intersection(&b), |pt| in_a(pt) && in_b(pt), near_boundary);
+    assert_covers(&a.difference(&b), |pt| in_a(pt) && !in_b(pt), near_boundary);
+
+    // Cutting a region in two
+    let strip = BezierRegion::new(vec![polygon(&[vec2(0.0, 10.0), vec2(30.0, 10.0), vec2(30.0, 20.0), vec2(0.0, 20.0)])]);
+    let cutter = BezierRegion::new(vec![circle(vec2(15.0, 15.0), 8.0)]);
+    let pieces = strip.difference(&cutter);
+    assert_eq!(pieces.contours.len(), 2);
+    assert!(pieces.contains(vec2(2.0, 15.0)));
+    assert!(pieces.contains(vec2(28.0, 15.0)));
+    assert!(!pieces.contains(vec2(15.0, 15.0)));
+}
+
+#[test]
+fn even_odd_regions() {
+    // A region with a hole, where the other region crosses both of its contours
+    let donut = BezierRegion::new(vec![square(vec2(0.0, 0.0), 30.0), square(vec2(10.0, 10.0), 10.0)]);
+    let bar = BezierRegion::new(vec![polygon(&[vec2(15.0, -5.0), vec2(25.0, -5.0), vec2(25.0, 35.0), vec2(15.0, 35.0)])]);
+    let in_donut = |pt: Vec2| in_square(pt, vec2(0.0, 0.0), 30.0) && !in_square(pt, vec2(10.0, 10.0), 10.0);
+    let in_bar = |pt: Vec2| pt.x > 15.0 && pt.x < 25.0 && pt.y > -5.0 && pt.y < 35.0;
+    let near = |a: f32, b: f32| (a - b).abs() < 0.1;
+    let near_boundary = |pt: Vec2| near_square(pt, vec2(0.0, 0.0), 30.0) || near_square(pt, vec2(10.0, 10.0), 10.0) || near(pt.x, 15.0) || near(pt.x, 25.0);
+
+    assert_covers(&donut.union(&bar), |pt| in_donut(pt) || in_bar(pt), near_boundary);
+    assert_covers(&donut.intersection(&bar), |pt| in_donut(pt) && in_bar(pt), near_boundary);
+    assert_covers(&donut.difference(&bar), |pt| in_donut(pt) && !in_bar(pt), near_boundary);
+    assert_covers(&bar.difference(&donut), |pt| in_bar(pt) && !in_donut(pt), near_boundary);
+}
+
+#[test]
+fn circle_region() {
+    let circle = BezierRegion::circle(vec2(15.0, 15.0), 10.0);
+    let in_circle = |pt: Vec2| pt.distance(vec2(15.0, 15.0)) < 10.0;
+    let near_boundary = |pt: Vec2| (pt.distance(vec2(15.0, 15.0)) - 10.0).abs() < 0.1;
+    assert_covers(&circle, in_circle, near_boundary);
+    let bounds = circle.bounds().unwrap();
+    assert_eq!((bounds.width(), bounds.height()), (20.0, 20.0));
+}
+
+#[test]
+fn overlaps() {
+    let square_region = BezierRegion::new(vec![square(vec2(0.0, 0.0), 20.0)]);
+
+    // Crossing the boundary
+    assert!(square_region.overlaps(&BezierRegion::circle(vec2(20.0, 10.0), 5.0)));
+    // Entirely inside, and entirely containing
+    assert!(square_region.overlaps(&BezierRegion::circle(vec2(10.0, 10.0), 5.0)));
+    assert!(square_region.overlaps(&BezierRegion::circle(vec2(10.0, 10.0), 50.0)));
+    // Far away, and only close enough for the bounding boxes to overlap
+    assert!(!square_region.overlaps(&BezierRegion::circle(vec2(50.0, 50.0), 5.0)));
+    assert!(!square_region.overlaps(&BezierRegion::circle(vec2(23.0, 23.0), 4.0)));
+    // Inside the hole of a region
+    let donut = BezierRegion::new(vec![square(vec2(0.0, 0.0), 30.0), square(vec2(10.0, 10.0), 10.0)]);
+    assert!(!donut.overlaps(&BezierRegion::circle(vec2(15.0, 15.0), 3.0)));
+    assert!(!square_region.overlaps(&BezierRegion::default()));
+}
+
+#[test]
+fn erase_circles() {
+    let circle_pt = |pt: Vec2, center: Vec2, radius: f32| pt.distance(center) < radius;
+    let near_circle = |pt: Vec2, center: Vec2, radius: f32| (pt.distance(center) - radius).abs() < 0.1;
+
+    // Biting into the edge of a region
+    let square_region = BezierRegion::new(vec![square(vec2(0.0, 0.0), 20.0)]);
+    let bite = square_region.difference(&BezierRegion::circle(vec2(20.0, 10.0), 5.0));
+    assert_eq!(bite.contours.len(), 1);
+    assert_covers(&bite, |pt| in_square(pt, vec2(0.0, 0.0), 20.0) && !circle_pt(pt, vec2(20.0, 10.0), 5.0), |pt| near_square(pt, vec2(0.0, 0.0), 20.0) || near_circle(pt, vec2(20.0, 10.0), 5.0));
+
+    // Punching a hole in a region
+    let hole = square_region.difference(&BezierRegion::circle(vec2(10.0, 10.0), 5.0));
+    assert_eq!(hole.contours.len(), 2);
+    assert_covers(&hole, |pt| in_square(pt, vec2(0.0, 0.0), 20.0) && !circle_pt(pt, vec2(10.0, 10.0), 5.0), |pt| near_square(pt, vec2(0.0, 0.0), 20.0) || near_circle(pt, vec2(10.0, 10.0), 5.0));
+
+    // Erasing a whole region
+    assert!(square_region.difference(&BezierRegion::circle(vec2(10.0, 10.0), 50.0)).is_empty());
+
+    // Erasing along a stroke, one circle at a time, cuts a thin region in two
+    let mut strip = BezierRegion::new(vec![polygon(&[vec2(0.0, 8.0), vec2(30.0, 8.0), vec2(30.0, 12.0), vec2(0.0, 12.0)])]);
+    for i in 0..=8 {
+        strip = strip.difference(&BezierRegion::circle(vec2(15.0, i as f32 * 2.5), 3.0));
+    }
+    assert_eq!(strip.contours.len(), 2);
+    assert!(strip.contains(vec2(5.0, 10.0)));
+    assert!(strip.contains(vec2(25.0, 10.0)));
+    assert!(!strip.contains(vec2(15.0, 10.0)));
+}
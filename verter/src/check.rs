@@ -0,0 +1,243 @@
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{Error, File, PageHeader};
+
+/// A problem found while checking the integrity of a Verter file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// The page chain starting at `chain` loops back on itself at `page`
+    Cycle { chain: u64, page: u64 },
+    /// `page` belongs to the page chain starting at `chain`, but also to another page chain
+    CrossLinkedPage { chain: u64, page: u64 },
+    /// The page chain starting at `chain` contains `ptr`, which does not point to a page in the file
+    InvalidPointer { chain: u64, ptr: u64 },
+    /// The page chain starting at `chain` contains `page`, which is marked as deleted
+    DeletedPageInChain { chain: u64, page: u64 },
+    /// The final page of the page chain starting at `chain` claims to contain more bytes than fit in a page
+    InvalidPageSize { chain: u64, page: u64 },
+    /// The contents of `page`, which belongs to the page chain starting at `chain`, do not match its checksum
+    ChecksumMismatch { chain: u64, page: u64 },
+    /// `page` is neither part of a page chain nor in the list of deleted pages
+    OrphanedPage { page: u64 },
+    /// The list of deleted pages is corrupted at `ptr`
+    CorruptedFreeList { ptr: u64 }
+}
+
+impl Display for Problem {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Cycle { chain, page } => write!(f, "chain {} loops back on itself at page {}", chain, page),
+            Problem::CrossLinkedPage { chain, page } => write!(f, "page {} of chain {} also belongs to another chain", page, chain),
+            Problem::InvalidPointer { chain, ptr } => write!(f, "chain {} contains invalid pointer {}", chain, ptr),
+            Problem::DeletedPageInChain { chain, page } => write!(f, "chain {} contains deleted page {}", chain, page),
+            Problem::InvalidPageSize { chain, page } => write!(f, "final page {} of chain {} has an invalid size", page, chain),
+            Problem::ChecksumMismatch { chain, page } => write!(f, "page {} of chain {} does not match its checksum", page, chain),
+            Problem::OrphanedPage { page } => write!(f, "page {} is neither part of a chain nor deleted", page),
+            Problem::CorruptedFreeList { ptr } => write!(f, "list of deleted pages is corrupted at {}", ptr),
+        }
+    }
+
+}
+
+/// The result of checking the integrity of a Verter file
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// The problems found in the file
+    pub problems: Vec<Problem>,
+    /// The page chains that were cut short at their first problem during repair
+    pub quarantined_chains: Vec<u64>,
+    /// Was the list of deleted pages rebuilt during repair?
+    pub rebuilt_free_list: bool
+}
+
+impl CheckReport {
+
+    /// Is the file free of problems?
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+}
+
+/// The result of walking a single page chain
+struct ChainWalk {
+    /// The last page of the chain before the first problem, if any page is valid
+    last_valid_page: Option<u64>,
+    /// Did the walk run into a problem?
+    broken: bool
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PageOwner {
+    Chain(u64),
+    FreeList
+}
+
+impl File {
+
+    /// Check the integrity of the file.
+    /// Walks the root page chain, the given page chains and the list of deleted pages, reporting any problems found.
+    /// Since Verter does not know where pointers to page chains are stored, `chains` should contain every page chain still in use. 
+    /// Any page not reachable from the root, `chains` or the list of deleted pages is reported as orphaned.
+    pub fn check(&mut self, chains: &[u64]) -> Result<CheckReport, Error> {
+        let mut report = CheckReport::default();
+        let mut owners = HashMap::new();
+        self.check_chains(chains, &mut owners, &mut report)?;
+        self.check_free_list(&mut owners, &mut report)?;
+        self.check_orphans(&owners, &mut report);
+        Ok(report)
+    }
+
+    /// Check the integrity of the file, and repair any problems found.
+    /// Page chains with problems are quarantined by cutting them short right before their first problem.
+    /// The list of deleted pages is rebuilt from all the pages that are not part of a page chain, including orphaned pages.
+    /// The repair is atomic, so the file is left untouched if it fails.
+    pub fn repair(&mut self, chains: &[u64]) -> Result<CheckReport, Error> {
+        self.atomic(|file| file.repair_pages(chains))
+    }
+
+    fn repair_pages(&mut self, chains: &[u64]) -> Result<CheckReport, Error> {
+        let mut report = CheckReport::default();
+        let mut owners = HashMap::new();
+        let walks = self.check_chains(chains, &mut owners, &mut report)?;
+        let mut all_owners = owners.clone();
+        let free_list_ok = self.check_free_list(&mut all_owners, &mut report)?;
+        self.check_orphans(&all_owners, &mut report);
+
+        // Quarantine broken chains
+        for (chain, walk) in walks {
+            if !walk.broken {
+                continue;
+            }
+            match walk.last_valid_page {
+                Some(last_valid_page) => {
+                    self.write_page_header(last_valid_page, PageHeader::FinalPage(self.config.page_size as u64))?;
+                },
+                // If not even the first page is valid, turn it into an empty chain so that the pointer to the chain stays valid
+                None if self.is_page_ptr(chain) && !all_owners.contains_key(&chain) => {
+                    self.write_page_header(chain, PageHeader::FinalPage(0))?;
+                    owners.insert(chain, PageOwner::Chain(chain));
+                },
+                None => {}
+            }
+            report.quarantined_chains.push(chain);
+        }
+
+        // Rebuild the free list from every page that isn't part of a chain
+        if !free_list_ok || report.problems.iter().any(|problem| matches!(problem, Problem::OrphanedPage { .. })) {
+            let mut first_free_page = 0;
+            for idx in (0..self.n_pages()).rev() {
+                let page = self.page_ptr(idx);
+                if owners.contains_key(&page) {
+                    continue;
+                }
+                self.write_page_header(page, PageHeader::DeletedPage(first_free_page))?;
+                first_free_page = page;
+            }
+            self.write_u64(self.first_free_page_ptr(), first_free_page)?;
+            self.free_list = None;
+            report.rebuilt_free_list = true;
+        }
+
+        Ok(report)
+    }
+
+    fn check_chains(&mut self, chains: &[u64], owners: &mut HashMap<u64, PageOwner>, report: &mut CheckReport) -> Result<Vec<(u64, ChainWalk)>, Error> {
+        let root_page = self.root_page()?;
+        let mut walks = Vec::new();
+        for chain in std::iter::once(root_page).chain(chains.iter().copied()) {
+            // The same chain might be passed in multiple times
+            if owners.get(&chain) == Some(&PageOwner::Chain(chain)) {
+                continue;
+            }
+            let walk = self.walk_chain(chain, owners, report)?;
+            walks.push((chain, walk));
+        }
+        Ok(walks)
+    }
+
+    fn walk_chain(&mut self, chain: u64, owners: &mut HashMap<u64, PageOwner>, report: &mut CheckReport) -> Result<ChainWalk, Error> {
+        let mut walk = ChainWalk {
+            last_valid_page: None,
+            broken: true
+        };
+        let mut page = chain;
+        loop {
+            if !self.is_page_ptr(page) {
+                report.problems.push(Problem::InvalidPointer { chain, ptr: page });
+                return Ok(walk);
+            }
+            match owners.get(&page) {
+                Some(PageOwner::Chain(owner)) if *owner == chain => {
+                    report.problems.push(Problem::Cycle { chain, page });
+                    return Ok(walk);
+                },
+                Some(_) => {
+                    report.problems.push(Problem::CrossLinkedPage { chain, page });
+                    return Ok(walk);
+                },
+                None => {}
+            }
+            if !self.page_checksum_valid(page)? {
+                report.problems.push(Problem::ChecksumMismatch { chain, page });
+                return Ok(walk);
+            }
+
+            match self.read_page_header(page)? {
+                PageHeader::NextPage(next) => {
+                    owners.insert(page, PageOwner::Chain(chain));
+                    walk.last_valid_page = Some(page);
+                    page = next;
+                },
+                PageHeader::FinalPage(size) => {
+                    if size > self.config.page_size as u64 {
+                        report.problems.push(Problem::InvalidPageSize { chain, page });
+                        return Ok(walk);
+                    }
+                    owners.insert(page, PageOwner::Chain(chain));
+                    walk.last_valid_page = Some(page);
+                    walk.broken = false;
+                    return Ok(walk);
+                },
+                PageHeader::DeletedPage(_) => {
+                    report.problems.push(Problem::DeletedPageInChain { chain, page });
+                    return Ok(walk);
+                }
+            }
+        }
+    }
+
+    /// Walk the list of deleted pages. Returns true if the list is valid.
+    fn check_free_list(&mut self, owners: &mut HashMap<u64, PageOwner>, report: &mut CheckReport) -> Result<bool, Error> {
+        let mut ptr = self.first_free_page()?;
+        while ptr != 0 {
+            if !self.is_page_ptr(ptr) || owners.contains_key(&ptr) {
+                report.problems.push(Problem::CorruptedFreeList { ptr });
+                return Ok(false);
+            }
+            ptr = match self.read_page_header(ptr)? {
+                PageHeader::DeletedPage(next) => {
+                    owners.insert(ptr, PageOwner::FreeList);
+                    next
+                },
+                _ => {
+                    report.problems.push(Problem::CorruptedFreeList { ptr });
+                    return Ok(false);
+                }
+            };
+        }
+        Ok(true)
+    }
+
+    fn check_orphans(&self, owners: &HashMap<u64, PageOwner>, report: &mut CheckReport) {
+        for idx in 0..self.n_pages() {
+            let page = self.page_ptr(idx);
+            if !owners.contains_key(&page) {
+                report.problems.push(Problem::OrphanedPage { page });
+            }
+        }
+    }
+
+}
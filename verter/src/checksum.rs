@@ -0,0 +1,48 @@
+
+/// Lookup table for the CRC-32 (IEEE 802.3) polynomial
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Incrementally computes a CRC-32 checksum
+pub(crate) struct Checksum {
+    crc: u32
+}
+
+impl Checksum {
+
+    pub(crate) fn new() -> Self {
+        Self {
+            crc: 0xFFFFFFFF
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.crc = CRC32_TABLE[((self.crc ^ *byte as u32) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        !self.crc
+    }
+
+}
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut checksum = Checksum::new();
+    checksum.update(data);
+    checksum.finish()
+}
@@ -0,0 +1,120 @@
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Config, Error, File, PageHeader};
+
+/// The result of compacting a Verter file
+pub struct Compaction {
+    /// Maps the old pointers of the page chains that were moved to their new pointers.
+    /// Chains that were not moved are not included.
+    pub moved_chains: HashMap<u64, u64>,
+    /// The number of bytes by which the file shrunk
+    pub reclaimed_bytes: u64
+}
+
+impl Compaction {
+
+    /// Get the pointer to a page chain after compaction, given its pointer before compaction.
+    pub fn remap(&self, ptr: u64) -> u64 {
+        self.moved_chains.get(&ptr).copied().unwrap_or(ptr)
+    }
+
+}
+
+impl File {
+
+    /// Shrink the file by moving the pages at the end of the file into deleted pages and truncating the file.
+    /// Page chains might get moved in the process, so pointers to page chains stored in the file must be updated using the returned `Compaction`.
+    /// To keep the file consistent, compact the file and update the pointers in a single transaction.
+    /// The root page chain is updated automatically.
+    pub fn compact(&mut self) -> Result<Compaction, Error> {
+        self.atomic(Self::compact_pages)
+    }
+
+    fn compact_pages(&mut self) -> Result<Compaction, Error> {
+        let n_pages = self.n_pages();
+
+        // Find all the deleted pages
+        let mut deleted = HashSet::new();
+        let mut free_page = self.first_free_page()?;
+        while free_page != 0 {
+            if !self.is_page_ptr(free_page) || !deleted.insert(free_page) {
+                return Err(Error::CorruptedFile);
+            }
+            free_page = match self.read_page_header(free_page)? {
+                PageHeader::DeletedPage(next) => next,
+                _ => return Err(Error::CorruptedFile)
+            };
+        }
+
+        let n_live_pages = n_pages - deleted.len() as u64;
+        let new_size = self.page_ptr(n_live_pages);
+
+        // Find the page preceding each page in its chain
+        let mut prev_pages = HashMap::new();
+        for idx in 0..n_pages {
+            let page = self.page_ptr(idx);
+            if deleted.contains(&page) {
+                continue;
+            }
+            if let PageHeader::NextPage(next) = self.read_page_header(page)? {
+                prev_pages.insert(next, page);
+            }
+        }
+
+        // Move the live pages past the new end of the file into the deleted pages before it
+        let free_slots = (0..n_live_pages).map(|idx| self.page_ptr(idx)).filter(|page| deleted.contains(page));
+        let pages_to_move = (n_live_pages..n_pages).map(|idx| self.page_ptr(idx)).filter(|page| !deleted.contains(page));
+        let moves = pages_to_move.zip(free_slots).collect::<HashMap<_, _>>();
+
+        let mut page = vec![0; self.total_page_size() as usize];
+        for (from, to) in &moves {
+            self.read_bytes(*from, &mut page)?;
+            self.write_bytes(*to, &page)?;
+        }
+
+        // Update the pointers to the moved pages
+        let root_page = self.root_page()?;
+        let mut moved_chains = HashMap::new();
+        for (from, to) in &moves {
+            match prev_pages.get(from) {
+                Some(prev) => {
+                    let prev = moves.get(prev).copied().unwrap_or(*prev);
+                    self.write_page_header(prev, PageHeader::NextPage(*to))?;
+                },
+                None if *from == root_page => {
+                    self.write_root_page(*to)?;
+                },
+                None => {
+                    moved_chains.insert(*from, *to);
+                }
+            }
+        }
+
+        // All the deleted pages are now either reused or past the end of the file
+        self.write_u64(self.first_free_page_ptr(), 0)?;
+        self.free_list = None;
+
+        let reclaimed_bytes = self.file_size() - new_size;
+        self.modifications.blocks.split_off(&new_size);
+        self.modifications.truncate = Some(new_size);
+
+        Ok(Compaction {
+            moved_chains,
+            reclaimed_bytes
+        })
+    }
+
+}
+
+/// Compact the Verter file at the given path. See `File::compact`.
+/// Page chains might get moved, so `remap` is called to update the pointers stored in the file before anything is written to disk.
+/// The compaction and the changes made by `remap` are committed in a single transaction.
+pub fn compact<P: AsRef<std::path::Path>, F: FnOnce(&mut File, &Compaction) -> Result<(), Error>>(path: P, config: Config, remap: F) -> Result<Compaction, Error> {
+    let mut file = File::open(path, config)?;
+    let mut transaction = file.transaction()?;
+    let compaction = transaction.compact()?;
+    remap(&mut transaction, &compaction)?;
+    transaction.commit()?;
+    Ok(compaction)
+}
@@ -0,0 +1,68 @@
+
+use std::collections::{BTreeSet, HashMap};
+
+/// A copy of the free list kept in memory.
+/// Finding a run of adjacent deleted pages and unlinking it from the list would otherwise mean walking the whole list on disk.
+#[derive(Default)]
+pub(crate) struct FreeList {
+    /// The deleted pages, in ascending order
+    pages: BTreeSet<u64>,
+    /// The page linking to each deleted page, or 0 for the first page in the list
+    prev: HashMap<u64, u64>
+}
+
+impl FreeList {
+
+    /// Build a copy of a free list, given its pages in the order they are linked
+    pub(crate) fn new(list: &[u64]) -> Self {
+        let mut free_list = Self::default();
+        let mut prev = 0;
+        for page in list {
+            free_list.pages.insert(*page);
+            free_list.prev.insert(*page, prev);
+            prev = *page;
+        }
+        free_list
+    }
+
+    /// Record that a page was added to the front of the list, linking to `next`
+    pub(crate) fn push(&mut self, page: u64, next: u64) {
+        self.pages.insert(page);
+        self.prev.insert(page, 0);
+        if next != 0 {
+            self.prev.insert(next, page);
+        }
+    }
+
+    /// Record that a page linking to `next` was removed from the list.
+    /// Returns the page that linked to the removed page, or 0 if it was the first in the list.
+    pub(crate) fn remove(&mut self, page: u64, next: u64) -> u64 {
+        self.pages.remove(&page);
+        let prev = self.prev.remove(&page).unwrap_or(0);
+        if next != 0 {
+            self.prev.insert(next, prev);
+        }
+        prev
+    }
+
+    /// Find `n_pages` adjacent deleted pages, returning the first one
+    pub(crate) fn find_run(&self, n_pages: u64, total_page_size: u64) -> Option<u64> {
+        let mut run_start = 0;
+        let mut run_length = 0;
+        let mut prev_page = None;
+        for page in &self.pages {
+            if prev_page.is_some_and(|prev_page| *page == prev_page + total_page_size) {
+                run_length += 1;
+            } else {
+                run_start = *page;
+                run_length = 1;
+            }
+            if run_length == n_pages {
+                return Some(run_start);
+            }
+            prev_page = Some(*page);
+        }
+        None
+    }
+
+}
@@ -0,0 +1,63 @@
+
+use crate::{checksum::crc32, Modifications, BYTES_IN_U64};
+
+const JOURNAL_MAGIC_BYTES: &[u8] = b"VJOURNAL";
+
+/// Marker stored in place of the truncated file size when the file is not truncated
+const NO_TRUNCATION: u64 = u64::MAX;
+
+/// A redo journal, stored alongside a Verter file by its `Storage`.
+/// Every modification to the file is first durably written to the journal, and only then to the file itself.
+/// If the program crashes or loses power halfway through modifying the file, the journal is replayed the next time the file is opened.
+pub(crate) struct Journal;
+
+impl Journal {
+
+    pub(crate) fn encode(modifications: &Modifications) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(JOURNAL_MAGIC_BYTES);
+        data.extend_from_slice(&modifications.truncate.unwrap_or(NO_TRUNCATION).to_le_bytes());
+        data.extend_from_slice(&(modifications.blocks.len() as u64).to_le_bytes());
+        for (ptr, block) in &modifications.blocks {
+            data.extend_from_slice(&ptr.to_le_bytes());
+            data.extend_from_slice(&(block.len() as u64).to_le_bytes());
+            data.extend_from_slice(block);
+        }
+        let checksum = crc32(&data) as u64;
+        data.extend_from_slice(&checksum.to_le_bytes());
+        data
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Option<Modifications> {
+        fn read_u64(data: &mut &[u8]) -> Option<u64> {
+            let bytes = data.get(..BYTES_IN_U64 as usize)?;
+            *data = &data[BYTES_IN_U64 as usize..];
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        }
+
+        // Verify the checksum. If it does not match, the journal was only partially written and the file itself was never touched.
+        let checksum_start = data.len().checked_sub(BYTES_IN_U64 as usize)?;
+        let mut checksum_data = &data[checksum_start..];
+        let expected_checksum = read_u64(&mut checksum_data)?;
+        if crc32(&data[..checksum_start]) as u64 != expected_checksum {
+            return None;
+        }
+
+        let mut data = data[..checksum_start].strip_prefix(JOURNAL_MAGIC_BYTES)?;
+        let mut modifications = Modifications::default();
+        let truncate = read_u64(&mut data)?;
+        if truncate != NO_TRUNCATION {
+            modifications.truncate = Some(truncate);
+        }
+        let n_blocks = read_u64(&mut data)?;
+        for _ in 0..n_blocks {
+            let ptr = read_u64(&mut data)?;
+            let size = read_u64(&mut data)? as usize;
+            let block = data.get(..size)?;
+            data = &data[size..];
+            modifications.blocks.insert(ptr, block.to_vec());
+        }
+        Some(modifications)
+    }
+
+}
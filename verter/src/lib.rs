@@ -2,7 +2,37 @@
 #[cfg(test)]
 mod test;
 
-use std::io::{Read, Seek, SeekFrom, Write};
+mod checksum;
+use checksum::crc32;
+
+mod modifications;
+use modifications::*;
+
+mod journal;
+use journal::*;
+
+mod storage;
+pub use storage::*;
+
+mod read_ahead;
+use read_ahead::*;
+
+mod free_list;
+use free_list::*;
+
+mod transaction;
+pub use transaction::*;
+
+mod compact;
+pub use compact::*;
+
+mod check;
+pub use check::*;
+
+mod stream;
+pub use stream::*;
+
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 pub enum Error {
@@ -10,17 +40,35 @@ pub enum Error {
     InvalidFile,
     InvalidPointer,
     DeletedPointer,
-    CorruptedFile
+    CorruptedFile,
+    TransactionInProgress,
+    /// The contents of a page do not match its checksum, meaning the page was damaged on disk
+    ChecksumMismatch
 }
 
 const BYTES_IN_U64: u64 = 8;
 
+/// The number of pages read from the disk at once
+const READ_AHEAD_PAGES: u64 = 64;
+
+/// When a page chain grows by at least this many pages in a single write, the new pages are allocated contiguously,
+/// either from a run of adjacent deleted pages or at the end of the file.
+/// This keeps large chains contiguous, so they can be read from the disk in big chunks.
+const EXTENT_THRESHOLD: u64 = 8;
+
 #[derive(Clone, Copy)]
 pub struct Config {
     /// The magic bytes at the start of the file
     pub magic_bytes: &'static [u8],
     /// The number of bytes per page, excluding the page header
-    pub page_size: usize
+    pub page_size: usize,
+    /// Should modifications be written to a journal before being applied to the file?
+    /// Journaling makes every modification atomic, so crashes or power loss can never leave the file half-written.
+    pub journal: bool,
+    /// Should newly created files store a checksum with every page?
+    /// Checksums are verified when reading, so damaged data is detected instead of silently returned.
+    /// Existing files keep the format they were created with, regardless of this setting.
+    pub checksums: bool
 }
 
 impl Default for Config {
@@ -28,7 +76,9 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             magic_bytes: b"VERTER__",
-            page_size: 120
+            page_size: 120,
+            journal: true,
+            checksums: false
         }
     }
 
@@ -73,35 +123,73 @@ impl PageHeader {
 
 }
 
+/// The top bit of the root page pointer in the file header marks files that store page checksums.
+/// Files created before checksums were introduced never have this bit set, so they can still be read.
+const CHECKSUMS_FLAG: u64 = 1u64 << 63;
+
 pub struct File {
-    file: std::fs::File,
-    config: Config
+    /// Where the bytes of the file are stored
+    storage: Box<dyn Storage>,
+    config: Config,
+    /// Does the file store a checksum at the end of every page?
+    checksums: bool,
+    /// The size of the file in the storage, excluding modifications that have not yet been committed
+    disk_size: u64,
+    /// The modifications made by the operation currently being performed.
+    /// They are only written to the file once the operation is committed.
+    modifications: Modifications,
+    /// The state of the modified blocks before the operation currently being performed, used to revert the operation if it fails.
+    /// `None` means the block was not modified before the operation.
+    operation_undo: BTreeMap<u64, Option<Vec<u8>>>,
+    /// Is a transaction in progress? If so, modifications are only committed when the transaction is committed.
+    in_transaction: bool,
+    /// Bytes read ahead from the disk
+    read_ahead: ReadAhead,
+    /// A copy of the free list, built the first time a run of deleted pages is needed and kept up to date as pages are allocated and deleted.
+    /// None if it wasn't built yet, or if the free list was changed in a way the copy doesn't follow, such as by rolling back modifications.
+    free_list: Option<FreeList>
 }
 
 impl File {
 
     /// Open a file.
     /// Creates and initiates it if it currently does not exist.
+    /// If a previous modification of the file was interrupted, the file is recovered using its journal.
     /// Will return an error if the file is invalid(ie has incorrect magic bytes).
     pub fn open<P: AsRef<std::path::Path>>(path: P, config: Config) -> Result<File, Error> {
-        let create = !std::fs::exists(&path).map_err(Error::IO)?;
-        
-        let file = std::fs::OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .open(path)
-            .map_err(Error::IO)?;
+        Self::open_storage(FileStorage::open(path).map_err(Error::IO)?, config)
+    }
+
+    /// Open a file kept in a storage other than the file system, such as `MemoryStorage`.
+    /// Creates and initiates the file if the storage is empty.
+    pub fn open_storage<S: Storage + 'static>(storage: S, config: Config) -> Result<File, Error> {
+        let mut storage = Box::new(storage);
+        let disk_size = storage.size().map_err(Error::IO)?;
+        let create = disk_size == 0;
 
         let mut file = Self {
-            file,
-            config
+            storage,
+            config,
+            checksums: create && config.checksums,
+            disk_size,
+            modifications: Modifications::default(),
+            operation_undo: BTreeMap::new(),
+            in_transaction: false,
+            read_ahead: ReadAhead::default(),
+            free_list: None
         };
 
+        if !create {
+            file.recover()?;
+        }
+        // A journal left behind by a file that no longer exists is stale
+        file.storage.clear_journal().map_err(Error::IO)?;
+
         if create {
-            file.create_header()?;
+            file.atomic(Self::create_header)?;
         } else {
             file.check_if_file_valid()?;
+            file.checksums = file.read_u64(file.root_page_ptr())? & CHECKSUMS_FLAG != 0;
         }
 
         Ok(file)
@@ -114,21 +202,23 @@ impl File {
         let mut data = Vec::new();
 
         loop {
+            self.verify_page(ptr)?;
             let header = self.read_page_header(ptr)?; 
             match header {
                 PageHeader::NextPage(next) => {
                     data.extend(std::iter::repeat(0).take(self.config.page_size));
-                    self.file.seek(SeekFrom::Start(ptr + BYTES_IN_U64)).map_err(Error::IO)?;
                     let read_to = data.len() - self.config.page_size;
-                    self.file.read(&mut data[read_to..]).map_err(Error::IO)?;
+                    self.read_bytes(ptr + BYTES_IN_U64, &mut data[read_to..])?;
                     ptr = next;
                 },
                 PageHeader::FinalPage(size) => {
                     let size = size as usize;
+                    if size > self.config.page_size {
+                        return Err(Error::CorruptedFile);
+                    }
                     data.extend(std::iter::repeat(0).take(size));
-                    self.file.seek(SeekFrom::Start(ptr + BYTES_IN_U64)).map_err(Error::IO)?;
                     let read_to = data.len() - size; 
-                    self.file.read(&mut data[read_to..]).map_err(Error::IO)?;
+                    self.read_bytes(ptr + BYTES_IN_U64, &mut data[read_to..])?;
                     break;
                 },
                 PageHeader::DeletedPage(_) => {
@@ -147,17 +237,20 @@ impl File {
     }
 
     /// Write data to a page chain.
-    pub fn write(&mut self, mut ptr: u64, mut data: &[u8]) -> Result<(), Error> {
+    pub fn write(&mut self, ptr: u64, data: &[u8]) -> Result<(), Error> {
+        self.atomic(|file| file.write_chain(ptr, data))
+    }
+
+    fn write_chain(&mut self, mut ptr: u64, mut data: &[u8]) -> Result<(), Error> {
         self.check_if_pointer_valid(ptr)?;
         
         while data.len() > self.config.page_size {
-            self.file.seek(SeekFrom::Start(ptr + BYTES_IN_U64)).map_err(Error::IO)?;
-            self.file.write(&data[..self.config.page_size]).map_err(Error::IO)?;
+            self.write_bytes(ptr + BYTES_IN_U64, &data[..self.config.page_size])?;
             data = &data[self.config.page_size..];
             ptr = match self.read_page_header(ptr)? {
                 PageHeader::NextPage(next) => next,
                 PageHeader::FinalPage(_) => {
-                    let new_page = self.alloc()?;
+                    let new_page = self.alloc_pages(self.pages_needed(data.len()))?;
                     self.write_page_header(ptr, PageHeader::NextPage(new_page))?;
                     new_page
                 },
@@ -170,12 +263,11 @@ impl File {
         let final_page_header = self.read_page_header(ptr)?;
         if let PageHeader::NextPage(truncated_pages) = final_page_header {
             // If there are more pages in this chain we no longer need, delete them
-            self.delete(truncated_pages)?;
+            self.delete_chain(truncated_pages)?;
         }
 
-        self.file.seek(SeekFrom::Start(ptr + BYTES_IN_U64)).map_err(Error::IO)?;
-        self.file.write(data).map_err(Error::IO)?;
-        self.file.write(&vec![0xFF; self.config.page_size - data.len()]).map_err(Error::IO)?; // Clear remainder of the page 
+        self.write_bytes(ptr + BYTES_IN_U64, data)?;
+        self.write_bytes(ptr + BYTES_IN_U64 + data.len() as u64, &vec![0xFF; self.config.page_size - data.len()])?; // Clear remainder of the page 
         self.write_page_header(ptr, PageHeader::FinalPage(data.len() as u64))?;
 
         Ok(())
@@ -183,20 +275,26 @@ impl File {
 
     /// Write to the root page chain
     pub fn write_root(&mut self, data: &[u8]) -> Result<(), Error> {
-        let root_page = self.root_page()?;
-        self.write(root_page, data)
+        self.atomic(|file| {
+            let root_page = file.root_page()?;
+            file.write_chain(root_page, data)
+        })
     }
 
     /// Allocate a new page.
     /// Either takes the first page in the free list or creates a new page at the end of the file.
     /// Initializes page with a header of PageHeader::FinalPage(0). 
     pub fn alloc(&mut self) -> Result<u64, Error> {
+        self.atomic(Self::alloc_page)
+    }
+
+    fn alloc_page(&mut self) -> Result<u64, Error> {
         let free_page = self.first_free_page()?;
 
         let page = if free_page == 0 {
             // Create new page at the end of the file
-            let new_page_ptr = self.file.seek(SeekFrom::End(0)).map_err(Error::IO)?;
-            self.file.write(&vec![0xFF; self.total_page_size() as usize]).map_err(Error::IO)?;
+            let new_page_ptr = self.file_size();
+            self.write_bytes(new_page_ptr, &vec![0xFF; self.total_page_size() as usize])?;
 
             new_page_ptr
         } else {
@@ -205,6 +303,9 @@ impl File {
             match new_free_page {
                 PageHeader::DeletedPage(next) => {
                     self.write_u64(self.first_free_page_ptr(), next)?;
+                    if let Some(free_list) = &mut self.free_list {
+                        free_list.remove(free_page, next);
+                    }
                 },
                 _ => return Err(Error::CorruptedFile)
             }
@@ -217,9 +318,93 @@ impl File {
         Ok(page)
     }
 
+    /// Allocate pages to extend a chain with.
+    /// If fewer than `EXTENT_THRESHOLD` pages are needed, a single page is allocated, reusing a deleted page if possible.
+    /// Otherwise, all `n_pages` pages are allocated contiguously and linked together,
+    /// reusing a run of adjacent deleted pages if there is one and appending the pages to the end of the file if not.
+    /// Returns the first allocated page.
+    fn alloc_pages(&mut self, n_pages: u64) -> Result<u64, Error> {
+        if n_pages < EXTENT_THRESHOLD {
+            return self.alloc_page();
+        }
+
+        let total_page_size = self.total_page_size();
+        let first_page = match self.find_free_run(n_pages)? {
+            Some(run) => {
+                self.take_free_run(run, n_pages)?;
+                run
+            },
+            None => self.file_size()
+        };
+        for i in 0..n_pages {
+            let page = first_page + i * total_page_size;
+            self.write_bytes(page, &vec![0xFF; total_page_size as usize])?;
+            let header = if i + 1 < n_pages {
+                PageHeader::NextPage(page + total_page_size)
+            } else {
+                PageHeader::FinalPage(0)
+            };
+            self.write_page_header(page, header)?;
+        }
+
+        Ok(first_page)
+    }
+
+    /// Find `n_pages` adjacent deleted pages, returning the first one
+    fn find_free_run(&mut self, n_pages: u64) -> Result<Option<u64>, Error> {
+        let total_page_size = self.total_page_size();
+        Ok(self.free_list()?.find_run(n_pages, total_page_size))
+    }
+
+    /// Remove the `n_pages` deleted pages starting at `first_page` from the free list
+    fn take_free_run(&mut self, first_page: u64, n_pages: u64) -> Result<(), Error> {
+        for i in 0..n_pages {
+            let page = first_page + i * self.total_page_size();
+            let next = match self.read_page_header(page)? {
+                PageHeader::DeletedPage(next) => next,
+                _ => return Err(Error::CorruptedFile)
+            };
+            match self.free_list()?.remove(page, next) {
+                0 => self.write_u64(self.first_free_page_ptr(), next)?,
+                prev => self.write_page_header(prev, PageHeader::DeletedPage(next))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the copy of the free list, walking the free list on disk to build it if needed
+    fn free_list(&mut self) -> Result<&mut FreeList, Error> {
+        if self.free_list.is_none() {
+            let mut pages = Vec::new();
+            let mut ptr = self.first_free_page()?;
+            while ptr != 0 {
+                // A free list longer than the file has a cycle in it
+                if pages.len() as u64 > self.n_pages() {
+                    return Err(Error::CorruptedFile);
+                }
+                pages.push(ptr);
+                ptr = match self.read_page_header(ptr)? {
+                    PageHeader::DeletedPage(next) => next,
+                    _ => return Err(Error::CorruptedFile)
+                };
+            }
+            self.free_list = Some(FreeList::new(&pages));
+        }
+        Ok(self.free_list.as_mut().unwrap())
+    }
+
+    /// The number of pages needed to store `len` bytes
+    fn pages_needed(&self, len: usize) -> u64 {
+        len.div_ceil(self.config.page_size) as u64
+    }
+
     /// Delete a page chain.
     /// Note that this simply adds the page to the free list, without actually ever shrinking the file.
-    pub fn delete(&mut self, mut ptr: u64) -> Result<(), Error> {
+    pub fn delete(&mut self, ptr: u64) -> Result<(), Error> {
+        self.atomic(|file| file.delete_chain(ptr))
+    }
+
+    fn delete_chain(&mut self, mut ptr: u64) -> Result<(), Error> {
         self.check_if_pointer_valid(ptr)?;
 
         loop {
@@ -227,10 +412,12 @@ impl File {
             let free_pages = self.first_free_page()?;
             self.write_page_header(ptr, PageHeader::DeletedPage(free_pages))?;
             self.write_u64(self.first_free_page_ptr(), ptr)?;
+            if let Some(free_list) = &mut self.free_list {
+                free_list.push(ptr, free_pages);
+            }
 
             // Write garbage to the deleted page
-            self.file.seek(SeekFrom::Start(ptr + BYTES_IN_U64)).map_err(Error::IO)?;
-            self.file.write(&vec![0xFF; self.config.page_size]).map_err(Error::IO)?;
+            self.write_bytes(ptr + BYTES_IN_U64, &vec![0xFF; self.config.page_size])?;
 
             match header {
                 PageHeader::NextPage(next) => ptr = next,
@@ -244,10 +431,228 @@ impl File {
         Ok(())
     }
 
+    /// Begin a transaction.
+    /// Until the transaction is committed, the modifications made to the file are kept in memory.
+    /// Committing the transaction applies all of them to the file atomically.
+    /// Returns an error if a transaction is already in progress.
+    pub fn begin_transaction(&mut self) -> Result<(), Error> {
+        if self.in_transaction {
+            return Err(Error::TransactionInProgress);
+        }
+        self.in_transaction = true;
+        Ok(())
+    }
+
+    /// Atomically apply all modifications made during the current transaction to the file.
+    pub fn commit_transaction(&mut self) -> Result<(), Error> {
+        self.in_transaction = false;
+        self.commit()
+    }
+
+    /// Discard all modifications made during the current transaction.
+    pub fn rollback_transaction(&mut self) {
+        self.in_transaction = false;
+        self.modifications = Modifications::default();
+        self.free_list = None;
+    }
+
+    /// Is a transaction currently in progress?
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction
+    }
+
+    /// Begin a transaction, returning a guard through which the file can be modified.
+    /// The transaction is rolled back if the guard is dropped without being committed.
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        self.begin_transaction()?;
+        Ok(Transaction::new(self))
+    }
+
+    /// Perform an operation atomically.
+    /// If the operation succeeds, its modifications are committed to the file, unless a transaction is in progress.
+    /// If the operation fails, its modifications are reverted.
+    fn atomic<T, F: FnOnce(&mut Self) -> Result<T, Error>>(&mut self, operation: F) -> Result<T, Error> {
+        self.operation_undo.clear();
+        let truncate_before = self.modifications.truncate;
+        let result = operation(self);
+        let undo = std::mem::take(&mut self.operation_undo);
+        match result {
+            Ok(result) => {
+                if !self.in_transaction {
+                    self.commit()?;
+                }
+                Ok(result)
+            },
+            Err(err) => {
+                for (block_ptr, block) in undo {
+                    match block {
+                        Some(block) => self.modifications.blocks.insert(block_ptr, block),
+                        None => self.modifications.blocks.remove(&block_ptr)
+                    };
+                }
+                self.modifications.truncate = truncate_before;
+                self.free_list = None;
+                Err(err)
+            }
+        }
+    }
+
+    /// Write the modifications to the file, going through the journal if journaling is enabled.
+    fn commit(&mut self) -> Result<(), Error> {
+        if self.modifications.is_empty() {
+            return Ok(());
+        }
+        let mut modifications = std::mem::take(&mut self.modifications);
+        if self.checksums {
+            self.update_checksums(&mut modifications);
+        }
+
+        if self.config.journal {
+            self.storage.write_journal(&Journal::encode(&modifications)).map_err(Error::IO)?;
+        }
+
+        self.apply(&modifications)?;
+
+        if self.config.journal {
+            self.storage.sync().map_err(Error::IO)?;
+            self.storage.clear_journal().map_err(Error::IO)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replay the journal left behind by an interrupted modification, if there is one.
+    /// A journal that was never completely written is ignored, since the file itself was never touched.
+    fn recover(&mut self) -> Result<(), Error> {
+        let Some(journal) = self.storage.read_journal().map_err(Error::IO)? else {
+            return Ok(());
+        };
+        let modifications = Journal::decode(&journal).unwrap_or_default();
+        if modifications.is_empty() {
+            return Ok(());
+        }
+
+        self.apply(&modifications)?;
+        self.storage.sync().map_err(Error::IO)
+    }
+
+    /// Write modifications to the disk
+    fn apply(&mut self, modifications: &Modifications) -> Result<(), Error> {
+        self.read_ahead.clear();
+        if let Some(size) = modifications.truncate {
+            self.storage.set_size(size).map_err(Error::IO)?;
+            self.disk_size = size;
+        }
+        for (block_ptr, block) in &modifications.blocks {
+            self.storage.write_at(*block_ptr, block).map_err(Error::IO)?;
+            self.disk_size = self.disk_size.max(block_ptr + block.len() as u64);
+        }
+        Ok(())
+    }
+
+    /// Recompute the checksums of all modified pages
+    fn update_checksums(&self, modifications: &mut Modifications) {
+        let checksum_offset = BYTES_IN_U64 as usize + self.config.page_size;
+        for (block_ptr, block) in modifications.blocks.iter_mut() {
+            if *block_ptr == 0 {
+                continue;
+            }
+            let checksum = crc32(&block[..checksum_offset]) as u64;
+            block[checksum_offset..].copy_from_slice(&checksum.to_le_bytes());
+        }
+    }
+
+    /// Does the page at `ptr` match its checksum?
+    /// Pages with uncommitted modifications always match, since their checksums are only computed once they are committed.
+    fn page_checksum_valid(&mut self, ptr: u64) -> Result<bool, Error> {
+        if !self.checksums || self.modifications.blocks.contains_key(&ptr) {
+            return Ok(true);
+        }
+        let mut page = vec![0; self.total_page_size() as usize];
+        self.read_bytes(ptr, &mut page)?;
+        let (data, checksum) = page.split_at(BYTES_IN_U64 as usize + self.config.page_size);
+        Ok(crc32(data) as u64 == u64::from_le_bytes(checksum.try_into().unwrap()))
+    }
+
+    fn verify_page(&mut self, ptr: u64) -> Result<(), Error> {
+        if !self.page_checksum_valid(ptr)? {
+            return Err(Error::ChecksumMismatch);
+        }
+        Ok(())
+    }
+
+    /// Get the pointer to the start of the block containing `ptr`.
+    fn block_ptr(&self, ptr: u64) -> u64 {
+        if ptr < self.header_size() {
+            return 0;
+        }
+        ptr - (ptr - self.header_size()) % self.total_page_size()
+    }
+
+    fn block_size(&self, block_ptr: u64) -> u64 {
+        if block_ptr == 0 {
+            self.header_size()
+        } else {
+            self.total_page_size()
+        }
+    }
+
+    /// Get a block for modification, loading it from the disk if it was not yet modified.
+    fn modified_block(&mut self, block_ptr: u64) -> Result<&mut Vec<u8>, Error> {
+        if !self.operation_undo.contains_key(&block_ptr) {
+            self.operation_undo.insert(block_ptr, self.modifications.blocks.get(&block_ptr).cloned());
+        }
+        if !self.modifications.blocks.contains_key(&block_ptr) {
+            let mut block = vec![0xFF; self.block_size(block_ptr) as usize];
+            if block_ptr < self.modifications.truncate.unwrap_or(self.disk_size) {
+                self.storage.read_at(block_ptr, &mut block).map_err(Error::IO)?;
+            }
+            self.modifications.blocks.insert(block_ptr, block);
+        }
+        Ok(self.modifications.blocks.get_mut(&block_ptr).unwrap())
+    }
+
+    /// Read bytes from the file, taking uncommitted modifications into account.
+    /// The bytes being read must not cross a block boundary.
+    fn read_bytes(&mut self, ptr: u64, bytes: &mut [u8]) -> Result<(), Error> {
+        let block_ptr = self.block_ptr(ptr);
+        if let Some(block) = self.modifications.blocks.get(&block_ptr) {
+            let offset = (ptr - block_ptr) as usize;
+            bytes.copy_from_slice(&block[offset..(offset + bytes.len())]);
+            return Ok(());
+        }
+
+        if self.read_ahead.read(ptr, bytes) {
+            return Ok(());
+        }
+        let read_ahead_size = (READ_AHEAD_PAGES * self.total_page_size()).max(bytes.len() as u64);
+        self.read_ahead.fill(self.storage.as_mut(), block_ptr, read_ahead_size)?;
+        if self.read_ahead.read(ptr, bytes) {
+            return Ok(());
+        }
+
+        // The bytes are past the end of the file
+        self.storage.read_at(ptr, bytes).map_err(Error::IO)?;
+        Ok(())
+    }
+
+    /// Write bytes to the file. The bytes are only actually written once the current operation is committed.
+    /// The bytes being written must not cross a block boundary.
+    fn write_bytes(&mut self, ptr: u64, bytes: &[u8]) -> Result<(), Error> {
+        // An empty write at the end of a page would otherwise create the next page
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let block_ptr = self.block_ptr(ptr);
+        let block = self.modified_block(block_ptr)?;
+        let offset = (ptr - block_ptr) as usize;
+        block[offset..(offset + bytes.len())].copy_from_slice(bytes);
+        Ok(())
+    }
+
     fn read_u64(&mut self, ptr: u64) -> Result<u64, Error> {
-        self.file.seek(SeekFrom::Start(ptr as u64)).map_err(Error::IO)?;
         let mut bytes = [0; BYTES_IN_U64 as usize];
-        self.file.read(&mut bytes).map_err(Error::IO)?;
+        self.read_bytes(ptr, &mut bytes)?;
         Ok(u64::from_le_bytes(bytes))
     }
 
@@ -256,9 +661,7 @@ impl File {
     }
 
     fn write_u64(&mut self, ptr: u64, val: u64) -> Result<(), Error> {
-        self.file.seek(SeekFrom::Start(ptr)).map_err(Error::IO)?;
-        self.file.write(&val.to_le_bytes()).map_err(Error::IO)?;
-        Ok(())
+        self.write_bytes(ptr, &val.to_le_bytes())
     }
 
     fn write_page_header(&mut self, ptr: u64, header: PageHeader) -> Result<(), Error> {
@@ -278,7 +681,8 @@ impl File {
     }
 
     fn total_page_size(&self) -> u64 {
-        BYTES_IN_U64 + self.config.page_size as u64
+        let checksum_size = if self.checksums { BYTES_IN_U64 } else { 0 };
+        BYTES_IN_U64 + self.config.page_size as u64 + checksum_size
     }
 
     fn root_page_ptr(&self) -> u64 {
@@ -290,46 +694,61 @@ impl File {
     }
 
     fn root_page(&mut self) -> Result<u64, Error> {
-        self.read_u64(self.root_page_ptr())
+        Ok(self.read_u64(self.root_page_ptr())? & !CHECKSUMS_FLAG)
+    }
+
+    fn write_root_page(&mut self, root_page: u64) -> Result<(), Error> {
+        let flag = if self.checksums { CHECKSUMS_FLAG } else { 0 };
+        self.write_u64(self.root_page_ptr(), root_page | flag)
     }
 
-    fn file_size(&self) -> Result<u64, Error> {
-        self.file.metadata().map(|metadata| metadata.len()).map_err(Error::IO)
+    /// The size of the file, including uncommitted modifications
+    fn file_size(&self) -> u64 {
+        self.modifications.file_size(self.disk_size)
     }
 
     fn create_header(&mut self) -> Result<(), Error> {
         // Magic Bytes
-        self.file.seek(SeekFrom::Start(self.magic_bytes_ptr())).map_err(Error::IO)?;
-        self.file.write(&self.config.magic_bytes).map_err(Error::IO)?;
+        self.write_bytes(self.magic_bytes_ptr(), self.config.magic_bytes)?;
 
         // First Free Page
         self.write_u64(self.first_free_page_ptr(), 0)?;
 
         // Root Page
-        self.write_u64(self.root_page_ptr(), 0)?;
+        self.write_root_page(0)?;
 
         // Initialize Root Page Chain
-        let first_root_page = self.alloc()?;
-        self.write_u64(self.root_page_ptr(), first_root_page)?;
+        let first_root_page = self.alloc_page()?;
+        self.write_root_page(first_root_page)?;
 
         Ok(())
     }
 
     fn check_if_file_valid(&mut self) -> Result<(), Error> {
-        self.file.seek(SeekFrom::Start(0)).map_err(Error::IO)?;
         let mut magic_bytes = vec![0; self.config.magic_bytes.len()];
-        let bytes_read = self.file.read(&mut magic_bytes).map_err(Error::IO)?;
+        let bytes_read = self.storage.read_at(0, &mut magic_bytes).map_err(Error::IO)?;
         if bytes_read < self.config.magic_bytes.len() || self.config.magic_bytes != magic_bytes {
             return Err(Error::InvalidFile)
         }
         Ok(())
     }
 
+    /// Does `ptr` point to the start of a page in the file?
+    fn is_page_ptr(&self, ptr: u64) -> bool {
+        ptr >= self.header_size() && (ptr - self.header_size()).is_multiple_of(self.total_page_size()) && ptr < self.file_size()
+    }
+
+    /// The number of pages in the file, including deleted pages
+    fn n_pages(&self) -> u64 {
+        self.file_size().saturating_sub(self.header_size()) / self.total_page_size()
+    }
+
+    fn page_ptr(&self, idx: u64) -> u64 {
+        self.header_size() + idx * self.total_page_size()
+    }
+
     fn check_if_pointer_valid(&mut self, ptr: u64) -> Result<(), Error> {
-        if ptr < self.header_size() || (ptr - self.header_size()) % self.total_page_size() != 0 {
-            return Err(Error::InvalidPointer);
-        }
-        if ptr >= self.file_size()? {
+        if !self.is_page_ptr(ptr) {
             return Err(Error::InvalidPointer);
         }
 
@@ -0,0 +1,28 @@
+
+use std::collections::BTreeMap;
+
+/// Modifications to a Verter file that have not yet been written to disk
+#[derive(Default)]
+pub(crate) struct Modifications {
+    /// The modified blocks, keyed by their position in the file.
+    /// A block is either the file header or a single page.
+    pub(crate) blocks: BTreeMap<u64, Vec<u8>>,
+    /// The size to truncate the file to before writing the modified blocks, if the file was shrunk
+    pub(crate) truncate: Option<u64>
+}
+
+impl Modifications {
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.truncate.is_none()
+    }
+
+    /// The size of the file once the modifications are applied
+    pub(crate) fn file_size(&self, disk_size: u64) -> u64 {
+        let modified_size = self.blocks.last_key_value()
+            .map(|(block_ptr, block)| block_ptr + block.len() as u64)
+            .unwrap_or(0);
+        self.truncate.unwrap_or(disk_size).max(modified_size)
+    }
+
+}
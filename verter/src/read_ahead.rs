@@ -0,0 +1,39 @@
+
+use crate::{Error, Storage};
+
+/// A buffer of bytes read ahead from the disk.
+/// Reading a chunk of the file at once means that walking through contiguous pages only takes a single read from the disk.
+#[derive(Default)]
+pub(crate) struct ReadAhead {
+    /// The position in the file of the first buffered byte
+    start: u64,
+    data: Vec<u8>
+}
+
+impl ReadAhead {
+
+    /// Copy bytes from the buffer. Returns false if the bytes are not buffered.
+    pub(crate) fn read(&self, ptr: u64, bytes: &mut [u8]) -> bool {
+        if ptr < self.start || ptr + bytes.len() as u64 > self.start + self.data.len() as u64 {
+            return false;
+        }
+        let offset = (ptr - self.start) as usize;
+        bytes.copy_from_slice(&self.data[offset..(offset + bytes.len())]);
+        true
+    }
+
+    /// Buffer up to `len` bytes starting at `start`
+    pub(crate) fn fill(&mut self, storage: &mut dyn Storage, start: u64, len: u64) -> Result<(), Error> {
+        self.start = start;
+        self.data.resize(len as usize, 0);
+        let read = storage.read_at(start, &mut self.data).map_err(Error::IO)?;
+        self.data.truncate(read);
+        Ok(())
+    }
+
+    /// Forget the buffered bytes, since the file was modified
+    pub(crate) fn clear(&mut self) {
+        self.data.clear();
+    }
+
+}
@@ -0,0 +1,230 @@
+
+use std::{io::{Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+
+/// A place where the bytes of a Verter file are stored.
+/// Besides the file itself, a storage also holds the journal used to make modifications crash-safe.
+pub trait Storage: Send {
+
+    /// Read bytes starting at `ptr`. Returns the number of bytes read, which is less than the length of `buf` past the end of the file.
+    fn read_at(&mut self, ptr: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+    /// Write bytes starting at `ptr`, growing the file if necessary.
+    fn write_at(&mut self, ptr: u64, data: &[u8]) -> std::io::Result<()>;
+    /// The size of the file in bytes
+    fn size(&mut self) -> std::io::Result<u64>;
+    /// Grow or shrink the file
+    fn set_size(&mut self, size: u64) -> std::io::Result<()>;
+    /// Make sure all writes so far are durably stored.
+    fn sync(&mut self) -> std::io::Result<()>;
+
+    /// Durably replace the contents of the journal.
+    fn write_journal(&mut self, data: &[u8]) -> std::io::Result<()>;
+    /// Read the contents of the journal, if there is one.
+    fn read_journal(&mut self) -> std::io::Result<Option<Vec<u8>>>;
+    /// Durably discard the contents of the journal.
+    fn clear_journal(&mut self) -> std::io::Result<()>;
+
+}
+
+/// Stores a Verter file on disk.
+/// The journal is stored in a separate file next to it(eg. `demo.verter.journal`).
+pub struct FileStorage {
+    file: std::fs::File,
+    journal_path: PathBuf,
+    /// The journal file, once it has been opened for writing
+    journal: Option<std::fs::File>
+}
+
+impl FileStorage {
+
+    /// Open a file, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+        Ok(Self {
+            file,
+            journal_path: Self::journal_path(path),
+            journal: None
+        })
+    }
+
+    /// The path of the journal belonging to the Verter file at `path`
+    pub fn journal_path(path: &Path) -> PathBuf {
+        let mut journal_path = path.as_os_str().to_owned();
+        journal_path.push(".journal");
+        journal_path.into()
+    }
+
+}
+
+impl Storage for FileStorage {
+
+    fn read_at(&mut self, ptr: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.seek(SeekFrom::Start(ptr))?;
+        let mut read = 0;
+        while read < buf.len() {
+            match self.file.read(&mut buf[read..])? {
+                0 => break,
+                n => read += n
+            }
+        }
+        Ok(read)
+    }
+
+    fn write_at(&mut self, ptr: u64, data: &[u8]) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(ptr))?;
+        self.file.write_all(data)
+    }
+
+    fn size(&mut self) -> std::io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    fn set_size(&mut self, size: u64) -> std::io::Result<()> {
+        self.file.set_len(size)
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn write_journal(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let journal = match &mut self.journal {
+            Some(journal) => journal,
+            None => self.journal.insert(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .truncate(true)
+                    .read(true)
+                    .write(true)
+                    .open(&self.journal_path)?
+            )
+        };
+
+        journal.set_len(0)?;
+        journal.seek(SeekFrom::Start(0))?;
+        journal.write_all(data)?;
+        journal.sync_data()
+    }
+
+    fn read_journal(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        match std::fs::read(&self.journal_path) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn clear_journal(&mut self) -> std::io::Result<()> {
+        if let Some(journal) = &mut self.journal {
+            journal.set_len(0)?;
+            return journal.sync_data();
+        }
+
+        // A journal we never opened was left behind by an earlier session, so we can remove it entirely
+        match std::fs::remove_file(&self.journal_path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err)
+        }
+    }
+
+}
+
+impl Drop for FileStorage {
+
+    fn drop(&mut self) {
+        // Only clean up the journal if it was fully applied. Otherwise, it is needed to recover the file.
+        if let Some(journal) = self.journal.take() {
+            if journal.metadata().map(|metadata| metadata.len() == 0).unwrap_or(false) {
+                drop(journal);
+                let _ = std::fs::remove_file(&self.journal_path);
+            }
+        }
+    }
+
+}
+
+/// Stores a Verter file in memory.
+/// Clones of a `MemoryStorage` share the same bytes, so a clone can be kept around to take snapshots of a file while it is open.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    data: Arc<Mutex<Vec<u8>>>,
+    journal: Arc<Mutex<Option<Vec<u8>>>>
+}
+
+impl MemoryStorage {
+
+    /// Create an empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a storage containing a copy of an existing file, eg. one taken using `MemoryStorage::bytes`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(bytes)),
+            journal: Arc::new(Mutex::new(None))
+        }
+    }
+
+    /// Get a copy of the stored bytes
+    pub fn bytes(&self) -> Vec<u8> {
+        self.data.lock().unwrap().clone()
+    }
+
+}
+
+impl Storage for MemoryStorage {
+
+    fn read_at(&mut self, ptr: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let data = self.data.lock().unwrap();
+        let start = (ptr as usize).min(data.len());
+        let end = (start + buf.len()).min(data.len());
+        buf[..(end - start)].copy_from_slice(&data[start..end]);
+        Ok(end - start)
+    }
+
+    fn write_at(&mut self, ptr: u64, bytes: &[u8]) -> std::io::Result<()> {
+        let mut data = self.data.lock().unwrap();
+        let start = ptr as usize;
+        let end = start + bytes.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[start..end].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn size(&mut self) -> std::io::Result<u64> {
+        Ok(self.data.lock().unwrap().len() as u64)
+    }
+
+    fn set_size(&mut self, size: u64) -> std::io::Result<()> {
+        self.data.lock().unwrap().resize(size as usize, 0);
+        Ok(())
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn write_journal(&mut self, data: &[u8]) -> std::io::Result<()> {
+        *self.journal.lock().unwrap() = Some(data.to_vec());
+        Ok(())
+    }
+
+    fn read_journal(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        Ok(self.journal.lock().unwrap().clone())
+    }
+
+    fn clear_journal(&mut self) -> std::io::Result<()> {
+        *self.journal.lock().unwrap() = None;
+        Ok(())
+    }
+
+}
@@ -0,0 +1,260 @@
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::{Error, File, PageHeader, BYTES_IN_U64};
+
+/// A position within a page chain, used to avoid walking the chain from the start for every access
+#[derive(Clone, Copy)]
+struct Cursor {
+    /// The page containing the position
+    page: u64,
+    /// The offset of the page's first byte within the chain
+    page_start: u64
+}
+
+impl File {
+
+    /// The length of the page's data, given its header
+    fn page_len(&self, header: PageHeader) -> Result<u64, Error> {
+        match header {
+            PageHeader::NextPage(_) => Ok(self.config.page_size as u64),
+            PageHeader::FinalPage(size) if size <= self.config.page_size as u64 => Ok(size),
+            PageHeader::FinalPage(_) | PageHeader::DeletedPage(_) => Err(Error::CorruptedFile)
+        }
+    }
+
+    /// Read the bytes of a chain starting at `offset` into `buf`, starting the walk at `cursor`.
+    /// Returns the number of bytes read, which is less than the length of `buf` if the chain ends first.
+    fn read_from(&mut self, cursor: &mut Cursor, mut offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut read = 0;
+        while read < buf.len() {
+            self.verify_page(cursor.page)?;
+            let header = self.read_page_header(cursor.page)?;
+            let page_len = self.page_len(header)?;
+
+            if offset < cursor.page_start + page_len {
+                let offset_in_page = offset - cursor.page_start;
+                let n = ((page_len - offset_in_page) as usize).min(buf.len() - read);
+                self.read_bytes(cursor.page + BYTES_IN_U64 + offset_in_page, &mut buf[read..(read + n)])?;
+                read += n;
+                offset += n as u64;
+                if read == buf.len() {
+                    break;
+                }
+            }
+
+            match header {
+                PageHeader::NextPage(next) => {
+                    cursor.page = next;
+                    cursor.page_start += self.config.page_size as u64;
+                },
+                _ => break
+            }
+        }
+        Ok(read)
+    }
+
+    /// Write `data` to a chain starting at `offset`, starting the walk at `cursor`.
+    /// The chain is extended if necessary. If `offset` is past the end of the chain, the gap is filled with zeros.
+    fn write_from(&mut self, cursor: &mut Cursor, mut offset: u64, mut data: &[u8]) -> Result<(), Error> {
+        let page_size = self.config.page_size as u64;
+        while !data.is_empty() {
+            self.verify_page(cursor.page)?;
+            let header = self.read_page_header(cursor.page)?;
+            let mut page_len = self.page_len(header)?;
+            let page_end = cursor.page_start + page_size;
+
+            if offset < page_end {
+                let offset_in_page = offset - cursor.page_start;
+                if offset_in_page > page_len {
+                    self.write_bytes(cursor.page + BYTES_IN_U64 + page_len, &vec![0; (offset_in_page - page_len) as usize])?;
+                }
+                let n = ((page_size - offset_in_page) as usize).min(data.len());
+                self.write_bytes(cursor.page + BYTES_IN_U64 + offset_in_page, &data[..n])?;
+                data = &data[n..];
+                offset += n as u64;
+                if let PageHeader::FinalPage(_) = header {
+                    page_len = page_len.max(offset_in_page + n as u64);
+                    self.write_page_header(cursor.page, PageHeader::FinalPage(page_len))?;
+                }
+                if data.is_empty() {
+                    break;
+                }
+            }
+
+            cursor.page = match header {
+                PageHeader::NextPage(next) => next,
+                _ => {
+                    // Fill up the final page before extending the chain
+                    if page_len < page_size {
+                        self.write_bytes(cursor.page + BYTES_IN_U64 + page_len, &vec![0; (page_size - page_len) as usize])?;
+                    }
+                    // Only allocate the pages needed for the data at once if there's no gap to fill with zeros
+                    let n_pages = if offset == page_end { self.pages_needed(data.len()) } else { 1 };
+                    let new_page = self.alloc_pages(n_pages)?;
+                    self.write_page_header(cursor.page, PageHeader::NextPage(new_page))?;
+                    new_page
+                }
+            };
+            cursor.page_start = page_end;
+        }
+        Ok(())
+    }
+
+    /// Walk to the final page of a chain
+    fn final_page(&mut self, ptr: u64) -> Result<(Cursor, u64), Error> {
+        let mut cursor = Cursor {
+            page: ptr,
+            page_start: 0
+        };
+        loop {
+            self.verify_page(cursor.page)?;
+            let header = self.read_page_header(cursor.page)?;
+            let page_len = self.page_len(header)?;
+            match header {
+                PageHeader::NextPage(next) => {
+                    cursor.page = next;
+                    cursor.page_start += page_len;
+                },
+                _ => return Ok((cursor, page_len))
+            }
+        }
+    }
+
+    /// Get the number of bytes stored in a page chain without reading its data.
+    pub fn size(&mut self, ptr: u64) -> Result<u64, Error> {
+        self.check_if_pointer_valid(ptr)?;
+        let (cursor, page_len) = self.final_page(ptr)?;
+        Ok(cursor.page_start + page_len)
+    }
+
+    /// Read `len` bytes from a page chain, starting at `offset`.
+    /// Only the pages containing the requested bytes are read, aside from the headers of the pages before them.
+    /// Returns fewer bytes if the chain ends before `offset + len`.
+    pub fn read_range(&mut self, ptr: u64, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+        self.check_if_pointer_valid(ptr)?;
+        let mut cursor = Cursor {
+            page: ptr,
+            page_start: 0
+        };
+        let mut data = vec![0; len];
+        let read = self.read_from(&mut cursor, offset, &mut data)?;
+        data.truncate(read);
+        Ok(data)
+    }
+
+    /// Overwrite the bytes of a page chain starting at `offset`, leaving the rest of the chain untouched.
+    /// The chain is extended if the data goes past its end.
+    pub fn write_range(&mut self, ptr: u64, offset: u64, data: &[u8]) -> Result<(), Error> {
+        self.atomic(|file| {
+            file.check_if_pointer_valid(ptr)?;
+            let mut cursor = Cursor {
+                page: ptr,
+                page_start: 0
+            };
+            file.write_from(&mut cursor, offset, data)
+        })
+    }
+
+    /// Append data to the end of a page chain.
+    pub fn append(&mut self, ptr: u64, data: &[u8]) -> Result<(), Error> {
+        self.atomic(|file| {
+            file.check_if_pointer_valid(ptr)?;
+            let (mut cursor, page_len) = file.final_page(ptr)?;
+            let size = cursor.page_start + page_len;
+            file.write_from(&mut cursor, size, data)
+        })
+    }
+
+    /// Open a stream over a page chain, implementing `std::io::Read`, `Write` and `Seek`.
+    /// Every write through the stream is committed immediately, unless a transaction is in progress.
+    pub fn stream(&mut self, ptr: u64) -> Result<Stream<'_>, Error> {
+        self.check_if_pointer_valid(ptr)?;
+        Ok(Stream {
+            file: self,
+            chain: ptr,
+            position: 0,
+            cursor: Cursor {
+                page: ptr,
+                page_start: 0
+            }
+        })
+    }
+
+}
+
+/// A stream over a single page chain, created by `File::stream`.
+pub struct Stream<'a> {
+    file: &'a mut File,
+    /// The first page of the chain
+    chain: u64,
+    /// The current position within the chain
+    position: u64,
+    /// A page at or before the current position, from which to continue walking the chain
+    cursor: Cursor
+}
+
+impl Stream<'_> {
+
+    /// Get a cursor from which the current position can be reached
+    fn cursor(&mut self) -> Cursor {
+        if self.cursor.page_start > self.position {
+            self.cursor = Cursor {
+                page: self.chain,
+                page_start: 0
+            };
+        }
+        self.cursor
+    }
+
+}
+
+fn to_io_error(err: Error) -> std::io::Error {
+    match err {
+        Error::IO(err) => err,
+        err => std::io::Error::other(format!("{:?}", err))
+    }
+}
+
+impl Read for Stream<'_> {
+
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut cursor = self.cursor();
+        let read = self.file.read_from(&mut cursor, self.position, buf).map_err(to_io_error)?;
+        self.cursor = cursor;
+        self.position += read as u64;
+        Ok(read)
+    }
+
+}
+
+impl Write for Stream<'_> {
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut cursor = self.cursor();
+        let position = self.position;
+        self.file.atomic(|file| file.write_from(&mut cursor, position, buf)).map_err(to_io_error)?;
+        self.cursor = cursor;
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+}
+
+impl Seek for Stream<'_> {
+
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::Current(delta) => (self.position, delta),
+            SeekFrom::End(delta) => (self.file.size(self.chain).map_err(to_io_error)?, delta)
+        };
+        self.position = base.checked_add_signed(delta).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        Ok(self.position)
+    }
+
+}
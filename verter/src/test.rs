@@ -1,5 +1,5 @@
 
-use crate::{Config, Error, File};
+use crate::{compact, Config, Error, File, Journal, MemoryStorage, PageHeader, Problem};
 
 #[test]
 fn hello_world() {
@@ -106,3 +106,573 @@ fn extension() {
     
     std::fs::remove_file("extension.verter").unwrap();
 }
+
+#[test]
+fn journal_recovery() {
+    let mut file = File::open("journal_recovery.verter", Config::default()).unwrap();
+    file.write_root(b"Old data").unwrap();
+
+    // Simulate a crash right after the journal was written, but before the file itself was modified
+    let root_page = file.root_page().unwrap();
+    file.write_chain(root_page, &vec![0xAB; 1000]).unwrap();
+    let modifications = std::mem::take(&mut file.modifications);
+    file.storage.write_journal(&Journal::encode(&modifications)).unwrap();
+    drop(file);
+    assert!(std::fs::exists("journal_recovery.verter.journal").unwrap());
+
+    let mut file = File::open("journal_recovery.verter", Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), vec![0xAB; 1000]);
+    assert!(!std::fs::exists("journal_recovery.verter.journal").unwrap());
+    drop(file);
+
+    std::fs::remove_file("journal_recovery.verter").unwrap();
+}
+
+#[test]
+fn torn_journal() {
+    let mut file = File::open("torn_journal.verter", Config::default()).unwrap();
+    file.write_root(b"Old data").unwrap();
+
+    // Simulate a crash while the journal was being written
+    let root_page = file.root_page().unwrap();
+    file.write_chain(root_page, &vec![0xAB; 1000]).unwrap();
+    let modifications = std::mem::take(&mut file.modifications);
+    file.storage.write_journal(&Journal::encode(&modifications)).unwrap();
+    drop(file);
+    let journal = std::fs::read("torn_journal.verter.journal").unwrap();
+    std::fs::write("torn_journal.verter.journal", &journal[..journal.len() / 2]).unwrap();
+
+    let mut file = File::open("torn_journal.verter", Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), b"Old data");
+    assert!(!std::fs::exists("torn_journal.verter.journal").unwrap());
+    drop(file);
+
+    std::fs::remove_file("torn_journal.verter").unwrap();
+}
+
+#[test]
+fn failed_operation_rollback() {
+    let mut file = File::open("failed_operation_rollback.verter", Config::default()).unwrap();
+    let alloc = file.alloc().unwrap();
+    file.write(alloc, &vec![0xCD; 500]).unwrap();
+
+    // Corrupt the end of the chain, so that deleting it fails halfway through
+    let mut last_page = alloc;
+    while let PageHeader::NextPage(next) = file.read_page_header(last_page).unwrap() {
+        last_page = next;
+    }
+    file.write_page_header(last_page, PageHeader::DeletedPage(0)).unwrap();
+    file.commit().unwrap();
+    let free_page = file.first_free_page().unwrap();
+
+    match file.delete(alloc) {
+        Err(Error::CorruptedFile) => {},
+        Ok(_) | Err(_) => panic!("should error with corrupted file")
+    }
+    assert_eq!(file.first_free_page().unwrap(), free_page);
+    assert!(matches!(file.read_page_header(alloc).unwrap(), PageHeader::NextPage(_)));
+
+    std::fs::remove_file("failed_operation_rollback.verter").unwrap();
+}
+
+#[test]
+fn transaction() {
+    let mut file = File::open("transaction.verter", Config::default()).unwrap();
+    let mut transaction = file.transaction().unwrap();
+    let alloc = transaction.alloc().unwrap();
+    transaction.write(alloc, b"Hello from a transaction").unwrap();
+    transaction.write_root(&alloc.to_le_bytes()).unwrap();
+    assert_eq!(transaction.read(alloc).unwrap(), b"Hello from a transaction");
+    transaction.commit().unwrap();
+    drop(file);
+
+    let mut file = File::open("transaction.verter", Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), alloc.to_le_bytes());
+    assert_eq!(file.read(alloc).unwrap(), b"Hello from a transaction");
+    drop(file);
+
+    std::fs::remove_file("transaction.verter").unwrap();
+}
+
+#[test]
+fn transaction_rollback() {
+    let mut file = File::open("transaction_rollback.verter", Config::default()).unwrap();
+    file.write_root(b"Old data").unwrap();
+    let file_size = file.file_size();
+
+    let mut transaction = file.transaction().unwrap();
+    let alloc = transaction.alloc().unwrap();
+    transaction.write(alloc, &vec![0xAB; 1000]).unwrap();
+    transaction.write_root(b"New data").unwrap();
+    transaction.rollback();
+    assert_eq!(file.read_root().unwrap(), b"Old data");
+    assert_eq!(file.file_size(), file_size);
+
+    // Dropping the transaction without committing it should also roll it back
+    let mut transaction = file.transaction().unwrap();
+    transaction.write_root(b"New data").unwrap();
+    drop(transaction);
+    assert_eq!(file.read_root().unwrap(), b"Old data");
+    assert!(!file.in_transaction());
+
+    std::fs::remove_file("transaction_rollback.verter").unwrap();
+}
+
+#[test]
+fn transaction_failed_operation() {
+    let mut file = File::open("transaction_failed_operation.verter", Config::default()).unwrap();
+    file.begin_transaction().unwrap();
+    match file.begin_transaction() {
+        Err(Error::TransactionInProgress) => {},
+        Ok(_) | Err(_) => panic!("should error with transaction in progress")
+    }
+
+    let alloc = file.alloc().unwrap();
+    file.write(alloc, b"Some data").unwrap();
+    assert!(file.write(3, b"Invalid").is_err());
+    file.commit_transaction().unwrap();
+
+    // The failed operation should not affect the rest of the transaction
+    assert_eq!(file.read(alloc).unwrap(), b"Some data");
+
+    std::fs::remove_file("transaction_failed_operation.verter").unwrap();
+}
+
+#[test]
+fn compaction() {
+    let mut file = File::open("compaction.verter", Config::default()).unwrap();
+    file.write_root(&vec![0x12; 300]).unwrap();
+    let chains = (0..20u8).map(|i| {
+        let alloc = file.alloc().unwrap();
+        file.write(alloc, &vec![i; 50 + 37 * i as usize]).unwrap();
+        (i, alloc)
+    }).collect::<Vec<_>>();
+    for (i, alloc) in &chains {
+        if i % 3 != 0 {
+            file.delete(*alloc).unwrap();
+        }
+    }
+    // Grow the root so that part of it ends up at the end of the file
+    file.write_root(&vec![0x34; 1500]).unwrap();
+    drop(file);
+
+    // Store the pointers to the remaining chains in a chain of their own, updating them as part of the compaction
+    let kept = chains.iter().filter(|(i, _)| i % 3 == 0).copied().collect::<Vec<_>>();
+    let ptrs_data = |ptrs: &[u64]| ptrs.iter().flat_map(|ptr| ptr.to_le_bytes()).collect::<Vec<_>>();
+    let mut file = File::open("compaction.verter", Config::default()).unwrap();
+    let ptrs_chain = file.alloc().unwrap();
+    file.write(ptrs_chain, &ptrs_data(&kept.iter().map(|(_, alloc)| *alloc).collect::<Vec<_>>())).unwrap();
+    drop(file);
+    let file_size = std::fs::metadata("compaction.verter").unwrap().len();
+
+    let mut new_ptrs_chain = 0;
+    let compaction = compact("compaction.verter", Config::default(), |file, compaction| {
+        new_ptrs_chain = compaction.remap(ptrs_chain);
+        file.write(new_ptrs_chain, &ptrs_data(&kept.iter().map(|(_, alloc)| compaction.remap(*alloc)).collect::<Vec<_>>()))
+    }).unwrap();
+    let new_file_size = std::fs::metadata("compaction.verter").unwrap().len();
+    assert!(compaction.reclaimed_bytes > 0);
+    assert_eq!(file_size - new_file_size, compaction.reclaimed_bytes);
+
+    let mut file = File::open("compaction.verter", Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), vec![0x34; 1500]);
+    let ptrs = file.read(new_ptrs_chain).unwrap();
+    for ((i, _), ptr) in kept.iter().zip(ptrs.chunks(8)) {
+        let ptr = u64::from_le_bytes(ptr.try_into().unwrap());
+        assert_eq!(file.read(ptr).unwrap(), vec![*i; 50 + 37 * *i as usize]);
+    }
+
+    // There should be no deleted pages left, so new pages are added to the end of the file
+    let alloc = file.alloc().unwrap();
+    assert_eq!(alloc, new_file_size);
+
+    std::fs::remove_file("compaction.verter").unwrap();
+}
+
+#[test]
+fn check() {
+    let mut file = File::open("check.verter", Config::default()).unwrap();
+    file.write_root(&vec![0x12; 300]).unwrap();
+    let a = file.alloc().unwrap();
+    file.write(a, &vec![0x34; 500]).unwrap();
+    let b = file.alloc().unwrap();
+    file.write(b, &vec![0x56; 500]).unwrap();
+    let c = file.alloc().unwrap();
+    file.delete(c).unwrap();
+    // Filling the final page exactly should not leave behind any orphaned pages
+    let d = file.alloc().unwrap();
+    file.write(d, &vec![0x78; 240]).unwrap();
+    file.delete(d).unwrap();
+
+    assert!(file.check(&[a, b]).unwrap().is_ok());
+
+    // Forgetting about a chain should make its pages orphaned
+    let report = file.check(&[a]).unwrap();
+    assert!(report.problems.contains(&Problem::OrphanedPage { page: b }));
+
+    // Make a loop back to the start of chain `a`
+    let mut last_page = a;
+    while let PageHeader::NextPage(next) = file.read_page_header(last_page).unwrap() {
+        last_page = next;
+    }
+    file.write_page_header(last_page, PageHeader::NextPage(a)).unwrap();
+    // Cross-link chain `b` with chain `a`
+    file.write_page_header(b, PageHeader::NextPage(a)).unwrap();
+    file.commit().unwrap();
+
+    let report = file.check(&[a, b]).unwrap();
+    assert!(report.problems.contains(&Problem::Cycle { chain: a, page: a }));
+    assert!(report.problems.contains(&Problem::CrossLinkedPage { chain: b, page: a }));
+    drop(file);
+
+    let mut file = File::open("check.verter", Config::default()).unwrap();
+    let report = file.repair(&[a, b]).unwrap();
+    assert_eq!(report.quarantined_chains, vec![a, b]);
+    assert!(report.rebuilt_free_list);
+    assert!(file.check(&[a, b]).unwrap().is_ok());
+    // The cycle is cut at the last page of the chain, which is now considered full
+    let a_data = file.read(a).unwrap();
+    assert_eq!(a_data.len(), 600);
+    assert_eq!(&a_data[..500], &[0x34; 500]);
+    assert_eq!(file.read(b).unwrap().len(), 120);
+    assert_eq!(file.read_root().unwrap(), vec![0x12; 300]);
+
+    std::fs::remove_file("check.verter").unwrap();
+}
+
+#[test]
+fn repair_free_list() {
+    let mut file = File::open("repair_free_list.verter", Config::default()).unwrap();
+    let a = file.alloc().unwrap();
+    file.write(a, &vec![0x34; 500]).unwrap();
+    let b = file.alloc().unwrap();
+    file.write(b, &vec![0x56; 500]).unwrap();
+    file.delete(b).unwrap();
+
+    // Point the free list into a live chain
+    file.write_u64(file.first_free_page_ptr(), a).unwrap();
+    file.commit().unwrap();
+    let report = file.check(&[a]).unwrap();
+    assert!(report.problems.contains(&Problem::CorruptedFreeList { ptr: a }));
+
+    let report = file.repair(&[a]).unwrap();
+    assert!(report.quarantined_chains.is_empty());
+    assert!(report.rebuilt_free_list);
+    assert!(file.check(&[a]).unwrap().is_ok());
+    assert_eq!(file.read(a).unwrap(), vec![0x34; 500]);
+
+    // The pages of the deleted chain should be reused
+    let new_alloc = file.alloc().unwrap();
+    assert!(new_alloc < file.header_size() + file.n_pages() * file.total_page_size());
+
+    std::fs::remove_file("repair_free_list.verter").unwrap();
+}
+
+#[test]
+fn checksums() {
+    let config = Config {
+        checksums: true,
+        ..Config::default()
+    };
+    let mut file = File::open("checksums.verter", config).unwrap();
+    let a = file.alloc().unwrap();
+    file.write(a, &vec![0x34; 500]).unwrap();
+    let b = file.alloc().unwrap();
+    file.write(b, &vec![0x56; 500]).unwrap();
+    drop(file);
+
+    // Flip a bit in the data of chain `a`
+    let mut data = std::fs::read("checksums.verter").unwrap();
+    data[a as usize + 20] ^= 1;
+    std::fs::write("checksums.verter", data).unwrap();
+
+    let mut file = File::open("checksums.verter", config).unwrap();
+    assert!(matches!(file.read(a), Err(Error::ChecksumMismatch)));
+    assert_eq!(file.read(b).unwrap(), vec![0x56; 500]);
+    let report = file.check(&[a, b]).unwrap();
+    assert!(report.problems.contains(&Problem::ChecksumMismatch { chain: a, page: a }));
+
+    // Repairing the file leaves behind an empty chain
+    file.repair(&[a, b]).unwrap();
+    assert!(file.check(&[a, b]).unwrap().is_ok());
+    assert_eq!(file.read(a).unwrap(), vec![]);
+    file.write(a, &vec![0x78; 500]).unwrap();
+    drop(file);
+
+    let mut file = File::open("checksums.verter", config).unwrap();
+    assert_eq!(file.read(a).unwrap(), vec![0x78; 500]);
+    assert_eq!(file.read(b).unwrap(), vec![0x56; 500]);
+
+    std::fs::remove_file("checksums.verter").unwrap();
+}
+
+#[test]
+fn checksums_backward_compatibility() {
+    let mut file = File::open("checksums_backward_compatibility.verter", Config::default()).unwrap();
+    let a = file.alloc().unwrap();
+    file.write(a, &vec![0x34; 500]).unwrap();
+    file.write_root(&a.to_le_bytes()).unwrap();
+    drop(file);
+
+    // Files created without checksums keep their format when opened with checksums enabled
+    let config = Config {
+        checksums: true,
+        ..Config::default()
+    };
+    let mut file = File::open("checksums_backward_compatibility.verter", config).unwrap();
+    assert!(!file.checksums);
+    assert_eq!(file.read_root().unwrap(), a.to_le_bytes());
+    assert_eq!(file.read(a).unwrap(), vec![0x34; 500]);
+    let b = file.alloc().unwrap();
+    file.write(b, &vec![0x56; 500]).unwrap();
+    drop(file);
+
+    let mut file = File::open("checksums_backward_compatibility.verter", Config::default()).unwrap();
+    assert_eq!(file.read(a).unwrap(), vec![0x34; 500]);
+    assert_eq!(file.read(b).unwrap(), vec![0x56; 500]);
+
+    std::fs::remove_file("checksums_backward_compatibility.verter").unwrap();
+}
+
+#[test]
+fn ranges() {
+    let mut file = File::open("ranges.verter", Config::default()).unwrap();
+    let data = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let alloc = file.alloc().unwrap();
+    file.write(alloc, &data).unwrap();
+
+    assert_eq!(file.size(alloc).unwrap(), 1000);
+    assert_eq!(file.read_range(alloc, 0, 1000).unwrap(), data);
+    assert_eq!(file.read_range(alloc, 110, 150).unwrap(), &data[110..260]);
+    assert_eq!(file.read_range(alloc, 950, 100).unwrap(), &data[950..]);
+    assert_eq!(file.read_range(alloc, 2000, 100).unwrap(), vec![]);
+
+    let mut expected = data.clone();
+    file.write_range(alloc, 230, &[0xAB; 100]).unwrap();
+    expected[230..330].copy_from_slice(&[0xAB; 100]);
+    assert_eq!(file.read(alloc).unwrap(), expected);
+
+    // Writing past the end extends the chain
+    file.write_range(alloc, 990, &[0xCD; 50]).unwrap();
+    expected.truncate(990);
+    expected.extend_from_slice(&[0xCD; 50]);
+    assert_eq!(file.read(alloc).unwrap(), expected);
+
+    // Writing after the end fills the gap with zeros
+    file.write_range(alloc, 1300, &[0xEF; 10]).unwrap();
+    expected.resize(1300, 0);
+    expected.extend_from_slice(&[0xEF; 10]);
+    assert_eq!(file.read(alloc).unwrap(), expected);
+
+    let empty = file.alloc().unwrap();
+    for i in 0..50 {
+        file.append(empty, &[i; 7]).unwrap();
+    }
+    assert_eq!(file.read(empty).unwrap(), (0..50).flat_map(|i| [i; 7]).collect::<Vec<_>>());
+    assert_eq!(file.size(empty).unwrap(), 350);
+    file.append(alloc, b"end").unwrap();
+    expected.extend_from_slice(b"end");
+    assert_eq!(file.read(alloc).unwrap(), expected);
+
+    std::fs::remove_file("ranges.verter").unwrap();
+}
+
+#[test]
+fn stream() {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    let mut file = File::open("stream.verter", Config {
+        checksums: true,
+        ..Config::default()
+    }).unwrap();
+    let alloc = file.alloc().unwrap();
+
+    let mut stream = file.stream(alloc).unwrap();
+    for i in 0..100u32 {
+        stream.write_all(&i.to_le_bytes()).unwrap();
+    }
+    assert_eq!(stream.stream_position().unwrap(), 400);
+    stream.seek(SeekFrom::Start(40)).unwrap();
+    let mut bytes = [0; 4];
+    stream.read_exact(&mut bytes).unwrap();
+    assert_eq!(u32::from_le_bytes(bytes), 10);
+    stream.seek(SeekFrom::End(-4)).unwrap();
+    stream.read_exact(&mut bytes).unwrap();
+    assert_eq!(u32::from_le_bytes(bytes), 99);
+    assert_eq!(stream.read(&mut bytes).unwrap(), 0);
+    assert!(stream.seek(SeekFrom::Current(-1000)).is_err());
+
+    stream.seek(SeekFrom::Start(200)).unwrap();
+    stream.write_all(&1234u32.to_le_bytes()).unwrap();
+    stream.seek(SeekFrom::Start(0)).unwrap();
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).unwrap();
+    assert_eq!(data.len(), 400);
+    assert_eq!(&data[200..204], &1234u32.to_le_bytes());
+    assert_eq!(&data[204..208], &51u32.to_le_bytes());
+    drop(file);
+
+    let mut file = File::open("stream.verter", Config::default()).unwrap();
+    assert_eq!(file.read(alloc).unwrap(), data);
+
+    std::fs::remove_file("stream.verter").unwrap();
+}
+
+#[test]
+fn extents() {
+    let mut file = File::open("extents.verter", Config::default()).unwrap();
+    let small_chains = (0..20).map(|_| file.alloc().unwrap()).collect::<Vec<_>>();
+    for chain in small_chains.iter().step_by(2) {
+        file.delete(*chain).unwrap();
+    }
+
+    // Small writes reuse deleted pages
+    let small = file.alloc().unwrap();
+    file.write(small, &vec![0x12; 300]).unwrap();
+    let mut page = small;
+    while let PageHeader::NextPage(next) = file.read_page_header(page).unwrap() {
+        assert!(small_chains.contains(&next));
+        page = next;
+    }
+
+    // Large writes are allocated contiguously
+    let large = file.alloc().unwrap();
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    file.write(large, &data).unwrap();
+    let mut n_pages = 1;
+    let mut page = match file.read_page_header(large).unwrap() {
+        PageHeader::NextPage(next) => next,
+        _ => panic!("chain should have more than one page")
+    };
+    while let PageHeader::NextPage(next) = file.read_page_header(page).unwrap() {
+        assert_eq!(next, page + file.total_page_size());
+        page = next;
+        n_pages += 1;
+    }
+    assert_eq!(n_pages, 5000 / 120);
+
+    // Appending lots of data extends the extent
+    file.append(large, &data).unwrap();
+    let mut expected = data.clone();
+    expected.extend_from_slice(&data);
+    assert_eq!(file.read(large).unwrap(), expected);
+    drop(file);
+
+    let mut file = File::open("extents.verter", Config::default()).unwrap();
+    assert_eq!(file.read(large).unwrap(), expected);
+    assert_eq!(file.read(small).unwrap(), vec![0x12; 300]);
+
+    std::fs::remove_file("extents.verter").unwrap();
+}
+
+#[test]
+fn extent_reuse() {
+    let mut file = File::open("extent_reuse.verter", Config::default()).unwrap();
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let old = file.alloc().unwrap();
+    file.write(old, &data).unwrap();
+    let keep = file.alloc().unwrap();
+    file.write(keep, &[1, 2, 3]).unwrap();
+    file.delete(old).unwrap();
+    let file_size = file.file_size();
+
+    // The extent of the deleted chain is reused instead of growing the file
+    let new = file.alloc().unwrap();
+    file.write(new, &data).unwrap();
+    assert_eq!(file.file_size(), file_size);
+    let mut page = match file.read_page_header(new).unwrap() {
+        PageHeader::NextPage(next) => next,
+        _ => panic!("chain should have more than one page")
+    };
+    while let PageHeader::NextPage(next) = file.read_page_header(page).unwrap() {
+        assert_eq!(next, page + file.total_page_size());
+        page = next;
+    }
+    assert_eq!(file.read(new).unwrap(), data);
+    assert_eq!(file.read(keep).unwrap(), vec![1, 2, 3]);
+    assert!(file.check(&[new, keep]).unwrap().is_ok());
+    drop(file);
+
+    std::fs::remove_file("extent_reuse.verter").unwrap();
+}
+
+#[test]
+fn free_list_copy() {
+    let mut file = File::open("free_list_copy.verter", Config::default()).unwrap();
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let chains = (0..3).map(|_| {
+        let chain = file.alloc().unwrap();
+        file.write(chain, &data).unwrap();
+        chain
+    }).collect::<Vec<_>>();
+    file.delete(chains[0]).unwrap();
+
+    // Building the copy of the free list
+    let a = file.alloc().unwrap();
+    file.write(a, &data).unwrap();
+
+    // The copy follows pages being deleted and allocated one at a time
+    file.delete(chains[1]).unwrap();
+    let small = file.alloc().unwrap();
+    file.write(small, &[1, 2, 3]).unwrap();
+
+    // The rest of the deleted chain is reused
+    let file_size = file.file_size();
+    let b = file.alloc().unwrap();
+    file.write(b, &data[..2000]).unwrap();
+    assert_eq!(file.file_size(), file_size);
+
+    // Rolled back deletions aren't reflected in the copy, so the pages of the chain aren't reused
+    file.begin_transaction().unwrap();
+    file.delete(chains[2]).unwrap();
+    file.rollback_transaction();
+    let c = file.alloc().unwrap();
+    file.write(c, &data).unwrap();
+    assert!(file.file_size() > file_size);
+
+    let chains = [a, small, b, c, chains[2]];
+    assert!(file.check(&chains).unwrap().is_ok());
+    drop(file);
+
+    let mut file = File::open("free_list_copy.verter", Config::default()).unwrap();
+    assert_eq!(file.read(a).unwrap(), data);
+    assert_eq!(file.read(b).unwrap(), data[..2000]);
+    assert_eq!(file.read(c).unwrap(), data);
+    assert_eq!(file.read(chains[4]).unwrap(), data);
+    assert!(file.check(&chains).unwrap().is_ok());
+    drop(file);
+
+    std::fs::remove_file("free_list_copy.verter").unwrap();
+}
+
+#[test]
+fn memory_storage() {
+    let storage = MemoryStorage::new();
+    let mut file = File::open_storage(storage.clone(), Config::default()).unwrap();
+    let alloc = file.alloc().unwrap();
+    file.write(alloc, &vec![0x12; 500]).unwrap();
+    file.write_root(&alloc.to_le_bytes()).unwrap();
+    let snapshot = storage.bytes();
+
+    // Simulate a crash right after the journal was written
+    let root_page = file.root_page().unwrap();
+    file.write_chain(root_page, b"New root").unwrap();
+    let modifications = std::mem::take(&mut file.modifications);
+    file.storage.write_journal(&Journal::encode(&modifications)).unwrap();
+    drop(file);
+
+    let mut file = File::open_storage(storage.clone(), Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), b"New root");
+    assert_eq!(file.read(alloc).unwrap(), vec![0x12; 500]);
+    drop(file);
+
+    // Snapshots are independent of the original storage
+    let mut file = File::open_storage(MemoryStorage::from_bytes(snapshot), Config::default()).unwrap();
+    assert_eq!(file.read_root().unwrap(), alloc.to_le_bytes());
+    assert_eq!(file.read(alloc).unwrap(), vec![0x12; 500]);
+
+    match File::open_storage(MemoryStorage::from_bytes(b"Not a verter file".to_vec()), Config::default()) {
+        Err(Error::InvalidFile) => {},
+        Ok(_) | Err(_) => panic!("should error with invalid file")
+    }
+}
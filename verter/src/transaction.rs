@@ -0,0 +1,61 @@
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{Error, File};
+
+/// A guard for a transaction in progress, created by `File::transaction`.
+/// All modifications made through the guard are applied to the file atomically once the transaction is committed.
+/// If the guard is dropped without being committed, the transaction is rolled back.
+pub struct Transaction<'a> {
+    file: &'a mut File,
+    finished: bool
+}
+
+impl<'a> Transaction<'a> {
+
+    pub(crate) fn new(file: &'a mut File) -> Self {
+        Self {
+            file,
+            finished: false
+        }
+    }
+
+    /// Atomically apply all modifications made during the transaction to the file.
+    pub fn commit(mut self) -> Result<(), Error> {
+        self.finished = true;
+        self.file.commit_transaction()
+    }
+
+    /// Discard all modifications made during the transaction.
+    pub fn rollback(mut self) {
+        self.finished = true;
+        self.file.rollback_transaction();
+    }
+
+}
+
+impl Deref for Transaction<'_> {
+    type Target = File;
+
+    fn deref(&self) -> &Self::Target {
+        self.file
+    }
+}
+
+impl DerefMut for Transaction<'_> {
+
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.file
+    }
+
+}
+
+impl Drop for Transaction<'_> {
+
+    fn drop(&mut self) {
+        if !self.finished {
+            self.file.rollback_transaction();
+        }
+    }
+
+}